@@ -0,0 +1,58 @@
+//! Benchmarks of the board operations at the heart of every search.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use square100::bench_fixtures;
+use square100::Coord;
+
+fn possible_moves(c: &mut Criterion) {
+    let positions = bench_fixtures::positions_10();
+    c.bench_function("possible_moves/10x10 game", |b| {
+        b.iter(|| {
+            positions
+                .iter()
+                .map(|board| black_box(board).possible_moves().len())
+                .sum::<usize>()
+        })
+    });
+    let board = bench_fixtures::opening(16, 60);
+    c.bench_function("possible_moves/16x16 opening", |b| {
+        b.iter(|| black_box(&board).possible_moves())
+    });
+}
+
+fn set_value(c: &mut Criterion) {
+    // `begin` places the 1 through `set_value`, with all of its checks
+    let mut board = bench_fixtures::empty(10);
+    c.bench_function("set_value/10x10 every cell", |b| {
+        b.iter(|| {
+            for y in 0..10 {
+                for x in 0..10 {
+                    board.begin(Coord::new(black_box(x), black_box(y))).unwrap();
+                    board.unmake_move().unwrap();
+                }
+            }
+        })
+    });
+    let mut board = bench_fixtures::game_10(50);
+    c.bench_function("make_move/10x10 midgame", |b| {
+        b.iter(|| {
+            let dir = board.possible_moves().first().unwrap();
+            board.make_move(black_box(dir)).unwrap();
+            board.unmake_move().unwrap();
+        })
+    });
+}
+
+fn scans(c: &mut Criterion) {
+    let board = bench_fixtures::opening(64, 1000);
+    let mut degrees = Vec::new();
+    c.bench_function("empty_degrees/64x64 opening", |b| {
+        b.iter(|| black_box(&board).empty_degrees(&mut degrees))
+    });
+    c.bench_function("empty_cells/64x64 opening", |b| {
+        b.iter(|| black_box(&board).empty_cells().count())
+    });
+}
+
+criterion_group!(benches, possible_moves, set_value, scans);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+//! Benchmarks of whole searches: solving and counting solutions.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use square100::analysis::{self, CountConfig};
+use square100::bench_fixtures;
+use square100::solver::{Outcome, Solver};
+
+fn solve(c: &mut Criterion) {
+    let solver = Solver::default();
+    let mut group = c.benchmark_group("solve");
+    for (name, board) in [
+        ("5x5 corner", bench_fixtures::corner_start(5)),
+        ("10x10 corner", bench_fixtures::corner_start(10)),
+        ("10x10 opening", bench_fixtures::opening(10, 10)),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| assert_eq!(solver.solve(&board).outcome, Outcome::Solved))
+        });
+    }
+    let empty = bench_fixtures::empty(6);
+    let starts: Vec<_> = empty
+        .empty_cells()
+        .map(|cell| empty.clone().start_at(cell).unwrap())
+        .collect();
+    group.bench_function("6x6 every start, one by one", |b| {
+        b.iter(|| {
+            starts
+                .iter()
+                .map(|start| solver.solve(start).stats.nodes)
+                .sum::<u64>()
+        })
+    });
+    group.bench_function("6x6 every start, solve_many", |b| {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        b.iter(|| solver.solve_many(&starts, threads).len())
+    });
+    group.finish();
+}
+
+fn count(c: &mut Criterion) {
+    let mut group = c.benchmark_group("count");
+    group.sample_size(10);
+    let board = bench_fixtures::corner_start(5);
+    let config = CountConfig::default();
+    group.bench_function("5x5 corner", |b| {
+        b.iter(|| analysis::count_solutions(&board, &config, &mut |_| {}).solutions)
+    });
+    let board = bench_fixtures::game_10(70);
+    group.bench_function("10x10 endgame", |b| {
+        b.iter(|| analysis::count_solutions(&board, &config, &mut |_| {}).solutions)
+    });
+    group.bench_function("5x5 dp", |b| {
+        b.iter(|| analysis::count_solutions_dp(5, 1 << 30).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, solve, count);
+criterion_main!(benches);
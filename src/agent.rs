@@ -0,0 +1,575 @@
+/*!
+Simple game playing agents and a harness to compare how they fail.
+
+An agent picks one move at a time with no backtracking, the way a person
+plays the puzzle on paper. Playing many games with an agent and recording
+the score at which each game blocked gives a depth distribution; comparing
+the distributions of different agents grounds the difficulty of a board in
+how real players get stuck.
+
+Two strategies build on the agents to solve a board without backtracking
+search: `restart_play` plays whole games again until one is won, and
+`mcts_play` plays one game, choosing each move by Monte Carlo tree search
+over simulated games.
+ */
+
+use crate::board::{Board, Direction};
+use crate::coord::Coord;
+use crate::rng::Rng;
+
+/// Agent chooses the next move of a game.
+pub trait Agent {
+    /// A short name for the agent, used in reports.
+    fn name(&self) -> &str;
+
+    /// Choose one of `moves`, which is never empty, to play on `board`.
+    fn choose(&mut self, board: &Board, moves: &[Direction]) -> Direction;
+}
+
+#[derive(Debug, Clone)]
+/// RandomAgent plays a uniformly random legal move.
+pub struct RandomAgent {
+    rng: Rng,
+}
+
+impl RandomAgent {
+    pub fn new(seed: u64) -> Self {
+        RandomAgent {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Agent for RandomAgent {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn choose(&mut self, _board: &Board, moves: &[Direction]) -> Direction {
+        *self.rng.choose(moves).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// WarnsdorffAgent plays the move to the cell with the fewest onward moves,
+/// breaking ties in direction order.
+pub struct WarnsdorffAgent;
+
+impl Agent for WarnsdorffAgent {
+    fn name(&self) -> &str {
+        "warnsdorff"
+    }
+
+    fn choose(&mut self, board: &Board, moves: &[Direction]) -> Direction {
+        *moves
+            .iter()
+            .min_by_key(|&&dir| board.degree_after(dir).unwrap())
+            .unwrap()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// ShuffledWarnsdorffAgent plays like `WarnsdorffAgent`, but breaks ties at
+/// random, so that repeated games from the same start differ.
+pub struct ShuffledWarnsdorffAgent {
+    rng: Rng,
+}
+
+impl ShuffledWarnsdorffAgent {
+    pub fn new(seed: u64) -> Self {
+        ShuffledWarnsdorffAgent {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Agent for ShuffledWarnsdorffAgent {
+    fn name(&self) -> &str {
+        "warnsdorff-shuffled"
+    }
+
+    fn choose(&mut self, board: &Board, moves: &[Direction]) -> Direction {
+        let onward: Vec<usize> = moves
+            .iter()
+            .map(|&dir| board.degree_after(dir).unwrap())
+            .collect();
+        let fewest = onward.iter().copied().min().unwrap();
+        let ties: Vec<Direction> = moves
+            .iter()
+            .zip(onward)
+            .filter(|&(_, n)| n == fewest)
+            .map(|(&dir, _)| dir)
+            .collect();
+        *self.rng.choose(&ties).unwrap()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// GreedyHumanAgent mimics how people typically play: it stays close to the
+/// area it has already filled, steers away from the edges once the board is
+/// half full, and never looks ahead to see whether a move strands a cell.
+/// Ties are broken randomly.
+pub struct GreedyHumanAgent {
+    rng: Rng,
+}
+
+impl GreedyHumanAgent {
+    pub fn new(seed: u64) -> Self {
+        GreedyHumanAgent {
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Return the cost of moving to (x, y); lower is more attractive.
+    fn cost(board: &Board, x: usize, y: usize) -> f64 {
+        let (mut cx, mut cy, mut filled) = (0.0, 0.0, 0.0);
+        for (x, y, _) in board.iter_cells().filter(|c| c.2 > 0) {
+            cx += x as f64;
+            cy += y as f64;
+            filled += 1.0;
+        }
+        let (dx, dy) = (x as f64 - cx / filled, y as f64 - cy / filled);
+        let mut cost = (dx * dx + dy * dy).sqrt();
+        let edge = x == 0 || y == 0 || x == board.width() - 1 || y == board.height() - 1;
+        if edge && filled * 2.0 > board.cell_count() as f64 {
+            cost += board.width().max(board.height()) as f64;
+        }
+        cost
+    }
+}
+
+impl Agent for GreedyHumanAgent {
+    fn name(&self) -> &str {
+        "greedy-human"
+    }
+
+    fn choose(&mut self, board: &Board, moves: &[Direction]) -> Direction {
+        let costs: Vec<f64> = moves
+            .iter()
+            .map(|&dir| {
+                let to = board.valid_move(dir).unwrap();
+                GreedyHumanAgent::cost(board, to.x, to.y)
+            })
+            .collect();
+        let best = costs.iter().cloned().fold(f64::INFINITY, f64::min);
+        let ties: Vec<Direction> = moves
+            .iter()
+            .zip(costs.iter())
+            .filter(|(_, &c)| c - best < 1e-9)
+            .map(|(&dir, _)| dir)
+            .collect();
+        *self.rng.choose(&ties).unwrap()
+    }
+}
+
+/// Play `board` with `agent` until the game is won or blocked, returning the
+/// final board. The board must already be started.
+pub fn play_out(agent: &mut dyn Agent, board: &Board) -> Board {
+    let mut board = board.clone();
+    loop {
+        let moves: Vec<Direction> = board.possible_moves().into_iter().collect();
+        if moves.is_empty() {
+            return board;
+        }
+        let dir = agent.choose(&board, &moves);
+        board = board.next_move(dir).unwrap();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The result of `restart_play` and `mcts_play`.
+pub struct Restarts {
+    /// The highest scoring game, which is won if any game was.
+    pub best: Board,
+    /// The number of games played, simulated ones included.
+    pub games: usize,
+    /// The number of moves played over all games.
+    pub moves: u64,
+}
+
+/// Play games on `board` with a `ShuffledWarnsdorffAgent` until one is won
+/// or `move_limit` moves have been played over all games, and return the
+/// best game. An unstarted board is started on a random empty cell for each
+/// game. The same `seed` always gives the same games.
+pub fn restart_play(board: &Board, move_limit: u64, seed: u64) -> Restarts {
+    let mut agent = ShuffledWarnsdorffAgent::new(seed);
+    let mut rng = Rng::new(seed);
+    let cells: Vec<_> = board.empty_cells().collect();
+    let mut restarts = Restarts {
+        best: board.clone(),
+        games: 0,
+        moves: 0,
+    };
+    while restarts.moves < move_limit && !restarts.best.is_won() {
+        let start = match rng.choose(&cells) {
+            Some(cell) if !board.is_started() => board.clone().start_at(*cell).unwrap(),
+            _ => board.clone(),
+        };
+        let game = play_out(&mut agent, &start);
+        restarts.games += 1;
+        restarts.moves += (game.score() - board.score()) as u64;
+        if game.score() > restarts.best.score() {
+            restarts.best = game;
+        }
+        if !start.is_started() || start.is_blocked() {
+            // there is nothing to play, however often we restart
+            break;
+        }
+    }
+    restarts
+}
+
+/// The number of games `mcts_play` simulates for each move.
+pub const MCTS_PLAYOUTS: usize = 64;
+
+/// How much `MctsAgent` favors moves it has tried less often over moves
+/// whose games went deeper.
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// Node is a position in the search tree of `MctsAgent`.
+struct Node {
+    board: Board,
+    /// The moves from the position not yet tried.
+    untried: Vec<Direction>,
+    /// The moves tried, with the index in the tree of the position each
+    /// leads to.
+    children: Vec<(Direction, usize)>,
+    /// The number of simulated games through the position.
+    visits: u32,
+    /// The sum of the scores of those games, each as a fraction of the
+    /// cells to fill.
+    total: f64,
+}
+
+impl Node {
+    fn new(board: Board) -> Node {
+        let untried = board.possible_moves().into_iter().collect();
+        Node {
+            board,
+            untried,
+            children: Vec::new(),
+            visits: 0,
+            total: 0.0,
+        }
+    }
+
+    /// Return the upper confidence bound of the score of the games through
+    /// the position, played from a parent visited `parent` times.
+    fn bound(&self, parent: u32) -> f64 {
+        let visits = f64::from(self.visits);
+        self.total / visits + EXPLORATION * (f64::from(parent).ln() / visits).sqrt()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// MctsAgent chooses each move by Monte Carlo tree search. It simulates up
+/// to `playouts` games from the position: each follows the moves of the
+/// highest upper confidence bound (UCT) down a tree of the positions earlier
+/// games passed through, tries one new move, and is finished by a
+/// `ShuffledWarnsdorffAgent`. The move whose games were followed most is
+/// played. Once `budget` simulated moves are spent it plays like
+/// `WarnsdorffAgent`.
+pub struct MctsAgent {
+    rng: Rng,
+    playout: ShuffledWarnsdorffAgent,
+    playouts: usize,
+    budget: u64,
+    /// The number of moves of simulated games so far.
+    pub moves: u64,
+    /// The number of games simulated so far.
+    pub games: usize,
+    /// The highest scoring game simulated so far.
+    pub best: Option<Board>,
+}
+
+impl MctsAgent {
+    pub fn new(seed: u64, playouts: usize, budget: u64) -> Self {
+        MctsAgent {
+            rng: Rng::new(seed),
+            playout: ShuffledWarnsdorffAgent::new(seed),
+            playouts,
+            budget,
+            moves: 0,
+            games: 0,
+            best: None,
+        }
+    }
+
+    /// Simulate one game from the root of `tree`, growing the tree by one
+    /// position, and return the game.
+    fn simulate(&mut self, tree: &mut Vec<Node>) -> Board {
+        let mut path = vec![0];
+        let mut node = 0;
+        while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+            let parent = tree[node].visits;
+            node = tree[node]
+                .children
+                .iter()
+                .map(|&(_, child)| child)
+                .max_by(|&a, &b| tree[a].bound(parent).total_cmp(&tree[b].bound(parent)))
+                .unwrap();
+            path.push(node);
+        }
+        if !tree[node].untried.is_empty() {
+            let i = self.rng.below(tree[node].untried.len());
+            let dir = tree[node].untried.swap_remove(i);
+            let next = tree[node].board.clone().next_move(dir).unwrap();
+            tree.push(Node::new(next));
+            let child = tree.len() - 1;
+            tree[node].children.push((dir, child));
+            node = child;
+            path.push(node);
+        }
+        let game = play_out(&mut self.playout, &tree[node].board);
+        let score = game.score() as f64 / game.cell_count() as f64;
+        for &n in &path {
+            tree[n].visits += 1;
+            tree[n].total += score;
+        }
+        game
+    }
+}
+
+impl Agent for MctsAgent {
+    fn name(&self) -> &str {
+        "mcts"
+    }
+
+    fn choose(&mut self, board: &Board, moves: &[Direction]) -> Direction {
+        let mut tree = vec![Node::new(board.clone())];
+        for _ in 0..self.playouts {
+            if self.moves >= self.budget || self.best.as_ref().is_some_and(Board::is_won) {
+                break;
+            }
+            let game = self.simulate(&mut tree);
+            self.moves += (game.score() - board.score()) as u64;
+            self.games += 1;
+            if self
+                .best
+                .as_ref()
+                .is_none_or(|best| game.score() > best.score())
+            {
+                self.best = Some(game);
+            }
+        }
+        match tree[0]
+            .children
+            .iter()
+            .max_by_key(|&&(_, child)| tree[child].visits)
+        {
+            Some(&(dir, _)) => dir,
+            None => WarnsdorffAgent.choose(board, moves),
+        }
+    }
+}
+
+/// Play one game on `board` with an `MctsAgent` of `MCTS_PLAYOUTS` playouts a
+/// move, until it is won or blocked or a simulated game is won, spending at
+/// most `move_limit` moves on simulated games. Return the best game played
+/// or simulated. An unstarted board is started on a random empty cell. The
+/// same `seed` always gives the same game.
+pub fn mcts_play(board: &Board, move_limit: u64, seed: u64) -> Restarts {
+    let mut rng = Rng::new(seed);
+    let cells: Vec<_> = board.empty_cells().collect();
+    let mut game = match rng.choose(&cells) {
+        Some(cell) if !board.is_started() => board.clone().start_at(*cell).unwrap(),
+        _ => board.clone(),
+    };
+    let mut agent = MctsAgent::new(seed, MCTS_PLAYOUTS, move_limit);
+    loop {
+        let moves: Vec<Direction> = game.possible_moves().into_iter().collect();
+        if moves.is_empty() || agent.best.as_ref().is_some_and(Board::is_won) {
+            break;
+        }
+        let dir = agent.choose(&game, &moves);
+        game.make_move(dir).unwrap();
+    }
+    let moves = agent.moves + (game.score() - board.score()) as u64;
+    let best = match agent.best {
+        Some(simulated) if simulated.score() > game.score() => simulated,
+        _ => game,
+    };
+    Restarts {
+        best,
+        games: agent.games + 1,
+        moves,
+    }
+}
+
+/// Start `board` on a random empty cell, unless it is already started, and
+/// play up to `moves` random moves. A move that would leave the game
+/// blocked is never chosen, so the opening stops early rather than hand
+/// over a lost position. The same `seed` always gives the same opening.
+pub fn random_opening(board: &Board, moves: usize, seed: u64) -> Board {
+    let mut rng = Rng::new(seed);
+    let mut board = board.clone();
+    if !board.is_started() {
+        let cells: Vec<_> = board.empty_cells().collect();
+        if let Some(cell) = rng.choose(&cells) {
+            board = board.start_at(*cell).unwrap();
+        }
+    }
+    for _ in 0..moves {
+        let open: Vec<Board> = board
+            .possible_moves()
+            .into_iter()
+            .map(|dir| board.clone().next_move(dir).unwrap())
+            .filter(|next| next.is_won() || !next.is_blocked())
+            .collect();
+        match rng.choose(&open) {
+            Some(next) => board = next.clone(),
+            None => break,
+        }
+    }
+    board
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// DepthDistribution counts how many games ended at each score.
+pub struct DepthDistribution {
+    /// `counts[n]` is the number of games that ended with score `n`.
+    pub counts: Vec<usize>,
+}
+
+impl DepthDistribution {
+    /// The number of games recorded.
+    pub fn games(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// The mean final score over all games.
+    pub fn mean(&self) -> f64 {
+        let total: usize = self.counts.iter().enumerate().map(|(n, c)| n * c).sum();
+        total as f64 / self.games().max(1) as f64
+    }
+
+    /// The fraction of games that filled the whole board.
+    pub fn win_rate(&self) -> f64 {
+        *self.counts.last().unwrap_or(&0) as f64 / self.games().max(1) as f64
+    }
+}
+
+/// Play `games` games of a `size` x `size` board with `agent`, each from a
+/// start cell chosen at random from `seed`, and record where they ended.
+pub fn depth_distribution(
+    agent: &mut dyn Agent,
+    size: usize,
+    games: usize,
+    seed: u64,
+) -> DepthDistribution {
+    let mut rng = Rng::new(seed);
+    let empty = Board::new(size);
+    let cells: Vec<Coord> = empty.empty_cells().collect();
+    let mut counts = vec![0; empty.cell_count() + 1];
+    for _ in 0..games {
+        let cell = *rng.choose(&cells).unwrap();
+        let start = empty.clone().start_at(cell).unwrap();
+        counts[play_out(agent, &start).score()] += 1;
+    }
+    DepthDistribution { counts }
+}
+
+/// Compare the random, Warnsdorff and greedy-human agents on the same start
+/// cells, returning each agent's name and depth distribution.
+pub fn compare_agents(size: usize, games: usize, seed: u64) -> Vec<(String, DepthDistribution)> {
+    let mut agents: Vec<Box<dyn Agent>> = vec![
+        Box::new(RandomAgent::new(seed)),
+        Box::new(WarnsdorffAgent),
+        Box::new(GreedyHumanAgent::new(seed)),
+    ];
+    agents
+        .iter_mut()
+        .map(|agent| {
+            let dist = depth_distribution(agent.as_mut(), size, games, seed);
+            (agent.name().to_string(), dist)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_out_ends_blocked_or_won() {
+        let start = Board::new(6).start_at(Coord::new(2, 2)).unwrap();
+        for agent in &mut [
+            Box::new(RandomAgent::new(1)) as Box<dyn Agent>,
+            Box::new(WarnsdorffAgent),
+            Box::new(GreedyHumanAgent::new(1)),
+        ] {
+            let end = play_out(agent.as_mut(), &start);
+            assert!(end.is_blocked());
+            assert!(end.score() > 1, "{} did not move", agent.name());
+        }
+    }
+
+    #[test]
+    fn restarts_play_until_won() {
+        let start = Board::new(8).start_at(Coord::new(3, 3)).unwrap();
+        let restarts = restart_play(&start, 1_000_000, 1);
+        assert!(restarts.best.is_won());
+        assert!(restarts.games >= 1);
+        assert!(restarts.moves >= 63);
+        assert_eq!(restart_play(&start, 1_000_000, 1), restarts);
+
+        let short = restart_play(&Board::new(8), 10, 1);
+        assert_eq!(short.games, 1);
+        assert!(short.best.is_started());
+        let blocked = play_out(
+            &mut WarnsdorffAgent,
+            &Board::new(5).start_at(Coord::new(0, 0)).unwrap(),
+        );
+        assert_eq!(restart_play(&blocked, 100, 1).games, 1);
+    }
+
+    #[test]
+    fn mcts_plays_until_won() {
+        let start = Board::new(8).start_at(Coord::new(3, 3)).unwrap();
+        let mcts = mcts_play(&start, 1_000_000, 1);
+        assert!(mcts.best.is_won());
+        assert_eq!(mcts.best.validate(), Ok(()));
+        assert!(mcts.games > 1);
+        assert_eq!(mcts_play(&start, 1_000_000, 1), mcts);
+
+        // without a budget it plays one Warnsdorff game
+        let greedy = mcts_play(&start, 0, 1);
+        assert_eq!(greedy.games, 1);
+        assert_eq!(greedy.best, play_out(&mut WarnsdorffAgent, &start));
+        let unstarted = mcts_play(&Board::new(6), 0, 1);
+        assert!(unstarted.best.is_started());
+    }
+
+    #[test]
+    fn random_openings_are_reproducible() {
+        let empty = Board::new(6);
+        let opening = random_opening(&empty, 5, 42);
+        assert_eq!(opening.score(), 6);
+        assert!(!opening.is_blocked());
+        assert_eq!(random_opening(&empty, 5, 42), opening);
+        let starts: Vec<_> = (0..20)
+            .map(|seed| random_opening(&empty, 0, seed).position_of(1))
+            .collect();
+        assert!(starts.iter().all(|start| start.is_some()));
+        assert!(starts.iter().any(|&start| start != starts[0]));
+
+        let started = empty.clone().start_at(Coord::new(2, 2)).unwrap();
+        assert_eq!(random_opening(&started, 0, 7), started);
+        // a long opening stops before it blocks itself
+        let long = random_opening(&empty, 100, 3);
+        assert!(long.is_won() || !long.is_blocked());
+    }
+
+    #[test]
+    fn compare_ranks_warnsdorff_above_random() {
+        let report = compare_agents(7, 200, 5);
+        let names: Vec<&str> = report.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["random", "warnsdorff", "greedy-human"]);
+        for (_, dist) in report.iter() {
+            assert_eq!(dist.games(), 200);
+            assert_eq!(dist.counts.len(), 50);
+        }
+        assert!(report[1].1.mean() > report[0].1.mean());
+        assert!(report[1].1.win_rate() > report[0].1.win_rate());
+    }
+}
@@ -0,0 +1,930 @@
+/*!
+Analysis tools that reason about the search space of a position rather than
+solving it.
+ */
+
+use crate::board::{Board, Direction, MoveRules};
+use crate::coord::Coord;
+use crate::rng::Rng;
+use crate::transposition::TranspositionTable;
+use failure::Fail;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Approximate memory used by one frontier state, besides its slots.
+const ENTRY_BYTES: usize = 64;
+
+#[derive(Debug, Fail, PartialEq)]
+/// Errors raised while counting solutions.
+pub enum CountError {
+    #[fail(
+        display = "Counting table exceeded the memory limit of {} bytes",
+        limit
+    )]
+    MemoryLimit { limit: usize },
+    #[fail(display = "Solution count does not fit in 64 bits")]
+    Overflow,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The result of a search tree size estimate.
+pub struct TreeEstimate {
+    /// Estimated number of nodes in the full search tree below the position,
+    /// counting the position itself.
+    pub nodes: f64,
+    /// Estimated number of complete solutions reachable from the position.
+    pub solutions: f64,
+    /// Standard error of the `nodes` estimate.
+    pub std_error: f64,
+    /// The number of random probes the estimate is based on.
+    pub samples: usize,
+}
+
+/// Estimate the size of the exhaustive search tree below `board` using
+/// Knuth's random probe method.
+///
+/// Each probe walks a single random path from the position to a dead end,
+/// multiplying the branching factors seen along the way. The sum of these
+/// partial products is an unbiased estimate of the number of nodes in the
+/// tree; averaging `samples` probes reduces its variance. An unstarted board
+/// branches over every possible start cell. The same `seed` always gives the
+/// same estimate.
+pub fn estimate_tree_size(board: &Board, samples: usize, seed: u64) -> TreeEstimate {
+    let mut rng = Rng::new(seed);
+    let mut nodes = Vec::with_capacity(samples);
+    let mut solutions = 0.0;
+    for _ in 0..samples {
+        let (n, s) = probe(board, &mut rng);
+        nodes.push(n);
+        solutions += s;
+    }
+    if samples == 0 {
+        return TreeEstimate {
+            nodes: 0.0,
+            solutions: 0.0,
+            std_error: 0.0,
+            samples,
+        };
+    }
+    let count = samples as f64;
+    let mean = nodes.iter().sum::<f64>() / count;
+    let std_error = if samples > 1 {
+        let var = nodes.iter().map(|n| (n - mean) * (n - mean)).sum::<f64>() / (count - 1.0);
+        (var / count).sqrt()
+    } else {
+        0.0
+    };
+    TreeEstimate {
+        nodes: mean,
+        solutions: solutions / count,
+        std_error,
+        samples,
+    }
+}
+
+/// Walk one random path from `board`, returning the node and solution
+/// estimates for that path.
+fn probe(board: &Board, rng: &mut Rng) -> (f64, f64) {
+    let mut board = board.clone();
+    let mut weight = 1.0;
+    let mut nodes = 1.0;
+    if !board.is_started() {
+        // holes are not start cells
+        let cells: Vec<Coord> = board.empty_cells().collect();
+        let cell = match rng.choose(&cells) {
+            Some(cell) => *cell,
+            None => return (nodes, 0.0),
+        };
+        weight *= board.remaining() as f64;
+        nodes += weight;
+        board = board.start_at(cell).unwrap();
+    }
+    loop {
+        if board.is_won() {
+            return (nodes, weight);
+        }
+        let moves: Vec<Direction> = board.possible_moves().into_iter().collect();
+        if moves.is_empty() {
+            return (nodes, 0.0);
+        }
+        weight *= moves.len() as f64;
+        nodes += weight;
+        let dir = *rng.choose(&moves).unwrap();
+        board = board.next_move(dir).unwrap();
+    }
+}
+
+/// A state of the frontier of `count_solutions_dp`: a byte for each cell of
+/// the frontier, then the number of path ends left behind. A cell's byte
+/// holds its degree in the top two bits and, for a cell ending a fragment
+/// of the path, the slot of the fragment's other end below, or `OUTSIDE`.
+type Frontier = Box<[u8]>;
+
+/// The other end of a fragment that has left the frontier, as an end of
+/// the path.
+const OUTSIDE: u8 = 63;
+
+/// Count the solutions of a `size` x `size` board over every start cell,
+/// exactly, using no more than roughly `max_memory` bytes.
+///
+/// A solution is a Hamiltonian path of the jump graph, walked from either
+/// end, so the counter counts the paths with the frontier ("broken
+/// profile") technique: the cells are taken row by row, deciding for each
+/// jump to a later cell whether the path uses it. Only the cells with a
+/// decision still pending, those up to three rows on, make up the
+/// frontier, and all that matters of the choices behind it is how many
+/// jumps each frontier cell has taken, which cells end the same fragment
+/// of path, and how many ends of the path were left behind. Choices that
+/// agree on those are merged and counted together, so the work grows with
+/// the number of frontier states rather than the number of paths. A cell
+/// leaves the frontier with one jump (an end of the path) or two, and a
+/// fragment may only be closed off once every cell is on it.
+///
+/// The states of each row are split between threads and the counts they
+/// lead to summed back, which is exact. Returns `CountError::MemoryLimit`
+/// if the states outgrow `max_memory`, and `CountError::Overflow` if a
+/// count does not fit in a `u64`; the count is never approximated. The
+/// size is clamped to 5..=16, which keeps the slots in a byte.
+pub fn count_solutions_dp(size: usize, max_memory: usize) -> Result<u64, CountError> {
+    let board = Board::new(size.min(16));
+    let (size, cells) = (board.size(), board.cell_count());
+    let jumps: Vec<Vec<usize>> = (0..cells)
+        .map(|i| {
+            Direction::iterator()
+                .filter_map(|dir| {
+                    let (dx, dy) = dir.delta(&MoveRules::CLASSIC)?;
+                    let x = (i % size) as i32 + dx;
+                    let y = (i / size) as i32 + dy;
+                    if x >= 0 && y >= 0 && x < size as i32 && y < size as i32 {
+                        Some(y as usize * size + x as usize)
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+    let mut frontier: Vec<usize> = Vec::new();
+    let mut states: HashMap<Frontier, u64> = HashMap::new();
+    states.insert(Box::new([0]), 1);
+    for cell in 0..cells {
+        let before = frontier.len();
+        let later: Vec<usize> = jumps[cell]
+            .iter()
+            .copied()
+            .filter(|&to| to > cell)
+            .collect();
+        for &joining in std::iter::once(&cell).chain(&later) {
+            if !frontier.contains(&joining) {
+                frontier.push(joining);
+            }
+        }
+        let slot = |c: usize| frontier.iter().position(|&f| f == c).unwrap();
+        let mut step = Step {
+            joined: frontier.len() - before,
+            edges: later.iter().map(|&to| (slot(cell), slot(to))).collect(),
+            leaving: slot(cell),
+            pending: Vec::new(),
+            rest: false,
+        };
+        frontier.remove(step.leaving);
+        step.rest = frontier.len() == cells - cell - 1;
+        step.pending = frontier
+            .iter()
+            .map(|&f| jumps[f].iter().filter(|&&to| to > cell).count())
+            .collect();
+
+        // the states a row leads to are held twice while they are merged, so
+        // the workers share half the memory and the merged table the rest
+        let entry = ENTRY_BYTES + frontier.len();
+        let limit = CountError::MemoryLimit { limit: max_memory };
+        let current: Vec<(Frontier, u64)> = states.into_iter().collect();
+        let chunk = current.len().div_ceil(threads).max(1);
+        let step = &step;
+        let parts: Vec<Result<HashMap<Frontier, u64>, CountError>> = thread::scope(|scope| {
+            let workers: Vec<_> = current
+                .chunks(chunk)
+                .map(|part| {
+                    scope.spawn(move || {
+                        let mut next = HashMap::new();
+                        for (state, count) in part {
+                            step.advance(state, *count, &mut next)?;
+                            if next.len() * entry > max_memory / 2 / threads {
+                                return Err(CountError::MemoryLimit { limit: max_memory });
+                            }
+                        }
+                        Ok(next)
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        states = HashMap::new();
+        for part in parts {
+            for (state, count) in part? {
+                add_count(states.entry(state).or_insert(0), count)?;
+                if states.len() * entry > max_memory / 2 {
+                    return Err(limit);
+                }
+            }
+        }
+    }
+    // every path is a solution from either end
+    let paths = states.get(&[2][..]).copied().unwrap_or(0);
+    paths.checked_mul(2).ok_or(CountError::Overflow)
+}
+
+/// Add `count` to `total`, failing rather than wrapping.
+fn add_count(total: &mut u64, count: u64) -> Result<(), CountError> {
+    *total = total.checked_add(count).ok_or(CountError::Overflow)?;
+    Ok(())
+}
+
+/// Step is the work of one cell of `count_solutions_dp`, in terms of the
+/// slots of the frontier: the cells joining it, the jumps from the cell to
+/// later cells and the slot of the cell, which leaves it.
+struct Step {
+    /// The number of cells added at the end of the frontier.
+    joined: usize,
+    /// The jumps to decide, as pairs of slots.
+    edges: Vec<(usize, usize)>,
+    /// The slot of the cell leaving the frontier.
+    leaving: usize,
+    /// The number of jumps still to decide from each cell of the frontier
+    /// once the cell has left.
+    pending: Vec<usize>,
+    /// `true` if every cell after this one is on the frontier once it has
+    /// left.
+    rest: bool,
+}
+
+impl Step {
+    /// Add the states `state` leads to, each `count` times, to `next`.
+    fn advance(
+        &self,
+        state: &[u8],
+        count: u64,
+        next: &mut HashMap<Frontier, u64>,
+    ) -> Result<(), CountError> {
+        let (slots, ends) = state.split_at(state.len() - 1);
+        let mut cells = slots.to_vec();
+        cells.resize(slots.len() + self.joined, 0);
+        self.decide(0, &mut cells, ends[0], count, next)
+    }
+
+    /// Decide the jumps from `edge` on, both ways, then take the cell off
+    /// the frontier.
+    fn decide(
+        &self,
+        edge: usize,
+        cells: &mut Vec<u8>,
+        ends: u8,
+        count: u64,
+        next: &mut HashMap<Frontier, u64>,
+    ) -> Result<(), CountError> {
+        if edge == self.edges.len() {
+            return self.leave(cells, ends, count, next);
+        }
+        self.decide(edge + 1, cells, ends, count, next)?;
+        let (a, b) = self.edges[edge];
+        let (degree_a, degree_b) = (cells[a] >> 6, cells[b] >> 6);
+        // the other end of the fragment each cell ends
+        let other = |slot: usize, degree: u8| {
+            if degree == 0 {
+                slot as u8
+            } else {
+                cells[slot] & OUTSIDE
+            }
+        };
+        let (end_a, end_b) = (other(a, degree_a), other(b, degree_b));
+        // the jump is taken unless it would close a loop
+        if degree_a == 2 || degree_b == 2 || end_a == b as u8 {
+            return Ok(());
+        }
+        let saved = cells.clone();
+        cells[a] = (degree_a + 1) << 6;
+        cells[b] = (degree_b + 1) << 6;
+        for (end, mate) in [(end_a, end_b), (end_b, end_a)] {
+            if end != OUTSIDE {
+                cells[end as usize] = cells[end as usize] & !OUTSIDE | mate;
+            }
+        }
+        self.decide(edge + 1, cells, ends, count, next)?;
+        *cells = saved;
+        Ok(())
+    }
+
+    /// Take the cell off the frontier and record the state left, unless it
+    /// can no longer become a path.
+    fn leave(
+        &self,
+        cells: &[u8],
+        ends: u8,
+        count: u64,
+        next: &mut HashMap<Frontier, u64>,
+    ) -> Result<(), CountError> {
+        let v = self.leaving;
+        let mate = cells[v] & OUTSIDE;
+        let ends = match cells[v] >> 6 {
+            0 => return Ok(()),
+            // a fragment with both ends left behind can take no more cells,
+            // so every cell must be on it already
+            1 if mate == OUTSIDE => {
+                let full = cells
+                    .iter()
+                    .enumerate()
+                    .all(|(u, &c)| u == v || c >> 6 == 2);
+                if !self.rest || !full {
+                    return Ok(());
+                }
+                ends + 1
+            }
+            1 => ends + 1,
+            _ => ends,
+        };
+        let mut state: Vec<u8> = Vec::with_capacity(cells.len());
+        let mut forced = 0;
+        for (_, &cell) in cells.iter().enumerate().filter(|&(u, _)| u != v) {
+            let degree = cell >> 6;
+            let mut other = cell & OUTSIDE;
+            if degree == 1 && other != OUTSIDE {
+                if other as usize == v {
+                    other = OUTSIDE;
+                } else if other as usize > v {
+                    other -= 1;
+                }
+            }
+            // a cell that can not reach two jumps ends the path
+            let pending = self.pending[state.len()];
+            if degree as usize + pending < 2 {
+                forced += 1;
+            }
+            state.push(if degree == 1 {
+                1 << 6 | other
+            } else {
+                degree << 6
+            });
+        }
+        if ends + forced > 2 {
+            return Ok(());
+        }
+        state.push(ends);
+        add_count(next.entry(state.into_boxed_slice()).or_insert(0), count)
+    }
+}
+
+/// `count_solutions` reports its progress after every this many positions.
+pub const COUNT_PROGRESS_INTERVAL: u64 = 1 << 20;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// CountConfig sets the limits of `count_solutions`.
+pub struct CountConfig {
+    /// Stop after visiting this many positions; `None` searches the whole
+    /// tree.
+    pub node_limit: Option<u64>,
+    /// Count solutions that are rotations or reflections of each other
+    /// once.
+    pub modulo_symmetry: bool,
+    /// The number of threads to search on; 0 and 1 both search on the
+    /// calling thread.
+    pub threads: usize,
+    /// The memory, in bytes, of the table of positions known to have no
+    /// completion, shared out equally between the threads; 0 keeps no
+    /// table. See `transposition`.
+    pub tt_size: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+/// The result of `count_solutions`, or its progress so far.
+pub struct SolutionCount {
+    /// The number of solutions found. If the count is not complete this is
+    /// a lower bound.
+    pub solutions: u64,
+    /// The number of positions visited.
+    pub nodes: u64,
+    /// `false` if the node limit stopped the search early.
+    pub complete: bool,
+}
+
+/// Count the solutions below `board` by walking the whole search tree, or
+/// until `config.node_limit` positions have been visited. An unstarted board
+/// is counted over every start cell. `progress` is called with the count so
+/// far every `COUNT_PROGRESS_INTERVAL` positions.
+///
+/// Start cells that are rotations or reflections of each other have the
+/// same number of solutions, so only one start of each such set is searched.
+/// No symmetry maps a complete solution onto itself, so counting modulo
+/// symmetry divides the count by the number of symmetries of the position.
+///
+/// Positions found to have no completion are remembered, in a table of
+/// `config.tt_size` bytes, and not searched again when another path reaches
+/// them. The table has a fixed size, so memory use does not grow with the
+/// search; which positions it keeps, and so the number of positions
+/// visited, depends on its size.
+///
+/// With `config.threads` above 1 the starts, or the moves from a started
+/// board, are shared out between threads. The count is the same, but
+/// `progress` is then called from the calling thread as the node total
+/// passes each multiple of the interval, and a limited search may stop in
+/// a different place. Each thread keeps a table of its own, and the number
+/// of positions visited then varies from run to run.
+pub fn count_solutions(
+    board: &Board,
+    config: &CountConfig,
+    progress: &mut dyn FnMut(&SolutionCount),
+) -> SolutionCount {
+    // the transforms of `Board::symmetries` that leave the position as it is
+    let group: Vec<usize> = board
+        .symmetries()
+        .enumerate()
+        .filter(|(_, image)| image == board)
+        .map(|(transform, _)| transform)
+        .collect();
+    let limit = config.node_limit.unwrap_or(u64::MAX);
+    let divisor = if config.modulo_symmetry {
+        group.len() as u64
+    } else {
+        1
+    };
+    // the positions to walk, each with the number of starts it stands for
+    let mut items: Vec<(Board, u64)> = Vec::new();
+    let mut nodes = 0;
+    if !board.is_started() {
+        let mut seen = HashSet::new();
+        for cell in board.empty_cells() {
+            let start = match board.clone().start_at(cell) {
+                Ok(start) => start,
+                Err(_) => continue,
+            };
+            if seen.contains(&start) {
+                continue;
+            }
+            let orbit: HashSet<Board> = start
+                .symmetries()
+                .enumerate()
+                .filter(|(transform, _)| group.contains(transform))
+                .map(|(_, image)| image)
+                .collect();
+            items.push((start, orbit.len() as u64));
+            seen.extend(orbit);
+        }
+    } else if config.threads > 1 && !board.is_won() && limit > 0 {
+        // share out the moves from the position, which counts as visited
+        nodes = 1;
+        for dir in board.possible_moves() {
+            items.push((board.clone().next_move(dir).unwrap(), 1));
+        }
+    } else {
+        items.push((board.clone(), 1));
+    }
+
+    if config.threads <= 1 {
+        let mut search = Enumeration {
+            limit,
+            nodes,
+            found: 0,
+            solutions: 0,
+            weight: 1,
+            divisor,
+            progress,
+            tally: None,
+            dead: TranspositionTable::with_memory(config.tt_size),
+        };
+        let complete = items.into_iter().all(|(mut start, weight)| {
+            search.weight = weight;
+            search.walk(&mut start)
+        });
+        return search.count(complete);
+    }
+
+    let tally = Tally {
+        nodes: AtomicU64::new(nodes),
+        found: AtomicU64::new(0),
+        next: AtomicUsize::new(0),
+        stopped: AtomicBool::new(false),
+    };
+    let count = |complete| SolutionCount {
+        solutions: tally.found.load(Ordering::Relaxed) / divisor,
+        nodes: tally.nodes.load(Ordering::Relaxed),
+        complete,
+    };
+    let workers = config.threads.min(items.len());
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut ignore = |_: &SolutionCount| {};
+                    let mut search = Enumeration {
+                        limit,
+                        nodes: 0,
+                        found: 0,
+                        solutions: 0,
+                        weight: 1,
+                        divisor,
+                        progress: &mut ignore,
+                        tally: Some(&tally),
+                        dead: TranspositionTable::with_memory(config.tt_size / workers.max(1)),
+                    };
+                    while let Some((start, weight)) =
+                        items.get(tally.next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        search.weight = *weight;
+                        if !search.walk(&mut start.clone()) {
+                            tally.stopped.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+        // the workers cannot call `progress`, so report for them
+        let mut reported = 0;
+        while !workers.iter().all(|worker| worker.is_finished()) {
+            thread::sleep(Duration::from_millis(50));
+            let so_far = count(false);
+            if so_far.nodes / COUNT_PROGRESS_INTERVAL > reported {
+                reported = so_far.nodes / COUNT_PROGRESS_INTERVAL;
+                progress(&so_far);
+            }
+        }
+    });
+    count(!tally.stopped.load(Ordering::Relaxed))
+}
+
+/// Tally is the state shared by the threads of a `count_solutions` search.
+struct Tally {
+    /// The positions visited by all threads.
+    nodes: AtomicU64,
+    /// The solutions found by all threads, weighted.
+    found: AtomicU64,
+    /// The next item to hand out.
+    next: AtomicUsize,
+    /// Set once the node limit stops a thread.
+    stopped: AtomicBool,
+}
+
+/// Enumeration is the state of one `count_solutions` search.
+struct Enumeration<'a> {
+    /// The most positions to visit.
+    limit: u64,
+    /// The positions visited so far.
+    nodes: u64,
+    /// The solutions found so far, each counted `weight` times.
+    found: u64,
+    /// The solutions found so far by this search, each counted once.
+    solutions: u64,
+    /// The number of starts the current start stands for.
+    weight: u64,
+    /// What `found` is divided by to count modulo symmetry.
+    divisor: u64,
+    progress: &'a mut dyn FnMut(&SolutionCount),
+    /// The shared counts, if this is one thread of a parallel search; the
+    /// node limit then applies to them.
+    tally: Option<&'a Tally>,
+    /// Positions known to have no completion.
+    dead: TranspositionTable,
+}
+
+impl<'a> Enumeration<'a> {
+    /// Count the solutions below `board`, returning `false` if the node
+    /// limit was reached first. The board is left as it was.
+    fn walk(&mut self, board: &mut Board) -> bool {
+        if let Some(tally) = self.tally {
+            let limit = self.limit;
+            let visit = tally
+                .nodes
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |nodes| {
+                    (nodes < limit).then_some(nodes + 1)
+                });
+            if visit.is_err() {
+                return false;
+            }
+        } else {
+            if self.nodes >= self.limit {
+                return false;
+            }
+            self.nodes += 1;
+            if self.nodes.is_multiple_of(COUNT_PROGRESS_INTERVAL) {
+                let count = self.count(false);
+                (self.progress)(&count);
+            }
+        }
+        if board.is_won() {
+            match self.tally {
+                Some(tally) => {
+                    tally.found.fetch_add(self.weight, Ordering::Relaxed);
+                }
+                None => self.found += self.weight,
+            }
+            self.solutions += 1;
+            return true;
+        }
+        let moves = board.possible_moves();
+        // a position without moves is cheaper to see than to look up
+        if moves.is_empty() || self.dead.contains(board.zobrist()) {
+            return true;
+        }
+        let before = self.solutions;
+        for dir in moves {
+            board.make_move(dir).unwrap();
+            let complete = self.walk(board);
+            board.unmake_move().unwrap();
+            if !complete {
+                return false;
+            }
+        }
+        if self.solutions == before {
+            self.dead.insert(board.zobrist(), board.remaining());
+        }
+        true
+    }
+
+    /// Return the count so far.
+    fn count(&self, complete: bool) -> SolutionCount {
+        SolutionCount {
+            solutions: self.found / self.divisor,
+            nodes: self.nodes,
+            complete,
+        }
+    }
+}
+
+/// The number of random probes used to estimate the cost of a request.
+const CAPABILITY_SAMPLES: usize = 200;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Capability is one kind of answer the crate can give about a position.
+pub enum Capability {
+    /// Estimate the size of the search tree and the number of solutions.
+    Estimate,
+    /// Try to find a solution with a heuristic, without any guarantee.
+    HeuristicSolve,
+    /// Find a long path, not necessarily the longest possible.
+    LongestPath,
+    /// Search the whole tree: prove (un)solvability and count solutions.
+    ExhaustiveSearch,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Capability::Estimate => "estimate",
+            Capability::HeuristicSolve => "heuristic solve",
+            Capability::LongestPath => "longest path",
+            Capability::ExhaustiveSearch => "exhaustive search",
+        };
+        f.pad(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Availability tells whether a capability can be used on a position.
+pub enum Availability {
+    /// The capability gives its full answer within the budget.
+    Available,
+    /// The capability runs within the budget but only gives a best effort
+    /// answer; the reason says what is lost.
+    BestEffort(String),
+    /// The capability would not finish within the budget.
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// CapabilityReport lists what can be done with a position within a node
+/// budget, ordered from cheapest to most expensive.
+pub struct CapabilityReport {
+    /// The tree size estimate the report is based on.
+    pub estimate: TreeEstimate,
+    /// The node budget the report was made for.
+    pub budget: u64,
+    /// Each capability and its availability.
+    pub capabilities: Vec<(Capability, Availability)>,
+}
+
+impl CapabilityReport {
+    /// Return `true` if `capability` gives its full answer.
+    pub fn is_available(&self, capability: Capability) -> bool {
+        self.capabilities
+            .iter()
+            .any(|(c, a)| *c == capability && *a == Availability::Available)
+    }
+}
+
+/// Report which capabilities remain usable on `board` if at most `budget`
+/// search nodes may be spent.
+///
+/// Rather than letting an exhaustive request hang on a board that is too
+/// large, callers can query this first and offer the user the options that
+/// will actually finish. The decision is based on a quick, deterministic
+/// `estimate_tree_size`; estimation and heuristics are linear in the board
+/// size and always remain.
+pub fn capabilities(board: &Board, budget: u64) -> CapabilityReport {
+    let estimate = estimate_tree_size(board, CAPABILITY_SAMPLES, 0);
+    let exhaustive = if estimate.nodes <= budget as f64 {
+        Availability::Available
+    } else {
+        Availability::Unavailable(format!(
+            "about {:.3e} nodes, over the budget of {}",
+            estimate.nodes, budget
+        ))
+    };
+    let longest = if exhaustive == Availability::Available {
+        Availability::Available
+    } else {
+        Availability::BestEffort("the path found may not be the longest".to_string())
+    };
+    CapabilityReport {
+        estimate,
+        budget,
+        capabilities: vec![
+            (Capability::Estimate, Availability::Available),
+            (
+                Capability::HeuristicSolve,
+                Availability::BestEffort("may fail on solvable positions".to_string()),
+            ),
+            (Capability::LongestPath, longest),
+            (Capability::ExhaustiveSearch, exhaustive),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SOLUTION_5, SOLUTION_COUNT_5};
+
+    // Exhaustively count the nodes and solutions below `board`.
+    fn exact(board: &mut Board) -> (u64, u64) {
+        if board.is_won() {
+            return (1, 1);
+        }
+        let mut nodes = 1;
+        let mut solutions = 0;
+        for dir in board.possible_moves() {
+            let (n, s) = exact(&mut board.next_move(dir).unwrap());
+            nodes += n;
+            solutions += s;
+        }
+        (nodes, solutions)
+    }
+
+    fn partial_5() -> Board {
+        SOLUTION_5.board_after(7)
+    }
+
+    #[test]
+    fn count_dp_matches_brute_force() {
+        let mut brute = 0;
+        for y in 0..5 {
+            for x in 0..5 {
+                brute += exact(&mut Board::new(5).start_at(Coord::new(x, y)).unwrap()).1;
+            }
+        }
+        assert_eq!(brute, SOLUTION_COUNT_5);
+        assert_eq!(count_solutions_dp(5, 1 << 30), Ok(brute));
+        assert_eq!(
+            count_solutions_dp(5, 1024),
+            Err(CountError::MemoryLimit { limit: 1024 })
+        );
+    }
+
+    #[test]
+    fn count_matches_brute_force() {
+        let none = CountConfig::default();
+        let modulo = CountConfig {
+            modulo_symmetry: true,
+            ..CountConfig::default()
+        };
+        let mut corner = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        let (nodes, solutions) = exact(&mut corner);
+        let count = count_solutions(&corner, &none, &mut |_| {});
+        assert_eq!(
+            count,
+            SolutionCount {
+                solutions,
+                nodes,
+                complete: true
+            }
+        );
+        // the corner lies on a diagonal, the only other symmetry it keeps
+        let count = count_solutions(&corner, &modulo, &mut |_| {});
+        assert_eq!(count.solutions, solutions / 2);
+
+        let board = Board::new(5);
+        let count = count_solutions(&board, &none, &mut |_| {});
+        assert_eq!(count.solutions, SOLUTION_COUNT_5);
+        assert!(count.complete);
+        let count = count_solutions(&board, &modulo, &mut |_| {});
+        assert_eq!(count.solutions, SOLUTION_COUNT_5 / 8);
+        let threaded = CountConfig {
+            threads: 3,
+            ..modulo.clone()
+        };
+        let count = count_solutions(&board, &threaded, &mut |_| {});
+        assert_eq!(count.solutions, SOLUTION_COUNT_5 / 8);
+        let count = count_solutions(&corner, &threaded, &mut |_| {});
+        assert_eq!((count.solutions, count.nodes), (solutions / 2, nodes));
+
+        // remembering dead positions saves work, whatever the table size
+        for tt_size in [64, 1 << 10, 1 << 20] {
+            let remembered = CountConfig {
+                tt_size,
+                ..CountConfig::default()
+            };
+            let count = count_solutions(&corner, &remembered, &mut |_| {});
+            assert_eq!(count.solutions, solutions);
+            assert!(count.complete && count.nodes < nodes);
+            let count = count_solutions(&board, &remembered, &mut |_| {});
+            assert_eq!(count.solutions, SOLUTION_COUNT_5);
+            let threaded = CountConfig {
+                threads: 3,
+                ..remembered
+            };
+            let count = count_solutions(&board, &threaded, &mut |_| {});
+            assert_eq!(count.solutions, SOLUTION_COUNT_5);
+        }
+
+        let mut reports = 0;
+        let limited = CountConfig {
+            node_limit: Some(COUNT_PROGRESS_INTERVAL + 10),
+            ..CountConfig::default()
+        };
+        let count = count_solutions(&Board::new(6), &limited, &mut |_| reports += 1);
+        assert!(!count.complete);
+        assert_eq!(count.nodes, COUNT_PROGRESS_INTERVAL + 10);
+        assert_eq!(reports, 1);
+    }
+
+    #[test]
+    fn capabilities_degrade_with_size() {
+        let small = capabilities(&partial_5(), 1_000_000);
+        assert!(small.is_available(Capability::Estimate));
+        assert!(small.is_available(Capability::ExhaustiveSearch));
+        assert!(small.is_available(Capability::LongestPath));
+        assert!(!small.is_available(Capability::HeuristicSolve));
+
+        let large = capabilities(&Board::new(15), 1_000_000);
+        assert!(large.is_available(Capability::Estimate));
+        assert!(!large.is_available(Capability::ExhaustiveSearch));
+        assert!(matches!(
+            large.capabilities[3],
+            (Capability::ExhaustiveSearch, Availability::Unavailable(_))
+        ));
+        assert!(matches!(
+            large.capabilities[2],
+            (Capability::LongestPath, Availability::BestEffort(_))
+        ));
+    }
+
+    #[test]
+    fn estimate_is_deterministic() {
+        let board = partial_5();
+        assert_eq!(
+            estimate_tree_size(&board, 100, 7),
+            estimate_tree_size(&board, 100, 7)
+        );
+        let none = estimate_tree_size(&board, 0, 7);
+        assert_eq!(none.nodes, 0.0);
+        assert_eq!(none.samples, 0);
+    }
+
+    #[test]
+    fn estimate_is_close_to_exact() {
+        let mut board = partial_5();
+        let (nodes, solutions) = exact(&mut board);
+        let estimate = estimate_tree_size(&board, 20_000, 1);
+        let error = (estimate.nodes - nodes as f64).abs();
+        assert!(
+            error < 5.0 * estimate.std_error + 1.0,
+            "estimate {:?}, exact {}",
+            estimate,
+            nodes
+        );
+        assert!(solutions > 0);
+        assert!(estimate.solutions > 0.0);
+    }
+
+    #[test]
+    fn unstarted_board_branches_over_starts() {
+        let estimate = estimate_tree_size(&Board::new(5), 10, 3);
+        assert!(estimate.nodes > 25.0);
+    }
+
+    #[test]
+    fn holes_are_not_start_cells() {
+        let mut board = Board::new(5);
+        board.block_cell(Coord::new(0, 0)).unwrap();
+        board.block_cell(Coord::new(4, 4)).unwrap();
+        // each probe branches over the 23 cells left, as the root and its
+        // starts are counted before the first move
+        let estimate = estimate_tree_size(&board, 200, 3);
+        assert_eq!(estimate.samples, 200);
+        assert!(estimate.nodes >= 24.0);
+        capabilities(&board, 1_000);
+    }
+}
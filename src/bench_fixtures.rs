@@ -0,0 +1,87 @@
+/*!
+Deterministic boards for benchmarks.
+
+A timing is only worth comparing with another if both measured the same
+work. The boards here are built from fixed games and seeds, so every run,
+on every machine, starts from the same positions: the crate's criterion
+suite in `benches/` uses them, and so can downstream benchmarks.
+
+The positions are taken from the known games of `fixtures`, whose
+solutions are checked by the crate's tests, so a benchmark of a solve is
+known to time a search that succeeds.
+ */
+
+use crate::agent;
+use crate::board::Board;
+use crate::coord::Coord;
+use crate::fixtures::{solutions_10, SOLUTION_5};
+
+/// The seed of every random choice made by this module.
+pub const SEED: u64 = 100;
+
+/// Return an empty `size` x `size` board.
+pub fn empty(size: usize) -> Board {
+    Board::new(size)
+}
+
+/// Return a `size` x `size` board with the 1 in the top-left corner.
+pub fn corner_start(size: usize) -> Board {
+    Board::new(size).start_at(Coord::new(0, 0)).unwrap()
+}
+
+/// Return a `size` x `size` board after a random opening of `moves` moves,
+/// the same on every call.
+pub fn opening(size: usize, moves: usize) -> Board {
+    agent::random_opening(&Board::new(size), moves, SEED)
+}
+
+/// Return the 5x5 game of `fixtures::SOLUTION_5` after `count` moves.
+pub fn game_5(count: usize) -> Board {
+    SOLUTION_5.board_after(count)
+}
+
+/// Return the first solved 10x10 board of `fixtures::SOLUTIONS_10` cut
+/// back to its first `count` moves. Every such position has a completion.
+pub fn game_10(count: usize) -> Board {
+    let mut board = solutions_10().swap_remove(0);
+    while board.score() > count + 1 {
+        board.unmake_move().unwrap();
+    }
+    board
+}
+
+/// Return every position of the first solved 10x10 game, from the start
+/// to the full board: a mix of open, crowded and finished positions.
+pub fn positions_10() -> Vec<Board> {
+    let mut board = solutions_10().swap_remove(0);
+    let mut positions = vec![board.clone()];
+    while board.score() > 1 {
+        board.unmake_move().unwrap();
+        positions.push(board.clone());
+    }
+    positions.reverse();
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boards_are_repeatable() {
+        assert_eq!(opening(10, 20), opening(10, 20));
+        assert_eq!(opening(10, 20).score(), 21);
+        assert_eq!(corner_start(8).value_at(Coord::new(0, 0)), 1);
+        assert!(!empty(6).is_started());
+        assert_eq!(game_5(24), SOLUTION_5.board());
+
+        let positions = positions_10();
+        assert_eq!(positions.len(), 100);
+        for (count, board) in positions.iter().enumerate() {
+            assert_eq!(board.score(), count + 1);
+            assert_eq!(board.validate(), Ok(()));
+        }
+        assert_eq!(game_10(40), positions[40]);
+        assert!(positions[99].is_won());
+    }
+}
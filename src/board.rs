@@ -25,12 +25,32 @@ top-left corner
 
 //#[deny(missing_docs)]
 
+use crate::cells::{Cells, INLINE_CELLS};
+use crate::coord::{Coord, Delta};
+use crate::format::{Artifact, Format, FormatError, Validate};
+use crate::neighbors::Neighbors;
+#[cfg(feature = "simd")]
+use crate::scan;
+use crate::zobrist;
 use failure::Fail;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::ops::{Deref, Index};
 use std::slice::Iter;
+use std::str::FromStr;
+use std::sync::Arc;
 
 #[derive(Debug, Fail, PartialEq)]
-/// Custom Error for invalid board actions.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Custom Error for invalid board actions. Each kind of failure is its own
+/// variant carrying the cells and values involved, so callers can match on
+/// it instead of on the message.
 pub enum BoardError {
     #[fail(display = "Board not started.")]
     NotStarted, // Board not started
@@ -47,7 +67,7 @@ pub enum BoardError {
         display = "cannot set cell [{}, {}] = {}: value already used",
         x, y, value
     )]
-    ValueUsed { x: usize, y: usize, value: u8 }, // cannot set cell [{}, {}] = {}, value already used
+    ValueUsed { x: usize, y: usize, value: u16 }, // cannot set cell [{}, {}] = {}, value already used
     #[fail(
         display = "Cannot set cell [{},{}] = {}: value larger than {}",
         x, y, value, max
@@ -55,13 +75,127 @@ pub enum BoardError {
     ValueOutOfRange {
         x: usize,
         y: usize,
-        value: u8,
+        value: u16,
         max: usize,
     }, // cannot set cell [{}, {}] = {}, larger than {}
     #[fail(display = "Cell [{},{}] is not empty", x, y)]
     NotEmpty { x: usize, y: usize }, // cell [{}, {}] is not empty
+    #[fail(display = "Invalid cell '{}' in row {}", token, row)]
+    InvalidToken { row: usize, token: String }, // invalid cell '{}' in row {}
+    #[fail(display = "Invalid board dimensions {}x{}", cols, rows)]
+    InvalidDimensions { cols: usize, rows: usize }, // invalid board dimensions {}x{}
+    #[fail(display = "Value {} is missing from the board", value)]
+    MissingValue { value: u16 }, // value {} is missing from the board
+    #[fail(display = "{}", _0)]
+    Format(#[cause] FormatError), // error in the artifact header
+    #[fail(display = "Move {} failed: {}", index, error)]
+    MoveFailed {
+        index: usize,
+        error: Box<BoardError>,
+    }, // move {} failed: {}
+    #[fail(display = "Values {} and {} are not a legal jump apart", from, to)]
+    IllegalJump { from: u16, to: u16 }, // values {} and {} are not a legal jump apart
+    #[fail(
+        display = "Current position [{},{}] does not hold the highest value",
+        x, y
+    )]
+    BadPosition { x: usize, y: usize }, // current position [{},{}] does not hold the highest value
+    #[fail(display = "Cell [{},{}] is a hole", x, y)]
+    Hole { x: usize, y: usize }, // cell [{},{}] is a hole
+    #[fail(display = "Malformed binary board at byte {}", offset)]
+    BadBinary { offset: usize }, // malformed binary board at byte {}
+    #[fail(display = "Invalid move rules '{}'", rules)]
+    InvalidRules { rules: String }, // invalid move rules '{}'
+}
+
+impl BoardError {
+    /// Return the error behind any `MoveFailed` wrappers, the one to match
+    /// on to tell what went wrong.
+    pub fn root(&self) -> &BoardError {
+        match self {
+            BoardError::MoveFailed { error, .. } => error.root(),
+            error => error,
+        }
+    }
+}
+
+impl From<FormatError> for BoardError {
+    fn from(err: FormatError) -> Self {
+        BoardError::Format(err)
+    }
+}
+
+#[derive(Debug, Fail, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[fail(
+    display = "Invalid direction '{}': expected e.g. Down Right or DR",
+    name
+)]
+/// Error raised when parsing a `Direction`.
+pub struct DirectionError {
+    name: String,
+}
+
+/// Return the images of (x, y) under the rotations and reflections of a
+/// `width` x `height` board, the identity first: eight for a square board,
+/// four (the reflections) for a rectangular one. The order is the same for
+/// every cell, so index `i` always names the same transformation.
+pub(crate) fn symmetric_cells(
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+) -> Vec<(usize, usize)> {
+    let (mx, my) = (width - 1, height - 1);
+    let mut cells = vec![(x, y), (mx - x, y), (x, my - y), (mx - x, my - y)];
+    if width == height {
+        cells.extend_from_slice(&[(y, x), (my - y, x), (y, mx - x), (my - y, mx - x)]);
+    }
+    cells
+}
+
+/// The text grid format of a `Board`, as produced by its `Display` impl.
+/// Version 2 replaced the `torus` line of version 1 with a `rules` line
+/// giving the jump of each direction.
+pub const BOARD_FORMAT: Format = Format {
+    kind: "board",
+    version: 2,
+    migrations: &[board_v1_to_v2],
+};
+
+// Version 1 only wrote whether the classic rules were played on a torus.
+fn board_v1_to_v2(body: &str) -> Result<String, FormatError> {
+    let mut lines: Vec<String> = body.lines().map(str::to_string).collect();
+    if let Some(first) = lines.iter_mut().find(|line| !line.trim().is_empty()) {
+        if first.trim() == "torus" {
+            *first = format!("rules {}", MoveRules::CLASSIC.toroidal());
+        }
+    }
+    Ok(lines.join("\n"))
 }
 
+/// The first bytes of a board in the binary format of `Board::to_bytes`.
+pub const BINARY_MAGIC: [u8; 2] = *b"SQ";
+
+/// The version of the binary format written by `Board::to_bytes`.
+pub const BINARY_VERSION: u8 = 1;
+
+// Flags of the binary format.
+const BINARY_TORUS: u8 = 1;
+const BINARY_HOLES: u8 = 2;
+const BINARY_RULES: u8 = 4;
+
+/// The smallest number of cells in a row or column of a board.
+pub const MIN_SIZE: usize = 5;
+
+/// The largest number of cells in a row or column of a board. The values of
+/// a board this size still fit in a `u16`.
+pub const MAX_SIZE: usize = 255;
+
+/// The largest number of cells a move may jump along either axis, so that
+/// an offset fits in the signed bytes of the binary format.
+pub const MAX_JUMP: i32 = i8::MAX as i32;
+
 /// Distance from source for horizontal or vertical moves.
 pub const HV_OFFSET: i32 = 3;
 
@@ -69,6 +203,7 @@ pub const HV_OFFSET: i32 = 3;
 pub const DIAG_OFFSET: i32 = 2;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// Direction represents the direction of a move from the source location.
 pub enum Direction {
     Down,
@@ -81,19 +216,422 @@ pub enum Direction {
     DownLeft,
 }
 
+/// Every direction, counterclockwise from `Down`.
+static DIRECTIONS: [Direction; 8] = [
+    Direction::Down,
+    Direction::DownRight,
+    Direction::Right,
+    Direction::UpRight,
+    Direction::Up,
+    Direction::UpLeft,
+    Direction::Left,
+    Direction::DownLeft,
+];
+
 impl Direction {
     pub fn iterator() -> Iter<'static, Direction> {
-        static DIRECTIONS: [Direction; 8] = [
-            Direction::Down,
-            Direction::DownRight,
-            Direction::Right,
-            Direction::UpRight,
-            Direction::Up,
-            Direction::UpLeft,
-            Direction::Left,
-            Direction::DownLeft,
-        ];
-        DIRECTIONS.into_iter()
+        DIRECTIONS.iter()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+/// Moves is a set of directions held as one bit per direction, so it is
+/// built and passed around without allocating. It iterates in the order of
+/// `Direction::iterator`.
+pub struct Moves {
+    bits: u8,
+}
+
+impl Moves {
+    /// Return the number of directions in the set.
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Return `true` if the set has no directions.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Return `true` if `dir` is in the set.
+    pub fn contains(&self, dir: Direction) -> bool {
+        self.bits & (1 << dir as u8) != 0
+    }
+
+    /// Add `dir` to the set.
+    pub fn insert(&mut self, dir: Direction) {
+        self.bits |= 1 << dir as u8;
+    }
+
+    /// Return the first direction in the set, if any.
+    pub fn first(&self) -> Option<Direction> {
+        self.iter().next()
+    }
+
+    /// Return an iterator over the directions in the set.
+    pub fn iter(&self) -> MovesIter {
+        MovesIter { bits: self.bits }
+    }
+}
+
+impl FromIterator<Direction> for Moves {
+    fn from_iter<I: IntoIterator<Item = Direction>>(dirs: I) -> Self {
+        let mut moves = Moves::default();
+        for dir in dirs {
+            moves.insert(dir);
+        }
+        moves
+    }
+}
+
+impl IntoIterator for Moves {
+    type Item = Direction;
+    type IntoIter = MovesIter;
+
+    fn into_iter(self) -> MovesIter {
+        self.iter()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// MovesIter iterates over the directions of a `Moves` set.
+pub struct MovesIter {
+    bits: u8,
+}
+
+impl Iterator for MovesIter {
+    type Item = Direction;
+
+    fn next(&mut self) -> Option<Direction> {
+        if self.bits == 0 {
+            return None;
+        }
+        let dir = DIRECTIONS[self.bits.trailing_zeros() as usize];
+        self.bits &= self.bits - 1;
+        Some(dir)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for MovesIter {}
+
+impl Direction {
+    /// Return the (x, y) offset of a move in this direction under `rules`,
+    /// or `None` if the rules do not allow it.
+    pub fn delta(self, rules: &MoveRules) -> Option<(i32, i32)> {
+        rules.delta(self)
+    }
+
+    /// Return the direction pointing the other way.
+    pub fn opposite(self) -> Direction {
+        self.turn(4)
+    }
+
+    /// Return the direction a quarter turn clockwise, as it appears when
+    /// the board is turned that way; `Down` becomes `Left`.
+    pub fn rotate_cw(self) -> Direction {
+        self.turn(6)
+    }
+
+    /// Return the direction a quarter turn counterclockwise; `Down` becomes
+    /// `Right`.
+    pub fn rotate_ccw(self) -> Direction {
+        self.turn(2)
+    }
+
+    /// Return the direction in compact notation: the initials of its name,
+    /// `D`, `DR`, `R`, `UR`, `U`, `UL`, `L` or `DL`.
+    pub fn to_compact(self) -> &'static str {
+        match self {
+            Direction::Down => "D",
+            Direction::DownRight => "DR",
+            Direction::Right => "R",
+            Direction::UpRight => "UR",
+            Direction::Up => "U",
+            Direction::UpLeft => "UL",
+            Direction::Left => "L",
+            Direction::DownLeft => "DL",
+        }
+    }
+
+    // The directions are listed counterclockwise an eighth of a turn apart,
+    // so turning is a step along the list.
+    fn turn(self, eighths: usize) -> Direction {
+        *Direction::iterator()
+            .nth((self as usize + eighths) % 8)
+            .unwrap()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MoveRulesFields"))]
+/// MoveRules gives the (x, y) offset of a move in each direction; a
+/// direction without an offset can not be played. The classic rules jump
+/// `HV_OFFSET` cells horizontally or vertically and `DIAG_OFFSET` cells
+/// diagonally, and stop at the edges of the board. Each part of an offset
+/// is at most `MAX_JUMP` cells either way.
+pub struct MoveRules {
+    /// The offset of each direction, in the order of `Direction::iterator`.
+    deltas: [Option<(i32, i32)>; 8],
+    /// Whether moves off one edge re-enter on the opposite edge.
+    toroidal: bool,
+}
+
+impl MoveRules {
+    /// The rules of the original puzzle.
+    pub const CLASSIC: MoveRules = MoveRules::new(HV_OFFSET, DIAG_OFFSET);
+
+    /// Create rules jumping `hv` cells horizontally or vertically and `diag`
+    /// cells diagonally. A distance of 0 leaves those directions out, so
+    /// `MoveRules::new(3, 0)` has no diagonal moves.
+    ///
+    /// Panics if either distance is more than `MAX_JUMP` either way; use
+    /// `custom` to check offsets that are not known to be in range.
+    pub const fn new(hv: i32, diag: i32) -> MoveRules {
+        assert!(
+            -MAX_JUMP <= hv && hv <= MAX_JUMP && -MAX_JUMP <= diag && diag <= MAX_JUMP,
+            "move distance out of range"
+        );
+        // Scale the unit step (dx, dy) by `distance`.
+        const fn step(distance: i32, dx: i32, dy: i32) -> Option<(i32, i32)> {
+            if distance == 0 {
+                None
+            } else {
+                Some((distance * dx, distance * dy))
+            }
+        }
+        MoveRules {
+            deltas: [
+                step(hv, 0, 1),
+                step(diag, 1, 1),
+                step(hv, 1, 0),
+                step(diag, 1, -1),
+                step(hv, 0, -1),
+                step(diag, -1, -1),
+                step(hv, -1, 0),
+                step(diag, -1, 1),
+            ],
+            toroidal: false,
+        }
+    }
+
+    /// Create the rules of a knight's tour: each direction jumps one cell
+    /// one way and two the other, turning counterclockwise from `Down`
+    /// (1, 2) in the order of `Direction::iterator`.
+    pub const fn knight() -> MoveRules {
+        MoveRules {
+            deltas: [
+                Some((1, 2)),
+                Some((2, 1)),
+                Some((2, -1)),
+                Some((1, -2)),
+                Some((-1, -2)),
+                Some((-2, -1)),
+                Some((-2, 1)),
+                Some((-1, 2)),
+            ],
+            toroidal: false,
+        }
+    }
+
+    /// Create rules from the (x, y) offset of each direction, in the order
+    /// of `Direction::iterator`; `None` leaves a direction out. An offset of
+    /// (0, 0) is not a move, and one jumping more than `MAX_JUMP` cells
+    /// along an axis is too long; either returns `InvalidRules`. The moves
+    /// need not be reversible or symmetric (see `is_reversible` and
+    /// `is_symmetric`).
+    pub fn custom(deltas: [Option<(i32, i32)>; 8]) -> Result<MoveRules, BoardError> {
+        let rules = MoveRules {
+            deltas,
+            toroidal: false,
+        };
+        let valid = |&(dx, dy): &(i32, i32)| {
+            (dx, dy) != (0, 0)
+                && (-MAX_JUMP..=MAX_JUMP).contains(&dx)
+                && (-MAX_JUMP..=MAX_JUMP).contains(&dy)
+        };
+        if !deltas.iter().flatten().all(valid) {
+            return Err(BoardError::InvalidRules {
+                rules: rules.to_string(),
+            });
+        }
+        Ok(rules)
+    }
+
+    /// Return these rules played on a torus: a move off one edge of the
+    /// board re-enters on the opposite edge.
+    pub const fn toroidal(mut self) -> MoveRules {
+        self.toroidal = true;
+        self
+    }
+
+    #[inline]
+    /// Return `true` if moves wrap around the edges of the board.
+    pub fn is_toroidal(&self) -> bool {
+        self.toroidal
+    }
+
+    /// Return the (x, y) offset of a move in `dir`, or `None` if `dir` is
+    /// not played under these rules.
+    pub fn delta(&self, dir: Direction) -> Option<(i32, i32)> {
+        self.deltas[dir as usize]
+    }
+
+    /// Return the direction whose move has the (x, y) offset `(dx, dy)`.
+    pub fn direction_of(&self, dx: i32, dy: i32) -> Option<Direction> {
+        Direction::iterator()
+            .find(|dir| self.delta(**dir) == Some((dx, dy)))
+            .cloned()
+    }
+
+    /// Return the directions played under these rules.
+    pub fn directions(&self) -> impl Iterator<Item = Direction> + '_ {
+        Direction::iterator()
+            .copied()
+            .filter(move |dir| self.delta(*dir).is_some())
+    }
+
+    /// Return the cell a move in `dir` from `from` lands on, on a board of
+    /// `width` x `height` cells, or `None` if the move leaves the board or
+    /// `dir` is not played. Whether the cell is free is not checked.
+    pub fn target(
+        &self,
+        width: usize,
+        height: usize,
+        from: Coord,
+        dir: Direction,
+    ) -> Option<Coord> {
+        let (dx, dy) = self.delta(dir)?;
+        if self.toroidal {
+            let (width, height) = (width as i32, height as i32);
+            let x = (from.x as i32 + dx).rem_euclid(width);
+            let y = (from.y as i32 + dy).rem_euclid(height);
+            Some(Coord::new(x as usize, y as usize))
+        } else {
+            (from + Delta::new(dx, dy)).filter(|to| to.within(width, height))
+        }
+    }
+
+    /// Return the direction of a move from cell `from` to cell `to` on a
+    /// board of `width` x `height` cells, if they are one move apart.
+    pub fn direction_between(
+        &self,
+        width: usize,
+        height: usize,
+        from: Coord,
+        to: Coord,
+    ) -> Option<Direction> {
+        self.directions()
+            .find(|&dir| self.target(width, height, from, dir) == Some(to))
+    }
+
+    /// Return `true` if each move can be taken back by another: the
+    /// reverse of every offset is an offset too. A path of moves read
+    /// backwards is then a path of moves.
+    pub fn is_reversible(&self) -> bool {
+        self.deltas
+            .iter()
+            .flatten()
+            .all(|&(dx, dy)| self.direction_of(-dx, -dy).is_some())
+    }
+
+    /// Return `true` if the reflections of a `width` x `height` board, and
+    /// its rotations if it is square, map every move onto a move, so a game
+    /// turned or mirrored is still a game under these rules.
+    pub fn is_symmetric(&self, width: usize, height: usize) -> bool {
+        let has = |dx, dy| self.direction_of(dx, dy).is_some();
+        self.deltas
+            .iter()
+            .flatten()
+            .all(|&(dx, dy)| has(-dx, dy) && has(dx, -dy) && (width != height || has(dy, dx)))
+    }
+}
+
+impl Default for MoveRules {
+    fn default() -> Self {
+        MoveRules::CLASSIC
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+/// MoveRulesFields is deserialized `MoveRules`, before its offsets are
+/// checked.
+struct MoveRulesFields {
+    deltas: [Option<(i32, i32)>; 8],
+    toroidal: bool,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MoveRulesFields> for MoveRules {
+    type Error = BoardError;
+
+    fn try_from(fields: MoveRulesFields) -> Result<MoveRules, BoardError> {
+        let rules = MoveRules::custom(fields.deltas)?;
+        Ok(if fields.toroidal {
+            rules.toroidal()
+        } else {
+            rules
+        })
+    }
+}
+
+impl fmt::Display for MoveRules {
+    /// Write the offset of each direction in the order of
+    /// `Direction::iterator` as `dx,dy`, or `-` for a direction that is not
+    /// played, then `torus` for toroidal rules. The classic rules are
+    /// `0,3 2,2 3,0 2,-2 0,-3 -2,-2 -3,0 -2,2`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, delta) in self.deltas.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match delta {
+                Some((dx, dy)) => write!(f, "{},{}", dx, dy)?,
+                None => write!(f, "-")?,
+            }
+        }
+        if self.toroidal {
+            write!(f, " torus")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MoveRules {
+    type Err = BoardError;
+
+    /// Parse rules written by the `Display` impl.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || BoardError::InvalidRules {
+            rules: s.trim().to_string(),
+        };
+        let mut words: Vec<&str> = s.split_whitespace().collect();
+        let toroidal = words.last() == Some(&"torus");
+        if toroidal {
+            words.pop();
+        }
+        if words.len() != DIRECTIONS.len() {
+            return Err(bad());
+        }
+        let mut deltas = [None; 8];
+        for (delta, word) in deltas.iter_mut().zip(words) {
+            if word == "-" {
+                continue;
+            }
+            let (dx, dy) = word.split_once(',').ok_or_else(bad)?;
+            *delta = Some((
+                dx.parse().map_err(|_| bad())?,
+                dy.parse().map_err(|_| bad())?,
+            ));
+        }
+        let rules = MoveRules::custom(deltas)?;
+        Ok(if toroidal { rules.toroidal() } else { rules })
     }
 }
 
@@ -113,278 +651,3179 @@ impl fmt::Display for Direction {
     }
 }
 
+impl FromStr for Direction {
+    type Err = DirectionError;
+
+    /// Parse a direction from its name, as written by `Display`, or from
+    /// compact notation. Case, spaces, `-` and `_` are ignored, so
+    /// "down right", "Down-Right", "DR" and "dr" are all `DownRight`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key: String = s
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+            .collect::<String>()
+            .to_ascii_uppercase();
+        Direction::iterator()
+            .find(|dir| {
+                key == dir.to_compact() || key == dir.to_string().replace(' ', "").to_uppercase()
+            })
+            .copied()
+            .ok_or_else(|| DirectionError {
+                name: s.to_string(),
+            })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Feasibility is the outcome of `Board::quick_feasibility`.
+pub enum Feasibility {
+    /// The board can not be completed, for the given reason.
+    Infeasible(Infeasibility),
+    /// The quick checks found nothing wrong; only a search can tell.
+    Unknown,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Infeasibility is the reason a board can not be completed.
+pub enum Infeasibility {
+    /// No move can land on the empty cell.
+    Stranded(Coord),
+    /// The empty cell can not be reached from the current position.
+    Unreachable(Coord),
+}
+
+impl fmt::Display for Infeasibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Infeasibility::Stranded(cell) => write!(f, "no move lands on cell {}", cell),
+            Infeasibility::Unreachable(cell) => {
+                write!(f, "cell {} can not be reached", cell)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// CellChange is a cell whose value differs between two boards, or that is
+/// a hole on only one of them; see `Board::diff`.
+pub struct CellChange {
+    pub cell: Coord,
+    /// The value on the first board, 0 if the cell is empty.
+    pub before: u16,
+    /// The value on the second board, 0 if the cell is empty.
+    pub after: u16,
+    /// `true` if the cell is a hole on the first board.
+    pub hole_before: bool,
+    /// `true` if the cell is a hole on the second board.
+    pub hole_after: bool,
+}
+
 #[derive(Debug, Clone)]
-/// Board represents the puzzle board. It is a square grid of
-/// values 0-(size x size), where size is the vertical/horizontal
-/// dimensions of the board. O represents an empty cell.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "BoardFields"))]
+/// Board represents the puzzle board. It is a grid of values
+/// 0-(width x height), square unless created with `with_dimensions`.
+/// O represents an empty cell.
 pub struct Board {
-    /// The number of vertical/horizontal cells in te board.
-    size: usize,
-    /// The total number of cells in the board (size x size).
+    /// The number of horizontal cells in the board.
+    width: usize,
+    /// The number of vertical cells in the board.
+    height: usize,
+    /// The total number of cells in the board (width x height).
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     cells: usize,
     /// The values of the cell in the board.
-    values: Vec<u8>,
+    values: Cells<u16, INLINE_CELLS>,
+    /// The cells that can never be visited.
+    holes: Cells<bool, INLINE_CELLS>,
+    /// The number of cells that are not holes.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    open: usize,
+    /// One bit per cell, in the order of `values`, set if the cell holds a
+    /// value or is a hole. Move checks test a bit here rather than reading
+    /// both `values` and `holes`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    occupied: Cells<u64, { INLINE_CELLS / 64 }>,
+    /// The number of cells whose bit in `occupied` is clear: the cells
+    /// still to fill.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    remaining: usize,
+    /// The bits of `occupied` laid out inside a border of set bits (see
+    /// `neighbors::Border`), so that a move check is an add and a bit test;
+    /// empty under toroidal rules.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    padded: Cells<u64, { INLINE_CELLS / 16 }>,
+    /// For each cell, the number of cells one jump away whose bit in
+    /// `occupied` is clear, updated as cells are taken and given back.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    degrees: Cells<u8, INLINE_CELLS>,
+    /// The value placed last, at (x, y); 0 if the board is not started.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    last: u16,
+    /// The Zobrist key of the taken cells and the current cell.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    zobrist: u64,
     /// The x location of the last cell set in the board.
     x: usize,
     /// The y location of the last cell set in the board.
     y: usize,
+    /// The moves allowed on the board.
+    rules: MoveRules,
+    /// The target of each move from each cell under `rules`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    neighbors: Arc<Neighbors>,
 }
 
-impl Board {
-    /// Create a new board with the dimensions `size` x `size`.
-    pub fn new(size: usize) -> Self {
-        let mut size = size;
-        if size < 5 {
-            size = 5;
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+/// BoardFields is a deserialized `Board`: the fields it is written with.
+/// Everything else, from the cell counts and occupancy bits to the value
+/// placed last, is worked out again from these, so a board read back can
+/// not hold a stale copy of them.
+struct BoardFields {
+    width: usize,
+    height: usize,
+    values: Vec<u16>,
+    holes: Vec<bool>,
+    x: usize,
+    y: usize,
+    rules: MoveRules,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BoardFields> for Board {
+    type Error = BoardError;
+
+    /// Check the fields of a deserialized board before building it, as they
+    /// may come from anywhere, and the board it holds with `validate`.
+    fn try_from(fields: BoardFields) -> Result<Board, BoardError> {
+        let (width, height) = (fields.width, fields.height);
+        let cells = width.saturating_mul(height);
+        if width != width.clamp(MIN_SIZE, MAX_SIZE)
+            || height != height.clamp(MIN_SIZE, MAX_SIZE)
+            || fields.values.len() != cells
+            || fields.holes.len() != cells
+        {
+            return Err(BoardError::InvalidDimensions {
+                cols: width,
+                rows: height,
+            });
+        }
+        if fields.x >= width || fields.y >= height {
+            return Err(BoardError::BadPosition {
+                x: fields.x,
+                y: fields.y,
+            });
         }
-        if size > 16 {
-            size = 16;
+        let mut board =
+            Board::with_dimensions(fields.width, fields.height).with_rules(fields.rules);
+        let cells = fields.values.iter().zip(&fields.holes);
+        for (i, (&value, &hole)) in cells.enumerate() {
+            board.values[i] = value;
+            if hole {
+                board.holes[i] = true;
+                board.open -= 1;
+            }
+            if value != 0 || hole {
+                board.occupy(i);
+            }
         }
+        let last = fields.values.iter().copied().fold(0, u16::max);
+        board.set_current(fields.x, fields.y, last);
+        board.validate()?;
+        Ok(board)
+    }
+}
+
+impl Board {
+    /// Create a new board with the dimensions `size` x `size`. The size is
+    /// clamped to `MIN_SIZE..=MAX_SIZE`; use `try_new` to reject sizes
+    /// outside that range instead.
+    pub fn new(size: usize) -> Self {
+        Board::with_dimensions(size, size)
+    }
 
-        Board {
-            size,
-            cells: size * size,
-            values: vec![0; size * size],
+    /// Create a new board with the dimensions `size` x `size`, or return
+    /// `InvalidDimensions` if the size is outside `MIN_SIZE..=MAX_SIZE`.
+    pub fn try_new(size: usize) -> Result<Self, BoardError> {
+        BoardBuilder::new().size(size).build()
+    }
+
+    /// Create a new board `width` cells wide and `height` cells high. Each
+    /// dimension is clamped to `MIN_SIZE..=MAX_SIZE`.
+    pub fn with_dimensions(width: usize, height: usize) -> Self {
+        let (width, height) = (
+            width.clamp(MIN_SIZE, MAX_SIZE),
+            height.clamp(MIN_SIZE, MAX_SIZE),
+        );
+
+        let mut board = Board {
+            width,
+            height,
+            cells: width * height,
+            values: Cells::filled(0, width * height),
+            holes: Cells::filled(false, width * height),
+            open: width * height,
+            occupied: Cells::filled(0, (width * height).div_ceil(64)),
+            remaining: width * height,
+            padded: Cells::filled(0, 0),
+            degrees: Cells::filled(0, width * height),
+            last: 0,
+            zobrist: 0,
             x: 0,
             y: 0,
-        }
+            rules: MoveRules::CLASSIC,
+            neighbors: Neighbors::get(width, height, MoveRules::CLASSIC),
+        };
+        board.count_degrees();
+        board.pad();
+        board
     }
 
-    #[inline]
-    /// Return `true` if the board has been started; otherwise `false`.
-    pub fn is_started(&self) -> bool {
-        self.value_at(self.x, self.y) > 0
+    /// Create a `width` x `height` board of any shape: only the cells for
+    /// which `mask` returns `true` have to be filled, the others are holes.
+    /// The dimensions are checked as by `BoardBuilder::build`.
+    pub fn from_mask(
+        width: usize,
+        height: usize,
+        mask: impl Fn(Coord) -> bool,
+    ) -> Result<Board, BoardError> {
+        let outside = (0..width * height)
+            .map(|i| Coord::from_index(i, width))
+            .filter(|&cell| !mask(cell));
+        BoardBuilder::new()
+            .dimensions(width, height)
+            .holes(outside)
+            .build()
     }
 
-    /// Return a list of all possible moves from the current location.
-    /// Returns an empty list if there are no moves, or the board is empty.
-    pub fn possible_moves(&self) -> Vec<&'static Direction> {
-        Direction::iterator()
-            .filter(|&x| self.valid_move(*x).is_some())
-            .collect()
+    /// Create a board of `size` x `size` with the value 1 at `start`,
+    /// followed by every move of `moves`. Errors are those of `start_at`
+    /// and `apply_moves`.
+    pub fn from_moves(size: usize, start: Coord, moves: &[Direction]) -> Result<Board, BoardError> {
+        Board::new(size).start_at(start)?.apply_moves(moves)
     }
 
-    /// Determines if a move in the given direction is valid. A move is valid
-    /// if the resulting position is valid, and if the the resulting position
-    /// is an empty cell. If the move is valid, it returns `Some((x, y))`
-    /// where (x, y) is the cell location resulting from the move. Otherwise,
-    /// it returns `None`.
-    fn valid_move(&self, dir: Direction) -> Option<(usize, usize)> {
-        let x: i32 = self.x as i32;
-        let y: i32 = self.y as i32;
-        let size: i32 = self.size as i32;
-        if self.is_started() {
-            let (x, y) = match dir {
-                Direction::Down => (x, y + HV_OFFSET),
-                Direction::DownRight => (x + DIAG_OFFSET, y + DIAG_OFFSET),
-                Direction::Right => (x + HV_OFFSET, y),
-                Direction::UpRight => (x + DIAG_OFFSET, y - DIAG_OFFSET),
-                Direction::Up => (x, y - HV_OFFSET),
-                Direction::UpLeft => (x - DIAG_OFFSET, y - DIAG_OFFSET),
-                Direction::Left => (x - HV_OFFSET, y),
-                Direction::DownLeft => (x - DIAG_OFFSET, y + DIAG_OFFSET),
-            };
-            if x >= 0
-                && y >= 0
-                && x < size
-                && y < size
-                && self.value_at(x as usize, y as usize) == 0 as u8
-            {
-                return Some((x as usize, y as usize));
-            }
-        }
-        None
+    /// Return this board with the moves allowed by `rules` in place of the
+    /// classic ones. Values already placed are kept as they are.
+    pub fn with_rules(mut self, rules: MoveRules) -> Board {
+        if rules != self.rules {
+            self.rules = rules;
+            self.neighbors = Neighbors::get(self.width, self.height, rules);
+            self.count_degrees();
+            self.pad();
+        }
+        self
     }
 
-    /// Return true if the board is complete. A board is complete if the value
-    /// of the last move equals the maximum number of cells, and there are no
-    /// empty cells in the board.
     #[inline]
-    pub fn is_won(&self) -> bool {
-        static ZERO: u8 = 0 as u8;
-        self.value_at(self.x, self.y) == self.cells as u8 && !self.values.contains(&ZERO)
+    /// Return the moves allowed on the board.
+    pub fn rules(&self) -> &MoveRules {
+        &self.rules
     }
 
-    /// Return `true` if there are no possible moves for the current board.
     #[inline]
-    pub fn is_blocked(&self) -> bool {
-        self.is_started() && self.possible_moves().len() == 0
+    /// Return the table of move targets of the board, its cells indexed row
+    /// by row.
+    pub fn neighbors(&self) -> &Neighbors {
+        &self.neighbors
     }
 
     #[inline]
-    /// The score is simply the highest value on the board.
-    pub fn score(&self) -> usize {
-        self.values.iter().cloned().fold(0, u8::max) as usize
+    /// Return the number of vertical/horizontal cells of a square board. For
+    /// a rectangular board this is the width.
+    pub fn size(&self) -> usize {
+        self.width
     }
 
     #[inline]
-    /// Return the value at the given location on the board.
-    pub fn value_at(&self, x: usize, y: usize) -> u8 {
-        self.values[y * self.size + x]
+    /// Return the number of horizontal cells in the board.
+    pub fn width(&self) -> usize {
+        self.width
     }
 
-    /// Start the puzzle by placing a 1 in the given location.
-    pub fn start_at(&mut self, x: usize, y: usize) -> Result<Board, BoardError> {
-        self.set_value(x, y, 1)
+    #[inline]
+    /// Return the number of vertical cells in the board.
+    pub fn height(&self) -> usize {
+        self.height
     }
 
-    /// Make the next move on the board using a given direction.
-    pub fn next_move(&mut self, dir: Direction) -> Result<Board, BoardError> {
-        if !self.is_started() {
-            return Err(BoardError::NotStarted);
-        }
-        match self.valid_move(dir) {
-            Some((x, y)) => {
-                let val = self.value_at(self.x, self.y);
-                self.set_value(x, y, val + 1)
-            }
-            None => Err(BoardError::BadDirection {
-                dir,
-                x: self.x,
-                y: self.y,
-            }),
-        }
+    #[inline]
+    /// Return `true` if the board is as wide as it is high.
+    pub fn is_square(&self) -> bool {
+        self.width == self.height
+    }
+
+    #[inline]
+    /// Return the number of cells to fill: every cell of the board except
+    /// the holes.
+    pub fn cell_count(&self) -> usize {
+        self.open
     }
 
-    /// Set the value of location on the board to `value`.
-    fn set_value(&mut self, x: usize, y: usize, value: u8) -> Result<Board, BoardError> {
-        if x >= self.size || y >= self.size {
+    /// Make `cell` a hole: a cell that can never be visited and does not
+    /// count toward completion. The cell must be empty.
+    pub fn block_cell(&mut self, cell: Coord) -> Result<(), BoardError> {
+        let Coord { x, y } = cell;
+        if x >= self.width || y >= self.height {
             return Err(BoardError::IndexOutOfRange {
                 x,
                 y,
-                max: self.size,
+                max: if x >= self.width {
+                    self.width
+                } else {
+                    self.height
+                },
             });
         }
-        if value < 1 {
-            return Err(BoardError::CannotClear { x, y });
-        }
-        if value <= self.score() as u8 {
-            return Err(BoardError::ValueUsed { x, y, value });
+        if self.value_at(cell) != 0 {
+            return Err(BoardError::NotEmpty { x, y });
         }
-        if value > self.cells as u8 {
-            return Err(BoardError::ValueOutOfRange {
-                x,
-                y,
-                value,
-                max: self.cells,
-            });
+        let i = y * self.width + x;
+        if !self.holes[i] {
+            self.holes[i] = true;
+            self.open -= 1;
+            self.occupy(i);
         }
-        if self.value_at(x, y) != 0 {
-            return Err(BoardError::NotEmpty { x, y });
+        Ok(())
+    }
+
+    #[inline]
+    /// Return `true` if `cell` is a hole. Cells off the board are not.
+    pub fn is_hole(&self, cell: Coord) -> bool {
+        self.index_of(cell.x, cell.y).is_some_and(|i| self.holes[i])
+    }
+
+    /// Return the location of the last value placed on the board, or `None`
+    /// if the board has not been started.
+    pub fn current_position(&self) -> Option<Coord> {
+        if self.is_started() {
+            Some(Coord::new(self.x, self.y))
+        } else {
+            None
         }
-        let mut board = self.clone();
-        board.x = x;
-        board.y = y;
-        board.values[y * self.size + x] = value;
-        Ok(board)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[inline]
+    /// Return `true` if the board has been started; otherwise `false`.
+    pub fn is_started(&self) -> bool {
+        self.last > 0
+    }
+
+    #[inline]
+    /// Return `true` if `cell` is on the board, holds no value and is not a
+    /// hole: a cell a move may land on.
+    pub fn is_vacant(&self, cell: Coord) -> bool {
+        self.index_of(cell.x, cell.y)
+            .is_some_and(|i| !self.is_occupied(i))
+    }
+
+    #[inline]
+    /// Return `true` if the cell at `index`, counted row by row, holds a
+    /// value or is a hole.
+    pub(crate) fn is_occupied(&self, index: usize) -> bool {
+        self.occupied[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    #[inline]
+    // Mark the cell at `index` as holding a value or a hole.
+    fn occupy(&mut self, index: usize) {
+        if !self.is_occupied(index) {
+            self.occupied[index / 64] |= 1 << (index % 64);
+            self.remaining -= 1;
+            self.zobrist ^= zobrist::occupied_key(index);
+            for from in self.neighbors.sources(index) {
+                self.degrees[from] -= 1;
+            }
+            if let Some(border) = self.neighbors.border() {
+                let i = border.index(index % self.width, index / self.width);
+                self.padded[i / 64] |= 1 << (i % 64);
+            }
+        }
+    }
+
+    #[inline]
+    // Mark the cell at `index` as empty.
+    fn vacate(&mut self, index: usize) {
+        if self.is_occupied(index) {
+            self.occupied[index / 64] &= !(1 << (index % 64));
+            self.remaining += 1;
+            self.zobrist ^= zobrist::occupied_key(index);
+            for from in self.neighbors.sources(index) {
+                self.degrees[from] += 1;
+            }
+            if let Some(border) = self.neighbors.border() {
+                let i = border.index(index % self.width, index / self.width);
+                self.padded[i / 64] &= !(1 << (i % 64));
+            }
+        }
+    }
+
+    // Lay out the occupancy bits inside their border from scratch.
+    fn pad(&mut self) {
+        let border = match self.neighbors.border() {
+            Some(border) => *border,
+            None => {
+                self.padded = Cells::filled(0, 0);
+                return;
+            }
+        };
+        self.padded = Cells::filled(!0, border.len().div_ceil(64));
+        for cell in 0..self.values.len() {
+            if self.is_occupied(cell) {
+                continue;
+            }
+            let i = border.index(cell % self.width, cell / self.width);
+            self.padded[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    // Count the empty cells one jump away from each cell from scratch.
+    fn count_degrees(&mut self) {
+        for cell in 0..self.values.len() {
+            let targets = self.neighbors.targets(cell);
+            self.degrees[cell] = targets.filter(|&to| !self.is_occupied(to)).count() as u8;
+        }
+    }
+
+    #[inline]
+    /// Return the number of empty cells one jump away from `cell`: the
+    /// moves a path arriving there could go on with. Where every move can
+    /// be reversed, this is also the number of ways into the cell. The
+    /// counts are kept up to date move by move, so this takes constant
+    /// time. Cells off the board have degree 0.
+    pub fn degree(&self, cell: Coord) -> usize {
+        self.index_of(cell.x, cell.y)
+            .map_or(0, |i| self.degrees[i] as usize)
+    }
+
+    /// Return the number of moves there would be after a move in `dir`, or
+    /// `None` if the move can not be played: what Warnsdorff's rule ranks
+    /// moves by. The board is not changed or copied.
+    pub fn degree_after(&self, dir: Direction) -> Option<usize> {
+        if !self.can_move(dir) {
+            return None;
+        }
+        let to = self.neighbors.target(self.y * self.width + self.x, dir)?;
+        // the cell moved to no longer counts as a way on from itself
+        let stays = self.neighbors.targets(to).filter(|&n| n == to).count();
+        Some(self.degrees[to] as usize - stays)
+    }
+
+    /// Return the set of possible moves from the current location. The set
+    /// is empty if there are no moves, or the board is empty.
+    pub fn possible_moves(&self) -> Moves {
+        Direction::iterator()
+            .copied()
+            .filter(|dir| self.can_move(*dir))
+            .collect()
+    }
+
+    /// Return the number of possible moves from the current location.
+    pub fn possible_move_count(&self) -> usize {
+        if self.is_started() {
+            self.degrees[self.y * self.width + self.x] as usize
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    // Return `true` if a move in `dir` from the current position is legal.
+    fn can_move(&self, dir: Direction) -> bool {
+        if !self.is_started() {
+            return false;
+        }
+        match self.neighbors.border() {
+            Some(border) => {
+                let to = border.step(border.index(self.x, self.y), dir);
+                self.padded[to / 64] & (1 << (to % 64)) == 0
+            }
+            None => self
+                .neighbors
+                .target(self.y * self.width + self.x, dir)
+                .is_some_and(|to| !self.is_occupied(to)),
+        }
+    }
+
+    /// Determines if a move in the given direction is valid. A move is valid
+    /// if the resulting position is valid, and if the the resulting position
+    /// is an empty cell. If the move is valid, it returns `Some((x, y))`
+    /// where (x, y) is the cell location resulting from the move. Otherwise,
+    /// it returns `None`.
+    pub(crate) fn valid_move(&self, dir: Direction) -> Option<Coord> {
+        if !self.can_move(dir) {
+            return None;
+        }
+        self.neighbors
+            .target(self.y * self.width + self.x, dir)
+            .map(|to| Coord::from_index(to, self.width))
+    }
+
+    /// Return true if the board is complete. A board is complete if the value
+    /// of the last move equals the number of cells to fill, and there are no
+    /// empty cells in the board other than holes.
+    #[inline]
+    pub fn is_won(&self) -> bool {
+        self.remaining == 0 && self.last == self.open as u16
+    }
+
+    #[inline]
+    /// Return the number of cells still to fill: the empty cells that are
+    /// not holes.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    #[inline]
+    /// Return the Zobrist key of the position (see `zobrist`): a hash of
+    /// the cells taken and the current cell, kept up to date move by move.
+    /// Positions that leave the same game to play have the same key,
+    /// whatever order their cells were taken in.
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+
+    /// Return `true` if there are no possible moves for the current board.
+    #[inline]
+    pub fn is_blocked(&self) -> bool {
+        self.is_started() && !Direction::iterator().any(|dir| self.can_move(*dir))
+    }
+
+    #[inline]
+    /// The score is simply the highest value on the board: the value placed
+    /// last.
+    pub fn score(&self) -> usize {
+        self.last as usize
+    }
+
+    #[inline]
+    /// Return the value at the given location on the board.
+    ///
+    /// Panics if `cell` is not on the board; `try_value_at` does not.
+    pub fn value_at(&self, cell: Coord) -> u16 {
+        self[cell]
+    }
+
+    #[inline]
+    /// Return the value at the given location on the board, or `None` if
+    /// `cell` is not on the board.
+    pub fn try_value_at(&self, cell: Coord) -> Option<u16> {
+        self.index_of(cell.x, cell.y).map(|i| self.values[i])
+    }
+
+    #[inline]
+    /// Return a reference to the value at `cell`, or `None` if it is not on
+    /// the board; the fallible counterpart of `board[cell]`.
+    pub fn get(&self, cell: impl Into<Coord>) -> Option<&u16> {
+        let cell = cell.into();
+        self.index_of(cell.x, cell.y).map(|i| &self.values[i])
+    }
+
+    #[inline]
+    // Return the index of (x, y) in `values` and `holes`, if it is on the
+    // board.
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Start the puzzle by placing a 1 in the given location, returning the
+    /// started board. This board is left as it was; the copy takes time
+    /// linear in the number of cells, so use `begin` to start in place.
+    pub fn start_at(&mut self, cell: Coord) -> Result<Board, BoardError> {
+        let mut board = self.clone();
+        board.begin(cell)?;
+        Ok(board)
+    }
+
+    /// Start the puzzle in place by placing a 1 in the given location, in
+    /// constant time. `unmake_move` takes the start back.
+    pub fn begin(&mut self, cell: Coord) -> Result<(), BoardError> {
+        self.set_value(cell.x, cell.y, 1)
+    }
+
+    /// Make the next move on the board using a given direction, returning
+    /// the new board. Like `start_at` this copies the board; `make_move`
+    /// plays the move in place.
+    pub fn next_move(&mut self, dir: Direction) -> Result<Board, BoardError> {
+        if !self.is_started() {
+            return Err(BoardError::NotStarted);
+        }
+        let mut board = self.clone();
+        board.make_move(dir)?;
+        Ok(board)
+    }
+
+    /// Return an iterator over every cell as `(x, y, value)`, row by row.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (usize, usize, u16)> + '_ {
+        let width = self.width;
+        self.values
+            .iter()
+            .enumerate()
+            .map(move |(i, &v)| (i % width, i / width, v))
+    }
+
+    /// Return an iterator over the cells still to be filled, row by row.
+    /// Holes are not included.
+    pub fn empty_cells(&self) -> impl Iterator<Item = Coord> + '_ {
+        let width = self.width;
+        #[cfg(feature = "simd")]
+        let cells = scan::vacant(&self.occupied, self.values.len());
+        #[cfg(not(feature = "simd"))]
+        let cells = (0..self.values.len()).filter(move |&i| !self.is_occupied(i));
+        cells.map(move |i| Coord::from_index(i, width))
+    }
+
+    /// Return the first cell still to be filled, row by row: where a search
+    /// of the empty cells can start.
+    pub fn first_empty(&self) -> Option<Coord> {
+        #[cfg(feature = "simd")]
+        let first = scan::first_vacant(&self.occupied, self.values.len());
+        #[cfg(not(feature = "simd"))]
+        let first = (0..self.values.len()).find(|&i| !self.is_occupied(i));
+        first.map(|i| Coord::from_index(i, self.width))
+    }
+
+    /// Write to `degrees` the number of empty cells one jump away from each
+    /// empty cell, row by row; other cells get 0. See `degree`.
+    pub fn empty_degrees(&self, degrees: &mut Vec<u8>) {
+        degrees.clear();
+        degrees.extend(self.degrees.iter().enumerate().map(|(cell, &degree)| {
+            if self.is_occupied(cell) {
+                0
+            } else {
+                degree
+            }
+        }));
+    }
+
+    /// Return the empty cells that can be reached from the current position
+    /// by a series of moves landing only on empty cells. Cells missing from
+    /// the set can never be filled, so a board is hopeless unless every
+    /// empty cell is reachable. The set is empty if the board has not been
+    /// started.
+    pub fn reachable_cells(&self) -> HashSet<Coord> {
+        let mut reached = HashSet::new();
+        let mut stack: Vec<usize> = self
+            .current_position()
+            .map(|cell| cell.index(self.width))
+            .into_iter()
+            .collect();
+        while let Some(from) = stack.pop() {
+            for to in self.neighbors.targets(from) {
+                if !self.is_occupied(to) && reached.insert(Coord::from_index(to, self.width)) {
+                    stack.push(to);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Run quick checks for a board that can not be completed: an empty
+    /// cell no move can land on, or one that can not be reached from the
+    /// current position. On an empty board any cell can hold the 1, so only
+    /// two cells no move lands on make it infeasible. The checks take time
+    /// linear in the size of the board; `Unknown` means they passed, not
+    /// that the board has a solution.
+    pub fn quick_feasibility(&self) -> Feasibility {
+        let current = self.current_position();
+        let mut stranded = None;
+        for cell in self.empty_cells() {
+            let entered = self.rules.directions().any(|dir| {
+                self.rules
+                    .target(self.width, self.height, cell, dir.opposite())
+                    .filter(|from| {
+                        self.rules.target(self.width, self.height, *from, dir) == Some(cell)
+                    })
+                    .is_some_and(|from| Some(from) == current || self.is_vacant(from))
+            });
+            if entered {
+                continue;
+            }
+            if current.is_some() || stranded.is_some() {
+                return Feasibility::Infeasible(Infeasibility::Stranded(cell));
+            }
+            stranded = Some(cell);
+        }
+        if current.is_some() {
+            let reachable = self.reachable_cells();
+            if let Some(cell) = self.empty_cells().find(|cell| !reachable.contains(cell)) {
+                return Feasibility::Infeasible(Infeasibility::Unreachable(cell));
+            }
+        }
+        Feasibility::Unknown
+    }
+
+    /// Return the cells whose values differ between this board and `other`,
+    /// or that are holes on one board and not the other, row by row. Boards
+    /// of different sizes are compared over the larger one, with the cells
+    /// missing from the smaller board counted as empty.
+    pub fn diff(&self, other: &Board) -> Vec<CellChange> {
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+        (0..width * height)
+            .map(|i| Coord::from_index(i, width))
+            .map(|cell| CellChange {
+                cell,
+                before: self.try_value_at(cell).unwrap_or(0),
+                after: other.try_value_at(cell).unwrap_or(0),
+                hole_before: self.is_hole(cell),
+                hole_after: other.is_hole(cell),
+            })
+            .filter(|change| {
+                change.before != change.after || change.hole_before != change.hole_after
+            })
+            .collect()
+    }
+
+    /// Return the board as it looks under each rotation and reflection, the
+    /// board itself first: eight boards for a square board, four (the
+    /// reflections) for a rectangular one. Values, holes and the current
+    /// position move together.
+    pub fn symmetries(&self) -> impl Iterator<Item = Board> + '_ {
+        let count = symmetric_cells(self.width, self.height, 0, 0).len();
+        (0..count).map(move |transform| self.transformed(transform))
+    }
+
+    /// Return the smallest of `symmetries`, comparing the values row by row
+    /// and then the holes. Boards that are rotations or reflections of each
+    /// other have the same canonical form.
+    pub fn canonicalize(&self) -> Board {
+        self.symmetries()
+            .min_by(|a, b| (&a.values, &a.holes).cmp(&(&b.values, &b.holes)))
+            .unwrap()
+    }
+
+    /// Return the board turned a quarter turn clockwise. A `width` x
+    /// `height` board becomes `height` x `width`.
+    pub fn rotate90(&self) -> Board {
+        let my = self.height - 1;
+        self.remap(self.height, self.width, |x, y| (my - y, x))
+    }
+
+    /// Return the board turned half a turn.
+    pub fn rotate180(&self) -> Board {
+        let (mx, my) = (self.width - 1, self.height - 1);
+        self.remap(self.width, self.height, |x, y| (mx - x, my - y))
+    }
+
+    /// Return the board turned a quarter turn counterclockwise. A `width` x
+    /// `height` board becomes `height` x `width`.
+    pub fn rotate270(&self) -> Board {
+        let mx = self.width - 1;
+        self.remap(self.height, self.width, |x, y| (y, mx - x))
+    }
+
+    /// Return the board mirrored left to right: each row is reversed.
+    pub fn mirror_h(&self) -> Board {
+        let mx = self.width - 1;
+        self.remap(self.width, self.height, |x, y| (mx - x, y))
+    }
+
+    /// Return the board mirrored top to bottom: the rows are reversed.
+    pub fn mirror_v(&self) -> Board {
+        let my = self.height - 1;
+        self.remap(self.width, self.height, |x, y| (x, my - y))
+    }
+
+    // Return the board under transformation `transform` of
+    // `symmetric_cells`.
+    fn transformed(&self, transform: usize) -> Board {
+        let (width, height) = (self.width, self.height);
+        self.remap(width, height, |x, y| {
+            symmetric_cells(width, height, x, y)[transform]
+        })
+    }
+
+    // Return a `width` x `height` board with the value and hole of each
+    // cell (x, y) moved to `image(x, y)`, and the current position with
+    // them.
+    fn remap(
+        &self,
+        width: usize,
+        height: usize,
+        image: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Board {
+        let mut board = Board::with_dimensions(width, height).with_rules(self.rules);
+        board.open = self.open;
+        for (x, y, value) in self.iter_cells() {
+            let (tx, ty) = image(x, y);
+            let i = ty * width + tx;
+            board.values[i] = value;
+            board.holes[i] = self.is_hole(Coord::new(x, y));
+            if value != 0 || board.holes[i] {
+                board.occupy(i);
+            }
+        }
+        if self.is_started() {
+            let (x, y) = image(self.x, self.y);
+            board.set_current(x, y, self.last);
+        }
+        board
+    }
+
+    /// Encode the board in a compact binary format, for storing many boards
+    /// where the text format is too large. The encoding is:
+    ///
+    /// - `BINARY_MAGIC` and the `BINARY_VERSION` byte;
+    /// - a flags byte: 1 for a toroidal board, 2 if there are holes, 4 if
+    ///   the rules are not the classic ones;
+    /// - the width and the height, one byte each;
+    /// - with flag 4, the (x, y) offset of each direction as two signed
+    ///   bytes, (0, 0) for a direction that can not be played;
+    /// - with flag 2, one bit per cell, set for a hole;
+    /// - the values, row by row, each in as many bits as the number of
+    ///   cells needs.
+    ///
+    /// Bits are packed from the lowest bit of each byte up.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.rules.is_toroidal() {
+            flags |= BINARY_TORUS;
+        }
+        if self.open < self.cells {
+            flags |= BINARY_HOLES;
+        }
+        if self.rules.deltas != MoveRules::CLASSIC.deltas {
+            flags |= BINARY_RULES;
+        }
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend_from_slice(&[BINARY_VERSION, flags, self.width as u8, self.height as u8]);
+        if flags & BINARY_RULES != 0 {
+            // offsets are at most `MAX_JUMP`, so each fits in a signed byte
+            for delta in self.rules.deltas.iter() {
+                let (dx, dy) = delta.unwrap_or((0, 0));
+                bytes.extend_from_slice(&[dx as i8 as u8, dy as i8 as u8]);
+            }
+        }
+        let mut bits = BitWriter::new(bytes);
+        if flags & BINARY_HOLES != 0 {
+            for &hole in self.holes.iter() {
+                bits.write(hole as u32, 1);
+            }
+            bits.flush();
+        }
+        let width = value_bits(self.cells);
+        for &value in self.values.iter() {
+            bits.write(u32::from(value), width);
+        }
+        bits.finish()
+    }
+
+    /// Decode a board written by `to_bytes`. The header, the size and the
+    /// length are checked, with errors reported as `BadBinary`, and the
+    /// values are checked as `BoardBuilder::build` and `validate` do.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Board, BoardError> {
+        if bytes.len() < 6 || bytes[..2] != BINARY_MAGIC {
+            return Err(BoardError::BadBinary { offset: 0 });
+        }
+        if bytes[2] != BINARY_VERSION {
+            return Err(BoardError::Format(FormatError::UnsupportedVersion {
+                kind: "binary board".to_string(),
+                version: u32::from(bytes[2]),
+                current: u32::from(BINARY_VERSION),
+            }));
+        }
+        let flags = bytes[3];
+        if flags & !(BINARY_TORUS | BINARY_HOLES | BINARY_RULES) != 0 {
+            return Err(BoardError::BadBinary { offset: 3 });
+        }
+        let (width, height) = (bytes[4] as usize, bytes[5] as usize);
+        if width < MIN_SIZE || height < MIN_SIZE {
+            return Err(BoardError::BadBinary { offset: 4 });
+        }
+        let mut rest = &bytes[6..];
+        let mut rules = MoveRules::CLASSIC;
+        if flags & BINARY_RULES != 0 {
+            if rest.len() < 16 {
+                return Err(BoardError::BadBinary {
+                    offset: bytes.len(),
+                });
+            }
+            let mut deltas = [None; 8];
+            for (delta, pair) in deltas.iter_mut().zip(rest[..16].chunks(2)) {
+                let (dx, dy) = (i32::from(pair[0] as i8), i32::from(pair[1] as i8));
+                *delta = if (dx, dy) == (0, 0) {
+                    None
+                } else {
+                    Some((dx, dy))
+                };
+            }
+            rules = MoveRules::custom(deltas).map_err(|_| BoardError::BadBinary { offset: 6 })?;
+            rest = &rest[16..];
+        }
+        if flags & BINARY_TORUS != 0 {
+            rules = rules.toroidal();
+        }
+
+        let cells = width * height;
+        let hole_bytes = if flags & BINARY_HOLES != 0 {
+            cells.div_ceil(8)
+        } else {
+            0
+        };
+        let value_bytes = (cells * value_bits(cells) as usize).div_ceil(8);
+        if rest.len() != hole_bytes + value_bytes {
+            // the first byte missing, or the first one too many
+            let end = bytes.len() - rest.len() + hole_bytes + value_bytes;
+            return Err(BoardError::BadBinary {
+                offset: end.min(bytes.len()),
+            });
+        }
+        let mut builder = BoardBuilder::new().dimensions(width, height).rules(rules);
+        let mut bits = BitReader::new(&rest[..hole_bytes]);
+        for i in 0..cells {
+            if hole_bytes > 0 && bits.read(1) == 1 {
+                builder = builder.hole(Coord::from_index(i, width));
+            }
+        }
+        let mut bits = BitReader::new(&rest[hole_bytes..]);
+        for i in 0..cells {
+            // at most 16 bits, as boards have at most 255 x 255 cells
+            let value = bits.read(value_bits(cells)) as u16;
+            builder = builder.value(Coord::from_index(i, width), value);
+        }
+        let board = builder.build()?;
+        board.validate()?;
+        Ok(board)
+    }
+
+    /// Return the location of `value`, or `None` if it has not been placed.
+    pub fn position_of(&self, value: u16) -> Option<Coord> {
+        if value == 0 {
+            return None;
+        }
+        self.values
+            .iter()
+            .position(|&v| v == value)
+            .map(|i| Coord::from_index(i, self.width))
+    }
+
+    /// Return the locations of the placed values, ordered by value.
+    pub fn path(&self) -> Vec<Coord> {
+        let mut cells: Vec<(u16, usize)> = self
+            .values
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| v > 0)
+            .map(|(i, &v)| (v, i))
+            .collect();
+        cells.sort_unstable();
+        cells
+            .into_iter()
+            .map(|(_, i)| Coord::from_index(i, self.width))
+            .collect()
+    }
+
+    /// Return the directions of the moves played since the start, in order.
+    /// For a board parsed from text whose consecutive values are not all a
+    /// legal jump apart, the list stops before the first illegal step.
+    pub fn moves(&self) -> Vec<Direction> {
+        self.path()
+            .windows(2)
+            .map_while(|pair| self.jump(pair[0], pair[1]))
+            .collect()
+    }
+
+    /// Reconstruct the moves played since the start by following the values
+    /// 1, 2, ... in order, checking that they are contiguous and that each
+    /// consecutive pair is a legal jump apart.
+    pub fn to_moves(&self) -> Result<Vec<Direction>, BoardError> {
+        if !self.is_started() {
+            return Err(BoardError::NotStarted);
+        }
+        let path = self.path();
+        for (i, cell) in path.iter().enumerate() {
+            let value = (i + 1) as u16;
+            if self.value_at(*cell) != value {
+                return Err(BoardError::MissingValue { value });
+            }
+        }
+        path.windows(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                self.jump(pair[0], pair[1]).ok_or(BoardError::IllegalJump {
+                    from: (i + 1) as u16,
+                    to: (i + 2) as u16,
+                })
+            })
+            .collect()
+    }
+
+    /// Return the game in move notation: the start cell and the compact
+    /// form of each move, e.g. `A1: R D L UR`. Together with the board size
+    /// this is enough to replay it, and far shorter than the grid.
+    pub fn to_notation(&self) -> Result<String, BoardError> {
+        let moves = self.to_moves()?;
+        let start = self.position_of(1).ok_or(BoardError::NotStarted)?;
+        let mut notation = format!("{}:", start);
+        for dir in moves {
+            notation.push(' ');
+            notation.push_str(dir.to_compact());
+        }
+        Ok(notation)
+    }
+
+    /// Check that the board is consistent: its dimensions are valid, its
+    /// values are unique and form the range 1..k, each consecutive pair of
+    /// values is a legal jump apart, and the current position holds k; a
+    /// board that is not started holds no value at all. Boards are checked
+    /// like this as they are parsed or deserialized; use it before trusting
+    /// a board put together some other way.
+    pub fn validate(&self) -> Result<(), BoardError> {
+        if self.width != self.width.clamp(MIN_SIZE, MAX_SIZE)
+            || self.height != self.height.clamp(MIN_SIZE, MAX_SIZE)
+            || self.cells != self.width * self.height
+            || self.values.len() != self.cells
+            || self.holes.len() != self.cells
+            || self.open != self.holes.iter().filter(|&&hole| !hole).count()
+        {
+            return Err(BoardError::InvalidDimensions {
+                cols: self.width,
+                rows: self.values.len() / self.width.max(1),
+            });
+        }
+        let mut seen = vec![false; self.cells + 1];
+        for (x, y, value) in self.iter_cells() {
+            if value > 0 && self.is_hole(Coord::new(x, y)) {
+                return Err(BoardError::Hole { x, y });
+            }
+            if value as usize > self.open {
+                return Err(BoardError::ValueOutOfRange {
+                    x,
+                    y,
+                    value,
+                    max: self.open,
+                });
+            }
+            if value > 0 && seen[value as usize] {
+                return Err(BoardError::ValueUsed { x, y, value });
+            }
+            seen[value as usize] = true;
+        }
+        // the values are scanned, as `last` can not be trusted here
+        let max = self.values.iter().copied().fold(0, u16::max) as usize;
+        if let Some(value) = (1..max).find(|&v| !seen[v]) {
+            return Err(BoardError::MissingValue {
+                value: value as u16,
+            });
+        }
+        if self.value_at(Coord::new(self.x, self.y)) as usize != max || self.last as usize != max {
+            return Err(BoardError::BadPosition {
+                x: self.x,
+                y: self.y,
+            });
+        }
+        if max == 0 {
+            return Ok(());
+        }
+        self.to_moves().map(|_| ())
+    }
+
+    /// Return a sandbox for playing speculative moves on this board. Every
+    /// move played in the sandbox is undone when the sandbox is dropped,
+    /// unless it is committed first.
+    pub fn sandbox(&mut self) -> Sandbox<'_> {
+        Sandbox {
+            board: self,
+            played: 0,
+        }
+    }
+
+    /// Retract the last move: clear the cell holding the highest value and
+    /// make the cell holding the previous value the current position.
+    /// Undoing the start leaves an empty board. This is `unmake_move`.
+    pub fn undo(&mut self) -> Result<(), BoardError> {
+        self.unmake_move()
+    }
+
+    /// Play a move in `dir` in place, in constant time. Unlike `next_move`
+    /// no copy of the board is made, so searches can play and retract moves
+    /// on one board with `make_move` and `unmake_move`.
+    pub fn make_move(&mut self, dir: Direction) -> Result<(), BoardError> {
+        if !self.is_started() {
+            return Err(BoardError::NotStarted);
+        }
+        let to = self.valid_move(dir).ok_or(BoardError::BadDirection {
+            dir,
+            x: self.x,
+            y: self.y,
+        })?;
+        self.place_value(to.x, to.y, self.last + 1);
+        Ok(())
+    }
+
+    /// Retract the last move in place, the inverse of `make_move`. The
+    /// previous value is looked for a move away first, so this does not
+    /// scan the board unless the values were not placed by moves.
+    pub fn unmake_move(&mut self) -> Result<(), BoardError> {
+        if !self.is_started() {
+            return Err(BoardError::NotStarted);
+        }
+        let from = self.y * self.width + self.x;
+        let value = self.last;
+        self.values[from] = 0;
+        self.vacate(from);
+        let previous = if value > 1 {
+            self.neighbors
+                .targets(from)
+                .find(|&cell| self.values[cell] == value - 1)
+                .map(|cell| Coord::from_index(cell, self.width))
+                .or_else(|| self.position_of(value - 1))
+        } else {
+            None
+        };
+        let previous = previous.unwrap_or_default();
+        self.set_current(previous.x, previous.y, value - 1);
+        Ok(())
+    }
+
+    /// Play every move of `moves` in order, returning the resulting board.
+    /// If a move fails, the error is `MoveFailed` with the index of the
+    /// failing move in `moves`, and this board is left untouched. The board
+    /// is copied once, then the moves are played on the copy in place.
+    pub fn apply_moves(&self, moves: &[Direction]) -> Result<Board, BoardError> {
+        let mut board = self.clone();
+        for (index, dir) in moves.iter().enumerate() {
+            board
+                .make_move(*dir)
+                .map_err(|error| BoardError::MoveFailed {
+                    index,
+                    error: Box::new(error),
+                })?;
+        }
+        Ok(board)
+    }
+
+    /// Return the direction of a jump from cell `from` to cell `to`, if they
+    /// are a legal jump apart under the rules of the board.
+    fn jump(&self, from: Coord, to: Coord) -> Option<Direction> {
+        self.rules
+            .direction_between(self.width, self.height, from, to)
+    }
+
+    /// Write `value` at (x, y) and make it the current position, without
+    /// any of the checks of `set_value`.
+    pub(crate) fn place_value(&mut self, x: usize, y: usize, value: u16) {
+        self.values[y * self.width + x] = value;
+        self.occupy(y * self.width + x);
+        self.set_current(x, y, value);
+    }
+
+    #[inline]
+    // Make (x, y), which holds `value`, the current position; a value of 0
+    // leaves the board unstarted.
+    fn set_current(&mut self, x: usize, y: usize, value: u16) {
+        if self.last > 0 {
+            self.zobrist ^= zobrist::current_key(self.y * self.width + self.x);
+        }
+        if value > 0 {
+            self.zobrist ^= zobrist::current_key(y * self.width + x);
+        }
+        self.x = x;
+        self.y = y;
+        self.last = value;
+    }
+
+    #[cfg(test)]
+    /// Return the Zobrist key of the board worked out from scratch, for
+    /// checking the one kept up to date.
+    fn compute_zobrist(&self) -> u64 {
+        let taken = (0..self.values.len()).filter(|&cell| self.is_occupied(cell));
+        let key = taken.fold(0, |key, cell| key ^ zobrist::occupied_key(cell));
+        if self.is_started() {
+            key ^ zobrist::current_key(self.y * self.width + self.x)
+        } else {
+            key
+        }
+    }
+
+    /// Set the value of location on the board to `value` in place, leaving
+    /// the board as it was if the value can not be placed there.
+    fn set_value(&mut self, x: usize, y: usize, value: u16) -> Result<(), BoardError> {
+        if x >= self.width || y >= self.height {
+            return Err(BoardError::IndexOutOfRange {
+                x,
+                y,
+                max: if x >= self.width {
+                    self.width
+                } else {
+                    self.height
+                },
+            });
+        }
+        if value < 1 {
+            return Err(BoardError::CannotClear { x, y });
+        }
+        if value <= self.last {
+            return Err(BoardError::ValueUsed { x, y, value });
+        }
+        if value > self.open as u16 {
+            return Err(BoardError::ValueOutOfRange {
+                x,
+                y,
+                value,
+                max: self.open,
+            });
+        }
+        if self.is_hole(Coord::new(x, y)) {
+            return Err(BoardError::Hole { x, y });
+        }
+        if self.value_at(Coord::new(x, y)) != 0 {
+            return Err(BoardError::NotEmpty { x, y });
+        }
+        self.place_value(x, y, value);
+        Ok(())
+    }
+}
+
+/// Return the number of bits the binary format uses for each value of a
+/// board of `cells` cells.
+fn value_bits(cells: usize) -> u32 {
+    usize::BITS - cells.leading_zeros()
+}
+
+/// BitWriter packs values into bytes, from the lowest bit up.
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// The number of bits of the last byte in use; 8 when it is full.
+    used: u32,
+}
+
+impl BitWriter {
+    fn new(bytes: Vec<u8>) -> Self {
+        BitWriter { bytes, used: 8 }
+    }
+
+    /// Append the lowest `width` bits of `value`.
+    fn write(&mut self, value: u32, width: u32) {
+        for bit in 0..width {
+            if self.used == 8 {
+                self.bytes.push(0);
+                self.used = 0;
+            }
+            *self.bytes.last_mut().unwrap() |= (((value >> bit) & 1) as u8) << self.used;
+            self.used += 1;
+        }
+    }
+
+    /// Start the next value at a byte boundary.
+    fn flush(&mut self) {
+        self.used = 8;
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// BitReader reads values packed by `BitWriter`. Bits past the end read
+/// as 0; callers check the length first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// The index of the next bit.
+    next: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, next: 0 }
+    }
+
+    /// Read the next `width` bits as a value.
+    fn read(&mut self, width: u32) -> u32 {
+        let mut value = 0;
+        for bit in 0..width {
+            let byte = self.bytes.get(self.next / 8).copied().unwrap_or(0);
+            value |= u32::from((byte >> (self.next % 8)) & 1) << bit;
+            self.next += 1;
+        }
+        value
+    }
+}
+
+/// Sandbox is a scoped guard over a board; see `Board::sandbox`. It
+/// dereferences to the board, so every read-only query works on it.
+pub struct Sandbox<'a> {
+    board: &'a mut Board,
+    /// The number of values placed through the sandbox.
+    played: usize,
+}
+
+impl<'a> Sandbox<'a> {
+    /// Start the board at `cell` inside the sandbox.
+    pub fn start_at(&mut self, cell: Coord) -> Result<(), BoardError> {
+        self.board.begin(cell)?;
+        self.played += 1;
+        Ok(())
+    }
+
+    /// Play a move inside the sandbox.
+    pub fn next_move(&mut self, dir: Direction) -> Result<(), BoardError> {
+        self.board.make_move(dir)?;
+        self.played += 1;
+        Ok(())
+    }
+
+    /// Undo the last move played inside the sandbox. Moves made before the
+    /// sandbox was opened can not be undone through it.
+    pub fn undo(&mut self) -> Result<(), BoardError> {
+        if self.played == 0 {
+            return Err(BoardError::NotStarted);
+        }
+        self.board.undo()?;
+        self.played -= 1;
+        Ok(())
+    }
+
+    /// The number of moves currently played inside the sandbox.
+    pub fn depth(&self) -> usize {
+        self.played
+    }
+
+    /// Keep the moves played inside the sandbox.
+    pub fn commit(mut self) {
+        self.played = 0;
+    }
+}
+
+impl<'a> Deref for Sandbox<'a> {
+    type Target = Board;
+
+    fn deref(&self) -> &Board {
+        self.board
+    }
+}
+
+impl<'a> Drop for Sandbox<'a> {
+    fn drop(&mut self) {
+        for _ in 0..self.played {
+            self.board.undo().unwrap();
+        }
+    }
+}
+
+impl PartialEq for Board {
+    /// Boards are equal if they have the same dimensions, rules, holes and
+    /// values. The current position follows from the values, so it is not
+    /// compared.
+    fn eq(&self, other: &Board) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.rules == other.rules
+            && self.holes == other.holes
+            && self.values == other.values
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    /// Hash the Zobrist key in place of the values: equal boards have the
+    /// same cells taken and the same current cell, so the same key.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        self.rules.hash(state);
+        self.zobrist.hash(state);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// BoardBuilder collects the settings of a board and checks them together
+/// in `build`. Setters consume and return the builder, so they chain.
+pub struct BoardBuilder {
+    width: usize,
+    height: usize,
+    rules: MoveRules,
+    toroidal: bool,
+    holes: Vec<Coord>,
+    values: Vec<(Coord, u16)>,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        BoardBuilder::new()
+    }
+}
+
+impl BoardBuilder {
+    /// Start an empty 10x10 board with the classic rules.
+    pub fn new() -> Self {
+        BoardBuilder {
+            width: 10,
+            height: 10,
+            rules: MoveRules::CLASSIC,
+            toroidal: false,
+            holes: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Make the board `size` x `size`.
+    pub fn size(self, size: usize) -> Self {
+        self.dimensions(size, size)
+    }
+
+    /// Make the board `width` x `height`.
+    pub fn dimensions(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Allow the moves of `rules`.
+    pub fn rules(mut self, rules: MoveRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Make moves off one edge re-enter on the opposite edge, whatever the
+    /// rules say.
+    pub fn toroidal(mut self, toroidal: bool) -> Self {
+        self.toroidal = toroidal;
+        self
+    }
+
+    /// Make `cell` a hole.
+    pub fn hole(mut self, cell: Coord) -> Self {
+        self.holes.push(cell);
+        self
+    }
+
+    /// Make every cell of `cells` a hole.
+    pub fn holes(mut self, cells: impl IntoIterator<Item = Coord>) -> Self {
+        self.holes.extend(cells);
+        self
+    }
+
+    /// Place `value` at `cell`. The highest value placed becomes the
+    /// current position.
+    pub fn value(mut self, cell: Coord, value: u16) -> Self {
+        self.values.push((cell, value));
+        self
+    }
+
+    /// Create the board. Unlike `Board::with_dimensions` the dimensions are
+    /// not clamped: outside `MIN_SIZE..=MAX_SIZE` they are
+    /// `InvalidDimensions`. Holes and values must be on the board, values
+    /// may not be placed on holes, and the values must be unique and form
+    /// the range 1..k. Whether consecutive values are a move apart is left
+    /// to `Board::validate`.
+    pub fn build(self) -> Result<Board, BoardError> {
+        let (width, height) = (self.width, self.height);
+        if width != width.clamp(MIN_SIZE, MAX_SIZE) || height != height.clamp(MIN_SIZE, MAX_SIZE) {
+            return Err(BoardError::InvalidDimensions {
+                cols: width,
+                rows: height,
+            });
+        }
+        let mut board = Board::with_dimensions(width, height).with_rules(if self.toroidal {
+            self.rules.toroidal()
+        } else {
+            self.rules
+        });
+        for cell in self.holes {
+            board.block_cell(cell)?;
+        }
+        let mut seen = vec![false; board.cells + 1];
+        for (Coord { x, y }, value) in self.values {
+            if !Coord::new(x, y).within(width, height) {
+                return Err(BoardError::IndexOutOfRange {
+                    x,
+                    y,
+                    max: if x >= width { width } else { height },
+                });
+            }
+            if value as usize > board.open {
+                return Err(BoardError::ValueOutOfRange {
+                    x,
+                    y,
+                    value,
+                    max: board.open,
+                });
+            }
+            if value == 0 {
+                continue;
+            }
+            if board.is_hole(Coord::new(x, y)) {
+                return Err(BoardError::Hole { x, y });
+            }
+            if seen[value as usize] {
+                return Err(BoardError::ValueUsed { x, y, value });
+            }
+            if board.value_at(Coord::new(x, y)) != 0 {
+                return Err(BoardError::NotEmpty { x, y });
+            }
+            seen[value as usize] = true;
+            board.values[y * width + x] = value;
+            board.occupy(y * width + x);
+            if value > board.last {
+                board.set_current(x, y, value);
+            }
+        }
+        let max = board.score();
+        if let Some(value) = (1..max).find(|&v| !seen[v]) {
+            return Err(BoardError::MissingValue {
+                value: value as u16,
+            });
+        }
+        Ok(board)
+    }
+}
+
+impl<C: Into<Coord>> Index<C> for Board {
+    type Output = u16;
+
+    /// Return the value at `cell`, given as a `Coord` or an `(x, y)` pair,
+    /// so `board[(3, 4)]` is `board.value_at(Coord::new(3, 4))`.
+    ///
+    /// Panics if the cell is not on the board, like `value_at`; use `get`
+    /// or `try_value_at` for coordinates that may be off the board.
+    fn index(&self, cell: C) -> &u16 {
+        let cell = cell.into();
+        match self.get(cell) {
+            Some(value) => value,
+            None => panic!(
+                "cell [{},{}] is not on the {}x{} board",
+                cell.x, cell.y, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Board {
+    /// Render the board as a grid of right-aligned values, one row per line,
+    /// with `.` marking empty cells and `#` holes. A board played under
+    /// other than the classic rules starts with a `rules` line giving them
+    /// (see `MoveRules`).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.cells.to_string().len();
+        if self.rules != MoveRules::CLASSIC {
+            writeln!(f, "rules {}", self.rules)?;
+        }
+        for (y, row) in self.values.chunks(self.width).enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for (x, &value) in row.iter().enumerate() {
+                if x > 0 {
+                    write!(f, " ")?;
+                }
+                if self.is_hole(Coord::new(x, y)) {
+                    write!(f, "{:>width$}", "#", width = width)?;
+                } else if value == 0 {
+                    write!(f, "{:>width$}", ".", width = width)?;
+                } else {
+                    write!(f, "{:>width$}", value, width = width)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Board {
+    type Error = BoardError;
+
+    fn validate(&self) -> Result<(), BoardError> {
+        Board::validate(self)
+    }
+}
+
+impl Artifact for Board {
+    const FORMAT: Format = BOARD_FORMAT;
+
+    /// Parse a board from a grid of whitespace separated values, one row per
+    /// line, as written by the `Display` impl. Empty cells may be written as
+    /// `0`, `.` or `_`, and holes as `#`. A first `rules` line sets the
+    /// rules, which are the classic ones otherwise; the cell holding the
+    /// highest value becomes the current position.
+    fn parse_body(body: &str) -> Result<Board, BoardError> {
+        let mut lines = body.lines().filter(|l| !l.trim().is_empty()).peekable();
+        let mut rules = MoveRules::CLASSIC;
+        if let Some(line) = lines.peek().and_then(|l| l.trim().strip_prefix("rules ")) {
+            rules = line.parse()?;
+            lines.next();
+        }
+        let mut rows = Vec::new();
+        for (row, line) in lines.enumerate() {
+            let mut values = Vec::new();
+            for token in line.split_whitespace() {
+                // a hole is `None`
+                let value = match token {
+                    "0" | "." | "_" => Some(0),
+                    "#" => None,
+                    _ => Some(token.parse::<u16>().map_err(|_| BoardError::InvalidToken {
+                        row,
+                        token: token.to_string(),
+                    })?),
+                };
+                values.push(value);
+            }
+            rows.push(values);
+        }
+        let height = rows.len();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if let Some(row) = rows.iter().find(|r| r.len() != width) {
+            return Err(BoardError::InvalidDimensions {
+                cols: row.len(),
+                rows: height,
+            });
+        }
+        let mut builder = BoardBuilder::new().dimensions(width, height).rules(rules);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, value) in row.iter().enumerate() {
+                builder = match value {
+                    None => builder.hole(Coord::new(x, y)),
+                    Some(0) => builder,
+                    Some(value) => builder.value(Coord::new(x, y), *value),
+                };
+            }
+        }
+        builder.build()
+    }
+}
+
+impl FromStr for Board {
+    type Err = BoardError;
+
+    /// Parse a board written by the `Display` impl, with or without a
+    /// `BOARD_FORMAT` header; a first line of `torus`, from version 1, plays
+    /// the classic rules on a torus. The values must be unique, form the
+    /// range 1..k and each be a legal jump from the one before, as
+    /// `validate` checks.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board::parse_artifact(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // the first tests are kept as written, comparing with bools
+    #![allow(clippy::bool_assert_comparison, clippy::explicit_counter_loop)]
+
+    use super::*;
+    use std::collections::HashMap;
+
+    // Return a 5x5 board holding each (x, y, value) of `values`, whether or
+    // not they are legal jumps apart, which parsing would check.
+    fn unchecked(values: &[(usize, usize, u16)]) -> Board {
+        let builder = BoardBuilder::new().size(5);
+        values
+            .iter()
+            .fold(builder, |builder, &(x, y, value)| {
+                builder.value(Coord::new(x, y), value)
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    // Start a board and check that invariants hold.
+    fn new_board() {
+        let mut board = Board::new(10);
+        // newly created board has a size of 10
+        assert_eq!(board.size(), 10);
+        // newly created bboard has cell count of 100
+        assert_eq!(board.cells, 100);
+        // newly created board has a score of 0
+        assert_eq!(board.score(), 0);
+        // newly created board is not started
+        assert_eq!(board.is_started(), false);
+        // unstarted board cannot be won
+        assert_eq!(board.is_won(), false);
+        // no possible moves because board isn't started
+        assert_eq!(board.possible_moves().len(), 0);
+        // start the board
+        board = board.start_at(Coord::new(5, 5)).unwrap();
+        // board is started
+        assert_eq!(board.is_started(), true);
+        // cell at (5, 5) should be 1
+        assert_eq!(board.values[55], 1);
+        // score is 1
+        assert_eq!(board.score(), 1);
+        // all moves should be possible
+        assert_eq!(board.possible_moves().len(), 8);
+        // board isn't won
+        assert_eq!(board.is_won(), false);
+    }
+
+    #[test]
+    // Test a complete set of moves on a 5x5 board and confirm that all
+    // invariants hold. The completed board looks like:
+    //
+    //      1 24 14  2 25
+    //     16 21  5  8 20
+    //     13 10 18 23 11
+    //      4  7 15  3  6
+    //     17 22 12  9 19
+    fn win_5() {
+        let moves = [
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+            Direction::UpRight,
+            Direction::DownRight,
+            Direction::Left,
+            Direction::UpRight,
+            Direction::Down,
+            Direction::UpLeft,
+            Direction::Right,
+            Direction::DownLeft,
+            Direction::UpLeft,
+            Direction::UpRight,
+            Direction::Down,
+            Direction::UpLeft,
+            Direction::Down,
+            Direction::UpRight,
+            Direction::DownRight,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::UpRight,
+            Direction::UpLeft,
+            Direction::Right,
+        ];
+        // The number of possible moves on the board after each move.
+        let possible_moves = [
+            3, 2, 2, 1, 2, 2, 2, 2, 2, 1, 2, 1, 2, 1, 2, 1, 1, 2, 2, 1, 1, 1, 1, 1, 0,
+        ];
+
+        let mut board = Board::new(5);
+        assert_eq!(board.is_started(), false);
+        board = board.start_at(Coord::new(0, 0)).unwrap();
+        assert_eq!(board.is_started(), true);
+        let mut possible = possible_moves.iter();
+        let mut i = 1;
+        for m in moves.iter() {
+            assert_eq!(
+                board.possible_moves().len(),
+                *possible.next().unwrap(),
+                "testing move {}",
+                i
+            );
+            assert_eq!(board.is_won(), false);
+            assert_eq!(board.is_blocked(), false);
+            assert_eq!(board.score(), i);
+            let ret = board.next_move(*m);
+            assert_eq!(ret.is_ok(), true, "testing move {}", i);
+            board = ret.unwrap();
+            i += 1;
+        }
+        assert_eq!(board.possible_moves().len(), *possible.next().unwrap());
+        // ensure we have checked all values
+        assert_eq!(possible.next().is_none(), true);
+        // board is now won
+        assert_eq!(board.is_won(), true);
+        // score should be 25 (max board)
+        assert_eq!(board.score(), board.cells);
+        // there should be no possible moves;
+        assert_eq!(board.is_blocked(), true);
+    }
+
+    #[test]
+    fn history_of_won_boards() {
+        let board = crate::fixtures::SOLUTION_5.board();
+        assert_eq!(board.moves(), crate::fixtures::SOLUTION_5.moves);
+        let path = board.path();
+        assert_eq!(path.len(), 25);
+        assert_eq!(path[0], Coord::new(0, 0));
+        assert_eq!(path[1], Coord::new(3, 0));
+        assert_eq!(path[24], Coord::new(4, 0));
+    }
+
+    #[test]
+    fn display_won() {
+        // the board renders like the example in the docs
+        assert_eq!(
+            crate::fixtures::SOLUTION_5.board().to_string(),
+            " 1 24 14  2 25\n\
+             16 21  5  8 20\n\
+             13 10 18 23 11\n \
+             4  7 15  3  6\n\
+             17 22 12  9 19"
+        );
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let text = " 1 24 14  2 25
+                    16 21  5  8 20
+                    13 10 18 23 11
+                     4  7 15  3  6
+                    17 22 12  9 19";
+        let board: Board = text.parse().unwrap();
+        assert!(board.is_won());
+        assert_eq!((board.x, board.y), (4, 0));
+        assert_eq!(
+            board.to_string().parse::<Board>().unwrap().values,
+            board.values
+        );
+
+        let partial: Board = "square100 board v1
+                              1 _ . 2 0
+                              . . . . .
+                              . . . . .
+                              . . . . .
+                              . . . . ."
+            .parse()
+            .unwrap();
+        assert_eq!(partial.score(), 2);
+        assert_eq!((partial.x, partial.y), (3, 0));
+        assert_eq!(partial.possible_moves().len(), 2);
+
+        let empty: Board = Board::new(6).to_string().parse().unwrap();
+        assert!(!empty.is_started());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let grid = |first: &str| format!("{}\n{}", first, ". . . . .\n".repeat(4));
+        assert_eq!(
+            grid("1 x . . .").parse::<Board>().err(),
+            Some(BoardError::InvalidToken {
+                row: 0,
+                token: "x".to_string()
+            })
+        );
+        assert_eq!(
+            grid("1 . . .").parse::<Board>().err(),
+            Some(BoardError::InvalidDimensions { cols: 4, rows: 5 })
+        );
+        assert_eq!(
+            "1 . .\n. . .\n. . .".parse::<Board>().err(),
+            Some(BoardError::InvalidDimensions { cols: 3, rows: 3 })
+        );
+        assert_eq!(
+            grid("1 26 . . .").parse::<Board>().err(),
+            Some(BoardError::ValueOutOfRange {
+                x: 1,
+                y: 0,
+                value: 26,
+                max: 25
+            })
+        );
+        assert_eq!(
+            grid("1 2 . . 2").parse::<Board>().err(),
+            Some(BoardError::ValueUsed {
+                x: 4,
+                y: 0,
+                value: 2
+            })
+        );
+        assert_eq!(
+            grid("1 . 3 . .").parse::<Board>().err(),
+            Some(BoardError::MissingValue { value: 2 })
+        );
+        assert!(grid("square100 replay v1\n")
+            .parse::<Board>()
+            .unwrap_err()
+            .to_string()
+            .contains("replay"));
+    }
+
+    #[test]
+    fn display_partial() {
+        let mut board = Board::new(10);
+        assert_eq!(board.to_string().lines().count(), 10);
+        assert_eq!(
+            board.to_string().lines().next(),
+            Some("  .   .   .   .   .   .   .   .   .   .")
+        );
+        board = board.start_at(Coord::new(1, 0)).unwrap();
+        board = board.next_move(Direction::Right).unwrap();
+        assert_eq!(
+            board.to_string().lines().next(),
+            Some("  .   1   .   .   2   .   .   .   .   .")
+        );
+    }
+
+    #[test]
+    fn history_of_partial_boards() {
+        let mut board = Board::new(6);
+        assert!(board.path().is_empty());
+        assert!(board.moves().is_empty());
+        board = board.start_at(Coord::new(0, 5)).unwrap();
+        assert_eq!(board.path(), vec![Coord::new(0, 5)]);
+        assert!(board.moves().is_empty());
+        board = board.next_move(Direction::UpRight).unwrap();
+        assert_eq!(board.path(), vec![Coord::new(0, 5), Coord::new(2, 3)]);
+        assert_eq!(board.moves(), vec![Direction::UpRight]);
+
+        // a board with a gap in its jumps stops at the gap
+        let parsed = unchecked(&[(0, 0, 1), (1, 0, 2), (0, 3, 3)]);
+        assert_eq!(
+            parsed.path(),
+            vec![Coord::new(0, 0), Coord::new(1, 0), Coord::new(0, 3)]
+        );
+        assert!(parsed.moves().is_empty());
+    }
+
+    #[test]
+    fn large_boards() {
+        let mut board = Board::new(16).start_at(Coord::new(0, 0)).unwrap();
+        assert_eq!(board.cell_count(), 256);
+        assert_eq!(board.next_move(Direction::Right).unwrap().score(), 2);
+
+        let mut board = Board::new(32).start_at(Coord::new(0, 0)).unwrap();
+        for _ in 0..299 {
+            let dir = board.possible_moves().first().unwrap();
+            board = board.next_move(dir).unwrap();
+        }
+        assert_eq!(board.score(), 300);
+        assert!(board.position_of(300).is_some());
+        assert_eq!(board.validate(), Ok(()));
+        let parsed: Board = board.to_string().parse().unwrap();
+        assert_eq!(parsed, board);
+    }
+
+    #[test]
+    fn rectangular_boards() {
+        let mut board = Board::with_dimensions(8, 12);
+        assert_eq!((board.width(), board.height()), (8, 12));
+        assert_eq!(board.cell_count(), 96);
+        assert!(!board.is_square());
+        assert!(Board::new(7).is_square());
+        assert_eq!(
+            board.start_at(Coord::new(8, 0)).err(),
+            Some(BoardError::IndexOutOfRange { x: 8, y: 0, max: 8 })
+        );
+        assert_eq!(
+            board.start_at(Coord::new(0, 12)).err(),
+            Some(BoardError::IndexOutOfRange {
+                x: 0,
+                y: 12,
+                max: 12
+            })
+        );
+
+        let board = Board::with_dimensions(8, 12)
+            .start_at(Coord::new(7, 11))
+            .unwrap();
+        assert_eq!(board.possible_moves().len(), 3);
+        let board = board.apply_moves(&[Direction::Up, Direction::Up]).unwrap();
+        assert_eq!(board.current_position(), Some(Coord::new(7, 5)));
+        assert_eq!(board.validate(), Ok(()));
+
+        let text = board.to_string();
+        assert_eq!(text.lines().count(), 12);
+        assert_eq!(text.lines().next().unwrap().split_whitespace().count(), 8);
+        let parsed: Board = text.parse().unwrap();
+        assert_eq!(parsed, board);
+        assert_ne!(parsed, Board::with_dimensions(12, 8));
+    }
+
+    #[test]
+    fn equality_and_hashing() {
+        use std::collections::HashSet;
+
+        let mut board = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        let moved = board.next_move(Direction::Right).unwrap();
+        assert_ne!(board, moved);
+        assert_ne!(Board::new(5), Board::new(6));
+
+        // the same position reached by playing and by undoing
+        let mut undone = moved.clone();
+        undone.undo().unwrap();
+        assert_eq!(undone, board);
+        let parsed: Board = board.to_string().parse().unwrap();
+        assert_eq!(parsed, board);
+
+        let set: HashSet<Board> = vec![board.clone(), undone, parsed, moved]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&board));
+    }
+
+    #[test]
+    fn validate_boards() {
+        assert_eq!(Board::new(5).validate(), Ok(()));
+        let board = crate::fixtures::SOLUTION_5.board_after(6);
+        assert_eq!(board.validate(), Ok(()));
+
+        let mut moved = board.clone();
+        moved.x = 0;
+        moved.y = 0;
+        assert_eq!(
+            moved.validate(),
+            Err(BoardError::BadPosition { x: 0, y: 0 })
+        );
+
+        let mut duplicate = board.clone();
+        duplicate.values[24] = 3;
+        assert_eq!(
+            duplicate.validate(),
+            Err(BoardError::ValueUsed {
+                x: 4,
+                y: 4,
+                value: 3
+            })
+        );
+
+        let mut gap = board.clone();
+        let Coord { x, y } = gap.position_of(4).unwrap();
+        gap.values[y * 5 + x] = 0;
+        assert_eq!(gap.validate(), Err(BoardError::MissingValue { value: 4 }));
+
+        let mut short = board.clone();
+        short.values = short.values[..24].to_vec().into();
+        assert!(short.validate().is_err());
+
+        // a board that is not started holds no value
+        let mut unstarted = Board::new(5);
+        unstarted.values[12] = 9;
+        assert_eq!(
+            unstarted.validate(),
+            Err(BoardError::MissingValue { value: 1 })
+        );
+        unstarted.values[12] = 1;
+        assert_eq!(
+            unstarted.validate(),
+            Err(BoardError::BadPosition { x: 0, y: 0 })
+        );
+        let mut dropped = board.clone();
+        dropped.last = 0;
+        assert!(dropped.validate().is_err());
+
+        let jumps = unchecked(&[(0, 0, 1), (1, 0, 2)]);
+        assert_eq!(
+            jumps.validate(),
+            Err(BoardError::IllegalJump { from: 1, to: 2 })
+        );
+        // parsing checks the same
+        assert_eq!(
+            jumps.to_string().parse::<Board>(),
+            Err(BoardError::IllegalJump { from: 1, to: 2 })
+        );
+    }
+
+    #[test]
+    fn parse_directions() {
+        for &dir in Direction::iterator() {
+            assert_eq!(dir.to_string().parse(), Ok(dir));
+            assert_eq!(dir.to_compact().parse(), Ok(dir));
+            assert_eq!(dir.to_compact().to_lowercase().parse(), Ok(dir));
+        }
+        assert_eq!("down-right".parse(), Ok(Direction::DownRight));
+        assert_eq!(" UpLeft ".parse(), Ok(Direction::UpLeft));
+        for bad in ["", "X", "RD", "Down Down", "Upward"].iter() {
+            assert!(bad.parse::<Direction>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn move_sets() {
+        let board = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        let moves = board.possible_moves();
+        assert_eq!(moves.len(), 3);
+        assert_eq!(board.possible_move_count(), 3);
+        assert!(moves.contains(Direction::DownRight) && !moves.contains(Direction::Up));
+        assert_eq!(moves.first(), Some(Direction::Down));
+        assert_eq!(
+            moves.iter().collect::<Vec<_>>(),
+            vec![Direction::Down, Direction::DownRight, Direction::Right]
+        );
+        assert_eq!(moves.iter().len(), 3);
+        assert_eq!(moves.into_iter().collect::<Moves>(), moves);
+        assert!(Board::new(5).possible_moves().is_empty());
+        assert_eq!(Moves::default().first(), None);
+    }
+
+    #[test]
+    fn direction_turns() {
+        for &dir in Direction::iterator() {
+            assert_ne!(dir.opposite(), dir);
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.rotate_cw().rotate_ccw(), dir);
+            assert_eq!(dir.rotate_cw().rotate_cw(), dir.opposite());
+            for rules in [MoveRules::CLASSIC, MoveRules::knight()].iter() {
+                let (dx, dy) = dir.delta(rules).unwrap();
+                assert_eq!(dir.opposite().delta(rules), Some((-dx, -dy)));
+                assert_eq!(dir.rotate_cw().delta(rules), Some((-dy, dx)));
+                assert_eq!(dir.rotate_ccw().delta(rules), Some((dy, -dx)));
+            }
+        }
+        assert_eq!(Direction::Down.rotate_cw(), Direction::Left);
+        assert_eq!(Direction::UpLeft.opposite(), Direction::DownRight);
+        assert_eq!(Direction::DownRight.delta(&MoveRules::new(3, 0)), None);
+    }
+
+    #[test]
+    fn move_rules() {
+        assert_eq!(MoveRules::default(), MoveRules::CLASSIC);
+        assert_eq!(MoveRules::CLASSIC.delta(Direction::Up), Some((0, -3)));
+        assert_eq!(MoveRules::CLASSIC.delta(Direction::UpLeft), Some((-2, -2)));
+        assert_eq!(MoveRules::CLASSIC.directions().count(), 8);
+
+        // no diagonals
+        let rules = MoveRules::new(3, 0);
+        assert_eq!(rules.delta(Direction::DownRight), None);
+        assert_eq!(rules.directions().count(), 4);
+        let mut board = Board::new(5)
+            .with_rules(rules)
+            .start_at(Coord::new(0, 0))
+            .unwrap();
+        assert_eq!(
+            board.possible_moves().into_iter().collect::<Vec<_>>(),
+            vec![Direction::Down, Direction::Right]
+        );
+        assert!(board.next_move(Direction::DownRight).is_err());
+
+        // jump over three
+        let rules = MoveRules::new(4, 2);
+        assert_eq!(rules.direction_of(4, 0), Some(Direction::Right));
+        assert_eq!(rules.direction_of(3, 0), None);
+        let mut board = Board::new(5)
+            .with_rules(rules)
+            .start_at(Coord::new(0, 0))
+            .unwrap();
+        let board = board.next_move(Direction::Right).unwrap();
+        assert_eq!(board.value_at(Coord::new(4, 0)), 2);
+        assert_eq!(board.to_moves(), Ok(vec![Direction::Right]));
+        assert!(board.validate().is_ok());
+
+        // the same values under other rules are another board
+        let classic = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        let other = classic.clone().with_rules(rules);
+        assert_ne!(classic, other);
+        assert_eq!(other.rules(), &rules);
+
+        // rules in text
+        assert_eq!(
+            MoveRules::new(3, 0).to_string(),
+            "0,3 - 3,0 - 0,-3 - -3,0 -"
+        );
+        for rules in [
+            MoveRules::CLASSIC,
+            MoveRules::new(3, 0),
+            MoveRules::knight().toroidal(),
+        ]
+        .iter()
+        {
+            assert_eq!(rules.to_string().parse::<MoveRules>(), Ok(*rules));
+        }
+        for text in [
+            "",
+            "torus",
+            "0,3 2,2",
+            "0,3 - - - - - - x,1",
+            "0,0 - - - - - - -",
+        ]
+        .iter()
+        {
+            assert_eq!(
+                text.parse::<MoveRules>(),
+                Err(BoardError::InvalidRules {
+                    rules: text.to_string()
+                })
+            );
+        }
+
+        let knight = MoveRules::knight();
+        assert_eq!(knight.direction_of(-2, 1), Some(Direction::Left));
+        assert_eq!(knight.direction_of(2, 2), None);
+        let mut board = Board::new(5)
+            .with_rules(knight)
+            .start_at(Coord::new(0, 0))
+            .unwrap();
+        assert_eq!(board.possible_moves().len(), 2);
+        assert_eq!(
+            board
+                .next_move(Direction::Down)
+                .unwrap()
+                .value_at(Coord::new(1, 2)),
+            2
+        );
+    }
+
+    #[test]
+    fn checked_cell_access() {
+        let board = Board::with_dimensions(6, 5)
+            .start_at(Coord::new(5, 4))
+            .unwrap();
+        assert_eq!(board.try_value_at(Coord::new(5, 4)), Some(1));
+        assert_eq!(board.try_value_at(Coord::new(0, 0)), Some(0));
+        assert_eq!(board.try_value_at(Coord::new(6, 0)), None);
+        assert_eq!(board.try_value_at(Coord::new(0, 5)), None);
+        assert!(!board.is_hole(Coord::new(6, 0)));
+
+        assert_eq!(board[(5, 4)], 1);
+        assert_eq!(board[Coord::new(0, 0)], 0);
+        assert_eq!(board.get((5, 4)), Some(&1));
+        assert_eq!(board.get(Coord::new(6, 0)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "cell [6,0] is not on the 6x5 board")]
+    fn value_at_panics_off_the_board() {
+        Board::with_dimensions(6, 5).value_at(Coord::new(6, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "cell [0,5] is not on the 6x5 board")]
+    fn indexing_panics_off_the_board() {
+        let _ = Board::with_dimensions(6, 5)[(0, 5)];
+    }
+
+    #[test]
+    #[should_panic(expected = "move distance out of range")]
+    fn rules_panic_on_long_jumps() {
+        MoveRules::new(MAX_JUMP + 1, 2);
+    }
+
+    #[test]
+    fn diff_boards() {
+        let game = &crate::fixtures::SOLUTION_5;
+        let (before, after) = (game.board_after(1), game.board_after(3));
+        assert!(before.diff(&before).is_empty());
+        let changes = before.diff(&after);
+        assert_eq!(changes.len(), 2);
+        for change in &changes {
+            assert_eq!(change.before, 0);
+            assert_eq!(after.position_of(change.after), Some(change.cell));
+        }
+        assert_eq!(after.diff(&before)[0].before, changes[0].after);
+
+        let wider = Board::with_dimensions(6, 5)
+            .start_at(Coord::new(5, 0))
+            .unwrap();
+        assert_eq!(
+            Board::new(5).diff(&wider),
+            vec![CellChange {
+                cell: Coord::new(5, 0),
+                before: 0,
+                after: 1,
+                hole_before: false,
+                hole_after: false
+            }]
+        );
+
+        // boards that differ only in their holes
+        let mut holed = Board::new(5);
+        holed.block_cell(Coord::new(2, 3)).unwrap();
+        assert_eq!(
+            Board::new(5).diff(&holed),
+            vec![CellChange {
+                cell: Coord::new(2, 3),
+                before: 0,
+                after: 0,
+                hole_before: false,
+                hole_after: true
+            }]
+        );
+        assert!(holed.diff(&Board::new(5))[0].hole_before);
+    }
+
+    #[test]
+    fn symmetries() {
+        let board = crate::fixtures::SOLUTION_5.board_after(12);
+        let images: Vec<Board> = board.symmetries().collect();
+        assert_eq!(images.len(), 8);
+        assert_eq!(images[0], board);
+        let canonical = board.canonicalize();
+        for image in &images {
+            assert_eq!(image.validate(), Ok(()));
+            assert_eq!(image.score(), 13);
+            assert_eq!(image.canonicalize(), canonical);
+            assert_eq!(image.to_moves().map(|m| m.len()), Ok(12));
+        }
+        assert!(images.contains(&canonical));
+        assert!(Board::new(5)
+            .symmetries()
+            .all(|image| image == Board::new(5)));
+
+        // rectangles only have their reflections; holes move with the values
+        let mut board = Board::with_dimensions(6, 5);
+        board.block_cell(Coord::new(1, 0)).unwrap();
+        let board = board.start_at(Coord::new(0, 0)).unwrap();
+        let images: Vec<Board> = board.symmetries().collect();
+        assert_eq!(images.len(), 4);
+        assert!(images[3].is_hole(Coord::new(4, 4)));
+        assert_eq!(images[3].current_position(), Some(Coord::new(5, 4)));
+    }
+
+    #[test]
+    fn rotate_and_mirror() {
+        let mut board = Board::with_dimensions(6, 5);
+        board.block_cell(Coord::new(1, 0)).unwrap();
+        let board = board
+            .start_at(Coord::new(0, 0))
+            .unwrap()
+            .next_move(Direction::Right)
+            .unwrap();
+
+        let turned = board.rotate90();
+        assert_eq!((turned.width(), turned.height()), (5, 6));
+        assert_eq!(turned.value_at(Coord::new(4, 0)), 1);
+        assert_eq!(turned.value_at(Coord::new(4, 3)), 2);
+        assert!(turned.is_hole(Coord::new(4, 1)));
+        assert_eq!(turned.current_position(), Some(Coord::new(4, 3)));
+        assert_eq!(turned.to_moves(), Ok(vec![Direction::Down]));
+        assert_eq!(turned.cell_count(), board.cell_count());
+
+        assert_eq!(turned.rotate270(), board);
+        assert_eq!(board.rotate90().rotate90(), board.rotate180());
+        assert_eq!(board.rotate180(), board.mirror_h().mirror_v());
+        assert_eq!(board.mirror_h().mirror_h(), board);
+        assert_eq!(board.mirror_h().value_at(Coord::new(5, 0)), 1);
+        assert_eq!(board.mirror_v().value_at(Coord::new(0, 4)), 1);
+        assert_eq!(board.rotate180().validate(), Ok(()));
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let solved = crate::fixtures::SOLUTION_5.board();
+        let bytes = solved.to_bytes();
+        // 25 values of 5 bits after the header
+        assert_eq!(bytes.len(), 6 + 16);
+        assert_eq!(bytes[..6], [b'S', b'Q', 1, 0, 5, 5]);
+        assert_eq!(Board::from_bytes(&bytes), Ok(solved));
+
+        let board = BoardBuilder::new()
+            .dimensions(6, 5)
+            .rules(MoveRules::knight())
+            .toroidal(true)
+            .hole(Coord::new(0, 0))
+            .value(Coord::new(3, 0), 1)
+            .build()
+            .unwrap();
+        let bytes = board.to_bytes();
+        assert_eq!(bytes[3], 7);
+        assert_eq!(Board::from_bytes(&bytes), Ok(board));
+        assert_eq!(
+            Board::from_bytes(&Board::new(255).to_bytes()),
+            Ok(Board::new(255))
+        );
+        // the longest jumps still fit in a byte; longer ones are not rules
+        let mut deltas = [None; 8];
+        deltas[0] = Some((MAX_JUMP, -MAX_JUMP));
+        deltas[5] = Some((-1, 2));
+        let far = Board::new(5).with_rules(MoveRules::custom(deltas).unwrap());
+        let far_bytes = far.to_bytes();
+        assert_eq!(Board::from_bytes(&far_bytes), Ok(far));
+        for delta in [(MAX_JUMP + 1, 0), (0, -MAX_JUMP - 1), (1000, 1)].iter() {
+            deltas[0] = Some(*delta);
+            assert!(matches!(
+                MoveRules::custom(deltas),
+                Err(BoardError::InvalidRules { .. })
+            ));
+        }
+        let mut too_far = far_bytes.clone();
+        too_far[6] = 0x80;
+        assert_eq!(
+            Board::from_bytes(&too_far),
+            Err(BoardError::BadBinary { offset: 6 })
+        );
+
+        assert_eq!(
+            Board::from_bytes(b"XQ\x01\x00\x05\x05"),
+            Err(BoardError::BadBinary { offset: 0 })
+        );
+        let mut newer = bytes.clone();
+        newer[2] = 2;
+        assert!(matches!(
+            Board::from_bytes(&newer),
+            Err(BoardError::Format(FormatError::UnsupportedVersion {
+                version: 2,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            Board::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BoardError::BadBinary { .. })
+        ));
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert_eq!(
+            Board::from_bytes(&extra),
+            Err(BoardError::BadBinary {
+                offset: bytes.len()
+            })
+        );
+        // the 1 moved onto the hole
+        let mut moved = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        moved.block_cell(Coord::new(1, 0)).unwrap();
+        let mut bytes = moved.to_bytes();
+        let values = bytes.len() - 16;
+        bytes[values] = 0b0010_0000;
+        assert_eq!(
+            Board::from_bytes(&bytes),
+            Err(BoardError::Hole { x: 1, y: 0 })
+        );
+        // 1 and 2 are side by side, which is not a jump
+        let apart = unchecked(&[(0, 0, 1), (1, 0, 2)]);
+        assert!(apart.validate().is_err());
+        assert_eq!(
+            Board::from_bytes(&apart.to_bytes()),
+            Err(apart.validate().unwrap_err())
+        );
+    }
+
+    #[test]
+    fn sizes_are_checked_or_clamped() {
+        assert_eq!(Board::try_new(5), Ok(Board::new(5)));
+        assert_eq!(Board::try_new(MAX_SIZE), Ok(Board::new(MAX_SIZE)));
+        assert_eq!(
+            Board::try_new(3),
+            Err(BoardError::InvalidDimensions { cols: 3, rows: 3 })
+        );
+        assert_eq!(
+            Board::try_new(MAX_SIZE + 1),
+            Err(BoardError::InvalidDimensions {
+                cols: MAX_SIZE + 1,
+                rows: MAX_SIZE + 1
+            })
+        );
+        assert_eq!(Board::new(3).size(), MIN_SIZE);
+        assert_eq!(Board::new(MAX_SIZE + 1).size(), MAX_SIZE);
+    }
+
+    #[test]
+    fn masked_boards() {
+        // a diamond: the cells within 3 steps of the center
+        let diamond = Board::from_mask(7, 7, |cell| {
+            (cell.x as i32 - 3).abs() + (cell.y as i32 - 3).abs() <= 3
+        })
+        .unwrap();
+        assert_eq!(diamond.cell_count(), 25);
+        assert!(diamond.is_hole(Coord::new(0, 0)));
+        assert!(!diamond.is_hole(Coord::new(3, 0)));
+        assert_eq!(diamond.empty_cells().count(), 25);
+        assert_eq!(
+            diamond.to_string().lines().next(),
+            Some(" #  #  #  .  #  #  #")
+        );
+        assert_eq!(diamond.to_string().parse(), Ok(diamond.clone()));
+        assert!(diamond.clone().start_at(Coord::new(0, 0)).is_err());
+
+        // filling every masked-in cell wins
+        let cross = Board::from_mask(5, 5, |cell| cell.x == 2 || cell.y == 2).unwrap();
+        let mut board = cross.clone().start_at(Coord::new(2, 2)).unwrap();
+        assert_eq!(cross.cell_count(), 9);
+        for cell in cross.empty_cells().filter(|&cell| cell != Coord::new(2, 2)) {
+            let value = board.score() as u16 + 1;
+            board.place_value(cell.x, cell.y, value);
+        }
+        assert!(board.is_won());
+
+        assert_eq!(
+            Board::from_mask(4, 5, |_| true),
+            Err(BoardError::InvalidDimensions { cols: 4, rows: 5 })
+        );
+    }
+
+    #[test]
+    fn empty_cells() {
+        let mut board = Board::new(5);
+        assert_eq!(board.empty_cells().count(), 25);
+        board.block_cell(Coord::new(1, 0)).unwrap();
+        let board = board.start_at(Coord::new(0, 0)).unwrap();
+        let empty: Vec<Coord> = board.empty_cells().collect();
+        assert_eq!(empty.len(), 23);
+        assert_eq!(empty[..2], [Coord::new(2, 0), Coord::new(3, 0)]);
+        assert!(empty.iter().all(|cell| board.value_at(*cell) == 0));
+        assert_eq!(board.first_empty(), Some(Coord::new(2, 0)));
+        assert_eq!(
+            crate::fixtures::SOLUTION_5.board().empty_cells().next(),
+            None
+        );
+        assert_eq!(crate::fixtures::SOLUTION_5.board().first_empty(), None);
+
+        for board in [
+            board.clone(),
+            board.with_rules(MoveRules::CLASSIC.toroidal()),
+            crate::bench_fixtures::opening(12, 60),
+        ] {
+            let mut degrees = Vec::new();
+            board.empty_degrees(&mut degrees);
+            for (cell, &degree) in degrees.iter().enumerate() {
+                let from = Coord::from_index(cell, board.width());
+                let mut expected = 0;
+                if board.is_vacant(from) {
+                    let targets = board.neighbors().targets(cell);
+                    expected = targets.filter(|&to| !board.is_occupied(to)).count();
+                }
+                assert_eq!(degree as usize, expected, "{}", from);
+            }
+        }
+    }
+
+    #[test]
+    fn degrees_follow_the_moves() {
+        // jumps right or down by one, which can not be reversed
+        let one_way = MoveRules {
+            deltas: [
+                Some((0, 1)),
+                None,
+                Some((1, 0)),
+                None,
+                None,
+                None,
+                None,
+                None,
+            ],
+            toroidal: false,
+        };
+        let check = |board: &Board| {
+            for cell in 0..board.values.len() {
+                let (x, y) = (cell % board.width, cell / board.width);
+                let targets = board.neighbors().targets(cell);
+                let vacant = targets.filter(|&to| !board.is_occupied(to)).count();
+                assert_eq!(board.degree(Coord::new(x, y)), vacant, "({}, {})", x, y);
+            }
+            for dir in board.possible_moves() {
+                let next = board.clone().next_move(dir).unwrap();
+                assert_eq!(board.degree_after(dir), Some(next.possible_move_count()));
+            }
+            assert_eq!(board.possible_move_count(), board.possible_moves().len());
+        };
+        for rules in [
+            MoveRules::CLASSIC,
+            // 3 wide, so a jump of 3 lands where it started
+            MoveRules::CLASSIC.toroidal(),
+            MoveRules::knight(),
+            one_way,
+        ] {
+            let mut board = Board::with_dimensions(3, 7).with_rules(rules);
+            board.block_cell(Coord::new(1, 4)).unwrap();
+            check(&board);
+            let empty = board.clone();
+            board.begin(Coord::new(0, 0)).unwrap();
+            while let Some(dir) = board.possible_moves().first() {
+                check(&board);
+                board.make_move(dir).unwrap();
+            }
+            check(&board);
+            while board.unmake_move().is_ok() {
+                check(&board);
+            }
+            assert_eq!(board.degrees, empty.degrees);
+            board.symmetries().for_each(|b| check(&b));
+        }
+        assert_eq!(Board::new(5).degree(Coord::new(9, 9)), 0);
+        assert_eq!(Board::new(5).degree_after(Direction::Up), None);
+    }
+
+    #[test]
+    fn padded_grid_follows_the_cells() {
+        let check = |board: &Board| {
+            let border = board.neighbors().border().unwrap();
+            let taken = |i: usize| board.padded[i / 64] & (1 << (i % 64)) != 0;
+            let inside: Vec<usize> = (0..board.values.len())
+                .map(|cell| border.index(cell % board.width, cell / board.width))
+                .collect();
+            for (cell, &i) in inside.iter().enumerate() {
+                assert_eq!(taken(i), board.is_occupied(cell));
+            }
+            assert!((0..border.len()).all(|i| inside.contains(&i) || taken(i)));
+            // the moves found on the padded grid are the ones in the table
+            let here = board.y * board.width + board.x;
+            let moves: Moves = Direction::iterator()
+                .copied()
+                .filter(|&dir| {
+                    board.is_started()
+                        && board
+                            .neighbors()
+                            .target(here, dir)
+                            .is_some_and(|to| !board.is_occupied(to))
+                })
+                .collect();
+            assert_eq!(board.possible_moves(), moves);
+        };
+        for rules in [
+            MoveRules::CLASSIC,
+            MoveRules::knight(),
+            MoveRules::new(1, 1),
+        ] {
+            let mut board = Board::with_dimensions(6, 5).with_rules(rules);
+            board.block_cell(Coord::new(2, 3)).unwrap();
+            board.begin(Coord::new(1, 1)).unwrap();
+            while let Some(dir) = board.possible_moves().first() {
+                check(&board);
+                board.make_move(dir).unwrap();
+            }
+            check(&board);
+            while board.unmake_move().is_ok() {
+                check(&board);
+            }
+        }
+        assert!(Board::new(5)
+            .with_rules(MoveRules::CLASSIC.toroidal())
+            .padded
+            .is_empty());
+    }
 
     #[test]
-    // Start a board and check that invariants hold.
-    fn new_board() {
-        let mut board = Board::new(10);
-        // newly created board has a size of 10
-        assert_eq!(board.size, 10);
-        // newly created bboard has cell count of 100
-        assert_eq!(board.cells, 100);
-        // newly created board has a score of 0
-        assert_eq!(board.score(), 0);
-        // newly created board is not started
-        assert_eq!(board.is_started(), false);
-        // unstarted board cannot be won
-        assert_eq!(board.is_won(), false);
-        // no possible moves because board isn't started
-        assert_eq!(board.possible_moves().len(), 0);
-        // start the board
-        board = board.start_at(5, 5).unwrap();
-        // board is started
-        assert_eq!(board.is_started(), true);
-        // cell at (5, 5) should be 1
-        assert_eq!(board.values[55], 1);
-        // score is 1
-        assert_eq!(board.score(), 1);
-        // all moves should be possible
+    fn occupancy_follows_the_cells() {
+        // a 9x9 board spans two words of the mask
+        let mut board = Board::new(9);
+        board.block_cell(Coord::new(8, 7)).unwrap();
+        let mut board = board.start_at(Coord::new(0, 0)).unwrap();
+        for dir in [Direction::Right, Direction::DownRight, Direction::Down] {
+            board.make_move(dir).unwrap();
+        }
+        board.undo().unwrap();
+        let check = |board: &Board| {
+            for (x, y, value) in board.iter_cells() {
+                let vacant = value == 0 && !board.is_hole(Coord::new(x, y));
+                assert_eq!(board.is_vacant(Coord::new(x, y)), vacant, "({}, {})", x, y);
+            }
+        };
+        check(&board);
+        board.symmetries().for_each(|b| check(&b));
+        assert!(!board.is_vacant(Coord::new(9, 0)));
+
+        let solved = crate::fixtures::SOLUTION_5.board();
+        assert!(solved.is_won() && solved.is_blocked());
+        let mut almost = solved.clone();
+        almost.unmake_move().unwrap();
+        assert!(!almost.is_won() && !almost.is_blocked());
+        assert_eq!((solved.remaining(), almost.remaining()), (0, 1));
+        assert_eq!((solved.score(), almost.score()), (25, 24));
+        assert_eq!(almost.current_position(), solved.position_of(24));
+        assert!(board.symmetries().all(|b| b.score() == board.score()));
+        let remaining = board.remaining();
+        assert_eq!(remaining, 81 - 1 - 3);
+        assert_eq!(remaining, board.empty_cells().count());
+        assert!(board.symmetries().all(|b| b.remaining() == remaining));
+    }
+
+    #[test]
+    fn zobrist_keys_follow_the_position() {
+        // every position up to six moves from a corner of a 6x6 board, the
+        // first depth where the same cells can be taken in two orders
+        fn walk(board: &mut Board, depth: usize, seen: &mut HashMap<u64, Board>) -> usize {
+            assert_eq!(board.zobrist(), board.compute_zobrist());
+            let mut transpositions = 0;
+            match seen.get(&board.zobrist()) {
+                Some(other) if other != &*board => {
+                    // the same cells taken in another order
+                    assert_eq!(other.current_position(), board.current_position());
+                    assert!(other.empty_cells().eq(board.empty_cells()));
+                    transpositions += 1;
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(board.zobrist(), board.clone());
+                }
+            }
+            if depth > 0 {
+                for dir in board.possible_moves() {
+                    let before = board.zobrist();
+                    board.make_move(dir).unwrap();
+                    transpositions += walk(board, depth - 1, seen);
+                    board.unmake_move().unwrap();
+                    assert_eq!(board.zobrist(), before);
+                }
+            }
+            transpositions
+        }
+        let mut board = Board::new(6);
+        board.block_cell(Coord::new(5, 5)).unwrap();
+        let key = board.zobrist();
+        board.begin(Coord::new(0, 0)).unwrap();
+        assert!(walk(&mut board, 6, &mut HashMap::new()) > 0);
+        board.unmake_move().unwrap();
+        assert_eq!(board.zobrist(), key);
+        assert_ne!(key, Board::new(6).zobrist());
+
+        let board = crate::fixtures::SOLUTION_5.board_after(10);
+        assert!(board
+            .symmetries()
+            .all(|b| b.zobrist() == b.compute_zobrist()));
+        let parsed: Board = board.to_string().parse().unwrap();
+        assert_eq!(parsed.zobrist(), board.zobrist());
+    }
+
+    #[test]
+    fn reachable_cells() {
+        assert!(Board::new(5).reachable_cells().is_empty());
+        let board = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        assert_eq!(board.reachable_cells().len(), 24);
+
+        // a solved board has nothing left to reach
+        assert!(crate::fixtures::SOLUTION_5
+            .board()
+            .reachable_cells()
+            .is_empty());
+
+        // the only ways into the corner are blocked by holes
+        let mut board = Board::new(5);
+        for &(x, y) in [(3, 0), (0, 3), (2, 2)].iter() {
+            board.block_cell(Coord::new(x, y)).unwrap();
+        }
+        let board = board.start_at(Coord::new(4, 4)).unwrap();
+        let reached = board.reachable_cells();
+        assert!(!reached.contains(&Coord::new(0, 0)));
+        assert_eq!(reached.len(), 20);
+        assert!(board.empty_cells().any(|cell| !reached.contains(&cell)));
+    }
+
+    #[test]
+    fn quick_feasibility() {
+        assert_eq!(Board::new(5).quick_feasibility(), Feasibility::Unknown);
+        assert_eq!(
+            crate::fixtures::SOLUTION_5.board().quick_feasibility(),
+            Feasibility::Unknown
+        );
+        assert_eq!(
+            crate::fixtures::SOLUTION_5
+                .board_after(10)
+                .quick_feasibility(),
+            Feasibility::Unknown
+        );
+
+        // every way into the corner is a hole
+        let mut board = Board::new(5);
+        for &(x, y) in [(3, 0), (0, 3), (2, 2)].iter() {
+            board.block_cell(Coord::new(x, y)).unwrap();
+        }
+        let stranded = Feasibility::Infeasible(Infeasibility::Stranded(Coord::new(0, 0)));
+        // the corner can still hold the 1
+        assert_eq!(board.quick_feasibility(), Feasibility::Unknown);
+        assert_eq!(
+            board
+                .start_at(Coord::new(4, 4))
+                .unwrap()
+                .quick_feasibility(),
+            stranded
+        );
+
+        // two such corners can not both be ends of the path
+        for &(x, y) in [(1, 4), (4, 1), (2, 2)].iter() {
+            board.block_cell(Coord::new(x, y)).unwrap();
+        }
+        assert_eq!(
+            board.quick_feasibility(),
+            Feasibility::Infeasible(Infeasibility::Stranded(Coord::new(4, 4)))
+        );
+
+        // cells reachable from each other, but not from the current position
+        let board = Board::new(6)
+            .with_rules(MoveRules::new(3, 0))
+            .start_at(Coord::new(0, 0))
+            .unwrap();
+        assert_eq!(
+            board.quick_feasibility(),
+            Feasibility::Infeasible(Infeasibility::Unreachable(Coord::new(1, 0)))
+        );
+    }
+
+    #[test]
+    fn holes() {
+        let solution = crate::fixtures::SOLUTION_5.board();
+        let last = solution.position_of(25).unwrap();
+
+        // a board missing its last value is won once that cell is a hole
+        let mut board = crate::fixtures::SOLUTION_5.board_after(23);
+        assert!(!board.is_won());
+        board.block_cell(last).unwrap();
+        assert!(board.is_hole(last));
+        assert_eq!(board.cell_count(), 24);
+        assert!(board.is_won());
+        assert_eq!(board.validate(), Ok(()));
+
+        // holes survive the text format
+        let text = board.to_string();
+        assert_eq!(
+            text.lines()
+                .nth(last.y)
+                .unwrap()
+                .split_whitespace()
+                .nth(last.x),
+            Some("#")
+        );
+        assert_eq!(text.parse::<Board>().unwrap(), board);
+        assert_ne!(board, crate::fixtures::SOLUTION_5.board_after(23));
+
+        // holes can not be visited
+        let mut board = Board::new(5);
+        board.block_cell(Coord::new(3, 0)).unwrap();
+        board.block_cell(Coord::new(3, 0)).unwrap();
+        assert_eq!(board.cell_count(), 24);
+        assert_eq!(
+            board.start_at(Coord::new(3, 0)),
+            Err(BoardError::Hole { x: 3, y: 0 })
+        );
+        let mut board = board.start_at(Coord::new(0, 0)).unwrap();
+        assert_eq!(
+            board.possible_moves().into_iter().collect::<Vec<_>>(),
+            vec![Direction::Down, Direction::DownRight]
+        );
+        assert!(board.next_move(Direction::Right).is_err());
+        assert_eq!(
+            board.block_cell(Coord::new(0, 0)),
+            Err(BoardError::NotEmpty { x: 0, y: 0 })
+        );
+        assert!(board.block_cell(Coord::new(5, 0)).is_err());
+
+        let mut bad = board.clone();
+        bad.holes[0] = true;
+        assert_eq!(
+            bad.validate().unwrap_err(),
+            BoardError::InvalidDimensions { cols: 5, rows: 5 }
+        );
+        bad.open -= 1;
+        assert_eq!(bad.validate(), Err(BoardError::Hole { x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn toroidal_boards() {
+        let rules = MoveRules::CLASSIC.toroidal();
+        assert!(rules.is_toroidal());
+        assert!(!MoveRules::CLASSIC.is_toroidal());
+        assert_eq!(
+            rules.target(5, 6, Coord::new(0, 0), Direction::Left),
+            Some(Coord::new(2, 0))
+        );
+        assert_eq!(
+            rules.target(5, 6, Coord::new(0, 0), Direction::UpLeft),
+            Some(Coord::new(3, 4))
+        );
+        assert_eq!(
+            MoveRules::CLASSIC.target(5, 6, Coord::new(0, 0), Direction::Left),
+            None
+        );
+        assert_eq!(
+            rules.direction_between(5, 6, Coord::new(0, 0), Coord::new(3, 4)),
+            Some(Direction::UpLeft)
+        );
+
+        // every move is open from a corner
+        let mut board = Board::new(5)
+            .with_rules(rules)
+            .start_at(Coord::new(0, 0))
+            .unwrap();
         assert_eq!(board.possible_moves().len(), 8);
-        // board isn't won
-        assert_eq!(board.is_won(), false);
+        let board = board.next_move(Direction::Up).unwrap();
+        assert_eq!(board.value_at(Coord::new(0, 2)), 2);
+        assert_eq!(board.to_moves(), Ok(vec![Direction::Up]));
+        assert!(board.validate().is_ok());
+
+        // the mode survives the text format and is part of equality
+        let text = board.to_string();
+        assert!(text.starts_with("rules 0,3 2,2 3,0 2,-2 0,-3 -2,-2 -3,0 -2,2 torus\n"));
+        let parsed: Board = board.to_artifact().parse().unwrap();
+        assert_eq!(parsed, board);
+        assert_eq!(parsed.rules(), &rules);
+        // as it did in version 1
+        let grid = text.split_once('\n').unwrap().1;
+        let v1: Board = format!("square100 board v1\ntorus\n{}", grid)
+            .parse()
+            .unwrap();
+        assert_eq!(v1, board);
+        assert_eq!(
+            grid.parse::<Board>(),
+            Err(BoardError::IllegalJump { from: 1, to: 2 })
+        );
     }
 
     #[test]
-    // Test a complete set of moves on a 5x5 board and confirm that all
-    // invariants hold. The completed board looks like:
-    //
-    //      1 24 14  2 25
-    //     16 21  5  8 20
-    //     13 10 18 23 11
-    //      4  7 15  3  6
-    //     17 22 12  9 19
-    fn win_5() {
-        let moves = [
-            Direction::Right,
-            Direction::Down,
-            Direction::Left,
-            Direction::UpRight,
-            Direction::DownRight,
-            Direction::Left,
-            Direction::UpRight,
-            Direction::Down,
-            Direction::UpLeft,
-            Direction::Right,
-            Direction::DownLeft,
-            Direction::UpLeft,
-            Direction::UpRight,
-            Direction::Down,
-            Direction::UpLeft,
-            Direction::Down,
-            Direction::UpRight,
-            Direction::DownRight,
-            Direction::Up,
-            Direction::Left,
-            Direction::Down,
-            Direction::UpRight,
-            Direction::UpLeft,
-            Direction::Right,
-        ];
-        // The number of possible moves on the board after each move.
-        let possible_moves = [
-            3, 2, 2, 1, 2, 2, 2, 2, 2, 1, 2, 1, 2, 1, 2, 1, 1, 2, 2, 1, 1, 1, 1, 1, 0,
-        ];
+    fn reconstruct_moves() {
+        let solved: Board = crate::fixtures::SOLUTION_5
+            .board()
+            .to_string()
+            .parse()
+            .unwrap();
+        let moves = solved.to_moves().unwrap();
+        assert_eq!(moves, crate::fixtures::SOLUTION_5.moves);
+        assert_eq!(
+            Board::from_moves(5, Coord::new(0, 0), &moves).unwrap(),
+            solved
+        );
+        assert_eq!(Board::new(5).to_moves(), Err(BoardError::NotStarted));
+        let notation = solved.to_notation().unwrap();
+        assert!(notation.starts_with("A1: R D L UR DR L "));
+        assert_eq!(notation.split(' ').count(), 25);
+        let started = Board::new(5).start_at(Coord::new(2, 1)).unwrap();
+        assert_eq!(started.to_notation(), Ok("C2:".to_string()));
+
+        let parsed = unchecked(&[(0, 0, 1), (1, 0, 2)]);
+        assert_eq!(
+            parsed.to_moves(),
+            Err(BoardError::IllegalJump { from: 1, to: 2 })
+        );
+    }
 
+    #[test]
+    fn accessors() {
+        let mut board = Board::new(7);
+        assert_eq!(board.size(), 7);
+        assert_eq!(board.cell_count(), 49);
+        assert_eq!(board.current_position(), None);
+        board = board.start_at(Coord::new(6, 2)).unwrap();
+        assert_eq!(board.current_position(), Some(Coord::new(6, 2)));
+        board = board.next_move(Direction::Left).unwrap();
+        assert_eq!(board.current_position(), Some(Coord::new(3, 2)));
+        // sizes are clamped to the supported range
+        assert_eq!(Board::new(1).size(), 5);
+        assert_eq!(Board::new(300).size(), MAX_SIZE);
+        assert_eq!(Board::new(99).cell_count(), 9801);
+    }
+
+    #[test]
+    fn iterate_cells() {
+        let board = Board::new(5).start_at(Coord::new(3, 1)).unwrap();
+        let cells: Vec<(usize, usize, u16)> = board.iter_cells().collect();
+        assert_eq!(cells.len(), 25);
+        assert_eq!(cells[0], (0, 0, 0));
+        assert_eq!(cells[4], (4, 0, 0));
+        assert_eq!(cells[5], (0, 1, 0));
+        assert_eq!(cells[8], (3, 1, 1));
+        assert_eq!(cells[24], (4, 4, 0));
+        assert_eq!(board.iter_cells().filter(|c| c.2 > 0).count(), 1);
+    }
+
+    #[test]
+    fn find_values() {
         let mut board = Board::new(5);
-        assert_eq!(board.is_started(), false);
-        board = board.start_at(0, 0).unwrap();
-        assert_eq!(board.is_started(), true);
-        let mut possible = possible_moves.iter();
-        let mut i = 1;
-        for m in moves.iter() {
-            assert_eq!(
-                board.possible_moves().len(),
-                *possible.next().unwrap(),
-                "testing move {}",
-                i
-            );
-            assert_eq!(board.is_won(), false);
-            assert_eq!(board.is_blocked(), false);
-            assert_eq!(board.score(), i);
-            let ret = board.next_move(*m);
-            assert_eq!(ret.is_ok(), true, "testing move {}", i);
-            board = ret.unwrap();
-            i += 1;
+        assert_eq!(board.position_of(1), None);
+        board = board.start_at(Coord::new(2, 4)).unwrap();
+        board = board.next_move(Direction::Up).unwrap();
+        assert_eq!(board.position_of(1), Some(Coord::new(2, 4)));
+        assert_eq!(board.position_of(2), Some(Coord::new(2, 1)));
+        assert_eq!(board.position_of(3), None);
+        // zero marks empty cells, not a placed value
+        assert_eq!(board.position_of(0), None);
+    }
+
+    #[test]
+    fn apply_move_lists() {
+        let board = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        let moved = board
+            .apply_moves(&[Direction::Right, Direction::Down])
+            .unwrap();
+        assert_eq!(moved.score(), 3);
+        assert_eq!(moved.current_position(), Some(Coord::new(3, 3)));
+        assert_eq!(board.score(), 1);
+        assert_eq!(board.apply_moves(&[]).unwrap().score(), 1);
+
+        assert_eq!(
+            board
+                .apply_moves(&[Direction::Right, Direction::Down, Direction::Down])
+                .err(),
+            Some(BoardError::MoveFailed {
+                index: 2,
+                error: Box::new(BoardError::BadDirection {
+                    dir: Direction::Down,
+                    x: 3,
+                    y: 3
+                })
+            })
+        );
+        let error = Board::new(5).apply_moves(&[Direction::Down]).unwrap_err();
+        assert_eq!(
+            error,
+            BoardError::MoveFailed {
+                index: 0,
+                error: Box::new(BoardError::NotStarted)
+            }
+        );
+        assert_eq!(error.root(), &BoardError::NotStarted);
+        assert_eq!(BoardError::NotStarted.root(), &BoardError::NotStarted);
+    }
+
+    #[test]
+    fn build_from_moves() {
+        let board =
+            Board::from_moves(5, Coord::new(0, 0), &[Direction::Right, Direction::Down]).unwrap();
+        assert_eq!(board.score(), 3);
+        assert_eq!(
+            board.path(),
+            vec![Coord::new(0, 0), Coord::new(3, 0), Coord::new(3, 3)]
+        );
+        assert_eq!(
+            Board::from_moves(5, Coord::new(0, 0), &[]).unwrap().moves(),
+            Vec::<Direction>::new()
+        );
+        assert_eq!(
+            Board::from_moves(5, Coord::new(5, 0), &[Direction::Right]).err(),
+            Some(BoardError::IndexOutOfRange { x: 5, y: 0, max: 5 })
+        );
+        assert_eq!(
+            Board::from_moves(5, Coord::new(0, 0), &[Direction::Right, Direction::Right]).err(),
+            Some(BoardError::MoveFailed {
+                index: 1,
+                error: Box::new(BoardError::BadDirection {
+                    dir: Direction::Right,
+                    x: 3,
+                    y: 0
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn sandbox_rolls_back() {
+        let mut board = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        let before = board.clone();
+        {
+            let mut sandbox = board.sandbox();
+            sandbox.next_move(Direction::Right).unwrap();
+            sandbox.next_move(Direction::Down).unwrap();
+            assert_eq!(sandbox.score(), 3);
+            assert_eq!(sandbox.depth(), 2);
+            assert!(sandbox.next_move(Direction::Up).is_err());
+            assert_eq!(sandbox.depth(), 2);
+            sandbox.undo().unwrap();
+            assert_eq!(sandbox.score(), 2);
         }
-        assert_eq!(board.possible_moves().len(), *possible.next().unwrap());
-        // ensure we have checked all values
-        assert_eq!(possible.next().is_none(), true);
-        // board is now won
-        assert_eq!(board.is_won(), true);
-        // score should be 25 (max board)
-        assert_eq!(board.score(), board.cells);
-        // there should be no possible moves;
-        assert_eq!(board.is_blocked(), true);
+        assert_eq!(board.values, before.values);
+        assert_eq!((board.x, board.y), (0, 0));
+
+        // moves before the sandbox can not be undone through it
+        let mut sandbox = board.sandbox();
+        assert_eq!(sandbox.undo(), Err(BoardError::NotStarted));
+        sandbox.next_move(Direction::Down).unwrap();
+        sandbox.commit();
+        assert_eq!(board.score(), 2);
+
+        let mut empty = Board::new(5);
+        {
+            let mut sandbox = empty.sandbox();
+            sandbox.start_at(Coord::new(2, 2)).unwrap();
+            assert!(sandbox.is_started());
+        }
+        assert!(!empty.is_started());
+    }
+
+    #[test]
+    fn undo_moves() {
+        let mut board = Board::new(5);
+        assert_eq!(board.undo(), Err(BoardError::NotStarted));
+        board = board.start_at(Coord::new(1, 1)).unwrap();
+        board = board.next_move(Direction::Right).unwrap();
+        board = board.next_move(Direction::Down).unwrap();
+        assert_eq!(board.score(), 3);
+
+        assert_eq!(board.undo(), Ok(()));
+        assert_eq!(board.score(), 2);
+        assert_eq!(board.value_at(Coord::new(4, 4)), 0);
+        assert_eq!((board.x, board.y), (4, 1));
+        // the retracted cell can be played again
+        board = board.next_move(Direction::Down).unwrap();
+        assert_eq!(board.value_at(Coord::new(4, 4)), 3);
+
+        board.undo().unwrap();
+        board.undo().unwrap();
+        assert_eq!((board.x, board.y), (1, 1));
+        board.undo().unwrap();
+        assert!(!board.is_started());
+        assert_eq!(board.score(), 0);
+        assert_eq!(board.undo(), Err(BoardError::NotStarted));
+
+        // starting in place, and taking the start back
+        board.block_cell(Coord::new(0, 0)).unwrap();
+        assert_eq!(
+            board.begin(Coord::new(0, 0)),
+            Err(BoardError::Hole { x: 0, y: 0 })
+        );
+        assert!(!board.is_started());
+        let empty = board.clone();
+        board.begin(Coord::new(2, 3)).unwrap();
+        assert_eq!(board, empty.clone().start_at(Coord::new(2, 3)).unwrap());
+        assert_eq!(
+            board.begin(Coord::new(1, 1)),
+            Err(BoardError::ValueUsed {
+                x: 1,
+                y: 1,
+                value: 1
+            })
+        );
+        board.unmake_move().unwrap();
+        assert!(board.is_vacant(Coord::new(2, 3)) && !board.is_started());
+    }
+
+    #[test]
+    fn build_boards() {
+        let board = BoardBuilder::new()
+            .dimensions(6, 5)
+            .rules(MoveRules::knight())
+            .toroidal(true)
+            .holes(vec![Coord::new(0, 0), Coord::new(5, 4)])
+            .value(Coord::new(3, 0), 1)
+            .value(Coord::new(4, 2), 2)
+            .build()
+            .unwrap();
+        assert_eq!((board.width(), board.height()), (6, 5));
+        assert_eq!(board.rules(), &MoveRules::knight().toroidal());
+        assert_eq!(board.cell_count(), 28);
+        assert!(board.is_hole(Coord::new(5, 4)));
+        assert_eq!(board.current_position(), Some(Coord::new(4, 2)));
+        assert_eq!(board.validate(), Ok(()));
+        // the text format keeps the rules too
+        let parsed: Board = board.to_string().parse().unwrap();
+        assert_eq!(parsed, board);
+
+        assert_eq!(BoardBuilder::new().build(), Ok(Board::new(10)));
+        assert_eq!(
+            BoardBuilder::new().size(4).build(),
+            Err(BoardError::InvalidDimensions { cols: 4, rows: 4 })
+        );
+        assert_eq!(
+            BoardBuilder::new().hole(Coord::new(10, 0)).build(),
+            Err(BoardError::IndexOutOfRange {
+                x: 10,
+                y: 0,
+                max: 10
+            })
+        );
+        let cell = Coord::new(1, 1);
+        assert_eq!(
+            BoardBuilder::new().hole(cell).value(cell, 1).build(),
+            Err(BoardError::Hole { x: 1, y: 1 })
+        );
+        assert_eq!(
+            BoardBuilder::new().value(cell, 2).build(),
+            Err(BoardError::MissingValue { value: 1 })
+        );
+        assert_eq!(
+            BoardBuilder::new()
+                .value(cell, 1)
+                .value(Coord::new(0, 0), 1)
+                .build(),
+            Err(BoardError::ValueUsed {
+                x: 0,
+                y: 0,
+                value: 1
+            })
+        );
+    }
+
+    #[test]
+    fn make_and_unmake_moves() {
+        let game = &crate::fixtures::SOLUTION_5;
+        let mut board = game.board_after(0);
+        for (i, dir) in game.moves.iter().enumerate() {
+            board.make_move(*dir).unwrap();
+            assert_eq!(board, game.board_after(i + 1));
+        }
+        assert!(board.is_won());
+        assert_eq!(
+            board.make_move(Direction::Up),
+            Err(BoardError::BadDirection {
+                dir: Direction::Up,
+                x: board.x,
+                y: board.y
+            })
+        );
+        for i in (0..game.moves.len()).rev() {
+            board.unmake_move().unwrap();
+            assert_eq!(board, game.board_after(i));
+        }
+        board.unmake_move().unwrap();
+        assert!(!board.is_started());
+        assert_eq!(board.unmake_move(), Err(BoardError::NotStarted));
+        assert_eq!(board.make_move(Direction::Up), Err(BoardError::NotStarted));
     }
 
     #[test]
@@ -392,7 +3831,7 @@ mod tests {
         const MAX: usize = 5;
         let mut board = Board::new(MAX);
         assert_eq!(board.is_started(), false);
-        let ret = board.start_at(MAX + 1, 1);
+        let ret = board.start_at(Coord::new(MAX + 1, 1));
         assert_eq!(ret.is_err(), true);
         assert_eq!(
             ret.err(),
@@ -403,4 +3842,73 @@ mod tests {
             })
         );
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let board = Board::new(5)
+            .start_at(Coord::new(0, 0))
+            .unwrap()
+            .next_move(Direction::DownRight)
+            .unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        let back: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.values, board.values);
+        assert_eq!((back.x, back.y), (2, 2));
+
+        assert_eq!(back, board);
+        assert_eq!(back.remaining(), 23);
+        assert_eq!(back.zobrist(), board.zobrist());
+
+        // the caches are not written, and stale ones are not read back
+        assert!(!json.contains("occupied") && !json.contains("remaining"));
+        let mut stale: serde_json::Value = serde_json::from_str(&json).unwrap();
+        stale["remaining"] = 0.into();
+        stale["last"] = 0.into();
+        stale["occupied"] = serde_json::json!([0]);
+        let back: Board = serde_json::from_value(stale).unwrap();
+        assert_eq!(back, board);
+        assert_eq!(back.remaining(), 23);
+        assert!(back.is_started() && !back.is_vacant(Coord::new(0, 0)));
+
+        let mut holed = Board::new(5).with_rules(MoveRules::CLASSIC.toroidal());
+        holed.block_cell(Coord::new(4, 4)).unwrap();
+        let json = serde_json::to_string(&holed).unwrap();
+        let back = serde_json::from_str::<Board>(&json).unwrap();
+        assert_eq!(back, holed);
+        assert_eq!((back.cell_count(), back.remaining()), (24, 24));
+
+        // fields from anywhere are checked rather than trusted
+        let json = serde_json::to_value(&board).unwrap();
+        let mut far = json.clone();
+        far["rules"]["deltas"][0] = serde_json::json!([MAX_JUMP + 1, 0]);
+        assert!(serde_json::from_value::<Board>(far).is_err());
+        let broken = [
+            ("width", serde_json::json!(0)),
+            ("height", serde_json::json!(MAX_SIZE + 1)),
+            ("values", serde_json::json!([])),
+            ("holes", serde_json::json!([true])),
+            ("x", serde_json::json!(5)),
+            ("y", serde_json::json!(usize::MAX)),
+        ];
+        for (field, value) in broken.iter() {
+            let mut fields = json.clone();
+            fields[field] = value.clone();
+            assert!(
+                serde_json::from_value::<Board>(fields).is_err(),
+                "{}",
+                field
+            );
+        }
+        let mut fields = json.clone();
+        fields["x"] = 4.into();
+        let err = serde_json::from_value::<Board>(fields).unwrap_err();
+        assert!(err.to_string().contains("[4,2]"), "{}", err);
+
+        let dir: Direction = serde_json::from_str("\"UpLeft\"").unwrap();
+        assert_eq!(dir, Direction::UpLeft);
+        let err = BoardError::NotEmpty { x: 1, y: 2 };
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(serde_json::from_str::<BoardError>(&json).unwrap(), err);
+    }
 }
@@ -0,0 +1,702 @@
+/*!
+The puzzle played on a cube.
+
+A `Board3D` is `size` x `size` x `size` cells. The jumps of the flat puzzle
+are extended to the third axis: a jump goes `HV_OFFSET` cells along any one
+axis, or `DIAG_OFFSET` cells along each of any two axes, which gives 18
+jumps from a cell in the middle of the cube. As on the flat board the values
+1, 2, ... are placed one jump apart until every cell is filled.
+
+The text format writes the layers from z = 0 up, each as a grid in the
+format of `Board`, separated by blank lines.
+ */
+
+use crate::board::{DIAG_OFFSET, HV_OFFSET, MIN_SIZE};
+use crate::format::{Artifact, Format, FormatError, Validate};
+use crate::solver::Searchable;
+use failure::Fail;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// The text format of a `Board3D`, as produced by its `Display` impl.
+pub const BOARD3D_FORMAT: Format = Format {
+    kind: "board3d",
+    version: 1,
+    migrations: &[],
+};
+
+/// The largest number of cells along an edge of a cube. The values of a
+/// cube this size still fit in a `u16`.
+pub const MAX_SIZE_3D: usize = 40;
+
+/// The (x, y, z) offsets of the jumps on a cube: along one axis, then
+/// across each plane of two axes.
+pub const JUMPS_3D: [(i32, i32, i32); 18] = [
+    (HV_OFFSET, 0, 0),
+    (-HV_OFFSET, 0, 0),
+    (0, HV_OFFSET, 0),
+    (0, -HV_OFFSET, 0),
+    (0, 0, HV_OFFSET),
+    (0, 0, -HV_OFFSET),
+    (DIAG_OFFSET, DIAG_OFFSET, 0),
+    (DIAG_OFFSET, -DIAG_OFFSET, 0),
+    (-DIAG_OFFSET, DIAG_OFFSET, 0),
+    (-DIAG_OFFSET, -DIAG_OFFSET, 0),
+    (DIAG_OFFSET, 0, DIAG_OFFSET),
+    (DIAG_OFFSET, 0, -DIAG_OFFSET),
+    (-DIAG_OFFSET, 0, DIAG_OFFSET),
+    (-DIAG_OFFSET, 0, -DIAG_OFFSET),
+    (0, DIAG_OFFSET, DIAG_OFFSET),
+    (0, DIAG_OFFSET, -DIAG_OFFSET),
+    (0, -DIAG_OFFSET, DIAG_OFFSET),
+    (0, -DIAG_OFFSET, -DIAG_OFFSET),
+];
+
+#[derive(Debug, Fail, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Errors raised by invalid actions on a cube.
+pub enum Board3DError {
+    #[fail(display = "Board not started.")]
+    NotStarted,
+    #[fail(
+        display = "Cell [{},{},{}] is outside the cube of size {}",
+        x, y, z, size
+    )]
+    OutOfRange {
+        x: usize,
+        y: usize,
+        z: usize,
+        size: usize,
+    },
+    #[fail(display = "Cell [{},{},{}] is not empty", x, y, z)]
+    NotEmpty { x: usize, y: usize, z: usize },
+    #[fail(
+        display = "Jump ({},{},{}) is invalid from the current cell",
+        dx, dy, dz
+    )]
+    BadJump { dx: i32, dy: i32, dz: i32 },
+    #[fail(
+        display = "Invalid cube: {} layers of {} rows of {} cells",
+        layers, rows, cols
+    )]
+    InvalidDimensions {
+        cols: usize,
+        rows: usize,
+        layers: usize,
+    },
+    #[fail(display = "Invalid cell '{}' in layer {} row {}", token, layer, row)]
+    InvalidToken {
+        layer: usize,
+        row: usize,
+        token: String,
+    },
+    #[fail(display = "Value {} is larger than {}", value, max)]
+    ValueOutOfRange { value: u16, max: usize },
+    #[fail(display = "Value {} is used more than once", value)]
+    ValueUsed { value: u16 },
+    #[fail(display = "Value {} is missing from the cube", value)]
+    MissingValue { value: u16 },
+    #[fail(display = "Values {} and {} are not a legal jump apart", from, to)]
+    IllegalJump { from: u16, to: u16 },
+    #[fail(
+        display = "Current position [{},{},{}] does not hold the highest value",
+        x, y, z
+    )]
+    BadPosition { x: usize, y: usize, z: usize },
+    #[fail(display = "{}", _0)]
+    Format(#[cause] FormatError),
+}
+
+impl From<FormatError> for Board3DError {
+    fn from(err: FormatError) -> Self {
+        Board3DError::Format(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Board3DFields"))]
+/// Board3D is the puzzle on a cube of `size` x `size` x `size` cells,
+/// indexed layer by layer, then row by row.
+pub struct Board3D {
+    /// The number of cells along each edge.
+    size: usize,
+    /// The values of the cells; 0 for an empty cell.
+    values: Vec<u16>,
+    /// The value placed last, at (x, y, z); 0 if the cube is not started.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    last: u16,
+    /// The location of the last value placed.
+    x: usize,
+    y: usize,
+    z: usize,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+/// Board3DFields is a deserialized `Board3D`: the fields it is written
+/// with. The value placed last is worked out again from these.
+struct Board3DFields {
+    size: usize,
+    values: Vec<u16>,
+    x: usize,
+    y: usize,
+    z: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Board3DFields> for Board3D {
+    type Error = Board3DError;
+
+    /// Build the cube and check it with `validate`, as the fields may come
+    /// from anywhere.
+    fn try_from(fields: Board3DFields) -> Result<Board3D, Board3DError> {
+        let board = Board3D {
+            size: fields.size,
+            last: fields.values.iter().copied().max().unwrap_or(0),
+            values: fields.values,
+            x: fields.x,
+            y: fields.y,
+            z: fields.z,
+        };
+        board.validate()?;
+        Ok(board)
+    }
+}
+
+impl Board3D {
+    /// Create an empty cube. The size is clamped to `MIN_SIZE..=MAX_SIZE_3D`.
+    pub fn new(size: usize) -> Self {
+        let size = size.clamp(MIN_SIZE, MAX_SIZE_3D);
+        Board3D {
+            size,
+            values: vec![0; size * size * size],
+            last: 0,
+            x: 0,
+            y: 0,
+            z: 0,
+        }
+    }
+
+    #[inline]
+    /// Return the number of cells along each edge.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    /// Return the total number of cells.
+    pub fn cell_count(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.size + y) * self.size + x
+    }
+
+    #[inline]
+    fn location(&self, index: usize) -> (usize, usize, usize) {
+        let size = self.size;
+        (index % size, index / size % size, index / (size * size))
+    }
+
+    #[inline]
+    /// Return the value at (x, y, z); 0 for an empty cell.
+    pub fn value_at(&self, x: usize, y: usize, z: usize) -> u16 {
+        self.values[self.index(x, y, z)]
+    }
+
+    #[inline]
+    /// Return `true` if the cube has been started.
+    pub fn is_started(&self) -> bool {
+        self.last > 0
+    }
+
+    /// Return the location of the last value placed, or `None` if the cube
+    /// has not been started.
+    pub fn current_position(&self) -> Option<(usize, usize, usize)> {
+        if self.is_started() {
+            Some((self.x, self.y, self.z))
+        } else {
+            None
+        }
+    }
+
+    /// The score is the highest value on the cube, the one placed last.
+    pub fn score(&self) -> usize {
+        self.last as usize
+    }
+
+    /// Return the cell a jump of `jump` from (x, y, z) lands on, if it is
+    /// inside the cube.
+    fn target(
+        &self,
+        (x, y, z): (usize, usize, usize),
+        (dx, dy, dz): (i32, i32, i32),
+    ) -> Option<(usize, usize, usize)> {
+        let size = self.size as i32;
+        let (x, y, z) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+        if [x, y, z].iter().all(|c| (0..size).contains(c)) {
+            Some((x as usize, y as usize, z as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Return the jumps of `JUMPS_3D` that land on an empty cell from the
+    /// current position. Returns an empty list if the cube is empty.
+    pub fn possible_moves(&self) -> Vec<(i32, i32, i32)> {
+        let current = match self.current_position() {
+            Some(current) => current,
+            None => return Vec::new(),
+        };
+        JUMPS_3D
+            .iter()
+            .copied()
+            .filter(|&jump| {
+                self.target(current, jump)
+                    .is_some_and(|(x, y, z)| self.value_at(x, y, z) == 0)
+            })
+            .collect()
+    }
+
+    /// Return `true` if every cell has been filled.
+    pub fn is_won(&self) -> bool {
+        self.score() == self.cell_count()
+    }
+
+    /// Return `true` if the cube is started and no move is possible.
+    pub fn is_blocked(&self) -> bool {
+        self.is_started() && self.possible_moves().is_empty()
+    }
+
+    /// Start the puzzle by placing a 1 at (x, y, z) of an empty cube.
+    pub fn start_at(&self, x: usize, y: usize, z: usize) -> Result<Board3D, Board3DError> {
+        if x >= self.size || y >= self.size || z >= self.size {
+            return Err(Board3DError::OutOfRange {
+                x,
+                y,
+                z,
+                size: self.size,
+            });
+        }
+        if self.is_started() {
+            return Err(Board3DError::NotEmpty { x, y, z });
+        }
+        Ok(self.place(x, y, z))
+    }
+
+    /// Jump by `jump`, one of `JUMPS_3D`, from the current position.
+    pub fn next_move(&self, jump: (i32, i32, i32)) -> Result<Board3D, Board3DError> {
+        let current = self.current_position().ok_or(Board3DError::NotStarted)?;
+        let (dx, dy, dz) = jump;
+        match self.target(current, jump) {
+            Some((x, y, z)) if JUMPS_3D.contains(&jump) && self.value_at(x, y, z) == 0 => {
+                Ok(self.place(x, y, z))
+            }
+            _ => Err(Board3DError::BadJump { dx, dy, dz }),
+        }
+    }
+
+    /// Return a copy with the next value placed at (x, y, z).
+    fn place(&self, x: usize, y: usize, z: usize) -> Board3D {
+        let mut board = self.clone();
+        board.last += 1;
+        board.values[self.index(x, y, z)] = board.last;
+        board.x = x;
+        board.y = y;
+        board.z = z;
+        board
+    }
+
+    /// Return the locations of the values 1, 2, ... in order, stopping at
+    /// the first value that has not been placed.
+    pub fn path(&self) -> Vec<(usize, usize, usize)> {
+        let mut cells: Vec<(u16, usize)> = self
+            .values
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| v > 0)
+            .map(|(i, &v)| (v, i))
+            .collect();
+        cells.sort_unstable();
+        cells
+            .iter()
+            .enumerate()
+            .take_while(|&(i, &(v, _))| v as usize == i + 1)
+            .map(|(_, &(_, index))| self.location(index))
+            .collect()
+    }
+
+    /// Check that the cube is consistent: its values are unique and form
+    /// the range 1..k, each consecutive pair of values is a legal jump
+    /// apart, and the current position holds k. Cubes are checked like this
+    /// as they are parsed or deserialized.
+    pub fn validate(&self) -> Result<(), Board3DError> {
+        let (size, cells) = (self.size, self.cell_count());
+        if size != size.clamp(MIN_SIZE, MAX_SIZE_3D) || cells != size * size * size {
+            return Err(Board3DError::InvalidDimensions {
+                cols: size,
+                rows: size,
+                layers: cells / (size * size).max(1),
+            });
+        }
+        let (x, y, z) = (self.x, self.y, self.z);
+        if x >= size || y >= size || z >= size {
+            return Err(Board3DError::OutOfRange { x, y, z, size });
+        }
+        let max = self.values.iter().copied().max().unwrap_or(0) as usize;
+        if max > cells {
+            return Err(Board3DError::ValueOutOfRange {
+                value: max as u16,
+                max: cells,
+            });
+        }
+        let mut seen = vec![false; cells + 1];
+        for &value in self.values.iter().filter(|&&v| v > 0) {
+            if seen[value as usize] {
+                return Err(Board3DError::ValueUsed { value });
+            }
+            seen[value as usize] = true;
+        }
+        if let Some(value) = (1..max).find(|&v| !seen[v]) {
+            return Err(Board3DError::MissingValue {
+                value: value as u16,
+            });
+        }
+        let path = self.path();
+        if self.score() != max || (max > 0 && path.last() != Some(&(x, y, z))) {
+            return Err(Board3DError::BadPosition { x, y, z });
+        }
+        for (i, pair) in path.windows(2).enumerate() {
+            let (from, to) = (pair[0], pair[1]);
+            let delta = (
+                to.0 as i32 - from.0 as i32,
+                to.1 as i32 - from.1 as i32,
+                to.2 as i32 - from.2 as i32,
+            );
+            if !JUMPS_3D.contains(&delta) {
+                return Err(Board3DError::IllegalJump {
+                    from: (i + 1) as u16,
+                    to: (i + 2) as u16,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return the images of (x, y, z) under the 48 rotations and reflections of
+/// a cube with `size` cells along each edge.
+fn symmetric_cells_3d(size: usize, x: usize, y: usize, z: usize) -> Vec<(usize, usize, usize)> {
+    let m = size - 1;
+    let mut cells = Vec::with_capacity(48);
+    for &(a, b, c) in [
+        (x, y, z),
+        (x, z, y),
+        (y, x, z),
+        (y, z, x),
+        (z, x, y),
+        (z, y, x),
+    ]
+    .iter()
+    {
+        for flips in 0..8 {
+            let flip = |v: usize, bit: usize| if flips & bit != 0 { m - v } else { v };
+            cells.push((flip(a, 1), flip(b, 2), flip(c, 4)));
+        }
+    }
+    cells
+}
+
+impl Searchable for Board3D {
+    fn cell_total(&self) -> usize {
+        self.cell_count()
+    }
+
+    fn is_free(&self, cell: usize) -> bool {
+        self.values[cell] == 0
+    }
+
+    fn current_cell(&self) -> Option<usize> {
+        self.current_position().map(|(x, y, z)| self.index(x, y, z))
+    }
+
+    fn jumps(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let from = self.location(cell);
+        JUMPS_3D
+            .iter()
+            .filter_map(move |&jump| self.target(from, jump).map(|(x, y, z)| self.index(x, y, z)))
+    }
+
+    fn depth(&self) -> usize {
+        self.score()
+    }
+
+    fn is_solved(&self) -> bool {
+        self.is_won()
+    }
+
+    fn fill(&mut self, cell: usize) {
+        self.last += 1;
+        self.values[cell] = self.last;
+        (self.x, self.y, self.z) = self.location(cell);
+    }
+
+    fn unfill(&mut self) {
+        let cell = self.index(self.x, self.y, self.z);
+        self.values[cell] = 0;
+        self.last -= 1;
+        let previous = self
+            .jumps(cell)
+            .find(|&n| self.last > 0 && self.values[n] == self.last)
+            .unwrap_or(0);
+        (self.x, self.y, self.z) = self.location(previous);
+    }
+
+    fn is_canonical_start(&self, cell: usize) -> bool {
+        let (x, y, z) = self.location(cell);
+        symmetric_cells_3d(self.size, x, y, z)
+            .into_iter()
+            .all(|(x, y, z)| self.index(x, y, z) >= cell)
+    }
+}
+
+impl fmt::Display for Board3D {
+    /// Render the cube as its layers from z = 0 up, separated by blank
+    /// lines; each layer is a grid of right-aligned values with `.` marking
+    /// empty cells.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.cell_count().to_string().len();
+        let size = self.size;
+        for (i, row) in self.values.chunks(size).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+                if i % size == 0 {
+                    writeln!(f)?;
+                }
+            }
+            for (j, &value) in row.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " ")?;
+                }
+                if value == 0 {
+                    write!(f, "{:>width$}", ".", width = width)?;
+                } else {
+                    write!(f, "{:>width$}", value, width = width)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Board3D {
+    type Error = Board3DError;
+
+    fn validate(&self) -> Result<(), Board3DError> {
+        Board3D::validate(self)
+    }
+}
+
+impl Artifact for Board3D {
+    const FORMAT: Format = BOARD3D_FORMAT;
+
+    /// Parse a cube from its layers, as written by the `Display` impl. Empty
+    /// cells may be written as `0`, `.` or `_`. The cell holding the highest
+    /// value becomes the current position.
+    fn parse_body(body: &str) -> Result<Board3D, Board3DError> {
+        let mut layers: Vec<Vec<Vec<u16>>> = vec![Vec::new()];
+        for line in body.lines() {
+            if line.trim().is_empty() {
+                if !layers.last().unwrap().is_empty() {
+                    layers.push(Vec::new());
+                }
+                continue;
+            }
+            let (layer, row) = (layers.len() - 1, layers.last().unwrap().len());
+            let values = line
+                .split_whitespace()
+                .map(|token| match token {
+                    "0" | "." | "_" => Ok(0),
+                    _ => token
+                        .parse::<u16>()
+                        .map_err(|_| Board3DError::InvalidToken {
+                            layer,
+                            row,
+                            token: token.to_string(),
+                        }),
+                })
+                .collect::<Result<Vec<u16>, _>>()?;
+            layers.last_mut().unwrap().push(values);
+        }
+        if layers.last().is_some_and(Vec::is_empty) {
+            layers.pop();
+        }
+        let size = layers.len();
+        if let Some(layer) = layers
+            .iter()
+            .find(|layer| layer.len() != size || layer.iter().any(|row| row.len() != size))
+        {
+            return Err(Board3DError::InvalidDimensions {
+                cols: layer
+                    .iter()
+                    .map(Vec::len)
+                    .find(|&n| n != size)
+                    .unwrap_or(size),
+                rows: layer.len(),
+                layers: size,
+            });
+        }
+        if size != size.clamp(MIN_SIZE, MAX_SIZE_3D) {
+            return Err(Board3DError::InvalidDimensions {
+                cols: size,
+                rows: size,
+                layers: size,
+            });
+        }
+
+        let mut board = Board3D::new(size);
+        let values = layers.into_iter().flatten().flatten();
+        for (index, value) in values.enumerate() {
+            board.values[index] = value;
+            if value > board.last {
+                board.last = value;
+                (board.x, board.y, board.z) = board.location(index);
+            }
+        }
+        Ok(board)
+    }
+}
+
+impl FromStr for Board3D {
+    type Err = Board3DError;
+
+    /// Parse a cube written by the `Display` impl, with or without a
+    /// `BOARD3D_FORMAT` header. The values must be unique, form the range
+    /// 1..k and each be a legal jump from the one before, as `validate`
+    /// checks.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board3D::parse_artifact(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{Outcome, Solver};
+
+    #[test]
+    fn play_on_a_cube() {
+        let board = Board3D::new(5);
+        assert_eq!(board.cell_count(), 125);
+        assert_eq!(Board3D::new(99).size(), MAX_SIZE_3D);
+        assert_eq!(board.next_move((3, 0, 0)), Err(Board3DError::NotStarted));
+        assert!(board.possible_moves().is_empty());
+
+        let board = board.start_at(0, 0, 0).unwrap();
+        assert_eq!(board.possible_moves().len(), 6);
+        let board = board.next_move((0, 0, 3)).unwrap();
+        assert_eq!(board.value_at(0, 0, 3), 2);
+        assert_eq!(board.current_position(), Some((0, 0, 3)));
+        assert_eq!(
+            board.next_move((0, 0, 3)),
+            Err(Board3DError::BadJump {
+                dx: 0,
+                dy: 0,
+                dz: 3
+            })
+        );
+        assert!(board.next_move((1, 1, 1)).is_err());
+        assert!(board.start_at(1, 1, 1).is_err());
+        assert_eq!(board.path(), vec![(0, 0, 0), (0, 0, 3)]);
+        assert_eq!(board.validate(), Ok(()));
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let board = Board3D::new(5)
+            .start_at(2, 2, 2)
+            .unwrap()
+            .next_move((-2, 0, 2))
+            .unwrap();
+        let text = board.to_string();
+        assert_eq!(text.split("\n\n").count(), 5);
+        assert_eq!(text.parse::<Board3D>(), Ok(board.clone()));
+        assert_eq!(board.to_artifact(), BOARD3D_FORMAT.wrap(&text));
+        assert_eq!(board.to_artifact().parse::<Board3D>(), Ok(board));
+
+        let flat = "1 . . . .\n".repeat(5);
+        assert!(matches!(
+            flat.parse::<Board3D>(),
+            Err(Board3DError::InvalidDimensions { layers: 1, .. })
+        ));
+        let empty = ". . . . .\n".repeat(5);
+        let first = format!("1 2 . . .\n{}", ". . . . .\n".repeat(4));
+        let jumps = [first, empty.clone(), empty.clone(), empty.clone(), empty].join("\n");
+        assert_eq!(
+            jumps.parse::<Board3D>(),
+            Err(Board3DError::IllegalJump { from: 1, to: 2 })
+        );
+        let parsed = Board3D::parse_body(&jumps).unwrap();
+        assert_eq!(parsed.current_position(), Some((1, 0, 0)));
+        assert_eq!(parsed.score(), 2);
+    }
+
+    #[test]
+    fn score_follows_moves() {
+        let mut board = Board3D::new(5).start_at(0, 0, 0).unwrap();
+        board.fill(board.index(3, 0, 0));
+        board.fill(board.index(3, 0, 3));
+        assert_eq!(board.score(), 3);
+        assert_eq!(board.validate(), Ok(()));
+        board.unfill();
+        assert_eq!(board.score(), 2);
+        assert_eq!(board.current_position(), Some((3, 0, 0)));
+        board.unfill();
+        board.unfill();
+        assert!(!board.is_started());
+        assert_eq!(board, Board3D::new(5));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let board = Board3D::new(5)
+            .start_at(2, 2, 2)
+            .unwrap()
+            .next_move((0, -2, -2))
+            .unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        assert!(!json.contains("last"));
+        assert_eq!(serde_json::from_str::<Board3D>(&json).unwrap(), board);
+
+        let mut fields: serde_json::Value = serde_json::from_str(&json).unwrap();
+        fields["x"] = serde_json::json!(4);
+        let error = serde_json::from_value::<Board3D>(fields.clone()).unwrap_err();
+        assert!(error.to_string().contains("[4,0,0] does not hold"));
+        fields["x"] = serde_json::json!(9);
+        assert!(serde_json::from_value::<Board3D>(fields.clone()).is_err());
+        fields["values"] = serde_json::json!([1, 2]);
+        assert!(serde_json::from_value::<Board3D>(fields).is_err());
+    }
+
+    #[test]
+    fn solve_a_cube() {
+        let result = Solver::default().solve(&Board3D::new(5));
+        assert_eq!(result.outcome, Outcome::Solved);
+        assert!(result.best.is_won());
+        assert_eq!(result.best.validate(), Ok(()));
+        assert_eq!(result.best.score(), 125);
+
+        // 10 of the 125 start cells are distinct up to symmetry
+        let board = Board3D::new(5);
+        let starts = (0..125).filter(|&c| board.is_canonical_start(c)).count();
+        assert_eq!(starts, 10);
+    }
+}
@@ -0,0 +1,153 @@
+/*!
+Cell storage that stays off the heap for small boards.
+
+Searches copy boards at every node, and a copy of a `Vec` is a trip to the
+allocator. `Cells<T, N>` holds up to `N` items inline, so copying it is a
+plain memory copy; longer contents go to a `Vec` as before. `Board` keeps
+its values, holes and occupancy bits this way, inline for boards of up to
+`INLINE_CELLS` cells, which covers every square board up to 16x16.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+
+/// The largest number of cells a board keeps inline.
+pub const INLINE_CELLS: usize = 256;
+
+#[derive(Clone)]
+/// Cells is a fixed-length run of items, inline if there are at most `N`
+/// of them and on the heap otherwise. It dereferences to a slice.
+pub enum Cells<T, const N: usize> {
+    Inline { len: usize, items: [T; N] },
+    Heap(Vec<T>),
+}
+
+impl<T: Copy, const N: usize> Cells<T, N> {
+    /// Return `len` copies of `value`.
+    pub fn filled(value: T, len: usize) -> Self {
+        if len <= N {
+            Cells::Inline {
+                len,
+                items: [value; N],
+            }
+        } else {
+            Cells::Heap(vec![value; len])
+        }
+    }
+
+    /// Return `true` if the items are held inline.
+    pub fn is_inline(&self) -> bool {
+        matches!(self, Cells::Inline { .. })
+    }
+}
+
+impl<T: Copy + Default, const N: usize> From<Vec<T>> for Cells<T, N> {
+    fn from(items: Vec<T>) -> Self {
+        if items.len() <= N {
+            let mut inline = [T::default(); N];
+            inline[..items.len()].copy_from_slice(&items);
+            Cells::Inline {
+                len: items.len(),
+                items: inline,
+            }
+        } else {
+            Cells::Heap(items)
+        }
+    }
+}
+
+impl<T, const N: usize> Deref for Cells<T, N> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        match self {
+            Cells::Inline { len, items } => &items[..*len],
+            Cells::Heap(items) => items,
+        }
+    }
+}
+
+impl<T, const N: usize> DerefMut for Cells<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            Cells::Inline { len, items } => &mut items[..*len],
+            Cells::Heap(items) => items,
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a Cells<T, N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for Cells<T, N> {
+    /// Compare the items, wherever they are held.
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for Cells<T, N> {}
+
+impl<T: PartialOrd, const N: usize> PartialOrd for Cells<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord, const N: usize> Ord for Cells<T, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: Hash, const N: usize> Hash for Cells<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for Cells<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize, const N: usize> Serialize for Cells<T, N> {
+    /// Write the items as a sequence, as a `Vec` would be written.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_and_heap_cells() {
+        let mut small: Cells<u16, 4> = Cells::filled(0, 3);
+        assert!(small.is_inline());
+        small[2] = 7;
+        assert_eq!(&small[..], &[0, 0, 7]);
+        assert_eq!(small, Cells::from(vec![0, 0, 7]));
+
+        let large: Cells<u16, 4> = Cells::from(vec![1, 2, 3, 4, 5]);
+        assert!(!large.is_inline());
+        assert_eq!(large.len(), 5);
+        assert!(small < large);
+        assert_eq!(format!("{:?}", small), "[0, 0, 7]");
+    }
+}
@@ -0,0 +1,206 @@
+/*!
+Cell locations and the offsets between them.
+
+A `Coord` names a cell by its column `x` and row `y`, counted from the
+top-left corner. Adding a `Delta` gives the cell that far away, or `None`
+if the result would have a negative coordinate; whether it is on a
+particular board is checked with `Coord::within`.
+
+Cells are written the way spreadsheets name them: the column as letters
+(`A` to `Z`, then `AA`, `AB`, ...) followed by the row counted from 1, so
+(3, 3) is `D4`. Parsing accepts that form in either case, and also the
+plain `X,Y` pair.
+ */
+
+use failure::Fail;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+
+#[derive(Debug, Fail, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[fail(display = "Invalid cell '{}': expected e.g. D4 or 3,3", name)]
+/// Error raised when parsing a `Coord`.
+pub struct CoordError {
+    name: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Coord is the location of a cell.
+pub struct Coord {
+    /// The column, counted from 0 at the left.
+    pub x: usize,
+    /// The row, counted from 0 at the top.
+    pub y: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Delta is the offset between two cells.
+pub struct Delta {
+    pub dx: i32,
+    pub dy: i32,
+}
+
+impl Coord {
+    /// Create the location of column `x`, row `y`.
+    pub const fn new(x: usize, y: usize) -> Coord {
+        Coord { x, y }
+    }
+
+    /// Return the location of cell `index` of a board `width` cells wide,
+    /// counting row by row.
+    pub fn from_index(index: usize, width: usize) -> Coord {
+        Coord::new(index % width, index / width)
+    }
+
+    /// Return the index of the cell on a board `width` cells wide, counting
+    /// row by row.
+    pub fn index(self, width: usize) -> usize {
+        self.y * width + self.x
+    }
+
+    /// Return `true` if the cell is on a board of `width` x `height` cells.
+    pub fn within(self, width: usize, height: usize) -> bool {
+        self.x < width && self.y < height
+    }
+
+    /// Return the offset from this cell to `other`.
+    pub fn delta_to(self, other: Coord) -> Delta {
+        Delta::new(
+            other.x as i32 - self.x as i32,
+            other.y as i32 - self.y as i32,
+        )
+    }
+}
+
+impl Delta {
+    /// Create the offset of `dx` columns and `dy` rows.
+    pub const fn new(dx: i32, dy: i32) -> Delta {
+        Delta { dx, dy }
+    }
+}
+
+impl Add<Delta> for Coord {
+    type Output = Option<Coord>;
+
+    /// Return the cell `delta` away, or `None` if it would be left of the
+    /// first column or above the first row.
+    fn add(self, delta: Delta) -> Option<Coord> {
+        let x = self.x as i64 + delta.dx as i64;
+        let y = self.y as i64 + delta.dy as i64;
+        if x < 0 || y < 0 {
+            None
+        } else {
+            Some(Coord::new(x as usize, y as usize))
+        }
+    }
+}
+
+impl From<(usize, usize)> for Coord {
+    fn from((x, y): (usize, usize)) -> Coord {
+        Coord::new(x, y)
+    }
+}
+
+impl From<Coord> for (usize, usize) {
+    fn from(coord: Coord) -> (usize, usize) {
+        (coord.x, coord.y)
+    }
+}
+
+impl From<(i32, i32)> for Delta {
+    fn from((dx, dy): (i32, i32)) -> Delta {
+        Delta::new(dx, dy)
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut letters = Vec::new();
+        let mut column = self.x + 1;
+        while column > 0 {
+            column -= 1;
+            letters.push(b'A' + (column % 26) as u8);
+            column /= 26;
+        }
+        letters.reverse();
+        let name = format!("{}{}", String::from_utf8(letters).unwrap(), self.y + 1);
+        f.pad(&name)
+    }
+}
+
+impl FromStr for Coord {
+    type Err = CoordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        let bad = || CoordError {
+            name: name.to_string(),
+        };
+        if let Some((x, y)) = name.split_once(',') {
+            return match (x.trim().parse(), y.trim().parse()) {
+                (Ok(x), Ok(y)) => Ok(Coord::new(x, y)),
+                _ => Err(bad()),
+            };
+        }
+        let split = name
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .ok_or_else(bad)?;
+        let (letters, digits) = name.split_at(split);
+        if letters.is_empty() || letters.len() > 3 {
+            return Err(bad());
+        }
+        let column = letters.bytes().fold(0, |n, c| {
+            n * 26 + (c.to_ascii_uppercase() - b'A') as usize + 1
+        });
+        match digits.parse::<usize>() {
+            Ok(row) if row > 0 && digits.bytes().all(|c| c.is_ascii_digit()) => {
+                Ok(Coord::new(column - 1, row - 1))
+            }
+            _ => Err(bad()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_parsing() {
+        assert_eq!(Coord::new(3, 3).to_string(), "D4");
+        assert_eq!(Coord::new(0, 0).to_string(), "A1");
+        assert_eq!(Coord::new(25, 9).to_string(), "Z10");
+        assert_eq!(Coord::new(26, 0).to_string(), "AA1");
+        assert_eq!(Coord::new(254, 254).to_string(), "IU255");
+        assert_eq!(format!("{:>4}", Coord::new(1, 1)), "  B2");
+        for &(x, y) in [(0, 0), (3, 3), (25, 1), (26, 2), (254, 254), (701, 0)].iter() {
+            let coord = Coord::new(x, y);
+            assert_eq!(coord.to_string().parse(), Ok(coord));
+        }
+        assert_eq!("d4".parse(), Ok(Coord::new(3, 3)));
+        assert_eq!(" 3, 4 ".parse(), Ok(Coord::new(3, 4)));
+        for bad in ["", "4", "D", "D0", "D-1", "4D", "D4x", "ABCD1", "3,", "a,b"].iter() {
+            assert!(bad.parse::<Coord>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        let coord = Coord::new(2, 1);
+        assert_eq!(coord + Delta::new(3, 0), Some(Coord::new(5, 1)));
+        assert_eq!(coord + Delta::new(-2, -1), Some(Coord::new(0, 0)));
+        assert_eq!(coord + Delta::new(-3, 0), None);
+        assert_eq!(coord.delta_to(Coord::new(0, 3)), Delta::new(-2, 2));
+        assert!(coord.within(3, 2));
+        assert!(!coord.within(2, 2));
+        assert_eq!(coord.index(5), 7);
+        assert_eq!(Coord::from_index(7, 5), coord);
+        assert_eq!(Coord::from((2, 1)), coord);
+        assert_eq!(<(usize, usize)>::from(coord), (2, 1));
+    }
+}
@@ -0,0 +1,225 @@
+/*!
+The daily puzzle: one start position a day, the same for everyone.
+
+The date decides everything. Its seed is the date written as a number,
+20240601 for 2024-06-01, and the board is the random opening (see
+`agent::random_opening`) of `DAILY_OPENING` moves that seed plays on an
+empty board. An opening the solver cannot complete within its budget is
+passed over for the one of the next seed, so every daily board is known to
+have a solution. Dates are in UTC, so the day changes at the same moment
+everywhere.
+ */
+
+use crate::agent;
+use crate::board::{Board, BoardError};
+use crate::coord::Coord;
+use crate::solver::{Outcome, Solver, SolverConfig};
+use failure::Fail;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The number of moves played for the player on the daily board.
+pub const DAILY_OPENING: usize = 5;
+
+/// The node budget of the check that a daily board can be completed.
+const DAILY_NODE_LIMIT: u64 = 1_000_000;
+
+/// The number of seeds tried before settling for an unchecked board.
+const DAILY_ATTEMPTS: u64 = 16;
+
+#[derive(Debug, Fail, PartialEq)]
+/// Errors raised while reading a date or checking a daily solution.
+pub enum DailyError {
+    #[fail(display = "Invalid date '{}': expected YYYY-MM-DD", _0)]
+    BadDate(String),
+    #[fail(display = "{}", _0)]
+    Board(#[cause] BoardError),
+    #[fail(display = "The board is not the daily puzzle of {}", date)]
+    WrongPuzzle { date: Date },
+    #[fail(
+        display = "The board is not complete: {} of {} cells filled",
+        score, cells
+    )]
+    Unfinished { score: usize, cells: usize },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Date is a day of the (proleptic) Gregorian calendar.
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Return the date `days` days after 1970-01-01.
+    pub fn from_days(days: i64) -> Date {
+        // Howard Hinnant's civil_from_days, on eras of 400 years
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400) as i32 + i32::from(month <= 2);
+        Date { year, month, day }
+    }
+
+    /// Return today's date in UTC.
+    pub fn today() -> Date {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Date::from_days((secs / 86_400) as i64)
+    }
+
+    /// Return the seed of the date's puzzle: the date as the number
+    /// YYYYMMDD.
+    pub fn seed(&self) -> u64 {
+        (self.year as i64 * 10_000 + self.month as i64 * 100 + self.day as i64) as u64
+    }
+
+    /// Return the number of days in `month` of `year`.
+    fn days_in_month(year: i32, month: u32) -> u32 {
+        match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = DailyError;
+
+    /// Parse a date written as YYYY-MM-DD.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || DailyError::BadDate(s.trim().to_string());
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or_else(bad);
+        let (year, month, day) = (next()?, next()?, next()?);
+        let date = Date {
+            year: year.parse().map_err(|_| bad())?,
+            month: month.parse().map_err(|_| bad())?,
+            day: day.parse().map_err(|_| bad())?,
+        };
+        if year.len() != 4
+            || !(1..=12).contains(&date.month)
+            || !(1..=Date::days_in_month(date.year, date.month)).contains(&date.day)
+        {
+            return Err(bad());
+        }
+        Ok(date)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Daily is the puzzle of one day: a board with the opening played.
+pub struct Daily {
+    pub date: Date,
+    /// The seed the board was made from; usually `date.seed()`.
+    pub seed: u64,
+    pub board: Board,
+}
+
+impl Daily {
+    /// Return the puzzle of `date` on a board of `size` x `size` cells.
+    pub fn new(date: Date, size: usize) -> Daily {
+        let empty = Board::new(size);
+        let solver = Solver::new(SolverConfig {
+            node_limit: Some(DAILY_NODE_LIMIT),
+            ..SolverConfig::default()
+        });
+        let first = date.seed();
+        let seed = (first..first + DAILY_ATTEMPTS)
+            .find(|&seed| {
+                let board = agent::random_opening(&empty, DAILY_OPENING, seed);
+                solver.solve(&board).outcome == Outcome::Solved
+            })
+            .unwrap_or(first);
+        Daily {
+            date,
+            seed,
+            board: agent::random_opening(&empty, DAILY_OPENING, seed),
+        }
+    }
+
+    /// Check that `solution` is a complete, legal game that carries on
+    /// from the daily board.
+    pub fn check(&self, solution: &Board) -> Result<(), DailyError> {
+        let same_puzzle = solution.width() == self.board.width()
+            && solution.height() == self.board.height()
+            && solution.rules() == self.board.rules()
+            && self
+                .board
+                .iter_cells()
+                .all(|(x, y, value)| value == 0 || solution.value_at(Coord::new(x, y)) == value);
+        if !same_puzzle {
+            return Err(DailyError::WrongPuzzle { date: self.date });
+        }
+        solution.validate().map_err(DailyError::Board)?;
+        if !solution.is_won() {
+            return Err(DailyError::Unfinished {
+                score: solution.score(),
+                cells: solution.cell_count(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        let date: Date = "2024-06-01".parse().unwrap();
+        assert_eq!(date, Date::from_days(19_875));
+        assert_eq!(date.to_string(), "2024-06-01");
+        assert_eq!(date.seed(), 20_240_601);
+        assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+        assert_eq!(Date::from_days(11_016).to_string(), "2000-02-29");
+        assert_eq!(Date::from_days(-1).to_string(), "1969-12-31");
+        for bad in ["2023-02-29", "2024-13-01", "2024-06", "24-06-01", "today"] {
+            assert_eq!(
+                bad.parse::<Date>(),
+                Err(DailyError::BadDate(bad.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn daily_puzzles() {
+        let date = "2024-06-01".parse().unwrap();
+        let daily = Daily::new(date, 8);
+        assert_eq!(daily, Daily::new(date, 8));
+        assert_eq!(daily.board.score(), DAILY_OPENING + 1);
+        assert_ne!(daily.board, Daily::new(Date::from_days(19_876), 8).board);
+
+        let solution = Solver::default().solve(&daily.board).best;
+        assert_eq!(daily.check(&solution), Ok(()));
+        assert_eq!(
+            daily.check(&daily.board),
+            Err(DailyError::Unfinished {
+                score: DAILY_OPENING + 1,
+                cells: 64
+            })
+        );
+        // a tour from another start cell
+        let cell = daily.board.empty_cells().next().unwrap();
+        let other = Board::new(8).start_at(cell).unwrap();
+        let other = Solver::default().solve(&other).best;
+        assert_eq!(daily.check(&other), Err(DailyError::WrongPuzzle { date }));
+    }
+}
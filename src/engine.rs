@@ -0,0 +1,448 @@
+/*!
+Line-based protocol for driving an engine over stdin and stdout.
+
+The protocol is modelled on UCI: a front end (a GUI, a tournament script)
+writes one command per line and the engine answers with zero or more
+response lines. Any program that speaks it can stand in for `board100
+engine`, so front ends can drive alternative engines interchangeably.
+
+Moves are written in a compact notation: `U`, `D`, `L` and `R` for the
+horizontal and vertical jumps, and `UL`, `UR`, `DL` and `DR` for the
+diagonal ones.
+
+Commands:
+
+```text
+board100                          identify; answered by "id ..." lines and "board100ok"
+isready                           answered by "readyok" once earlier commands are done
+position <size> [torus] [start <x> <y> [moves <move>...]]
+                                  set the current position; "torus" lets moves
+                                  wrap around the edges of the board
+setoption rules <rule>,...        set the pruning rules used by later searches;
+                                  "all" and "none" are accepted
+go [nodes <n>]                    search the current position, optionally bounded
+quit                              stop the engine
+```
+
+Responses:
+
+```text
+id name <name> <version>
+board100ok
+readyok
+info depth <d> nodes <n> backtracks <b>
+bestmove <move> | bestmove start <x> <y> | bestmove none
+error <message>
+```
+
+`info` reports the deepest position reached, `depth` being its highest
+value. `bestmove` is the first move of the line that reached it: a start
+cell when the position is empty, and `none` when no move is possible.
+ */
+
+use crate::board::{Board, Direction, MoveRules};
+use crate::coord::Coord;
+use crate::solver::{self, Rule, Solver, SolverConfig};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// The name the engine reports in its `id` response.
+pub const NAME: &str = "board100";
+
+/// Return the compact notation of `dir`.
+pub fn move_token(dir: Direction) -> &'static str {
+    dir.to_compact()
+}
+
+/// Parse a move written in compact notation.
+pub fn parse_move(token: &str) -> Option<Direction> {
+    token.parse().ok()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Command is one line sent to the engine.
+pub enum Command {
+    Hello,
+    IsReady,
+    Position {
+        size: usize,
+        toroidal: bool,
+        start: Option<Coord>,
+        moves: Vec<Direction>,
+    },
+    SetRules(BTreeSet<Rule>),
+    Go {
+        nodes: Option<u64>,
+    },
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let number = |word: Option<&str>, what: &str| -> Result<u64, String> {
+            word.and_then(|w| w.parse().ok())
+                .ok_or_else(|| format!("expected {} but found '{}'", what, word.unwrap_or("")))
+        };
+        let command = match words.next() {
+            Some("board100") => Command::Hello,
+            Some("isready") => Command::IsReady,
+            Some("quit") => Command::Quit,
+            Some("position") => {
+                let size = number(words.next(), "size")? as usize;
+                let mut words = words.by_ref().peekable();
+                let toroidal = words.next_if_eq(&"torus").is_some();
+                let mut start = None;
+                let mut moves = Vec::new();
+                match words.next() {
+                    None => {}
+                    Some("start") => {
+                        let x = number(words.next(), "x")? as usize;
+                        let y = number(words.next(), "y")? as usize;
+                        start = Some(Coord::new(x, y));
+                        match words.next() {
+                            None => {}
+                            Some("moves") => {
+                                for word in words.by_ref() {
+                                    moves.push(
+                                        parse_move(word)
+                                            .ok_or_else(|| format!("invalid move '{}'", word))?,
+                                    );
+                                }
+                            }
+                            Some(word) => {
+                                return Err(format!("expected 'moves' but found '{}'", word))
+                            }
+                        }
+                    }
+                    Some(word) => return Err(format!("expected 'start' but found '{}'", word)),
+                }
+                Command::Position {
+                    size,
+                    toroidal,
+                    start,
+                    moves,
+                }
+            }
+            Some("setoption") => match words.next() {
+                Some("rules") => {
+                    let list: Vec<&str> = words.by_ref().collect();
+                    Command::SetRules(solver::parse_rules(&list.join(""))?)
+                }
+                Some(word) => return Err(format!("unknown option '{}'", word)),
+                None => return Err("expected an option name".to_string()),
+            },
+            Some("go") => match words.next() {
+                None => Command::Go { nodes: None },
+                Some("nodes") => Command::Go {
+                    nodes: Some(number(words.next(), "node count")?),
+                },
+                Some(word) => return Err(format!("unknown go option '{}'", word)),
+            },
+            Some(word) => return Err(format!("unknown command '{}'", word)),
+            None => return Err("empty command".to_string()),
+        };
+        match words.next() {
+            None => Ok(command),
+            Some(word) => Err(format!("unexpected '{}'", word)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+/// Move is the engine's answer to `go`.
+pub enum Move {
+    /// Place the value 1 in a cell.
+    Start(Coord),
+    /// Jump in a direction.
+    Step(Direction),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Response is one line written by the engine.
+pub enum Response {
+    Id {
+        name: String,
+        version: String,
+    },
+    HelloOk,
+    ReadyOk,
+    Info {
+        depth: usize,
+        nodes: u64,
+        backtracks: u64,
+    },
+    BestMove(Option<Move>),
+    Error(String),
+}
+
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Response::Id { name, version } => write!(f, "id name {} {}", name, version),
+            Response::HelloOk => write!(f, "board100ok"),
+            Response::ReadyOk => write!(f, "readyok"),
+            Response::Info {
+                depth,
+                nodes,
+                backtracks,
+            } => write!(
+                f,
+                "info depth {} nodes {} backtracks {}",
+                depth, nodes, backtracks
+            ),
+            Response::BestMove(None) => write!(f, "bestmove none"),
+            Response::BestMove(Some(Move::Start(cell))) => {
+                write!(f, "bestmove start {} {}", cell.x, cell.y)
+            }
+            Response::BestMove(Some(Move::Step(dir))) => {
+                write!(f, "bestmove {}", move_token(*dir))
+            }
+            Response::Error(message) => write!(f, "error {}", message),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Engine holds the current position and solver settings between
+/// commands.
+pub struct Engine {
+    board: Board,
+    config: SolverConfig,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Engine {
+            board: Board::new(10),
+            config: SolverConfig::default(),
+        }
+    }
+}
+
+impl Engine {
+    /// Create an engine on an empty 10x10 board.
+    pub fn new() -> Self {
+        Engine::default()
+    }
+
+    /// Return the current position.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Handle one command, returning the responses to write.
+    pub fn handle(&mut self, command: &Command) -> Vec<Response> {
+        match command {
+            Command::Hello => vec![
+                Response::Id {
+                    name: NAME.to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+                Response::HelloOk,
+            ],
+            Command::IsReady => vec![Response::ReadyOk],
+            Command::Position {
+                size,
+                toroidal,
+                start,
+                moves,
+            } => {
+                let rules = if *toroidal {
+                    MoveRules::CLASSIC.toroidal()
+                } else {
+                    MoveRules::CLASSIC
+                };
+                let board = Board::try_new(*size).and_then(|board| {
+                    let mut board = board.with_rules(rules);
+                    match start {
+                        None => Ok(board),
+                        Some(start) => board
+                            .start_at(*start)
+                            .and_then(|board| board.apply_moves(moves)),
+                    }
+                });
+                match board {
+                    Ok(board) => {
+                        self.board = board;
+                        vec![]
+                    }
+                    Err(err) => vec![Response::Error(err.to_string())],
+                }
+            }
+            Command::SetRules(rules) => {
+                self.config.rules = rules.clone();
+                vec![]
+            }
+            Command::Go { nodes } => {
+                let solver = Solver::new(SolverConfig {
+                    node_limit: *nodes,
+                    ..self.config.clone()
+                });
+                let result = solver.solve(&self.board);
+                let info = Response::Info {
+                    depth: result.stats.max_depth,
+                    nodes: result.stats.nodes,
+                    backtracks: result.stats.backtracks,
+                };
+                let played = self.board.score();
+                let best = if played == 0 {
+                    result.best.position_of(1).map(Move::Start)
+                } else {
+                    result
+                        .best
+                        .moves()
+                        .get(played - 1)
+                        .map(|dir| Move::Step(*dir))
+                };
+                vec![info, Response::BestMove(best)]
+            }
+            Command::Quit => vec![],
+        }
+    }
+}
+
+/// Run the protocol, reading commands from `input` and writing responses to
+/// `output` until `quit` or the end of the input. Blank lines are ignored
+/// and malformed commands are answered with an `error` response.
+pub fn run<R: BufRead, W: Write>(engine: &mut Engine, input: R, mut output: W) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let responses = match line.parse::<Command>() {
+            Ok(Command::Quit) => break,
+            Ok(command) => engine.handle(&command),
+            Err(message) => vec![Response::Error(message)],
+        };
+        for response in responses {
+            writeln!(output, "{}", response)?;
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{DEAD_END_5, SOLUTION_5};
+
+    fn session(input: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        run(&mut Engine::new(), input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(
+            "position 5 start 0 0 moves R D UL".parse(),
+            Ok(Command::Position {
+                size: 5,
+                toroidal: false,
+                start: Some(Coord::new(0, 0)),
+                moves: vec![Direction::Right, Direction::Down, Direction::UpLeft],
+            })
+        );
+        assert_eq!(
+            "position 6 torus start 0 0 moves L".parse(),
+            Ok(Command::Position {
+                size: 6,
+                toroidal: true,
+                start: Some(Coord::new(0, 0)),
+                moves: vec![Direction::Left],
+            })
+        );
+        assert!("position 6 torus torus".parse::<Command>().is_err());
+        assert_eq!("go nodes 100".parse(), Ok(Command::Go { nodes: Some(100) }));
+        assert!("position 5 start 0".parse::<Command>().is_err());
+        assert!("position 5 start 0 0 moves X".parse::<Command>().is_err());
+        assert!("go fast".parse::<Command>().is_err());
+        assert_eq!(
+            "setoption rules degree, symmetry".parse(),
+            Ok(Command::SetRules(
+                [Rule::Degree, Rule::Symmetry].iter().copied().collect()
+            ))
+        );
+        assert!("setoption rules parity".parse::<Command>().is_err());
+        assert!("isready now".parse::<Command>().is_err());
+        for dir in Direction::iterator() {
+            assert_eq!(parse_move(move_token(*dir)), Some(*dir));
+        }
+    }
+
+    #[test]
+    fn play_a_session() {
+        let lines = session("board100\nisready\n\nposition 5\ngo\nquit\ngo\n");
+        assert!(lines[0].starts_with("id name board100 "));
+        assert_eq!(lines[1..3], ["board100ok", "readyok"]);
+        assert!(lines[3].starts_with("info depth 25 "));
+        assert_eq!(lines[4], "bestmove start 0 0");
+        assert_eq!(lines.len(), 5);
+
+        let lines = session("position 5 start 0 0 moves R D\ngo nodes 1000\n");
+        let token = lines[1].trim_start_matches("bestmove ");
+        let dir = parse_move(token).unwrap();
+        assert!(SOLUTION_5.board_after(2).possible_moves().contains(dir));
+
+        // the move off the left edge is only legal on a torus
+        let mut engine = Engine::new();
+        run(
+            &mut engine,
+            "position 5 torus start 0 0 moves L\n".as_bytes(),
+            io::sink(),
+        )
+        .unwrap();
+        assert_eq!(engine.board().value_at(Coord::new(2, 0)), 2);
+        assert!(engine.board().rules().is_toroidal());
+
+        let mut engine = Engine::new();
+        run(
+            &mut engine,
+            "setoption rules degree\n".as_bytes(),
+            io::sink(),
+        )
+        .unwrap();
+        assert!(engine.config.is_enabled(Rule::Degree));
+        assert!(!engine.config.is_enabled(Rule::Symmetry));
+    }
+
+    #[test]
+    fn report_errors_and_dead_ends() {
+        let lines = session("fly\nposition 5 start 0 0 moves U\nposition 3\n");
+        assert_eq!(lines[0], "error unknown command 'fly'");
+        assert!(lines[1].starts_with("error "));
+        assert_eq!(lines[2], "error Invalid board dimensions 3x3");
+
+        let mut engine = Engine::new();
+        engine.handle(&Command::Position {
+            size: 5,
+            toroidal: false,
+            start: Some(DEAD_END_5.start),
+            moves: DEAD_END_5.moves.to_vec(),
+        });
+        let responses = engine.handle(&Command::Go { nodes: None });
+        assert!(matches!(
+            responses[1],
+            Response::BestMove(Some(Move::Step(_)))
+        ));
+        engine.handle(&Command::Position {
+            size: 5,
+            toroidal: false,
+            start: Some(Coord::new(0, 0)),
+            moves: SOLUTION_5.moves.to_vec(),
+        });
+        let responses = engine.handle(&Command::Go { nodes: None });
+        assert_eq!(responses[1], Response::BestMove(None));
+    }
+}
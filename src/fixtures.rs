@@ -0,0 +1,215 @@
+/*!
+Known games and positions for tests, examples and benchmarks.
+
+The fixtures are checked by the crate's own tests, so downstream crates can
+rely on them being what their names say: solutions are complete and legal,
+dead positions really have no completion, and unsolvable shapes really fail
+the structural checks.
+ */
+
+use crate::board::Board;
+use crate::board::Direction::{self, *};
+use crate::coord::Coord;
+use crate::shape::Shape;
+
+#[derive(Debug, Copy, Clone)]
+/// Game is a start cell and a list of moves on an empty board.
+pub struct Game {
+    /// The board size.
+    pub size: usize,
+    /// The (x, y) location of the value 1.
+    pub start: Coord,
+    /// The moves played after the start.
+    pub moves: &'static [Direction],
+}
+
+impl Game {
+    /// Return the board after every move of the game has been played.
+    pub fn board(&self) -> Board {
+        self.board_after(self.moves.len())
+    }
+
+    /// Return the board after the first `count` moves of the game.
+    pub fn board_after(&self, count: usize) -> Board {
+        Board::from_moves(self.size, self.start, &self.moves[..count]).unwrap()
+    }
+}
+
+/// The solved 5x5 game from the crate documentation:
+///
+/// ```text
+///  1 24 14  2 25
+/// 16 21  5  8 20
+/// 13 10 18 23 11
+///  4  7 15  3  6
+/// 17 22 12  9 19
+/// ```
+pub const SOLUTION_5: Game = Game {
+    size: 5,
+    start: Coord::new(0, 0),
+    moves: &[
+        Right, Down, Left, UpRight, DownRight, Left, UpRight, Down, UpLeft, Right, DownLeft,
+        UpLeft, UpRight, Down, UpLeft, Down, UpRight, DownRight, Up, Left, Down, UpRight, UpLeft,
+        Right,
+    ],
+};
+
+/// The total number of solutions of the 5x5 board, over all start cells.
+pub const SOLUTION_COUNT_5: u64 = 12_400;
+
+/// Solved 10x10 boards, starting in the top-left corner, the center, the
+/// bottom-right corner and at (3, 7).
+pub const SOLUTIONS_10: [&str; 4] = [
+    "  1  46  41  16  45  42  17  30  43  18
+      68  22   3  69  21   4  60  20   5  59
+      40  15  96  47  88  29  44  87  28  31
+       2  70  67  23  73  66  24  58  61  19
+      95  48  81  92  97  80  91  32   6  86
+      39  14  56  71  89  57  72  84  27  34
+      52  75  98  53  74  65  25  79  62  10
+      94  49  82  93 100  83  90  33   7  85
+      38  13  55  37  12  54  36  11  26  35
+      51  76  99  50  77  64   8  78  63   9",
+    " 20  64  33  21  77  36   6  78  37   7
+      69  11  30  70  10  29  71   9  28  74
+      32  22  96  63  34  95  86  35   5  79
+      19  65  68  26  76  67  27  75  38   8
+      97  12  31  98  89  62  72  94  85  73
+      57  23  18  66  59   1  87  60   4  80
+      16  54 100  25  53  99  90  50  39  93
+      43  13  58  44  88  61  47  81  84  48
+      56  24  17  55  91   2  52  92   3  51
+      15  45  42  14  46  41  83  49  40  82",
+    "  9  38  70   8  39  77  55  40  76  54
+      35  26  11  36  51  12  84  52  13  83
+      71   7  33  74  92  59  75  91  58  41
+      10  37  69  25  81  78  56  82  79  53
+      34  27  89  64  50  90  85  49  14  86
+      72   6  32  73  93  60  80  94  57  42
+      19  63  68  24  88  65  23  87  45   2
+      31  28  98  61  29  99  96  48  15  95
+      67   5  20  66   4  21  44   3  22  43
+      18  62  30  17  97  47  16 100  46   1",
+    " 64  75   8  65  96   9  66  53  10  67
+      18  82 100  28  83  86  29  71  85  30
+       7  26  95  76  46  60  97  68  37  54
+      63  74  19  87  99  72  84  52  11  70
+      17  81  47  27  94  77  36  59  90  31
+       6  25  62  73  45  61  98  69  38  55
+      42   3  20  88  48  21  89  51  12  58
+      16  80  44   1  93  78  35  56  91  32
+       5  24  41   4  23  40  49  22  39  50
+      43   2  15  79  34  14  92  33  13  57",
+];
+
+/// Return the solved 10x10 boards of `SOLUTIONS_10`.
+pub fn solutions_10() -> Vec<Board> {
+    SOLUTIONS_10.iter().map(|s| s.parse().unwrap()).collect()
+}
+
+/// A 5x5 position with two legal moves, neither of which leads to a
+/// solution, although every empty cell can still be entered.
+pub const DEAD_END_5: Game = Game {
+    size: 5,
+    start: Coord::new(0, 0),
+    moves: &[Down, UpRight, DownRight, Up, Left, DownRight, DownLeft, Up],
+};
+
+/// A 5x5 position with two legal moves but an empty cell that can no longer
+/// be entered from anywhere, so it has no solution.
+pub const STRANDED_5: Game = Game {
+    size: 5,
+    start: Coord::new(0, 0),
+    moves: &[
+        Right, Down, UpLeft, Down, UpRight, Left, DownRight, Up, DownRight, Up,
+    ],
+};
+
+/// A 5x5 position with two legal moves and exactly one completion.
+pub const SINGLE_COMPLETION_5: Game = Game {
+    size: 5,
+    start: Coord::new(0, 0),
+    moves: &[Down, Right, UpLeft, Down, UpRight, Left],
+};
+
+/// Return shapes that fail `Shape::analyze`: one too small to have any
+/// jump, one with a corner cut off, and two 5x5 squares too far apart to
+/// jump between.
+pub fn unsolvable_shapes() -> Vec<Shape> {
+    let tiny = Shape::full("tiny", 2, 2);
+
+    let mut cut_corner = Shape::full("cut-corner", 5, 5);
+    for cell in cut_corner.neighbors(0, 0) {
+        cut_corner.set(cell.x, cell.y, false);
+    }
+
+    let mut split = Shape::full("split", 13, 5);
+    for y in 0..5 {
+        for x in 5..8 {
+            split.set(x, y, false);
+        }
+    }
+    vec![tiny, cut_corner, split]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shape::Verdict;
+
+    // Count the completions of `board` by brute force.
+    fn completions(board: &mut Board) -> u64 {
+        if board.is_won() {
+            return 1;
+        }
+        board
+            .possible_moves()
+            .iter()
+            .map(|dir| completions(&mut board.next_move(dir).unwrap()))
+            .sum()
+    }
+
+    #[test]
+    fn solutions_are_complete() {
+        let board = SOLUTION_5.board();
+        assert!(board.is_won());
+        assert_eq!(board.value_at(Coord::new(4, 0)), 25);
+        for board in solutions_10() {
+            assert!(board.is_won());
+            assert_eq!(board.moves().len(), 99);
+        }
+        assert_eq!(
+            crate::analysis::count_solutions_dp(5, 1 << 30),
+            Ok(SOLUTION_COUNT_5)
+        );
+    }
+
+    #[test]
+    fn partial_positions() {
+        let mut dead = DEAD_END_5.board();
+        assert_eq!(dead.possible_moves().len(), 2);
+        assert_eq!(completions(&mut dead), 0);
+
+        let mut stranded = STRANDED_5.board();
+        assert_eq!(stranded.possible_moves().len(), 2);
+        assert_eq!(completions(&mut stranded), 0);
+
+        let mut single = SINGLE_COMPLETION_5.board();
+        assert_eq!(single.possible_moves().len(), 2);
+        assert_eq!(completions(&mut single), 1);
+
+        assert_eq!(SOLUTION_5.board_after(3).score(), 4);
+    }
+
+    #[test]
+    fn shapes_are_unsolvable() {
+        for shape in unsolvable_shapes() {
+            assert!(
+                matches!(shape.analyze().verdict, Verdict::Unsolvable(_)),
+                "{}",
+                shape.name
+            );
+        }
+        assert_eq!(unsolvable_shapes()[2].analyze().regions, 2);
+    }
+}
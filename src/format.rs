@@ -0,0 +1,274 @@
+/*!
+Versioning for serialized artifacts.
+
+Every artifact the crate writes (boards, game records, checkpoints,
+tablebases, ...) starts with a one line header naming the artifact kind and
+the version of its encoding:
+
+```text
+square100 board v1
+```
+
+When an artifact is loaded, the header is checked against the expected kind
+and any older encoding is upgraded, one version at a time, by the migrations
+registered for that kind. This lets stored solution databases survive
+changes to the internal representation of a `Board`.
+
+Input without a header is treated as version 1 of the expected kind, so hand
+written files keep working.
+
+Boards of every shape are read the same way: a type implementing `Artifact`
+names its format and parses an upgraded body, and `Artifact::parse_artifact`
+upgrades the input, parses it and checks the result with `Validate`, so
+nothing is loaded without being checked.
+ */
+
+use failure::Fail;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Magic word that starts every artifact header.
+pub const MAGIC: &str = "square100";
+
+/// A migration upgrades the body of an artifact by exactly one version.
+pub type Migration = fn(&str) -> Result<String, FormatError>;
+
+#[derive(Debug, Fail, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Errors raised while reading a versioned artifact.
+pub enum FormatError {
+    #[fail(display = "Malformed header: '{}'", line)]
+    BadHeader { line: String },
+    #[fail(display = "Expected a '{}' artifact, found '{}'", expected, found)]
+    WrongKind { expected: String, found: String },
+    #[fail(
+        display = "Unsupported '{}' version {}: newest known version is {}",
+        kind, version, current
+    )]
+    UnsupportedVersion {
+        kind: String,
+        version: u32,
+        current: u32,
+    },
+    #[fail(display = "Cannot upgrade from version {}: {}", version, reason)]
+    Migration { version: u32, reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The parsed header of an artifact.
+pub struct Header {
+    /// The kind of artifact, e.g. `board`.
+    pub kind: String,
+    /// The encoding version of the artifact body.
+    pub version: u32,
+}
+
+impl Header {
+    /// Parse a header line of the form `square100 <kind> v<version>`. Returns
+    /// `None` if the line does not start with the magic word.
+    pub fn parse(line: &str) -> Option<Result<Header, FormatError>> {
+        let mut words = line.split_whitespace();
+        if words.next() != Some(MAGIC) {
+            return None;
+        }
+        let bad = || FormatError::BadHeader {
+            line: line.to_string(),
+        };
+        let kind = match words.next() {
+            Some(kind) => kind.to_string(),
+            None => return Some(Err(bad())),
+        };
+        let version = match words.next() {
+            Some(v) if v.starts_with('v') => match v[1..].parse::<u32>() {
+                Ok(version) if version > 0 => version,
+                _ => return Some(Err(bad())),
+            },
+            _ => return Some(Err(bad())),
+        };
+        if words.next().is_some() {
+            return Some(Err(bad()));
+        }
+        Some(Ok(Header { kind, version }))
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+/// Format describes the current encoding of one kind of artifact, and how to
+/// upgrade older encodings of it.
+pub struct Format {
+    /// The artifact kind written in the header.
+    pub kind: &'static str,
+    /// The current version of the encoding.
+    pub version: u32,
+    /// Upgrade steps; `migrations[i]` upgrades version `i + 1` to `i + 2`.
+    /// There must be exactly `version - 1` entries.
+    pub migrations: &'static [Migration],
+}
+
+impl Format {
+    /// Return the header line (including the trailing newline) for the
+    /// current version of this format.
+    pub fn header(&self) -> String {
+        format!("{} {} v{}\n", MAGIC, self.kind, self.version)
+    }
+
+    /// Prefix `body` with the header of the current version.
+    pub fn wrap(&self, body: &str) -> String {
+        self.header() + body
+    }
+
+    /// Strip the header from `input` and upgrade the body to the current
+    /// version. Input without a header is treated as version 1.
+    pub fn upgrade(&self, input: &str) -> Result<String, FormatError> {
+        debug_assert_eq!(self.migrations.len() + 1, self.version as usize);
+        let (first, rest) = match input.find('\n') {
+            Some(i) => (&input[..i], &input[i + 1..]),
+            None => (input, ""),
+        };
+        let (version, body) = match Header::parse(first) {
+            None => (1, input),
+            Some(header) => {
+                let header = header?;
+                if header.kind != self.kind {
+                    return Err(FormatError::WrongKind {
+                        expected: self.kind.to_string(),
+                        found: header.kind,
+                    });
+                }
+                (header.version, rest)
+            }
+        };
+        if version > self.version {
+            return Err(FormatError::UnsupportedVersion {
+                kind: self.kind.to_string(),
+                version,
+                current: self.version,
+            });
+        }
+        let mut body = body.to_string();
+        for migrate in &self.migrations[version as usize - 1..] {
+            body = migrate(&body)?;
+        }
+        Ok(body)
+    }
+}
+
+/// Validate is implemented by values that can be put together in an
+/// inconsistent state, e.g. by hand or by a bad file, and can check
+/// themselves before they are trusted.
+pub trait Validate {
+    /// The error describing what is wrong.
+    type Error;
+
+    /// Return `Ok` if the value is consistent, or the first problem found.
+    fn validate(&self) -> Result<(), Self::Error>;
+}
+
+/// Artifact is implemented by values stored in a versioned text format.
+/// `Display` writes the body, without the header, and `parse_body` reads an
+/// upgraded body back.
+pub trait Artifact: fmt::Display + Validate + Sized
+where
+    Self::Error: From<FormatError>,
+{
+    /// The format of the artifact.
+    const FORMAT: Format;
+
+    /// Parse the body of the current version of `FORMAT`. The value need
+    /// not be valid; `parse_artifact` checks it.
+    fn parse_body(body: &str) -> Result<Self, Self::Error>;
+
+    /// Return the artifact as text, with the header of its format.
+    fn to_artifact(&self) -> String {
+        Self::FORMAT.wrap(&self.to_string())
+    }
+
+    /// Read an artifact written by `to_artifact`, or by `Display` without a
+    /// header: upgrade it to the current version, parse it and validate it.
+    fn parse_artifact(input: &str) -> Result<Self, Self::Error> {
+        let body = Self::FORMAT.upgrade(input)?;
+        let artifact = Self::parse_body(&body)?;
+        artifact.validate()?;
+        Ok(artifact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v1_to_v2(body: &str) -> Result<String, FormatError> {
+        Ok(body.replace('.', "0"))
+    }
+
+    fn v2_to_v3(body: &str) -> Result<String, FormatError> {
+        if body.is_empty() {
+            return Err(FormatError::Migration {
+                version: 2,
+                reason: "empty body".to_string(),
+            });
+        }
+        Ok(body.to_uppercase())
+    }
+
+    const TEST: Format = Format {
+        kind: "test",
+        version: 3,
+        migrations: &[v1_to_v2, v2_to_v3],
+    };
+
+    #[test]
+    fn header_round_trip() {
+        assert_eq!(TEST.header(), "square100 test v3\n");
+        let header = Header::parse(TEST.header().trim_end()).unwrap().unwrap();
+        assert_eq!(
+            header,
+            Header {
+                kind: "test".to_string(),
+                version: 3
+            }
+        );
+        assert!(Header::parse("1 2 3").is_none());
+        assert!(Header::parse("square100 test 3").unwrap().is_err());
+        assert!(Header::parse("square100 test v0").unwrap().is_err());
+        assert!(Header::parse("square100 test v1 extra").unwrap().is_err());
+    }
+
+    #[test]
+    fn upgrade_runs_migrations() {
+        // current version is returned untouched
+        assert_eq!(TEST.upgrade(&TEST.wrap("a.b")).unwrap(), "a.b");
+        // older versions are upgraded step by step
+        assert_eq!(TEST.upgrade("square100 test v2\na.b").unwrap(), "A.B");
+        assert_eq!(TEST.upgrade("square100 test v1\na.b").unwrap(), "A0B");
+        // no header means version 1
+        assert_eq!(TEST.upgrade("a.b").unwrap(), "A0B");
+        assert_eq!(
+            TEST.upgrade("square100 test v2\n"),
+            Err(FormatError::Migration {
+                version: 2,
+                reason: "empty body".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn upgrade_rejects_foreign_input() {
+        assert_eq!(
+            TEST.upgrade("square100 board v1\n"),
+            Err(FormatError::WrongKind {
+                expected: "test".to_string(),
+                found: "board".to_string()
+            })
+        );
+        assert_eq!(
+            TEST.upgrade("square100 test v4\n"),
+            Err(FormatError::UnsupportedVersion {
+                kind: "test".to_string(),
+                version: 4,
+                current: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,339 @@
+/*!
+Clue puzzle generation.
+
+A clue puzzle (see `pack::Puzzle`) is only fair if the clues leave the
+player a single completion. `find_completions` searches for the boards that
+agree with every clue, and `generate` builds a puzzle from a solution by
+hiding values for as long as that search finds just the one.
+
+The values are hidden one at a time in a random order, starting from the
+whole solution. A value stays revealed if hiding it lets a second
+completion in, or if the search cannot tell within its node budget. Few
+clues make that search large, so the budget decides how sparse a puzzle
+can get: on a 10x10 board the default budget gets down to about 30 clues.
+A puzzle left with fewer clues than asked for is topped up with random
+ones, which keeps it unique.
+ */
+
+use crate::board::Board;
+use crate::coord::Coord;
+use crate::pack::Puzzle;
+use crate::rng::Rng;
+use crate::solver::{Outcome, Solver, SolverConfig};
+use failure::Fail;
+
+/// The default node budget of each completion search made by `generate`.
+pub const CHECK_NODE_LIMIT: u64 = 1_000_000;
+
+#[derive(Debug, Fail, PartialEq)]
+/// Errors raised while generating a puzzle.
+pub enum GenerateError {
+    #[fail(
+        display = "Cannot reveal {} clues on a board of {} cells",
+        clues, cells
+    )]
+    TooManyClues { clues: usize, cells: usize },
+    #[fail(
+        display = "No puzzle with a unique completion and {} clues found in {} attempts; \
+                   try more clues or a larger node budget",
+        clues, attempts
+    )]
+    NotFound { clues: usize, attempts: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The result of `find_completions`.
+pub struct Completions {
+    /// The completions found, in the order they were found.
+    pub boards: Vec<Board>,
+    /// The number of positions visited.
+    pub nodes: u64,
+    /// `false` if the node limit stopped the search before the whole tree
+    /// was searched or enough completions were found.
+    pub complete: bool,
+}
+
+impl Completions {
+    /// Return `true` if the search proved there is exactly one completion.
+    pub fn is_unique(&self) -> bool {
+        self.complete && self.boards.len() == 1
+    }
+}
+
+/// Search for up to `max` boards that complete `puzzle`, visiting no more
+/// than `node_limit` positions if one is given.
+pub fn find_completions(puzzle: &Puzzle, max: usize, node_limit: Option<u64>) -> Completions {
+    let empty = Board::with_dimensions(puzzle.width(), puzzle.height());
+    let mut clue_cells = vec![None; empty.cell_count() + 2];
+    for (x, y, value) in puzzle.clues() {
+        clue_cells[value as usize] = Some(Coord::new(x, y));
+    }
+    let mut search = CompletionSearch {
+        puzzle,
+        clue_cells,
+        max,
+        limit: node_limit.unwrap_or(u64::MAX),
+        nodes: 0,
+        boards: Vec::new(),
+    };
+    let starts: Vec<Coord> = match search.clue_cells[1] {
+        Some(cell) => vec![cell],
+        None => empty
+            .empty_cells()
+            .filter(|cell| puzzle.clue_at(cell.x, cell.y) == 0)
+            .collect(),
+    };
+    let mut complete = true;
+    for cell in starts {
+        let mut board = empty.clone().start_at(cell).unwrap();
+        if !search.walk(&mut board) {
+            complete = search.boards.len() >= max;
+            break;
+        }
+    }
+    Completions {
+        boards: search.boards,
+        nodes: search.nodes,
+        complete,
+    }
+}
+
+/// CompletionSearch is the state of one `find_completions` search.
+struct CompletionSearch<'a> {
+    puzzle: &'a Puzzle,
+    /// The cell of each revealed value, indexed by value.
+    clue_cells: Vec<Option<Coord>>,
+    /// The number of completions to stop at.
+    max: usize,
+    /// The most positions to visit.
+    limit: u64,
+    nodes: u64,
+    boards: Vec<Board>,
+}
+
+impl<'a> CompletionSearch<'a> {
+    /// Search below `board`, returning `false` once the search should stop.
+    /// The board is left as it was.
+    fn walk(&mut self, board: &mut Board) -> bool {
+        if self.nodes >= self.limit {
+            return false;
+        }
+        self.nodes += 1;
+        if board.is_won() {
+            self.boards.push(board.clone());
+            return self.boards.len() < self.max;
+        }
+        if !self.can_reach_next_clue(board) {
+            return true;
+        }
+        let (width, height) = (board.width(), board.height());
+        let from = board.current_position().unwrap();
+        let next = board.score() + 1;
+        for dir in board.possible_moves() {
+            let to = board.rules().target(width, height, from, dir).unwrap();
+            let allowed = match self.clue_cells[next] {
+                Some(cell) => cell == to,
+                None => self.puzzle.clue_at(to.x, to.y) == 0,
+            };
+            if !allowed {
+                continue;
+            }
+            board.make_move(dir).unwrap();
+            let go_on = self.walk(board);
+            board.unmake_move().unwrap();
+            if !go_on {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Return `false` if the next revealed value is further away than the
+    /// moves left to reach it can jump.
+    fn can_reach_next_clue(&self, board: &Board) -> bool {
+        let value = board.score();
+        let from = board.current_position().unwrap();
+        let next = (value + 1..self.clue_cells.len()).find_map(|v| Some((v, self.clue_cells[v]?)));
+        next.is_none_or(|(next, cell)| {
+            let jump = board.rules().directions().fold(0, |jump, dir| {
+                let (dx, dy) = board.rules().delta(dir).unwrap();
+                jump.max(dx.abs()).max(dy.abs())
+            }) as usize;
+            let distance = from.x.abs_diff(cell.x).max(from.y.abs_diff(cell.y));
+            distance <= jump * (next - value)
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// GenerateConfig holds the options of `generate`.
+pub struct GenerateConfig {
+    /// The board size.
+    pub size: usize,
+    /// The number of values to reveal.
+    pub clues: usize,
+    /// Only return a puzzle whose completion is unique.
+    pub unique: bool,
+    /// The seed of the random choices; the same seed gives the same puzzle.
+    pub seed: u64,
+    /// The node budget of each completion search.
+    pub node_limit: u64,
+    /// The number of solutions to try before giving up.
+    pub attempts: usize,
+}
+
+impl Default for GenerateConfig {
+    fn default() -> Self {
+        GenerateConfig {
+            size: 10,
+            clues: 12,
+            unique: true,
+            seed: 0,
+            node_limit: CHECK_NODE_LIMIT,
+            attempts: 5,
+        }
+    }
+}
+
+/// Generate a puzzle of `config.clues` clues, returning it with the
+/// solution it was taken from. The difficulty of a unique puzzle is the
+/// number of positions the last completion search visited, in thousands;
+/// other puzzles have difficulty 0.
+pub fn generate(config: &GenerateConfig) -> Result<(Puzzle, Board), GenerateError> {
+    let empty = Board::new(config.size);
+    let cells = empty.cell_count();
+    if config.clues > cells {
+        return Err(GenerateError::TooManyClues {
+            clues: config.clues,
+            cells,
+        });
+    }
+    let mut rng = Rng::new(config.seed);
+    let solver = Solver::new(SolverConfig {
+        node_limit: Some(config.node_limit),
+        ..SolverConfig::default()
+    });
+    let starts: Vec<Coord> = empty.empty_cells().collect();
+    for _ in 0..config.attempts {
+        let cell = *rng.choose(&starts).unwrap();
+        let result = solver.solve(&empty.clone().start_at(cell).unwrap());
+        if result.outcome != Outcome::Solved {
+            continue;
+        }
+        let solution = result.best;
+        let mut reveal: Vec<u16> = Vec::new();
+        if config.unique {
+            reveal = (1..=cells as u16).collect();
+            let mut order = reveal.clone();
+            for i in (1..order.len()).rev() {
+                order.swap(i, rng.below(i + 1));
+            }
+            let mut kept = 0;
+            for value in order {
+                let fewer: Vec<u16> = reveal.iter().copied().filter(|&v| v != value).collect();
+                let puzzle = Puzzle::from_solution(&solution, &fewer, 0);
+                if find_completions(&puzzle, 2, Some(config.node_limit)).is_unique() {
+                    reveal = fewer;
+                } else {
+                    kept += 1;
+                    if kept > config.clues {
+                        break;
+                    }
+                }
+            }
+            if reveal.len() > config.clues {
+                continue;
+            }
+        }
+        while reveal.len() < config.clues {
+            let hidden = hidden_values(&solution, &reveal);
+            reveal.push(*rng.choose(&hidden).unwrap());
+        }
+        let mut difficulty = 0;
+        if config.unique {
+            let puzzle = Puzzle::from_solution(&solution, &reveal, 0);
+            let found = find_completions(&puzzle, 2, Some(config.node_limit));
+            difficulty = (found.nodes / 1000) as u32;
+        }
+        return Ok((
+            Puzzle::from_solution(&solution, &reveal, difficulty),
+            solution,
+        ));
+    }
+    Err(GenerateError::NotFound {
+        clues: config.clues,
+        attempts: config.attempts,
+    })
+}
+
+/// Return the values of `solution` not in `reveal`.
+fn hidden_values(solution: &Board, reveal: &[u16]) -> Vec<u16> {
+    solution
+        .iter_cells()
+        .map(|(_, _, v)| v)
+        .filter(|v| !reveal.contains(v))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SOLUTION_5;
+
+    #[test]
+    fn completions_agree_with_the_clues() {
+        let solution = SOLUTION_5.board();
+        let all: Vec<u16> = (1..=25).collect();
+        let full = Puzzle::from_solution(&solution, &all, 0);
+        let found = find_completions(&full, 2, None);
+        assert!(found.is_unique());
+        assert_eq!(found.boards, vec![solution.clone()]);
+
+        // with only the 1 revealed, every tour from the corner completes it
+        let corner = Puzzle::from_solution(&solution, &[1], 0);
+        let found = find_completions(&corner, 1000, None);
+        assert!(found.complete);
+        assert_eq!(found.boards.len(), 552);
+        assert!(found.boards.iter().all(|b| corner.is_completed_by(b)));
+
+        let found = find_completions(&corner, 2, None);
+        assert_eq!((found.boards.len(), found.complete), (2, true));
+        let found = find_completions(&corner, 2, Some(10));
+        assert_eq!((found.nodes, found.complete), (10, false));
+    }
+
+    #[test]
+    fn generated_puzzles_are_unique() {
+        let config = GenerateConfig {
+            size: 6,
+            clues: 8,
+            seed: 3,
+            ..GenerateConfig::default()
+        };
+        let (puzzle, solution) = generate(&config).unwrap();
+        assert_eq!(puzzle.clues().count(), 8);
+        assert!(puzzle.is_solution(&solution));
+        assert!(find_completions(&puzzle, 2, None).is_unique());
+        assert_eq!(generate(&config).unwrap().0, puzzle);
+
+        let loose = GenerateConfig {
+            unique: false,
+            clues: 3,
+            ..config.clone()
+        };
+        let (puzzle, _) = generate(&loose).unwrap();
+        assert_eq!((puzzle.clues().count(), puzzle.difficulty), (3, 0));
+
+        let crowded = GenerateConfig {
+            clues: 37,
+            ..config
+        };
+        assert_eq!(
+            generate(&crowded),
+            Err(GenerateError::TooManyClues {
+                clues: 37,
+                cells: 36
+            })
+        );
+    }
+}
@@ -0,0 +1,351 @@
+/*!
+Board representations sharing one set of move rules.
+
+`Board` keeps its cells on the heap, so any size can be chosen at run time.
+`BoardN<N>` is an `N` x `N` board whose cells are stored inline, so it lives
+on the stack and copying it is a plain memory copy; searches that copy
+positions millions of times are much cheaper on it. Both implement `Grid`,
+which provides the move logic on top of a handful of accessors, so code
+written against `Grid` runs on either.
+ */
+
+use crate::board::{Board, BoardError, Direction, MoveRules, MAX_SIZE, MIN_SIZE};
+use crate::coord::Coord;
+
+/// Grid is a board the move rules can be played on. Implementors provide
+/// access to the cells; the moves are provided on top of them.
+pub trait Grid {
+    /// Return the number of horizontal cells.
+    fn width(&self) -> usize;
+
+    /// Return the number of vertical cells.
+    fn height(&self) -> usize;
+
+    /// Return the value at `cell`; 0 for an empty cell.
+    fn value_at(&self, cell: Coord) -> u16;
+
+    /// Return the location of the last value placed, or `None` if nothing
+    /// has been placed.
+    fn current_position(&self) -> Option<Coord>;
+
+    /// Return the highest value placed.
+    fn score(&self) -> usize;
+
+    /// Place the next value at `cell`, which must be an empty cell on the
+    /// board, and make it the current position.
+    fn place(&mut self, cell: Coord);
+
+    /// Return the moves allowed on the grid.
+    fn rules(&self) -> &MoveRules;
+
+    /// Return `true` if `cell` is a hole that can never be visited. Grids
+    /// have no holes unless they say otherwise.
+    fn is_hole(&self, _cell: Coord) -> bool {
+        false
+    }
+
+    /// Return the total number of cells.
+    fn cell_count(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// Return the cell a move in `dir` from the current position lands on,
+    /// if that move is legal.
+    fn target(&self, dir: Direction) -> Option<Coord> {
+        let from = self.current_position()?;
+        let to = self
+            .rules()
+            .target(self.width(), self.height(), from, dir)?;
+        if self.is_vacant(to) {
+            Some(to)
+        } else {
+            None
+        }
+    }
+
+    /// Return `true` if `cell` is empty and not a hole.
+    fn is_vacant(&self, cell: Coord) -> bool {
+        self.value_at(cell) == 0 && !self.is_hole(cell)
+    }
+
+    /// Return the legal moves from the current position.
+    fn legal_moves(&self) -> Vec<Direction> {
+        self.rules()
+            .directions()
+            .filter(|dir| self.target(*dir).is_some())
+            .collect()
+    }
+
+    /// Return `true` if every cell has been filled.
+    fn is_complete(&self) -> bool {
+        self.score() == self.cell_count()
+    }
+
+    /// Place the value 1 at `cell` of an empty grid.
+    fn begin(&mut self, cell: Coord) -> Result<(), BoardError> {
+        let Coord { x, y } = cell;
+        if x >= self.width() || y >= self.height() {
+            return Err(BoardError::IndexOutOfRange {
+                x,
+                y,
+                max: if x >= self.width() {
+                    self.width()
+                } else {
+                    self.height()
+                },
+            });
+        }
+        if self.score() > 0 {
+            return Err(BoardError::ValueUsed { x, y, value: 1 });
+        }
+        self.place(cell);
+        Ok(())
+    }
+
+    /// Play a move in `dir` in place.
+    fn play(&mut self, dir: Direction) -> Result<(), BoardError> {
+        let from = self.current_position().ok_or(BoardError::NotStarted)?;
+        let to = self.target(dir).ok_or(BoardError::BadDirection {
+            dir,
+            x: from.x,
+            y: from.y,
+        })?;
+        self.place(to);
+        Ok(())
+    }
+}
+
+impl Grid for Board {
+    fn width(&self) -> usize {
+        Board::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Board::height(self)
+    }
+
+    fn value_at(&self, cell: Coord) -> u16 {
+        Board::value_at(self, cell)
+    }
+
+    fn current_position(&self) -> Option<Coord> {
+        Board::current_position(self)
+    }
+
+    fn score(&self) -> usize {
+        Board::score(self)
+    }
+
+    fn rules(&self) -> &MoveRules {
+        Board::rules(self)
+    }
+
+    fn cell_count(&self) -> usize {
+        Board::cell_count(self)
+    }
+
+    fn is_hole(&self, cell: Coord) -> bool {
+        Board::is_hole(self, cell)
+    }
+
+    fn is_vacant(&self, cell: Coord) -> bool {
+        Board::is_vacant(self, cell)
+    }
+
+    fn place(&mut self, cell: Coord) {
+        let value = self.score() as u16 + 1;
+        self.place_value(cell.x, cell.y, value);
+    }
+
+    fn begin(&mut self, cell: Coord) -> Result<(), BoardError> {
+        Board::begin(self, cell)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// BoardN is an `N` x `N` board stored inline, without heap allocation.
+/// `N` must be in `MIN_SIZE..=MAX_SIZE`.
+pub struct BoardN<const N: usize> {
+    values: [[u16; N]; N],
+    x: u8,
+    y: u8,
+    score: u16,
+    rules: MoveRules,
+}
+
+impl<const N: usize> Default for BoardN<N> {
+    fn default() -> Self {
+        BoardN::new()
+    }
+}
+
+impl<const N: usize> BoardN<N> {
+    /// Create an empty board.
+    pub fn new() -> Self {
+        assert!(
+            (MIN_SIZE..=MAX_SIZE).contains(&N),
+            "board size {} out of range",
+            N
+        );
+        BoardN {
+            values: [[0; N]; N],
+            x: 0,
+            y: 0,
+            score: 0,
+            rules: MoveRules::CLASSIC,
+        }
+    }
+
+    /// Return this board with the moves allowed by `rules` in place of the
+    /// classic ones.
+    pub fn with_rules(mut self, rules: MoveRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Copy `board` into a fixed-size board. The board must be `N` x `N`
+    /// and have no holes.
+    pub fn from_board(board: &Board) -> Result<Self, BoardError> {
+        if board.width() != N || board.height() != N {
+            return Err(BoardError::InvalidDimensions {
+                cols: board.width(),
+                rows: board.height(),
+            });
+        }
+        if let Some((x, y, _)) = board
+            .iter_cells()
+            .find(|&(x, y, _)| board.is_hole(Coord::new(x, y)))
+        {
+            return Err(BoardError::Hole { x, y });
+        }
+        let mut fixed = BoardN::new().with_rules(*board.rules());
+        for (x, y, value) in board.iter_cells() {
+            fixed.values[y][x] = value;
+        }
+        fixed.score = board.score() as u16;
+        if let Some(current) = board.current_position() {
+            fixed.x = current.x as u8;
+            fixed.y = current.y as u8;
+        }
+        Ok(fixed)
+    }
+
+    /// Copy the board into a dynamic `Board`.
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new(N).with_rules(self.rules);
+        for value in 1..=self.score {
+            let cell = self.position_of(value).unwrap();
+            Grid::place(&mut board, cell);
+        }
+        board
+    }
+
+    /// Return the location of `value`, or `None` if it has not been placed.
+    pub fn position_of(&self, value: u16) -> Option<Coord> {
+        if value == 0 {
+            return None;
+        }
+        (0..N * N)
+            .map(|i| Coord::from_index(i, N))
+            .find(|cell| self.values[cell.y][cell.x] == value)
+    }
+}
+
+impl<const N: usize> Grid for BoardN<N> {
+    fn width(&self) -> usize {
+        N
+    }
+
+    fn height(&self) -> usize {
+        N
+    }
+
+    fn value_at(&self, cell: Coord) -> u16 {
+        self.values[cell.y][cell.x]
+    }
+
+    fn current_position(&self) -> Option<Coord> {
+        if self.score > 0 {
+            Some(Coord::new(self.x as usize, self.y as usize))
+        } else {
+            None
+        }
+    }
+
+    fn score(&self) -> usize {
+        self.score as usize
+    }
+
+    fn rules(&self) -> &MoveRules {
+        &self.rules
+    }
+
+    fn place(&mut self, cell: Coord) {
+        self.score += 1;
+        self.values[cell.y][cell.x] = self.score;
+        self.x = cell.x as u8;
+        self.y = cell.y as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{DEAD_END_5, SOLUTION_5};
+
+    // Play a game on any grid.
+    fn replay<G: Grid>(grid: &mut G, start: Coord, moves: &[Direction]) {
+        grid.begin(start).unwrap();
+        for dir in moves {
+            assert!(grid.legal_moves().contains(dir));
+            grid.play(*dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn fixed_and_dynamic_boards_agree() {
+        let mut fixed = BoardN::<5>::new();
+        let mut dynamic = Board::new(5);
+        replay(&mut fixed, SOLUTION_5.start, SOLUTION_5.moves);
+        replay(&mut dynamic, SOLUTION_5.start, SOLUTION_5.moves);
+        assert!(fixed.is_complete());
+        assert!(Grid::is_complete(&dynamic));
+        assert_eq!(dynamic, SOLUTION_5.board());
+        assert_eq!(fixed.to_board(), dynamic);
+        assert_eq!(BoardN::<5>::from_board(&dynamic), Ok(fixed));
+
+        let dead = DEAD_END_5.board();
+        let fixed = BoardN::<5>::from_board(&dead).unwrap();
+        let moves: Vec<Direction> = dead.possible_moves().into_iter().collect();
+        assert_eq!(fixed.legal_moves(), moves);
+        assert_eq!(fixed.current_position(), dead.current_position());
+    }
+
+    #[test]
+    fn fixed_board_errors() {
+        let mut fixed = BoardN::<6>::new();
+        assert_eq!(fixed.play(Direction::Up), Err(BoardError::NotStarted));
+        assert!(fixed.begin(Coord::new(6, 0)).is_err());
+        fixed.begin(Coord::new(0, 0)).unwrap();
+        assert_eq!(
+            fixed.play(Direction::Up),
+            Err(BoardError::BadDirection {
+                dir: Direction::Up,
+                x: 0,
+                y: 0
+            })
+        );
+        assert!(fixed.begin(Coord::new(1, 1)).is_err());
+
+        // copies are independent
+        let mut copy = fixed;
+        copy.play(Direction::Right).unwrap();
+        assert_eq!(fixed.score(), 1);
+        assert_eq!(copy.score(), 2);
+
+        assert_eq!(
+            BoardN::<6>::from_board(&Board::new(5)),
+            Err(BoardError::InvalidDimensions { cols: 5, rows: 5 })
+        );
+    }
+}
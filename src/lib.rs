@@ -0,0 +1,66 @@
+/*!
+
+A Rust solver for a simple numerical game.
+
+See the inital article at [simple-number].
+
+# Rules
+
+The rules are simple: on an empty 10x10 grid (100 squares in total) you put a
+number 1 on an arbitrary square. Starting from that square you can move
+horizontally or vertically jumping over two squares or diagonally jumping over
+one square. There you can place number 2. Your task is to reach number 100,
+filling all squares. You can not visit already visited squares.
+
+Here is an example of a solved game with a reduced 5x5 grid, starting at
+top-left corner:
+
+```text
+ 1 24 14  2 25
+16 21  5  8 20
+13 10 18 23 11
+ 4  7 15  3  6
+17 22 12  9 19
+```
+
+[simple-number]: https://www.nurkiewicz.com/2018/09/brute-forcing-seemingly-simple-number.html
+ */
+
+// The `failure` derive expands its impls inside an anonymous const.
+#![allow(non_local_definitions)]
+
+pub mod agent;
+pub mod analysis;
+pub mod bench_fixtures;
+pub mod board;
+pub mod board3d;
+pub mod cells;
+pub mod coord;
+pub mod daily;
+pub mod engine;
+pub mod fixtures;
+pub mod format;
+pub mod generator;
+pub mod grid;
+pub mod metrics;
+pub mod neighbors;
+pub mod ordering;
+pub mod pack;
+pub mod render;
+pub mod repl;
+pub mod replay;
+pub mod rng;
+pub mod rules;
+#[cfg(feature = "simd")]
+pub mod scan;
+pub mod shape;
+pub mod solution;
+pub mod solver;
+pub mod storage;
+pub mod transposition;
+pub mod zobrist;
+
+pub use crate::board::{
+    Board, BoardBuilder, BoardError, Direction, DirectionError, MoveRules, Moves,
+};
+pub use crate::coord::{Coord, Delta};
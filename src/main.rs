@@ -1,35 +1,2184 @@
 /*!
+Command line front end for the square100 solver.
 
-A Rust solver for a simple numerical game.
+The exit code tells scripts how a run went without parsing its output: 0
+when it succeeded (for `solve`, a solution was found), 1 when the board was
+proven unsolvable, 2 when the search gave up before finding an answer, 3 on
+any other error and 64 when the command line itself is wrong.
+ */
+
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use failure::{format_err, Error};
+use search_failure::SearchFailure;
+use serde_json::{json, Value};
+use square100::agent::{self, WarnsdorffAgent};
+use square100::analysis::{self, Availability, CountConfig};
+use square100::coord::CoordError;
+use square100::daily::{Daily, DailyError, Date};
+use square100::engine::{self, Engine};
+use square100::generator::{self, GenerateConfig};
+use square100::metrics::{self, Invocation};
+use square100::ordering::MoveTable;
+use square100::render::{self, RenderOptions, Style, StyleError, SvgOptions};
+use square100::repl::{self, Session};
+use square100::replay::Replay;
+use square100::rng::Rng;
+#[cfg(feature = "tui")]
+use square100::shape::{Catalog, Shape};
+use square100::solver::{Outcome, SolveEvent, Solver, SolverConfig};
+use square100::storage::{Area, Storage};
+use square100::{Board, BoardBuilder, BoardError, Coord, Direction, MoveRules};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "tui")]
+mod tui;
 
-See the inital article at [simple-number].
+/// Environment variable naming a metrics file, as an alternative to
+/// `--metrics`.
+const METRICS_ENV: &str = "BOARD100_METRICS";
 
-# Rules
+/// The exit code of a board proven to have no solution.
+const EXIT_UNSOLVABLE: i32 = 1;
+/// The exit code of a search that gave up before finding an answer.
+const EXIT_GAVE_UP: i32 = 2;
+/// The exit code of any other error.
+const EXIT_ERROR: i32 = 3;
+/// The exit code of a bad command line, as in BSD's sysexits.h.
+const EXIT_USAGE: i32 = 64;
 
-The rules are simple: on an empty 10x10 grid (100 squares in total) you put a
-number 1 on an arbitrary square. Starting from that square you can move
-horizontally or vertically jumping over two squares or diagonally jumping over
-one square. There you can place number 2. Your task is to reach number 100,
-filling all squares. You can not visit already visited squares.
+// The `failure` derive expands its impls inside an anonymous const, which
+// the non_local_definitions lint flags; the derived type is kept in a module
+// of its own so that the lint is allowed for it alone.
+#[allow(non_local_definitions)]
+mod search_failure {
+    use failure::Fail;
 
-Here is an example of a solved game with a reduced 5x5 grid, starting at
-top-left corner:
+    #[derive(Debug, Fail)]
+    /// SearchFailure is a search that ended without a solution; it decides
+    /// the exit code.
+    pub enum SearchFailure {
+        #[fail(display = "the board has no solution")]
+        Unsolvable,
+        #[fail(
+            display = "no solution found within {} ({} positions); the best game reached {} of {}",
+            budget, nodes, best, cells
+        )]
+        LimitReached {
+            budget: String,
+            nodes: u64,
+            best: usize,
+            cells: usize,
+        },
+        #[fail(display = "{} got stuck; the best game reached {}", strategy, best)]
+        Stuck { strategy: &'static str, best: usize },
+    }
+}
 
-     1 24 14  2 25
-    16 21  5  8 20
-    13 10 18 23 11
-     4  7 15  3  6
-    17 22 12  9 19
+impl SearchFailure {
+    /// The exit code and the outcome recorded in the metrics file.
+    fn exit(&self) -> (i32, &'static str) {
+        match self {
+            SearchFailure::Unsolvable => (EXIT_UNSOLVABLE, "unsolvable"),
+            SearchFailure::LimitReached { .. } => (EXIT_GAVE_UP, "limit"),
+            SearchFailure::Stuck { .. } => (EXIT_GAVE_UP, "stuck"),
+        }
+    }
+}
 
+#[derive(Parser)]
+#[command(
+    name = "board100",
+    version,
+    about = "Solver for the 100 squares number puzzle",
+    after_help = "Exit codes: 0 success, 1 unsolvable, 2 search gave up, 3 error, 64 usage error."
+)]
+struct Cli {
+    /// Append a record of this run to FILE (JSON lines). Defaults to the
+    /// BOARD100_METRICS environment variable; no metrics are kept if
+    /// neither is set.
+    #[arg(long, global = true, value_name = "FILE")]
+    metrics: Option<PathBuf>,
 
+    /// Keep all saved files under DIR instead of the platform's usual
+    /// locations, e.g. for a portable install. Defaults to the
+    /// BOARD100_DATA_DIR environment variable.
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
 
-[simple-number]: https://www.nurkiewicz.com/2018/09/brute-forcing-seemingly-simple-number.html
- */
+    /// How to print results: text for people, json for scripts, or csv for
+    /// spreadsheets.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// How to draw boards: plain whitespace separated values, or unicode
+    /// boxes around each cell.
+    #[arg(long, global = true, value_parser = parse_style, default_value = "plain")]
+    style: Style,
+
+    /// When to color boards: auto colors them on a terminal unless the
+    /// NO_COLOR environment variable is set.
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+/// OutputFormat is the way results are printed.
+enum OutputFormat {
+    /// Tables and grids, for reading.
+    Text,
+    /// One JSON document on stdout.
+    Json,
+    /// Comma-separated rows with a header, for spreadsheets.
+    Csv,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+/// ColorChoice is when boards are colored.
+enum ColorChoice {
+    /// On a terminal, unless NO_COLOR is set.
+    Auto,
+    /// Even when piped, e.g. into `less -R`.
+    Always,
+    /// Never.
+    Never,
+}
+
+impl ColorChoice {
+    /// Return `true` if output should be colored.
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show which analyses can finish on a board within a node budget.
+    Capabilities {
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// The start cell, as X,Y or a name such as D4; the board is left
+        /// empty if not given.
+        #[arg(long, value_parser = parse_cell)]
+        start: Option<Coord>,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+        /// The maximum number of search nodes to spend.
+        #[arg(long, default_value_t = 100_000_000)]
+        budget: u64,
+    },
+    /// Solve a board and print the solution.
+    Solve {
+        /// Complete the board in FILE, or read from stdin if it is `-`,
+        /// instead of an empty one. It may be in the text format or the
+        /// JSON written by --format json.
+        #[arg(value_name = "FILE", conflicts_with_all = ["size", "start", "torus"])]
+        board: Option<PathBuf>,
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// The start cell, as X,Y or a name such as D4; every cell is tried
+        /// if not given.
+        #[arg(long, value_parser = parse_cell)]
+        start: Option<Coord>,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+        /// How to look for a solution.
+        #[arg(long, value_enum, default_value_t = Strategy::Search)]
+        strategy: Strategy,
+        /// Give up after visiting this many positions, e.g. 500000 or 10M
+        /// (search and naive; restart and mcts count the moves they play,
+        /// simulated ones included). The best
+        /// partial board is still printed.
+        #[arg(long = "node-limit", alias = "nodes", value_name = "N", value_parser = parse_count)]
+        nodes: Option<u64>,
+        /// Give up after searching for this long, e.g. 30s or 2m (search
+        /// only). The best partial board is still printed.
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        time_limit: Option<Duration>,
+        /// Also write the solution to FILE: a replay if its name ends in
+        /// .b100, a PNG image if it ends in .png, otherwise an SVG figure.
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Also write an animated GIF of the solution, one frame per value,
+        /// to FILE.
+        #[arg(long, value_name = "FILE")]
+        gif: Option<PathBuf>,
+        /// Replay the solution one move at a time, redrawing the board.
+        #[arg(long)]
+        animate: bool,
+        /// Redraw the position the search is at while it runs, to watch it
+        /// backtrack (search and naive only; searches on one thread).
+        #[arg(long, conflicts_with = "verbose")]
+        watch: bool,
+        /// Also print the solution in move notation, the start cell and
+        /// each move (e.g. `A1: R D L UR`), below the grid.
+        #[arg(long)]
+        notation: bool,
+        /// The pause between moves when animating, between the frames of a
+        /// GIF and between redraws when watching, e.g. 100ms or 1s.
+        #[arg(long, value_parser = parse_duration, default_value = "100ms")]
+        delay: Duration,
+        /// Trace the search on stderr, one event per line: -v shows
+        /// pruned positions and how the search ended, -vv also each move
+        /// tried and taken back (search only).
+        #[arg(short, long, action = ArgAction::Count)]
+        verbose: u8,
+        /// The number of threads to search on; defaults to the number of
+        /// logical cores. 1 makes runs repeatable, for debugging; tracing
+        /// always searches on one.
+        #[arg(long, value_parser = parse_threads, default_value_t = default_threads())]
+        threads: usize,
+        /// Break ties between moves with the learned move table in FILE,
+        /// and record the solution found in it, creating it if need be.
+        /// Only search and naive order their moves with it.
+        #[arg(long, value_name = "FILE")]
+        learn: Option<PathBuf>,
+    },
+    /// Suggest the next move of a game in progress: the one most likely to
+    /// keep the board completable.
+    Hint {
+        /// The board, as written by `solve` in any of its formats.
+        #[arg(long, value_name = "FILE")]
+        board: PathBuf,
+        /// The most positions to visit after each candidate move, e.g.
+        /// 100k.
+        #[arg(long = "node-limit", alias = "nodes", value_name = "N", value_parser = parse_count)]
+        nodes: Option<u64>,
+    },
+    /// Try to solve a board from every start cell and show which succeed.
+    SolveAll {
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+        /// How to look for a solution.
+        #[arg(long, value_enum, default_value_t = Strategy::Search)]
+        strategy: Strategy,
+        /// Give up on a start after visiting this many positions, e.g.
+        /// 1M (search only).
+        #[arg(long = "node-limit", alias = "nodes", value_name = "N", value_parser = parse_count, default_value = "1M")]
+        nodes: u64,
+        /// The number of threads to solve the starts on; defaults to the
+        /// number of logical cores.
+        #[arg(long, value_parser = parse_threads, default_value_t = default_threads())]
+        threads: usize,
+    },
+    /// Time the solver from a set of start cells and report its speed.
+    Bench {
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+        /// The start cells: `all`, or a cell such as D4. May be given more
+        /// than once.
+        #[arg(long, value_parser = parse_starts, default_value = "all")]
+        starts: Vec<Starts>,
+        /// How to look for a solution.
+        #[arg(long, value_enum, default_value_t = Strategy::Search)]
+        strategy: Strategy,
+        /// How many times to solve from each start; times are averaged.
+        #[arg(long, default_value_t = 3)]
+        repeat: u32,
+        /// Give up on a start after visiting this many positions, e.g.
+        /// 1M (search only).
+        #[arg(long = "node-limit", alias = "nodes", value_name = "N", value_parser = parse_count, default_value = "1M")]
+        nodes: u64,
+    },
+    /// Run several strategies on the same boards and compare how often
+    /// each solves them, and how much time and work it takes.
+    CompareSolvers {
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+        /// The strategies to compare, separated by commas.
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            default_value = "search,naive,warnsdorff,restart,mcts"
+        )]
+        strategies: Vec<Strategy>,
+        /// The number of boards, each started on a different random cell.
+        #[arg(long, default_value_t = 10)]
+        boards: usize,
+        /// The seed that picks the start cells; the same seed gives the
+        /// same boards.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Give up on a board after visiting this many positions, e.g.
+        /// 100k (moves played, for restart and mcts).
+        #[arg(long = "node-limit", alias = "nodes", value_name = "N", value_parser = parse_count, default_value = "100k")]
+        nodes: u64,
+    },
+    /// Count the solutions of a board by searching the whole tree.
+    Count {
+        /// The board size.
+        #[arg(long, default_value_t = 5)]
+        size: usize,
+        /// The start cell, as X,Y or a name such as D4; every cell is
+        /// counted if not given.
+        #[arg(long, value_parser = parse_cell)]
+        start: Option<Coord>,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+        /// Count solutions that are rotations or reflections of each other
+        /// once.
+        #[arg(long)]
+        modulo_symmetry: bool,
+        /// Stop after visiting this many positions, e.g. 10M; the count
+        /// is then a lower bound.
+        #[arg(long = "node-limit", alias = "nodes", value_name = "N", value_parser = parse_count)]
+        nodes: Option<u64>,
+        /// The number of threads to search on; defaults to the number of
+        /// logical cores. 1 makes runs repeatable, for debugging.
+        #[arg(long, value_parser = parse_threads, default_value_t = default_threads())]
+        threads: usize,
+        /// The memory to keep positions known to have no completion in,
+        /// in bytes, e.g. 500k or 1G; 0 keeps none. The count is the same
+        /// whatever the size, but a larger table saves more work.
+        #[arg(long, value_name = "BYTES", value_parser = parse_count, default_value = "4M")]
+        tt_size: u64,
+    },
+    /// Make a clue puzzle: a board with some values of a solution revealed.
+    Generate {
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// The number of values to reveal.
+        #[arg(long, default_value_t = 12)]
+        clues: usize,
+        /// Only make a puzzle with a single completion.
+        #[arg(long)]
+        unique: bool,
+        /// The seed of the random choices; the same seed gives the same
+        /// puzzle. A seed is picked, and shown, if not given.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// The most positions to visit in each uniqueness check, e.g.
+        /// 100k.
+        #[arg(long = "node-limit", alias = "nodes", value_name = "N", value_parser = parse_count, default_value_t = generator::CHECK_NODE_LIMIT)]
+        nodes: u64,
+    },
+    /// Show the daily puzzle, the same start position for everyone, or
+    /// check a solution of it.
+    Daily {
+        /// The day of the puzzle, as YYYY-MM-DD; defaults to today (UTC).
+        #[arg(long, value_parser = parse_date)]
+        date: Option<Date>,
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// Check the completed board in FILE, or on stdin if it is `-`,
+        /// instead of showing the puzzle.
+        #[arg(long, value_name = "FILE")]
+        check: Option<PathBuf>,
+    },
+    /// Check a replay file and show the game it records.
+    Replay {
+        /// The replay file.
+        file: PathBuf,
+        /// Play the game back one move at a time, redrawing the board.
+        #[arg(long)]
+        animate: bool,
+        /// The pause between moves when animating, e.g. 100ms or 1s.
+        #[arg(long, value_parser = parse_duration, default_value = "100ms")]
+        delay: Duration,
+    },
+    /// Play a game in the terminal: enter a start cell, then one move per
+    /// line (e.g. `DR` or `down right`), `undo` or `quit`.
+    Play {
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+        /// Carry on with the game saved in FILE instead of a new one.
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["size", "torus"])]
+        resume: Option<PathBuf>,
+        /// Start on a random cell instead of asking for one.
+        #[arg(long, conflicts_with = "resume")]
+        random: bool,
+        /// The seed of a random game; the same seed gives the same game. A
+        /// seed is picked, and shown, if not given.
+        #[arg(long, requires = "random")]
+        seed: Option<u64>,
+        /// Also play this many random moves after the start.
+        #[arg(long, requires = "random", default_value_t = 0)]
+        opening: usize,
+        /// Save the game to FILE when the session ends, so it can be
+        /// resumed later.
+        #[arg(long, value_name = "FILE")]
+        save: Option<PathBuf>,
+    },
+    /// Play a game in a full-screen terminal interface.
+    #[cfg(feature = "tui")]
+    Tui {
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+    },
+    /// Draw a board shape in a full-screen terminal editor, check it as it
+    /// is drawn and save it to a shape catalog.
+    #[cfg(feature = "tui")]
+    EditShape {
+        /// The shape catalog to save to; an existing shape of the same name
+        /// is edited and replaced.
+        #[arg(value_name = "CATALOG")]
+        catalog: PathBuf,
+        /// The name of the shape in the catalog.
+        #[arg(long, default_value = "shape")]
+        name: String,
+        /// The number of columns of a new shape.
+        #[arg(long, default_value_t = 10)]
+        width: usize,
+        /// The number of rows of a new shape.
+        #[arg(long, default_value_t = 10)]
+        height: usize,
+    },
+    /// Speak the engine protocol on stdin and stdout (see the `engine`
+    /// module of the library).
+    Engine,
+    /// Explore positions by typing commands such as `start 4 4`, `move dr`,
+    /// `undo`, `hint` and `solve`; `help` lists them all.
+    Repl {
+        /// The board size.
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+        /// Let moves wrap around the edges of the board.
+        #[arg(long)]
+        torus: bool,
+    },
+    /// Show where saved files are kept.
+    Paths,
+    /// Summarize the runs recorded in a metrics file.
+    Report {
+        /// The metrics file to summarize; defaults to the --metrics file.
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+/// Strategy is the way `solve` looks for a solution.
+enum Strategy {
+    /// Backtracking search; finds a solution if there is one.
+    Search,
+    /// Backtracking search without any pruning rules, as a baseline.
+    Naive,
+    /// Always move to the cell with the fewest onward moves, without
+    /// backtracking. Fast, but can get stuck.
+    Warnsdorff,
+    /// Warnsdorff with random tie breaks, played again from the start
+    /// until a game is won or the node budget is spent on moves.
+    Restart,
+    /// Monte Carlo tree search: one game, each move the one whose simulated
+    /// games went deepest, until a game is won or the node budget is spent
+    /// on moves.
+    Mcts,
+}
+
+/// The move budget of `--strategy restart` and `--strategy mcts` when
+/// `solve` is given none.
+const RESTART_MOVE_LIMIT: u64 = 1_000_000;
+
+impl Strategy {
+    /// Return the solver options of a search strategy.
+    fn solver_config(self) -> SolverConfig {
+        match self {
+            Strategy::Naive => SolverConfig {
+                rules: Default::default(),
+                ..SolverConfig::default()
+            },
+            _ => SolverConfig::default(),
+        }
+    }
+
+    /// Return the strategy's name on the command line.
+    fn name(self) -> &'static str {
+        match self {
+            Strategy::Search => "search",
+            Strategy::Naive => "naive",
+            Strategy::Warnsdorff => "warnsdorff",
+            Strategy::Restart => "restart",
+            Strategy::Mcts => "mcts",
+        }
+    }
+}
 
-#![feature(custom_attribute)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Starts is a set of start cells to benchmark.
+enum Starts {
+    /// Every cell of the board.
+    All,
+    Cell(Coord),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// BenchRow is the result of benchmarking one start cell.
+struct BenchRow {
+    start: Coord,
+    /// `solved`, `unsolvable`, `limit` or `stuck`.
+    outcome: &'static str,
+    /// The positions visited by one solve.
+    nodes: u64,
+    /// The moves taken back by one solve.
+    backtracks: u64,
+    /// The mean time of one solve.
+    time: Duration,
+}
+
+impl BenchRow {
+    /// Return the number of positions visited per second.
+    fn nodes_per_sec(&self) -> f64 {
+        self.nodes as f64 / self.time.as_secs_f64().max(1e-9)
+    }
+}
+
+impl Command {
+    /// Return `true` if the subcommand talks to the user or another program
+    /// as it runs, so it has no results to format.
+    fn is_interactive(&self) -> bool {
+        match self {
+            Command::Play { .. } | Command::Engine | Command::Repl { .. } => true,
+            #[cfg(feature = "tui")]
+            Command::Tui { .. } | Command::EditShape { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// The name of the subcommand, as recorded in the metrics file.
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Capabilities { .. } => "capabilities",
+            Command::Solve { .. } => "solve",
+            Command::Hint { .. } => "hint",
+            Command::SolveAll { .. } => "solve-all",
+            Command::Bench { .. } => "bench",
+            Command::CompareSolvers { .. } => "compare-solvers",
+            Command::Count { .. } => "count",
+            Command::Generate { .. } => "generate",
+            Command::Daily { .. } => "daily",
+            Command::Replay { .. } => "replay",
+            Command::Play { .. } => "play",
+            #[cfg(feature = "tui")]
+            Command::Tui { .. } => "tui",
+            #[cfg(feature = "tui")]
+            Command::EditShape { .. } => "edit-shape",
+            Command::Engine => "engine",
+            Command::Repl { .. } => "repl",
+            Command::Paths => "paths",
+            Command::Report { .. } => "report",
+        }
+    }
+}
+
+/// Return `fields` as one CSV line, quoting the fields that need it.
+fn csv_row<S: AsRef<str>>(fields: &[S]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+    fields.join(",")
+}
+
+/// Return the values of `board` as CSV, one line per row.
+fn csv_values(board: &Board) -> String {
+    (0..board.height())
+        .map(|y| {
+            let row: Vec<String> = (0..board.width())
+                .map(|x| board.value_at(Coord::new(x, y)).to_string())
+                .collect();
+            csv_row(&row) + "\n"
+        })
+        .collect()
+}
 
-mod board;
+/// Return the board as JSON: its size, its rules as in the text format,
+/// its values row by row and the moves from the 1 onwards.
+fn board_json(board: &Board) -> Value {
+    let rows: Vec<Vec<u16>> = (0..board.height())
+        .map(|y| {
+            (0..board.width())
+                .map(|x| board.value_at(Coord::new(x, y)))
+                .collect()
+        })
+        .collect();
+    let moves: Vec<&str> = board
+        .to_moves()
+        .unwrap_or_default()
+        .into_iter()
+        .map(Direction::to_compact)
+        .collect();
+    let holes: Vec<String> = board
+        .iter_cells()
+        .filter(|&(x, y, _)| board.is_hole(Coord::new(x, y)))
+        .map(|(x, y, _)| Coord::new(x, y).to_string())
+        .collect();
+    json!({
+        "width": board.width(),
+        "height": board.height(),
+        "torus": board.rules().is_toroidal(),
+        "rules": board.rules().to_string(),
+        "holes": holes,
+        "values": rows,
+        "start": board.position_of(1).map(|cell| cell.to_string()),
+        "moves": moves,
+        "score": board.score(),
+    })
+}
+
+/// Read a board from `value`, as written by `board_json`. Its values must
+/// be a game, as in the text format, and if the start and moves are given
+/// they must lead to the values.
+fn board_from_json(value: &Value) -> Result<Board, Error> {
+    let bad = || format_err!("expected a board as written by --format json");
+    let rows = value["values"].as_array().ok_or_else(bad)?;
+    let width = rows.first().and_then(Value::as_array).map_or(0, Vec::len);
+    // boards written before the rules were kept only say if they are
+    // played on a torus
+    let rules = match value["rules"].as_str() {
+        Some(rules) => rules.parse()?,
+        None => classic_rules(value["torus"].as_bool().unwrap_or(false)),
+    };
+    let mut builder = BoardBuilder::new()
+        .dimensions(width, rows.len())
+        .rules(rules);
+    for (y, row) in rows.iter().enumerate() {
+        let row = row
+            .as_array()
+            .filter(|row| row.len() == width)
+            .ok_or_else(bad)?;
+        for (x, value) in row.iter().enumerate() {
+            match value.as_u64().ok_or_else(bad)? {
+                0 => {}
+                value => builder = builder.value(Coord::new(x, y), value as u16),
+            }
+        }
+    }
+    for hole in value["holes"].as_array().into_iter().flatten() {
+        let cell = hole.as_str().ok_or_else(bad)?;
+        builder = builder.hole(cell.parse()?);
+    }
+    let board = builder.build()?;
+    board.validate()?;
+    if let (Some(start), Some(moves)) = (value["start"].as_str(), value["moves"].as_array()) {
+        let start: Coord = start.parse()?;
+        let mut replayed = board.clone();
+        while replayed.is_started() {
+            replayed.unmake_move()?;
+        }
+        replayed = replayed.start_at(start)?;
+        for dir in moves {
+            let dir: Direction = dir.as_str().ok_or_else(bad)?.parse()?;
+            replayed.make_move(dir)?;
+        }
+        if replayed != board {
+            return Err(format_err!(
+                "the moves do not lead to the values of the board"
+            ));
+        }
+    }
+    Ok(board)
+}
+
+/// Write the game `board` to `file` in the serde format of `Board`, which
+/// keeps its holes and rules, for `play --resume`.
+#[cfg(feature = "serde")]
+fn save_game(board: &Board, file: &Path) -> Result<(), Error> {
+    let text = serde_json::to_string(board)?;
+    fs::write(file, text + "\n")
+        .map_err(|err| format_err!("cannot write {}: {}", file.display(), err))
+}
+
+/// Read a game written by `save_game` from `file`.
+#[cfg(feature = "serde")]
+fn load_game(file: &Path) -> Result<Board, Error> {
+    let text = fs::read_to_string(file)
+        .map_err(|err| format_err!("cannot read {}: {}", file.display(), err))?;
+    serde_json::from_str(&text)
+        .map_err(|err| format_err!("cannot resume {}: {}", file.display(), err))
+}
+
+#[cfg(not(feature = "serde"))]
+fn save_game(_board: &Board, _file: &Path) -> Result<(), Error> {
+    Err(format_err!("saving games needs the serde feature"))
+}
+
+#[cfg(not(feature = "serde"))]
+fn load_game(_file: &Path) -> Result<Board, Error> {
+    Err(format_err!("resuming games needs the serde feature"))
+}
+
+/// Read a board from `file`, or from stdin if it is `-`, in the text format
+/// of `Board` or the JSON written by `--format json`.
+fn load_board(file: &Path) -> Result<Board, Error> {
+    let text = if file == Path::new("-") {
+        io::read_to_string(io::stdin()).map_err(|err| format_err!("cannot read stdin: {}", err))?
+    } else {
+        fs::read_to_string(file)
+            .map_err(|err| format_err!("cannot read {}: {}", file.display(), err))?
+    };
+    if text.trim_start().starts_with('{') {
+        board_from_json(&serde_json::from_str(&text)?)
+    } else {
+        Ok(text.parse()?)
+    }
+}
+
+/// Read the move table in `file`, or return an empty one for boards like
+/// `board` if there is no such file. The table must be for boards of the
+/// size and rules of `board`.
+fn load_table(file: &Path, board: &Board) -> Result<MoveTable, Error> {
+    let table: MoveTable = match fs::read_to_string(file) {
+        Ok(text) => text.parse()?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => MoveTable::for_board(board),
+        Err(err) => return Err(format_err!("cannot read {}: {}", file.display(), err)),
+    };
+    table
+        .fits(board)
+        .map_err(|err| format_err!("{}: {}", file.display(), err))?;
+    Ok(table)
+}
+
+/// Play `board` interactively, reading a start cell and then moves from
+/// `input` and showing the board after each one on `output` as `options`
+/// say, until the game is won or blocked, the player quits, or the input
+/// ends. Returns the final board.
+fn play<R: BufRead, W: Write>(
+    mut board: Board,
+    options: RenderOptions,
+    input: R,
+    mut output: W,
+) -> io::Result<Board> {
+    writeln!(output, "{}", options.render(&board))?;
+    prompt(&board, &mut output)?;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "quit" {
+            break;
+        }
+        let played = if !board.is_started() {
+            match parse_cell(line) {
+                Ok(cell) => board.start_at(cell).map_err(|err| err.to_string()),
+                Err(message) => Err(message),
+            }
+        } else if line == "undo" {
+            board
+                .undo()
+                .map(|_| board.clone())
+                .map_err(|err| err.to_string())
+        } else {
+            match line.parse::<Direction>() {
+                Ok(dir) => board.next_move(dir).map_err(|err| err.to_string()),
+                Err(err) => Err(err.to_string()),
+            }
+        };
+        match played {
+            Ok(next) => {
+                board = next;
+                writeln!(output, "\n{}", options.render(&board))?;
+            }
+            Err(message) => writeln!(output, "{}", message)?,
+        }
+        if board.is_won() {
+            writeln!(output, "You won!")?;
+            return Ok(board);
+        }
+        if board.is_blocked() {
+            writeln!(
+                output,
+                "Blocked at {} of {}; enter undo to take the move back.",
+                board.score(),
+                board.cell_count()
+            )?;
+        }
+        prompt(&board, &mut output)?;
+    }
+    writeln!(output)?;
+    Ok(board)
+}
+
+/// Ask for the start cell of `board`, or for its next move.
+fn prompt<W: Write>(board: &Board, output: &mut W) -> io::Result<()> {
+    if board.is_started() {
+        let moves: Vec<&str> = board
+            .possible_moves()
+            .into_iter()
+            .map(|dir| dir.to_compact())
+            .collect();
+        write!(output, "moves: {}> ", moves.join(" "))?;
+    } else {
+        write!(output, "start cell (e.g. A1): ")?;
+    }
+    output.flush()
+}
+
+/// Show how `solution` was filled: the board is redrawn in place after
+/// each value placed, as `options` say, with a pause of `delay` in between.
+fn play_back<W: Write>(
+    solution: &Board,
+    options: RenderOptions,
+    delay: Duration,
+    mut output: W,
+) -> Result<(), Error> {
+    let moves = solution.to_moves()?;
+    let start = solution.position_of(1).ok_or(BoardError::NotStarted)?;
+    let mut empty = solution.clone();
+    while empty.is_started() {
+        empty.unmake_move()?;
+    }
+    // clear the screen and draw from the top-left corner
+    let draw = |frame: &Board, output: &mut W| -> io::Result<()> {
+        writeln!(output, "\x1b[2J\x1b[H{}", options.render(frame))?;
+        writeln!(output, "{} / {}", frame.score(), frame.cell_count())?;
+        output.flush()
+    };
+    let mut frame = empty.start_at(start)?;
+    draw(&frame, &mut output)?;
+    for dir in moves {
+        thread::sleep(delay);
+        frame.make_move(dir)?;
+        draw(&frame, &mut output)?;
+    }
+    Ok(())
+}
+
+/// Watch follows a search through its `SolveEvent`s and redraws the
+/// position it is at, at most once every `interval`.
+struct Watch<W> {
+    /// The position the search started from.
+    root: Board,
+    /// The position the search is at.
+    board: Board,
+    /// The highest score reached so far.
+    best: usize,
+    /// The number of moves tried so far.
+    tried: u64,
+    options: RenderOptions,
+    interval: Duration,
+    drawn: Option<Instant>,
+    output: W,
+}
+
+impl<W: Write> Watch<W> {
+    fn new(root: &Board, options: RenderOptions, interval: Duration, output: W) -> Self {
+        Watch {
+            root: root.clone(),
+            board: root.clone(),
+            best: root.score(),
+            tried: 0,
+            options,
+            interval,
+            drawn: None,
+            output,
+        }
+    }
+
+    /// Follow one event of the search, redrawing the board if it is time.
+    fn event(&mut self, event: &SolveEvent) -> Result<(), Error> {
+        let (width, height) = (self.board.width(), self.board.height());
+        match *event {
+            SolveEvent::Place { cell, .. } => {
+                let to = Coord::from_index(cell, width);
+                match self.board.current_position() {
+                    Some(from) => {
+                        let dir = self
+                            .board
+                            .rules()
+                            .direction_between(width, height, from, to)
+                            .ok_or(BoardError::IllegalJump {
+                                from: self.board.score() as u16,
+                                to: self.board.score() as u16 + 1,
+                            })?;
+                        self.board.make_move(dir)?;
+                    }
+                    None => self.board.begin(to)?,
+                }
+                self.tried += 1;
+                self.best = self.best.max(self.board.score());
+            }
+            SolveEvent::Backtrack { .. } if self.board.score() == 1 => {
+                self.board = self.root.clone();
+            }
+            SolveEvent::Backtrack { .. } => self.board.unmake_move()?,
+            _ => {}
+        }
+        if self
+            .drawn
+            .is_none_or(|drawn| drawn.elapsed() >= self.interval)
+        {
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    /// Redraw the board in place, with how far the search has got.
+    fn draw(&mut self) -> Result<(), Error> {
+        writeln!(
+            self.output,
+            "\x1b[2J\x1b[H{}",
+            self.options.render(&self.board)
+        )?;
+        writeln!(
+            self.output,
+            "{} / {}, best {}, {} moves tried",
+            self.board.score(),
+            self.board.cell_count(),
+            self.best,
+            self.tried
+        )?;
+        self.output.flush()?;
+        self.drawn = Some(Instant::now());
+        Ok(())
+    }
+}
+
+/// Write `board` to `file`, choosing the format by the file extension: a
+/// replay for `.b100`, PNG for `.png` and SVG otherwise.
+fn write_output(board: &Board, file: &Path) -> Result<(), Error> {
+    let extension = |name: &str| {
+        file.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(name))
+    };
+    let data = if extension("b100") {
+        Replay::from_board(board)?.to_string().into_bytes()
+    } else if extension("png") {
+        #[cfg(feature = "png")]
+        {
+            render::to_png(board, &render::PngOptions::default())
+        }
+        #[cfg(not(feature = "png"))]
+        return Err(format_err!(
+            "cannot write {}: board100 was built without the png feature",
+            file.display()
+        ));
+    } else {
+        render::to_svg(board, &SvgOptions::default()).into_bytes()
+    };
+    fs::write(file, data).map_err(|err| format_err!("cannot write {}: {}", file.display(), err))
+}
+
+/// Write an animated GIF of `board` being filled to `file`, with `delay`
+/// between the frames.
+fn write_gif(board: &Board, file: &Path, delay: Duration) -> Result<(), Error> {
+    #[cfg(feature = "gif")]
+    {
+        let data = render::to_gif(board, &render::PngOptions::default(), delay);
+        fs::write(file, data).map_err(|err| format_err!("cannot write {}: {}", file.display(), err))
+    }
+    #[cfg(not(feature = "gif"))]
+    {
+        let _ = (board, delay);
+        Err(format_err!(
+            "cannot write {}: board100 was built without the gif feature",
+            file.display()
+        ))
+    }
+}
+
+/// Return the classic moves, wrapping around the edges if `torus` is set.
+fn classic_rules(torus: bool) -> MoveRules {
+    if torus {
+        MoveRules::CLASSIC.toroidal()
+    } else {
+        MoveRules::CLASSIC
+    }
+}
+
+/// Parse a duration written as a number of milliseconds, optionally
+/// followed by `ms`, or a number of seconds or minutes followed by `s` or
+/// `m`.
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let bad = || format!("invalid duration '{}': expected e.g. 100ms, 1s or 2m", text);
+    let secs = |number: &str, scale: f64| {
+        number
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite() && *number >= 0.0)
+            .map(|number| Duration::from_secs_f64(number * scale))
+            .ok_or_else(bad)
+    };
+    if let Some(ms) = text.strip_suffix("ms") {
+        ms.trim()
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| bad())
+    } else if let Some(number) = text.strip_suffix('s') {
+        secs(number, 1.0)
+    } else if let Some(number) = text.strip_suffix('m') {
+        secs(number, 60.0)
+    } else {
+        text.parse().map(Duration::from_millis).map_err(|_| bad())
+    }
+}
+
+/// Parse a number of positions, optionally followed by `k`, `M` or `G` for
+/// thousands, millions or billions.
+fn parse_count(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let (number, scale) = match text.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&text[..i], 1_000),
+        Some((i, 'M')) => (&text[..i], 1_000_000),
+        Some((i, 'G')) => (&text[..i], 1_000_000_000),
+        _ => (text, 1),
+    };
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(scale))
+        .ok_or_else(|| format!("invalid count '{}': expected e.g. 5000 or 10M", text))
+}
+
+/// Return the default number of search threads: one per logical core.
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Parse a number of threads, which must be at least 1.
+fn parse_threads(text: &str) -> Result<usize, String> {
+    match text.trim().parse() {
+        Ok(threads) if threads > 0 => Ok(threads),
+        _ => Err(format!(
+            "invalid thread count '{}': expected a number of at least 1",
+            text
+        )),
+    }
+}
+
+/// Parse a date written as YYYY-MM-DD.
+fn parse_date(text: &str) -> Result<Date, String> {
+    text.parse().map_err(|err: DailyError| err.to_string())
+}
+
+/// Parse a cell location written as `X,Y` or as a name such as `D4`.
+fn parse_cell(text: &str) -> Result<Coord, String> {
+    text.parse().map_err(|err: CoordError| err.to_string())
+}
+
+/// Parse a set of start cells: `all` or a cell location.
+fn parse_starts(text: &str) -> Result<Starts, String> {
+    if text.trim().eq_ignore_ascii_case("all") {
+        Ok(Starts::All)
+    } else {
+        parse_cell(text).map(Starts::Cell)
+    }
+}
+
+/// Return the trace line of `event` on a board `width` cells wide, or `None`
+/// if it is not shown at `verbosity`.
+fn trace_line(event: &SolveEvent, width: usize, verbosity: u8) -> Option<String> {
+    let cell = |cell: usize| Coord::from_index(cell, width);
+    let line = match *event {
+        SolveEvent::Place { depth, cell: at } if verbosity >= 2 => {
+            format!("place depth={} cell={}", depth, cell(at))
+        }
+        SolveEvent::Backtrack { depth, cell: at } if verbosity >= 2 => {
+            format!("backtrack depth={} cell={}", depth, cell(at))
+        }
+        SolveEvent::Prune { depth, rule } if verbosity >= 1 => {
+            format!("prune depth={} rule={}", depth, rule)
+        }
+        SolveEvent::LimitReached { depth } if verbosity >= 1 => {
+            format!("limit depth={}", depth)
+        }
+        SolveEvent::Solved { depth } if verbosity >= 1 => format!("solved depth={}", depth),
+        _ => return None,
+    };
+    Some(line)
+}
+
+/// Solve `board`, which must be started, `repeat` times with `strategy` and
+/// return how it went. Search gives up after `nodes` positions.
+fn bench_start(board: &Board, strategy: Strategy, repeat: u32, nodes: u64) -> BenchRow {
+    let start = board.position_of(1).unwrap();
+    let repeat = repeat.max(1);
+    let began = Instant::now();
+    let mut row = None;
+    for _ in 0..repeat {
+        row = Some(match strategy {
+            Strategy::Search | Strategy::Naive => {
+                let config = SolverConfig {
+                    node_limit: Some(nodes),
+                    ..strategy.solver_config()
+                };
+                let result = Solver::new(config).solve(board);
+                let outcome = match result.outcome {
+                    Outcome::Solved => "solved",
+                    Outcome::Unsolvable => "unsolvable",
+                    Outcome::LimitReached => "limit",
+                };
+                (outcome, result.stats.nodes, result.stats.backtracks)
+            }
+            Strategy::Warnsdorff => {
+                let game = agent::play_out(&mut WarnsdorffAgent, board);
+                let outcome = if game.is_won() { "solved" } else { "stuck" };
+                (outcome, game.score() as u64, 0)
+            }
+            Strategy::Restart | Strategy::Mcts => {
+                let restarts = if strategy == Strategy::Restart {
+                    agent::restart_play(board, nodes, 0)
+                } else {
+                    agent::mcts_play(board, nodes, 0)
+                };
+                let outcome = if restarts.best.is_won() {
+                    "solved"
+                } else {
+                    "stuck"
+                };
+                (outcome, restarts.moves, restarts.games as u64 - 1)
+            }
+        });
+    }
+    let (outcome, nodes, backtracks) = row.unwrap();
+    BenchRow {
+        start,
+        outcome,
+        nodes,
+        backtracks,
+        time: began.elapsed() / repeat,
+    }
+}
+
+/// Solve `board` from each of `cells` once, as `bench_start` does, on
+/// `threads` threads. The rows are returned in the order of `cells`.
+fn sweep(
+    board: &Board,
+    cells: &[Coord],
+    strategy: Strategy,
+    nodes: u64,
+    threads: usize,
+) -> Result<Vec<BenchRow>, BoardError> {
+    let starts = cells
+        .iter()
+        .map(|cell| board.clone().start_at(*cell))
+        .collect::<Result<Vec<Board>, BoardError>>()?;
+    let next = AtomicUsize::new(0);
+    let rows = Mutex::new(vec![None; starts.len()]);
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, starts.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(start) = starts.get(i) else {
+                    break;
+                };
+                let row = bench_start(start, strategy, 1, nodes);
+                rows.lock().unwrap()[i] = Some(row);
+            });
+        }
+    });
+    Ok(rows.into_inner().unwrap().into_iter().flatten().collect())
+}
+
+/// Parse a board style for clap.
+fn parse_style(text: &str) -> Result<Style, String> {
+    text.parse().map_err(|err: StyleError| err.to_string())
+}
+
+/// Run `command`, returning a short description of the outcome.
+fn run(cli: &Cli, metrics_file: Option<&PathBuf>) -> Result<String, Error> {
+    let render = RenderOptions {
+        style: cli.style,
+        color: cli.color.enabled(),
+    };
+    let json = cli.format == OutputFormat::Json;
+    let csv = cli.format == OutputFormat::Csv;
+    if cli.format != OutputFormat::Text && cli.command.is_interactive() {
+        return Err(format_err!(
+            "{} is interactive and has no {} output",
+            cli.command.name(),
+            cli.format.to_possible_value().unwrap().get_name()
+        ));
+    }
+    match &cli.command {
+        Command::Capabilities {
+            size,
+            start,
+            torus,
+            budget,
+        } => {
+            let rules = classic_rules(*torus);
+            let mut board = Board::try_new(*size)?.with_rules(rules);
+            if let Some(start) = start {
+                board = board.start_at(*start)?;
+            }
+            let report = analysis::capabilities(&board, *budget);
+            if json {
+                let capabilities: Vec<Value> = report
+                    .capabilities
+                    .iter()
+                    .map(|(capability, availability)| {
+                        let (availability, reason) = match availability {
+                            Availability::Available => ("available", None),
+                            Availability::BestEffort(why) => ("best effort", Some(why)),
+                            Availability::Unavailable(why) => ("unavailable", Some(why)),
+                        };
+                        json!({
+                            "capability": capability.to_string(),
+                            "availability": availability,
+                            "reason": reason,
+                        })
+                    })
+                    .collect();
+                println!(
+                    "{}",
+                    json!({
+                        "estimate": {
+                            "nodes": report.estimate.nodes,
+                            "solutions": report.estimate.solutions,
+                            "std_error": report.estimate.std_error,
+                            "samples": report.estimate.samples,
+                        },
+                        "budget": report.budget,
+                        "capabilities": capabilities,
+                    })
+                );
+                return Ok(format!("{} capabilities", report.capabilities.len()));
+            }
+            if csv {
+                println!("capability,availability,reason");
+                for (capability, availability) in &report.capabilities {
+                    let (availability, reason) = match availability {
+                        Availability::Available => ("available", ""),
+                        Availability::BestEffort(why) => ("best effort", why.as_str()),
+                        Availability::Unavailable(why) => ("unavailable", why.as_str()),
+                    };
+                    println!(
+                        "{}",
+                        csv_row(&[&capability.to_string(), availability, reason])
+                    );
+                }
+                return Ok(format!("{} capabilities", report.capabilities.len()));
+            }
+            println!(
+                "estimated search tree: {:.3e} nodes, {:.3e} solutions",
+                report.estimate.nodes, report.estimate.solutions
+            );
+            for (capability, availability) in &report.capabilities {
+                match availability {
+                    Availability::Available => println!("{:<18} available", capability),
+                    Availability::BestEffort(why) => {
+                        println!("{:<18} best effort: {}", capability, why)
+                    }
+                    Availability::Unavailable(why) => {
+                        println!("{:<18} unavailable: {}", capability, why)
+                    }
+                }
+            }
+            Ok(format!("{} capabilities", report.capabilities.len()))
+        }
+        Command::Solve {
+            board,
+            size,
+            start,
+            torus,
+            strategy,
+            nodes,
+            time_limit,
+            output,
+            gif,
+            animate,
+            watch,
+            notation,
+            delay,
+            verbose,
+            threads,
+            learn,
+        } => {
+            if *animate && cli.format != OutputFormat::Text {
+                return Err(format_err!("--animate needs --format text"));
+            }
+            if *watch && cli.format != OutputFormat::Text {
+                return Err(format_err!("--watch needs --format text"));
+            }
+            let rules = classic_rules(*torus);
+            let board = match board {
+                Some(file) => load_board(file)?,
+                None => {
+                    let mut board = Board::try_new(*size)?.with_rules(rules);
+                    if let Some(start) = start {
+                        board = board.start_at(*start)?;
+                    }
+                    board
+                }
+            };
+            let mut table = match learn {
+                Some(file) => Some(load_table(file, &board)?),
+                None => None,
+            };
+            let mut stats = None;
+            let mut failure = None;
+            let solution = match strategy {
+                Strategy::Search | Strategy::Naive => {
+                    let config = SolverConfig {
+                        node_limit: *nodes,
+                        time_limit: *time_limit,
+                        ordering: table.clone().map(Arc::new),
+                        ..strategy.solver_config()
+                    };
+                    let solver = Solver::new(config);
+                    let result = if *watch {
+                        let mut watch = Watch::new(&board, render, *delay, io::stdout());
+                        let result = solver.solve_traced(&board, &mut |event| {
+                            // a closed stdout is no reason to stop solving
+                            let _ = watch.event(event);
+                        });
+                        watch.draw()?;
+                        result
+                    } else if *verbose > 0 {
+                        let width = board.width();
+                        let mut stderr = io::stderr().lock();
+                        solver.solve_traced(&board, &mut |event| {
+                            if let Some(line) = trace_line(event, width, *verbose) {
+                                // a closed stderr is no reason to stop solving
+                                let _ = writeln!(stderr, "{}", line);
+                            }
+                        })
+                    } else {
+                        solver.solve_parallel(&board, *threads)
+                    };
+                    stats = Some(result.stats.clone());
+                    match result.outcome {
+                        Outcome::Solved => result.best,
+                        Outcome::Unsolvable => return Err(SearchFailure::Unsolvable.into()),
+                        Outcome::LimitReached => {
+                            // show how far the search got before giving up
+                            let budget = match (nodes, time_limit) {
+                                (Some(limit), _) if result.stats.nodes >= *limit => {
+                                    format!("{} nodes", limit)
+                                }
+                                (_, Some(limit)) => format!("{:?}", limit),
+                                _ => "the budget".to_string(),
+                            };
+                            failure = Some(SearchFailure::LimitReached {
+                                budget,
+                                nodes: result.stats.nodes,
+                                best: result.best.score(),
+                                cells: result.best.cell_count(),
+                            });
+                            result.best
+                        }
+                    }
+                }
+                Strategy::Warnsdorff => {
+                    let starts = if board.is_started() {
+                        vec![board.clone()]
+                    } else {
+                        board
+                            .empty_cells()
+                            .map(|cell| board.clone().start_at(cell))
+                            .collect::<Result<_, _>>()?
+                    };
+                    let games: Vec<Board> = starts
+                        .iter()
+                        .map(|start| agent::play_out(&mut WarnsdorffAgent, start))
+                        .collect();
+                    match games.iter().find(|game| game.is_won()) {
+                        Some(game) => game.clone(),
+                        None => {
+                            let best = games.iter().map(Board::score).max().unwrap_or(0);
+                            return Err(SearchFailure::Stuck {
+                                strategy: strategy.name(),
+                                best,
+                            }
+                            .into());
+                        }
+                    }
+                }
+                Strategy::Restart | Strategy::Mcts => {
+                    let limit = nodes.unwrap_or(RESTART_MOVE_LIMIT);
+                    let restarts = if *strategy == Strategy::Restart {
+                        agent::restart_play(&board, limit, 0)
+                    } else {
+                        agent::mcts_play(&board, limit, 0)
+                    };
+                    if !restarts.best.is_won() {
+                        return Err(SearchFailure::Stuck {
+                            strategy: strategy.name(),
+                            best: restarts.best.score(),
+                        }
+                        .into());
+                    }
+                    restarts.best
+                }
+            };
+            if failure.is_none() {
+                if let Some(file) = output {
+                    write_output(&solution, file)?;
+                }
+                if let Some(file) = gif {
+                    write_gif(&solution, file, *delay)?;
+                }
+                if let (Some(file), Some(table)) = (learn, table.as_mut()) {
+                    table.train(&solution)?;
+                    fs::write(file, table.to_string())
+                        .map_err(|err| format_err!("cannot write {}: {}", file.display(), err))?;
+                }
+            }
+            if json {
+                let mut output = board_json(&solution);
+                output["solved"] = json!(failure.is_none());
+                if *notation {
+                    output["notation"] = json!(solution.to_notation()?);
+                }
+                output["strategy"] = json!(strategy.to_possible_value().unwrap().get_name());
+                output["stats"] = match stats {
+                    Some(stats) => json!({
+                        "nodes": stats.nodes,
+                        "backtracks": stats.backtracks,
+                        "max_depth": stats.max_depth,
+                    }),
+                    None => Value::Null,
+                };
+                println!("{}", output);
+            } else if csv {
+                print!("{}", csv_values(&solution));
+            } else {
+                if *animate && failure.is_none() {
+                    play_back(&solution, render, *delay, io::stdout())?;
+                } else {
+                    println!("{}", render.render(&solution));
+                }
+                if *notation {
+                    println!("{}", solution.to_notation()?);
+                }
+            }
+            match failure {
+                Some(failure) => Err(failure.into()),
+                None => Ok("solved".to_string()),
+            }
+        }
+        Command::Hint { board, nodes } => {
+            let board = load_board(board)?;
+            let solver = Solver::new(SolverConfig {
+                node_limit: *nodes,
+                ..SolverConfig::default()
+            });
+            let hint = solver.best_move(&board).ok_or_else(|| {
+                if board.is_started() {
+                    format_err!("there is no move left to play")
+                } else {
+                    format_err!("the game has not started; choose a start cell first")
+                }
+            })?;
+            let (outcome, why) = match hint.outcome {
+                Outcome::Solved => ("solved", "the board can still be completed"),
+                Outcome::LimitReached => {
+                    ("limit", "no completion found yet; this move gets furthest")
+                }
+                Outcome::Unsolvable => ("unsolvable", "every move leads to a dead end"),
+            };
+            if json {
+                println!(
+                    "{}",
+                    json!({ "move": hint.dir.to_compact(), "outcome": outcome })
+                );
+            } else if csv {
+                println!("move,outcome");
+                println!("{}", csv_row(&[hint.dir.to_compact(), outcome]));
+            } else {
+                println!("{} ({}): {}", hint.dir.to_compact(), hint.dir, why);
+            }
+            Ok(outcome.to_string())
+        }
+        Command::SolveAll {
+            size,
+            torus,
+            strategy,
+            nodes,
+            threads,
+        } => {
+            let rules = classic_rules(*torus);
+            let board = Board::try_new(*size)?.with_rules(rules);
+            let cells: Vec<Coord> = board.empty_cells().collect();
+            let began = Instant::now();
+            let rows = sweep(&board, &cells, *strategy, *nodes, *threads)?;
+            let elapsed = began.elapsed();
+            let solved = rows.iter().filter(|row| row.outcome == "solved").count();
+            if json {
+                let starts: Vec<Value> = rows
+                    .iter()
+                    .map(|row| {
+                        json!({
+                            "start": row.start.to_string(),
+                            "outcome": row.outcome,
+                            "nodes": row.nodes,
+                            "ms": row.time.as_secs_f64() * 1000.0,
+                        })
+                    })
+                    .collect();
+                println!(
+                    "{}",
+                    json!({
+                        "size": size,
+                        "strategy": strategy.to_possible_value().unwrap().get_name(),
+                        "solved": solved,
+                        "starts": starts,
+                        "ms": elapsed.as_secs_f64() * 1000.0,
+                    })
+                );
+            } else if csv {
+                println!("start,outcome,nodes,ms");
+                for row in &rows {
+                    println!(
+                        "{}",
+                        csv_row(&[
+                            row.start.to_string(),
+                            row.outcome.to_string(),
+                            row.nodes.to_string(),
+                            format!("{:.3}", row.time.as_secs_f64() * 1000.0),
+                        ])
+                    );
+                }
+            } else {
+                // one grid of outcomes, then one of times in milliseconds
+                for chunk in rows.chunks(board.width()) {
+                    let marks: Vec<&str> = chunk
+                        .iter()
+                        .map(|row| match row.outcome {
+                            "solved" => "✓",
+                            "limit" => "?",
+                            _ => "✗",
+                        })
+                        .collect();
+                    println!("{}", marks.join(" "));
+                }
+                println!();
+                for chunk in rows.chunks(board.width()) {
+                    let times: Vec<String> = chunk
+                        .iter()
+                        .map(|row| format!("{:>8.1}", row.time.as_secs_f64() * 1000.0))
+                        .collect();
+                    println!("{}", times.join(""));
+                }
+                println!(
+                    "\n{} of {} starts solved in {:.1?}",
+                    solved,
+                    rows.len(),
+                    elapsed
+                );
+            }
+            Ok(format!("{}/{} solved", solved, rows.len()))
+        }
+        Command::Bench {
+            size,
+            torus,
+            starts,
+            strategy,
+            repeat,
+            nodes,
+        } => {
+            let rules = classic_rules(*torus);
+            let board = Board::try_new(*size)?.with_rules(rules);
+            let mut cells = Vec::new();
+            for set in starts {
+                match set {
+                    Starts::All => cells.extend(board.empty_cells()),
+                    Starts::Cell(cell) => cells.push(*cell),
+                }
+            }
+            let rows = cells
+                .iter()
+                .map(|cell| {
+                    let started = board.clone().start_at(*cell)?;
+                    Ok(bench_start(&started, *strategy, *repeat, *nodes))
+                })
+                .collect::<Result<Vec<BenchRow>, BoardError>>()?;
+            let solved = rows.iter().filter(|row| row.outcome == "solved").count();
+            let total = BenchRow {
+                start: Coord::default(),
+                outcome: "",
+                nodes: rows.iter().map(|row| row.nodes).sum(),
+                backtracks: rows.iter().map(|row| row.backtracks).sum(),
+                time: rows.iter().map(|row| row.time).sum(),
+            };
+            if json {
+                let runs: Vec<Value> = rows
+                    .iter()
+                    .map(|row| {
+                        json!({
+                            "start": row.start.to_string(),
+                            "outcome": row.outcome,
+                            "nodes": row.nodes,
+                            "backtracks": row.backtracks,
+                            "ms_per_solve": row.time.as_secs_f64() * 1000.0,
+                            "nodes_per_sec": row.nodes_per_sec(),
+                        })
+                    })
+                    .collect();
+                println!(
+                    "{}",
+                    json!({
+                        "size": size,
+                        "strategy": strategy.to_possible_value().unwrap().get_name(),
+                        "repeat": repeat,
+                        "runs": runs,
+                        "total": {
+                            "solved": solved,
+                            "nodes": total.nodes,
+                            "backtracks": total.backtracks,
+                            "ms_per_solve": total.time.as_secs_f64() * 1000.0
+                                / rows.len().max(1) as f64,
+                            "nodes_per_sec": total.nodes_per_sec(),
+                        },
+                    })
+                );
+            } else if csv {
+                println!("start,outcome,nodes,backtracks,ms_per_solve,nodes_per_sec");
+                for row in &rows {
+                    println!(
+                        "{}",
+                        csv_row(&[
+                            row.start.to_string(),
+                            row.outcome.to_string(),
+                            row.nodes.to_string(),
+                            row.backtracks.to_string(),
+                            format!("{:.3}", row.time.as_secs_f64() * 1000.0),
+                            format!("{:.0}", row.nodes_per_sec()),
+                        ])
+                    );
+                }
+            } else {
+                println!(
+                    "{:<6} {:<10} {:>12} {:>12} {:>12} {:>12}",
+                    "start", "outcome", "nodes", "backtracks", "ms/solve", "nodes/s"
+                );
+                for row in &rows {
+                    println!(
+                        "{:<6} {:<10} {:>12} {:>12} {:>12.3} {:>12.3e}",
+                        row.start,
+                        row.outcome,
+                        row.nodes,
+                        row.backtracks,
+                        row.time.as_secs_f64() * 1000.0,
+                        row.nodes_per_sec()
+                    );
+                }
+                println!(
+                    "{:<6} {:<10} {:>12} {:>12} {:>12.3} {:>12.3e}",
+                    "total",
+                    format!("{}/{}", solved, rows.len()),
+                    total.nodes,
+                    total.backtracks,
+                    total.time.as_secs_f64() * 1000.0 / rows.len().max(1) as f64,
+                    total.nodes_per_sec()
+                );
+            }
+            Ok(format!("{}/{} solved", solved, rows.len()))
+        }
+        Command::CompareSolvers {
+            size,
+            torus,
+            strategies,
+            boards,
+            seed,
+            nodes,
+        } => {
+            let rules = classic_rules(*torus);
+            let board = Board::try_new(*size)?.with_rules(rules);
+            let mut cells: Vec<Coord> = board.empty_cells().collect();
+            let mut rng = Rng::new(*seed);
+            for i in (1..cells.len()).rev() {
+                cells.swap(i, rng.below(i + 1));
+            }
+            cells.truncate(*boards);
+            // one thread, so that the times compare fairly
+            let results = strategies
+                .iter()
+                .map(|&strategy| Ok((strategy, sweep(&board, &cells, strategy, *nodes, 1)?)))
+                .collect::<Result<Vec<_>, BoardError>>()?;
+            let summary = |rows: &[BenchRow]| {
+                let solved = rows.iter().filter(|row| row.outcome == "solved").count();
+                let count = rows.len().max(1) as f64;
+                let ms: f64 = rows.iter().map(|row| row.time.as_secs_f64()).sum();
+                let nodes: u64 = rows.iter().map(|row| row.nodes).sum();
+                (
+                    solved,
+                    solved as f64 / count,
+                    ms * 1000.0 / count,
+                    nodes as f64 / count,
+                )
+            };
+            if json {
+                let rows: Vec<Value> = results
+                    .iter()
+                    .map(|(strategy, rows)| {
+                        let (solved, rate, ms, nodes) = summary(rows);
+                        json!({
+                            "strategy": strategy.name(),
+                            "solved": solved,
+                            "success_rate": rate,
+                            "mean_ms": ms,
+                            "mean_nodes": nodes,
+                        })
+                    })
+                    .collect();
+                let starts: Vec<String> = cells.iter().map(Coord::to_string).collect();
+                println!(
+                    "{}",
+                    json!({ "size": size, "starts": starts, "strategies": rows })
+                );
+            } else if csv {
+                println!("strategy,solved,boards,success_rate,mean_ms,mean_nodes");
+                for (strategy, rows) in &results {
+                    let (solved, rate, ms, nodes) = summary(rows);
+                    println!(
+                        "{}",
+                        csv_row(&[
+                            strategy.name().to_string(),
+                            solved.to_string(),
+                            rows.len().to_string(),
+                            format!("{:.3}", rate),
+                            format!("{:.3}", ms),
+                            format!("{:.0}", nodes),
+                        ])
+                    );
+                }
+            } else {
+                println!(
+                    "{:<10} {:>8} {:>8} {:>12} {:>12}",
+                    "strategy", "solved", "rate", "mean ms", "mean nodes"
+                );
+                for (strategy, rows) in &results {
+                    let (solved, rate, ms, nodes) = summary(rows);
+                    println!(
+                        "{:<10} {:>8} {:>7.0}% {:>12.3} {:>12.0}",
+                        strategy.name(),
+                        format!("{}/{}", solved, rows.len()),
+                        rate * 100.0,
+                        ms,
+                        nodes
+                    );
+                }
+            }
+            Ok(format!("{} strategies", results.len()))
+        }
+        Command::Count {
+            size,
+            start,
+            torus,
+            modulo_symmetry,
+            nodes,
+            threads,
+            tt_size,
+        } => {
+            let rules = classic_rules(*torus);
+            let mut board = Board::try_new(*size)?.with_rules(rules);
+            if let Some(cell) = start {
+                board = board.start_at(*cell)?;
+            }
+            let config = CountConfig {
+                node_limit: *nodes,
+                modulo_symmetry: *modulo_symmetry,
+                threads: *threads,
+                tt_size: *tt_size as usize,
+            };
+            // progress goes to stderr, overwriting itself, when someone is
+            // watching
+            let watched = io::stderr().is_terminal();
+            let began = Instant::now();
+            let count = analysis::count_solutions(&board, &config, &mut |count| {
+                if watched {
+                    eprint!(
+                        "\r{} positions, {} solutions so far",
+                        count.nodes, count.solutions
+                    );
+                }
+            });
+            let elapsed = began.elapsed();
+            if watched && count.nodes >= analysis::COUNT_PROGRESS_INTERVAL {
+                eprintln!();
+            }
+            if json {
+                println!(
+                    "{}",
+                    json!({
+                        "size": size,
+                        "start": start.map(|cell| cell.to_string()),
+                        "torus": torus,
+                        "modulo_symmetry": modulo_symmetry,
+                        "solutions": count.solutions,
+                        "nodes": count.nodes,
+                        "complete": count.complete,
+                        "ms": elapsed.as_secs_f64() * 1000.0,
+                    })
+                );
+            } else if csv {
+                println!("solutions,nodes,complete,ms");
+                println!(
+                    "{}",
+                    csv_row(&[
+                        count.solutions.to_string(),
+                        count.nodes.to_string(),
+                        count.complete.to_string(),
+                        format!("{:.3}", elapsed.as_secs_f64() * 1000.0),
+                    ])
+                );
+            } else if count.complete {
+                println!(
+                    "{} solutions ({} positions in {:.1?})",
+                    count.solutions, count.nodes, elapsed
+                );
+            } else {
+                println!(
+                    "At least {} solutions; stopped after {} positions",
+                    count.solutions, count.nodes
+                );
+            }
+            Ok(format!("{} solutions", count.solutions))
+        }
+        Command::Generate {
+            size,
+            clues,
+            unique,
+            seed,
+            nodes,
+        } => {
+            let seed = match seed {
+                Some(seed) => *seed,
+                None => {
+                    let seed = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |time| time.as_nanos() as u64);
+                    eprintln!("Seed {}", seed);
+                    seed
+                }
+            };
+            let config = GenerateConfig {
+                size: *size,
+                clues: *clues,
+                unique: *unique,
+                seed,
+                node_limit: *nodes,
+                ..GenerateConfig::default()
+            };
+            let (puzzle, _) = generator::generate(&config)?;
+            let rows: Vec<Vec<u16>> = (0..puzzle.height())
+                .map(|y| (0..puzzle.width()).map(|x| puzzle.clue_at(x, y)).collect())
+                .collect();
+            if json {
+                println!(
+                    "{}",
+                    json!({
+                        "width": puzzle.width(),
+                        "height": puzzle.height(),
+                        "clues": rows,
+                        "unique": unique,
+                        "seed": seed,
+                        "difficulty": puzzle.difficulty,
+                        "solution_hash": format!("{:016x}", puzzle.solution_hash),
+                    })
+                );
+            } else if csv {
+                for row in &rows {
+                    let row: Vec<String> = row.iter().map(u16::to_string).collect();
+                    println!("{}", csv_row(&row));
+                }
+            } else {
+                println!("{}", puzzle);
+            }
+            Ok(format!("{} clues", clues))
+        }
+        Command::Daily { date, size, check } => {
+            Board::try_new(*size)?;
+            let daily = Daily::new(date.unwrap_or_else(Date::today), *size);
+            if let Some(file) = check {
+                daily.check(&load_board(file)?)?;
+                if json {
+                    println!(
+                        "{}",
+                        json!({ "date": daily.date.to_string(), "solved": true })
+                    );
+                } else if csv {
+                    println!("date,solved");
+                    println!("{}", csv_row(&[daily.date.to_string(), "true".to_string()]));
+                } else {
+                    println!("Solved the puzzle of {}", daily.date);
+                }
+                return Ok("solved".to_string());
+            }
+            if json {
+                let mut output = board_json(&daily.board);
+                output["date"] = json!(daily.date.to_string());
+                output["seed"] = json!(daily.seed);
+                println!("{}", output);
+            } else if csv {
+                print!("{}", csv_values(&daily.board));
+            } else {
+                // the board alone goes to stdout, so it can be saved and
+                // solved
+                eprintln!("Puzzle of {}", daily.date);
+                println!("{}", render.render(&daily.board));
+            }
+            Ok("shown".to_string())
+        }
+        Command::Replay {
+            file,
+            animate,
+            delay,
+        } => {
+            if *animate && cli.format != OutputFormat::Text {
+                return Err(format_err!("--animate needs --format text"));
+            }
+            let text = fs::read_to_string(file)
+                .map_err(|err| format_err!("cannot read {}: {}", file.display(), err))?;
+            let board = text.parse::<Replay>()?.board()?;
+            if json {
+                println!("{}", board_json(&board));
+            } else if csv {
+                print!("{}", csv_values(&board));
+            } else if *animate {
+                play_back(&board, render, *delay, io::stdout())?;
+            } else {
+                println!("{}", render.render(&board));
+                println!("{} / {}", board.score(), board.cell_count());
+            }
+            Ok(format!("score {}", board.score()))
+        }
+        Command::Play {
+            size,
+            torus,
+            resume,
+            random,
+            seed,
+            opening,
+            save,
+        } => {
+            let rules = classic_rules(*torus);
+            let mut board = match resume {
+                Some(file) => load_game(file)?,
+                None => Board::try_new(*size)?.with_rules(rules),
+            };
+            if *random {
+                let seed = seed.unwrap_or_else(|| {
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |time| time.as_nanos() as u64)
+                });
+                println!("Seed {}", seed);
+                board = agent::random_opening(&board, *opening, seed);
+            }
+            let stdin = io::stdin();
+            let board = play(board, render, stdin.lock(), io::stdout())?;
+            if let Some(file) = save {
+                save_game(&board, file)?;
+            }
+            Ok(format!("score {}", board.score()))
+        }
+        #[cfg(feature = "tui")]
+        Command::Tui { size, torus } => {
+            let rules = classic_rules(*torus);
+            let board = tui::run(Board::try_new(*size)?.with_rules(rules))?;
+            Ok(format!("score {}", board.score()))
+        }
+        #[cfg(feature = "tui")]
+        Command::EditShape {
+            catalog,
+            name,
+            width,
+            height,
+        } => {
+            let shape = match fs::read_to_string(catalog) {
+                Ok(text) => text
+                    .parse::<Catalog>()
+                    .map_err(|err| format_err!("cannot read {}: {}", catalog.display(), err))?
+                    .get(name)
+                    .cloned(),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                Err(err) => return Err(format_err!("cannot read {}: {}", catalog.display(), err)),
+            };
+            let shape = shape.unwrap_or_else(|| Shape::full(name, *width, *height));
+            // a shape too small or large for a board could not be solved
+            shape.to_board()?;
+            let (shape, saved) = tui::edit_shape(shape, catalog)?;
+            if saved {
+                Ok(format!("saved '{}' to {}", shape.name, catalog.display()))
+            } else {
+                Ok(format!("'{}' not saved", shape.name))
+            }
+        }
+        Command::Engine => {
+            let stdin = io::stdin();
+            engine::run(&mut Engine::new(), stdin.lock(), io::stdout())?;
+            Ok("finished".to_string())
+        }
+        Command::Repl { size, torus } => {
+            let rules = classic_rules(*torus);
+            let board = Board::try_new(*size)?.with_rules(rules);
+            let stdin = io::stdin();
+            repl::run(&mut Session::new(board, render), stdin.lock(), io::stdout())?;
+            Ok("finished".to_string())
+        }
+        Command::Paths => {
+            let storage = Storage::new(cli.data_dir.as_deref())?;
+            if json {
+                let paths: serde_json::Map<String, Value> = Area::ALL
+                    .iter()
+                    .map(|area| (area.to_string(), json!(storage.path(*area))))
+                    .collect();
+                println!("{}", Value::Object(paths));
+            } else if csv {
+                println!("area,path");
+                for area in Area::ALL.iter() {
+                    let path = storage.path(*area);
+                    println!(
+                        "{}",
+                        csv_row(&[area.to_string(), path.display().to_string()])
+                    );
+                }
+            } else {
+                for area in Area::ALL.iter() {
+                    println!("{:<12} {}", area, storage.path(*area).display());
+                }
+            }
+            Ok(format!("{} paths", Area::ALL.len()))
+        }
+        Command::Report { file } => {
+            let file = file
+                .as_ref()
+                .or(metrics_file)
+                .ok_or_else(|| format_err!("no metrics file given"))?;
+            let runs = metrics::read(file)?;
+            let report = metrics::summarize(&runs);
+            if json {
+                let commands: serde_json::Map<String, Value> = report
+                    .commands
+                    .iter()
+                    .map(|(command, s)| {
+                        let summary = json!({
+                            "runs": s.runs,
+                            "succeeded": s.succeeded,
+                            "total_ms": s.total.as_millis() as u64,
+                            "mean_ms": s.mean().as_millis() as u64,
+                            "longest_ms": s.longest.as_millis() as u64,
+                            "outcomes": s.outcomes,
+                        });
+                        (command.clone(), summary)
+                    })
+                    .collect();
+                println!("{}", json!({ "commands": commands }));
+            } else if csv {
+                println!("command,runs,succeeded,total_ms,mean_ms,longest_ms,outcomes");
+                for (command, s) in &report.commands {
+                    let outcomes: Vec<String> = s
+                        .outcomes
+                        .iter()
+                        .map(|(outcome, n)| format!("{}={}", outcome, n))
+                        .collect();
+                    println!(
+                        "{}",
+                        csv_row(&[
+                            command.clone(),
+                            s.runs.to_string(),
+                            s.succeeded.to_string(),
+                            s.total.as_millis().to_string(),
+                            s.mean().as_millis().to_string(),
+                            s.longest.as_millis().to_string(),
+                            outcomes.join(" "),
+                        ])
+                    );
+                }
+            } else {
+                print!("{}", report);
+            }
+            Ok(format!("{} runs", runs.len()))
+        }
+    }
+}
 
 fn main() {
-    println!("Hello, world!");
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            // --help and --version are not errors
+            let code = if err.use_stderr() { EXIT_USAGE } else { 0 };
+            let _ = err.print();
+            process::exit(code);
+        }
+    };
+    let metrics_file = cli
+        .metrics
+        .clone()
+        .or_else(|| std::env::var_os(METRICS_ENV).map(PathBuf::from));
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let timer = Instant::now();
+
+    let result = run(&cli, metrics_file.as_ref());
+    let (exit_code, outcome) = match &result {
+        Ok(outcome) => (0, outcome.clone()),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            let (code, outcome) = match err.downcast_ref::<SearchFailure>() {
+                Some(failure) => failure.exit(),
+                None => (EXIT_ERROR, "error"),
+            };
+            (code, outcome.to_string())
+        }
+    };
+
+    if let Some(file) = metrics_file {
+        let invocation = Invocation {
+            command: cli.command.name().to_string(),
+            args: std::env::args().skip(1).collect(),
+            started,
+            duration: timer.elapsed(),
+            exit_code,
+            outcome,
+        };
+        if let Err(err) = metrics::append(&file, &invocation) {
+            eprintln!(
+                "warning: cannot write metrics to {}: {}",
+                file.display(),
+                err
+            );
+        }
+    }
+    process::exit(exit_code);
 }
@@ -0,0 +1,249 @@
+/*!
+Local usage metrics for batch runs.
+
+When enabled, the command line tool appends one JSON object per invocation
+to a metrics file (JSON lines), recording the command, its arguments, how
+long it ran and how it ended. Nothing is ever sent anywhere; the file exists
+so that a campaign of batch runs can be summarized afterwards with
+`summarize`.
+ */
+
+use failure::Fail;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Fail)]
+/// Errors raised while reading or writing a metrics file.
+pub enum MetricsError {
+    #[fail(display = "{}", _0)]
+    Io(#[cause] io::Error),
+    #[fail(display = "Invalid metrics record on line {}: {}", line, reason)]
+    BadRecord { line: usize, reason: String },
+}
+
+impl From<io::Error> for MetricsError {
+    fn from(err: io::Error) -> Self {
+        MetricsError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Invocation records one run of the command line tool.
+pub struct Invocation {
+    /// The subcommand that was run, e.g. `solve`.
+    pub command: String,
+    /// The full argument list, excluding the program name.
+    pub args: Vec<String>,
+    /// Start time, in seconds since the Unix epoch.
+    pub started: u64,
+    /// How long the command ran.
+    pub duration: Duration,
+    /// The process exit code.
+    pub exit_code: i32,
+    /// A short description of how the run ended, e.g. `solved`.
+    pub outcome: String,
+}
+
+impl Invocation {
+    /// Encode the invocation as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        json!({
+            "command": self.command,
+            "args": self.args,
+            "started": self.started,
+            "duration_ms": self.duration.as_millis() as u64,
+            "exit_code": self.exit_code,
+            "outcome": self.outcome,
+        })
+        .to_string()
+    }
+
+    /// Decode an invocation from a line of JSON written by `to_json`.
+    pub fn from_json(line: &str) -> Result<Invocation, String> {
+        let value: Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+        let text = |key: &str| {
+            value[key]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("missing '{}'", key))
+        };
+        let number = |key: &str| {
+            value[key]
+                .as_u64()
+                .ok_or_else(|| format!("missing '{}'", key))
+        };
+        let args = value["args"]
+            .as_array()
+            .ok_or("missing 'args'")?
+            .iter()
+            .map(|a| a.as_str().map(str::to_string).ok_or("invalid 'args'"))
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(Invocation {
+            command: text("command")?,
+            args,
+            started: number("started")?,
+            duration: Duration::from_millis(number("duration_ms")?),
+            exit_code: value["exit_code"].as_i64().ok_or("missing 'exit_code'")? as i32,
+            outcome: text("outcome")?,
+        })
+    }
+}
+
+/// Append `invocation` to the metrics file at `path`, creating it if needed.
+pub fn append(path: &Path, invocation: &Invocation) -> Result<(), MetricsError> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", invocation.to_json())?;
+    Ok(())
+}
+
+/// Read every invocation recorded in the metrics file at `path`. Blank lines
+/// are ignored.
+pub fn read(path: &Path) -> Result<Vec<Invocation>, MetricsError> {
+    let mut invocations = Vec::new();
+    for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invocation =
+            Invocation::from_json(&line).map_err(|reason| MetricsError::BadRecord {
+                line: i + 1,
+                reason,
+            })?;
+        invocations.push(invocation);
+    }
+    Ok(invocations)
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// CommandSummary aggregates the runs of one subcommand.
+pub struct CommandSummary {
+    /// The number of runs.
+    pub runs: usize,
+    /// The number of runs that exited with code 0.
+    pub succeeded: usize,
+    /// The total running time.
+    pub total: Duration,
+    /// The longest single run.
+    pub longest: Duration,
+    /// The number of runs with each outcome.
+    pub outcomes: BTreeMap<String, usize>,
+}
+
+impl CommandSummary {
+    /// The mean running time.
+    pub fn mean(&self) -> Duration {
+        if self.runs == 0 {
+            Duration::default()
+        } else {
+            self.total / self.runs as u32
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Report summarizes a campaign of runs, per subcommand.
+pub struct Report {
+    pub commands: BTreeMap<String, CommandSummary>,
+}
+
+/// Summarize a list of invocations.
+pub fn summarize(invocations: &[Invocation]) -> Report {
+    let mut report = Report::default();
+    for run in invocations {
+        let summary = report.commands.entry(run.command.clone()).or_default();
+        summary.runs += 1;
+        if run.exit_code == 0 {
+            summary.succeeded += 1;
+        }
+        summary.total += run.duration;
+        summary.longest = summary.longest.max(run.duration);
+        *summary.outcomes.entry(run.outcome.clone()).or_insert(0) += 1;
+    }
+    report
+}
+
+impl fmt::Display for Report {
+    /// Write the report as a table with one row per subcommand.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:>6} {:>6} {:>12} {:>12} {:>12}  outcomes",
+            "command", "runs", "ok", "total", "mean", "longest"
+        )?;
+        for (command, s) in &self.commands {
+            let outcomes: Vec<String> = s
+                .outcomes
+                .iter()
+                .map(|(outcome, n)| format!("{}={}", outcome, n))
+                .collect();
+            writeln!(
+                f,
+                "{:<12} {:>6} {:>6} {:>12} {:>12} {:>12}  {}",
+                command,
+                s.runs,
+                s.succeeded,
+                format!("{:.3?}", s.total),
+                format!("{:.3?}", s.mean()),
+                format!("{:.3?}", s.longest),
+                outcomes.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(command: &str, millis: u64, exit_code: i32, outcome: &str) -> Invocation {
+        Invocation {
+            command: command.to_string(),
+            args: vec![command.to_string(), "--size".to_string(), "10".to_string()],
+            started: 1_700_000_000,
+            duration: Duration::from_millis(millis),
+            exit_code,
+            outcome: outcome.to_string(),
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let invocation = run("solve", 1234, 0, "solved");
+        let line = invocation.to_json();
+        assert!(!line.contains('\n'));
+        assert_eq!(Invocation::from_json(&line), Ok(invocation));
+        assert!(Invocation::from_json("{\"command\": \"solve\"}").is_err());
+        assert!(Invocation::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn append_read_and_summarize() {
+        let path =
+            std::env::temp_dir().join(format!("square100-metrics-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        append(&path, &run("solve", 100, 0, "solved")).unwrap();
+        append(&path, &run("solve", 300, 1, "unsolvable")).unwrap();
+        append(&path, &run("count", 50, 0, "counted")).unwrap();
+        let runs = read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(runs.len(), 3);
+
+        let report = summarize(&runs);
+        let solve = &report.commands["solve"];
+        assert_eq!(solve.runs, 2);
+        assert_eq!(solve.succeeded, 1);
+        assert_eq!(solve.mean(), Duration::from_millis(200));
+        assert_eq!(solve.longest, Duration::from_millis(300));
+        assert_eq!(solve.outcomes["unsolvable"], 1);
+        let table = report.to_string();
+        assert_eq!(table.lines().count(), 3);
+        assert!(table.lines().nth(1).unwrap().starts_with("count"));
+    }
+}
@@ -0,0 +1,308 @@
+/*!
+Precomputed jump targets.
+
+Every move check asks the same question: where does a jump in this
+direction from this cell land? The answer depends only on the board
+dimensions and the move rules, so `Neighbors` works it out once for every
+cell and direction and keeps the answers in a flat table. A move check is
+then a table lookup, with no signed arithmetic or edge tests. The table
+also answers the reverse question, which cells jump to a given cell, which
+is what keeping the degree counts of a board up to date asks.
+
+For the move checks themselves there is a cheaper layout still, the one
+chess engines use: surround the board with a border of taken cells as wide
+as the longest jump. A jump from any cell then lands inside the padded
+grid, and a move in a given direction is always the same step along it, so
+checking one is an add and a bit test, with no table to read. `Border`
+describes that layout; toroidal rules have no edges and do without it.
+
+Tables are shared: `Neighbors::get` keeps one per dimensions and rules for
+the life of the process, so boards of the same size and rules, and every
+copy of them, point at the same table. The largest board, 255x255, takes
+about 1 MB.
+ */
+
+use crate::board::{Direction, MoveRules};
+use crate::coord::Coord;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// The table entry of a move that leaves the board or is not played. Cell
+/// indexes stay below it, as boards have at most 255 x 255 cells.
+const NO_CELL: u16 = u16::MAX;
+
+/// The key of a cached table: the dimensions and the rules.
+type Key = (usize, usize, MoveRules);
+
+/// The tables built so far.
+static TABLES: OnceLock<Mutex<HashMap<Key, Arc<Neighbors>>>> = OnceLock::new();
+
+/// Neighbors holds the target of a move in each direction from each cell of
+/// a board, cells being indexed row by row.
+pub struct Neighbors {
+    width: usize,
+    height: usize,
+    rules: MoveRules,
+    /// The targets of each cell, in the order of `Direction::iterator`.
+    targets: Vec<[u16; 8]>,
+    /// The cells that jump to each cell, or `None` if they are the cells
+    /// it jumps to, as they are under rules whose every move can be
+    /// reversed.
+    sources: Option<Vec<[u16; 8]>>,
+    /// The padded layout of the board, or `None` under toroidal rules.
+    border: Option<Border>,
+}
+
+impl Neighbors {
+    /// Return the table of a `width` x `height` board played under `rules`,
+    /// building it on first use.
+    pub fn get(width: usize, height: usize, rules: MoveRules) -> Arc<Neighbors> {
+        let tables = TABLES.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut tables = tables.lock().unwrap_or_else(|err| err.into_inner());
+        tables
+            .entry((width, height, rules))
+            .or_insert_with(|| Arc::new(Neighbors::new(width, height, rules)))
+            .clone()
+    }
+
+    /// Build the table of a `width` x `height` board played under `rules`.
+    pub fn new(width: usize, height: usize, rules: MoveRules) -> Neighbors {
+        let targets = (0..width * height)
+            .map(|cell| {
+                let from = Coord::from_index(cell, width);
+                let mut row = [NO_CELL; 8];
+                for (slot, dir) in row.iter_mut().zip(Direction::iterator()) {
+                    if let Some(to) = rules.target(width, height, from, *dir) {
+                        *slot = to.index(width) as u16;
+                    }
+                }
+                row
+            })
+            .collect::<Vec<_>>();
+        let reversible = rules.directions().all(|dir| {
+            let (dx, dy) = rules.delta(dir).unwrap();
+            rules.direction_of(-dx, -dy).is_some()
+        });
+        let sources = (!reversible).then(|| {
+            // each direction maps at most one cell onto a given cell
+            let mut sources = vec![[NO_CELL; 8]; targets.len()];
+            for (cell, row) in targets.iter().enumerate() {
+                for (dir, &to) in row.iter().enumerate() {
+                    if to != NO_CELL {
+                        sources[to as usize][dir] = cell as u16;
+                    }
+                }
+            }
+            sources
+        });
+        Neighbors {
+            width,
+            height,
+            rules,
+            targets,
+            sources,
+            border: Border::new(width, height, rules),
+        }
+    }
+
+    #[inline]
+    /// Return the cell a move in `dir` from `cell` lands on, or `None` if
+    /// the move leaves the board or `dir` is not played.
+    pub fn target(&self, cell: usize, dir: Direction) -> Option<usize> {
+        match self.targets[cell][dir as usize] {
+            NO_CELL => None,
+            to => Some(to as usize),
+        }
+    }
+
+    #[inline]
+    /// Return the cells one move away from `cell`, in direction order.
+    pub fn targets(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        self.targets[cell]
+            .iter()
+            .filter(|&&to| to != NO_CELL)
+            .map(|&to| to as usize)
+    }
+
+    #[inline]
+    /// Return the cells that jump to `cell`, a cell once for each direction
+    /// that lands there.
+    pub fn sources(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let row = match &self.sources {
+            Some(sources) => &sources[cell],
+            None => &self.targets[cell],
+        };
+        row.iter()
+            .filter(|&&from| from != NO_CELL)
+            .map(|&from| from as usize)
+    }
+
+    #[inline]
+    /// Return the padded layout of the board, or `None` under toroidal
+    /// rules.
+    pub fn border(&self) -> Option<&Border> {
+        self.border.as_ref()
+    }
+
+    /// Return the dimensions and rules the table was built for.
+    pub fn key(&self) -> (usize, usize, MoveRules) {
+        (self.width, self.height, self.rules)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+/// Border is the layout of a board inside a border of taken cells as wide
+/// as the longest jump, cells being indexed row by row across the border.
+pub struct Border {
+    /// The width of the border on each side.
+    pad: usize,
+    /// The width of a padded row.
+    stride: usize,
+    /// The number of padded cells.
+    len: usize,
+    /// The step along the padded grid of a move in each direction, in the
+    /// order of `Direction::iterator`; 0 for a direction not played, which
+    /// lands on the current cell, taken by the path.
+    steps: [isize; 8],
+}
+
+impl Border {
+    /// Return the padded layout of a `width` x `height` board played under
+    /// `rules`, or `None` if the rules are toroidal.
+    pub fn new(width: usize, height: usize, rules: MoveRules) -> Option<Border> {
+        if rules.is_toroidal() {
+            return None;
+        }
+        let pad = rules
+            .directions()
+            .filter_map(|dir| rules.delta(dir))
+            .map(|(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs()) as usize)
+            .max()
+            .unwrap_or(0);
+        let stride = width + 2 * pad;
+        let mut steps = [0; 8];
+        for (step, dir) in steps.iter_mut().zip(Direction::iterator()) {
+            if let Some((dx, dy)) = rules.delta(*dir) {
+                *step = dy as isize * stride as isize + dx as isize;
+            }
+        }
+        Some(Border {
+            pad,
+            stride,
+            len: stride * (height + 2 * pad),
+            steps,
+        })
+    }
+
+    #[inline]
+    /// Return the number of cells of the padded grid.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    /// Return `true` if the padded grid has no cells, which it never does.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    /// Return the index in the padded grid of (x, y) on the board.
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        (y + self.pad) * self.stride + x + self.pad
+    }
+
+    #[inline]
+    /// Return the padded index a move in `dir` from padded index `from`
+    /// lands on. It is inside the grid for any `from` on the board.
+    pub fn step(&self, from: usize, dir: Direction) -> usize {
+        from.wrapping_add_signed(self.steps[dir as usize])
+    }
+}
+
+impl fmt::Debug for Neighbors {
+    /// Leave the table out; it can be rebuilt from the key.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Neighbors")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("rules", &self.rules)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_match_the_rules() {
+        for rules in [
+            MoveRules::CLASSIC,
+            MoveRules::CLASSIC.toroidal(),
+            MoveRules::knight(),
+            MoveRules::new(3, 0),
+        ] {
+            let table = Neighbors::get(7, 5, rules);
+            assert_eq!(table.key(), (7, 5, rules));
+            for cell in 0..35 {
+                let from = Coord::from_index(cell, 7);
+                for &dir in Direction::iterator() {
+                    let to = rules.target(7, 5, from, dir).map(|to| to.index(7));
+                    assert_eq!(table.target(cell, dir), to);
+                }
+                let mut sources: Vec<usize> = (0..35)
+                    .flat_map(|from| {
+                        table
+                            .targets(from)
+                            .filter(move |&to| to == cell)
+                            .map(move |_| from)
+                    })
+                    .collect();
+                sources.sort_unstable();
+                let mut found: Vec<usize> = table.sources(cell).collect();
+                found.sort_unstable();
+                assert_eq!(found, sources);
+                assert_eq!(
+                    table.targets(cell).count(),
+                    rules
+                        .directions()
+                        .filter(|dir| rules.target(7, 5, from, *dir).is_some())
+                        .count()
+                );
+            }
+        }
+        // a jump in the padded layout lands where the table says, or in the
+        // border
+        let border = Border::new(7, 5, MoveRules::CLASSIC).unwrap();
+        assert_eq!((border.len(), border.index(0, 0)), (13 * 11, 3 * 13 + 3));
+        let table = Neighbors::get(7, 5, MoveRules::CLASSIC);
+        for cell in 0..35 {
+            let from = Coord::from_index(cell, 7);
+            for &dir in Direction::iterator() {
+                let to = border.step(border.index(from.x, from.y), dir);
+                let inside = table
+                    .target(cell, dir)
+                    .map(|to| border.index(to % 7, to / 7));
+                let (x, y) = (to % 13, to / 13);
+                let on_board = (3..10).contains(&x) && (3..8).contains(&y);
+                assert!(to < border.len());
+                assert_eq!(on_board, inside.is_some());
+                assert!(inside.is_none_or(|inside| inside == to));
+            }
+        }
+        assert!(Border::new(7, 5, MoveRules::CLASSIC.toroidal()).is_none());
+
+        // the same table is handed out again
+        let table = Neighbors::get(7, 5, MoveRules::CLASSIC);
+        assert!(Arc::ptr_eq(
+            &table,
+            &Neighbors::get(7, 5, MoveRules::CLASSIC)
+        ));
+        assert!(!Arc::ptr_eq(
+            &table,
+            &Neighbors::get(5, 7, MoveRules::CLASSIC)
+        ));
+    }
+}
@@ -0,0 +1,379 @@
+/*!
+Learned move ordering.
+
+Warnsdorff's rule orders the moves of a position well, but it often cannot
+tell them apart: several cells have the same number of onward moves. A
+`MoveTable` breaks those ties from experience. It counts, for each cell,
+how often each jump out of it was part of a solution, over every solved
+board it was trained on, and a solver given one (see
+`SolverConfig::ordering`) tries the jump that most often led to a solution
+first among moves Warnsdorff's rule ranks the same. A table only helps on
+boards like the ones it was trained on, so it records their size and rules,
+and `fits` checks a board against them.
+
+Cells are numbered as in `Searchable`, so on a `Board` a jump from a cell
+is a direction from it. Tables are saved in a text format versioned with
+`ORDERING_FORMAT`: the size and rules of the boards (see `MoveRules`) and
+the number of solutions recorded, then a line for each cell a solution
+left, with the cell and the count of each jump out of it, as
+`target:count`:
+
+```text
+square100 ordering v2
+size 5x5
+rules 0,3 2,2 3,0 2,-2 0,-3 -2,-2 -3,0 -2,2
+solutions 3
+0 3:2 12:1
+1 4:1 13:2
+```
+ */
+
+use crate::board::{Board, MoveRules, MAX_SIZE, MIN_SIZE};
+use crate::format::{Format, FormatError};
+use crate::solver::Searchable;
+use failure::Fail;
+use std::fmt;
+use std::str::FromStr;
+
+/// The text format of a move table. Version 1 gave only the number of
+/// cells of the boards, not their shape or rules.
+pub const ORDERING_FORMAT: Format = Format {
+    kind: "ordering",
+    version: 2,
+    migrations: &[ordering_v1_to_v2],
+};
+
+// A version 1 table could have been trained on boards of any shape and
+// rules with its number of cells, so it can not be told which.
+fn ordering_v1_to_v2(_: &str) -> Result<String, FormatError> {
+    Err(FormatError::Migration {
+        version: 1,
+        reason: "the table does not record its board size and rules".to_string(),
+    })
+}
+
+#[derive(Debug, Fail, PartialEq)]
+/// Errors raised while training or reading a move table.
+pub enum OrderingError {
+    #[fail(display = "{}", _0)]
+    Format(#[cause] FormatError),
+    #[fail(display = "Expected '{}' line, found '{}'", expected, line)]
+    BadLine { expected: String, line: String },
+    #[fail(display = "Board has {} cells, table has {}", found, expected)]
+    WrongSize { expected: usize, found: usize },
+    #[fail(display = "Table is for {} boards, not {}", expected, found)]
+    WrongBoard { expected: String, found: String },
+    #[fail(display = "Board is not solved")]
+    NotSolved,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// MoveTable counts how often each jump out of each cell was part of a
+/// solution.
+pub struct MoveTable {
+    /// The number of columns of the boards the table is for.
+    width: usize,
+    /// The number of rows of the boards the table is for.
+    height: usize,
+    /// The rules of the boards the table is for.
+    rules: MoveRules,
+    /// For each cell, the cells it jumped to and how often.
+    counts: Vec<Vec<(usize, u64)>>,
+    /// The number of solutions recorded.
+    solutions: u64,
+}
+
+impl MoveTable {
+    /// Return an empty table for `width` x `height` boards played under
+    /// `rules`.
+    pub fn new(width: usize, height: usize, rules: MoveRules) -> MoveTable {
+        MoveTable {
+            width,
+            height,
+            rules,
+            counts: vec![Vec::new(); width * height],
+            solutions: 0,
+        }
+    }
+
+    /// Return an empty table for boards of the size and rules of `board`.
+    pub fn for_board(board: &Board) -> MoveTable {
+        MoveTable::new(board.width(), board.height(), *board.rules())
+    }
+
+    /// Return the number of cells of the boards the table is for.
+    pub fn cells(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Return the number of columns of the boards the table is for.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the number of rows of the boards the table is for.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the rules of the boards the table is for.
+    pub fn rules(&self) -> &MoveRules {
+        &self.rules
+    }
+
+    /// Return `WrongBoard` unless `board` has the size and rules of the
+    /// boards the table is for.
+    pub fn fits(&self, board: &Board) -> Result<(), OrderingError> {
+        let (width, height) = (board.width(), board.height());
+        if (width, height, board.rules()) == (self.width, self.height, &self.rules) {
+            return Ok(());
+        }
+        Err(OrderingError::WrongBoard {
+            expected: describe(self.width, self.height, &self.rules),
+            found: describe(width, height, board.rules()),
+        })
+    }
+
+    /// Return the number of solutions recorded.
+    pub fn solutions(&self) -> u64 {
+        self.solutions
+    }
+
+    #[inline]
+    /// Return how often a jump from `from` to `to` was part of a solution.
+    pub fn count(&self, from: usize, to: usize) -> u64 {
+        self.counts
+            .get(from)
+            .and_then(|jumps| jumps.iter().find(|&&(cell, _)| cell == to))
+            .map_or(0, |&(_, count)| count)
+    }
+
+    /// Record the jumps of `solution`, a solved board of the table's size.
+    pub fn train<P: Searchable>(&mut self, solution: &P) -> Result<(), OrderingError> {
+        if solution.cell_total() != self.cells() {
+            return Err(OrderingError::WrongSize {
+                expected: self.cells(),
+                found: solution.cell_total(),
+            });
+        }
+        if !solution.is_solved() {
+            return Err(OrderingError::NotSolved);
+        }
+        // walk the path back from its end
+        let mut board = solution.clone();
+        while let Some(to) = board.current_cell() {
+            board.unfill();
+            if let Some(from) = board.current_cell() {
+                self.add(from, to, 1);
+            }
+        }
+        self.solutions += 1;
+        Ok(())
+    }
+
+    /// Add the counts of `other`, a table for the same boards.
+    pub fn merge(&mut self, other: &MoveTable) -> Result<(), OrderingError> {
+        if (other.width, other.height, other.rules) != (self.width, self.height, self.rules) {
+            return Err(OrderingError::WrongBoard {
+                expected: describe(self.width, self.height, &self.rules),
+                found: describe(other.width, other.height, &other.rules),
+            });
+        }
+        for (from, jumps) in other.counts.iter().enumerate() {
+            for &(to, count) in jumps {
+                self.add(from, to, count);
+            }
+        }
+        self.solutions += other.solutions;
+        Ok(())
+    }
+
+    fn add(&mut self, from: usize, to: usize, count: u64) {
+        let jumps = &mut self.counts[from];
+        match jumps.iter_mut().find(|(cell, _)| *cell == to) {
+            Some((_, total)) => *total += count,
+            None => {
+                jumps.push((to, count));
+                jumps.sort_unstable();
+            }
+        }
+    }
+}
+
+/// Return the size and rules of a board, as errors give them.
+fn describe(width: usize, height: usize, rules: &MoveRules) -> String {
+    format!("{}x{} '{}'", width, height, rules)
+}
+
+impl fmt::Display for MoveTable {
+    /// Write the table in `ORDERING_FORMAT`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", ORDERING_FORMAT.header())?;
+        writeln!(f, "size {}x{}", self.width, self.height)?;
+        writeln!(f, "rules {}", self.rules)?;
+        writeln!(f, "solutions {}", self.solutions)?;
+        for (from, jumps) in self.counts.iter().enumerate() {
+            if jumps.is_empty() {
+                continue;
+            }
+            write!(f, "{}", from)?;
+            for (to, count) in jumps {
+                write!(f, " {}:{}", to, count)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MoveTable {
+    type Err = OrderingError;
+
+    /// Parse a table written by the `Display` impl.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = ORDERING_FORMAT.upgrade(s).map_err(OrderingError::Format)?;
+        let mut lines = body.lines().map(str::trim).filter(|line| !line.is_empty());
+        let bad = |expected: &str, line: &str| OrderingError::BadLine {
+            expected: expected.to_string(),
+            line: line.to_string(),
+        };
+        let mut field = |name: &str| {
+            let line = lines.next().unwrap_or("");
+            match line.split_once(' ') {
+                Some((key, value)) if key == name => Ok((value.trim(), line)),
+                _ => Err(bad(name, line)),
+            }
+        };
+        let (size, line) = field("size")?;
+        let (width, height) = size
+            .split_once('x')
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .filter(|&(width, height): &(usize, usize)| {
+                (MIN_SIZE..=MAX_SIZE).contains(&width) && (MIN_SIZE..=MAX_SIZE).contains(&height)
+            })
+            .ok_or_else(|| bad("size", line))?;
+        let (rules, line) = field("rules")?;
+        let rules = rules.parse().map_err(|_| bad("rules", line))?;
+        let (solutions, line) = field("solutions")?;
+        let mut table = MoveTable::new(width, height, rules);
+        table.solutions = solutions.parse().map_err(|_| bad("solutions", line))?;
+        for line in lines {
+            let mut words = line.split_whitespace();
+            let from = words
+                .next()
+                .and_then(|word| word.parse::<usize>().ok())
+                .filter(|&from| from < table.cells())
+                .ok_or_else(|| bad("cell", line))?;
+            for word in words {
+                let jump = word.split_once(':').and_then(|(to, count)| {
+                    Some((to.parse::<usize>().ok()?, count.parse::<u64>().ok()?))
+                });
+                match jump {
+                    Some((to, count)) if to < table.cells() => table.add(from, to, count),
+                    _ => return Err(bad("target:count", line)),
+                }
+            }
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SOLUTION_5, STRANDED_5};
+
+    #[test]
+    fn tables_count_solution_jumps() {
+        let board = SOLUTION_5.board();
+        let mut table = MoveTable::for_board(&board);
+        table.train(&board).unwrap();
+        table.train(&board).unwrap();
+        assert_eq!(table.solutions(), 2);
+        // the game starts in the top-left corner and moves right first
+        assert_eq!(table.count(0, 3), 2);
+        assert_eq!(table.count(0, 15), 0);
+        let jumps: u64 = (0..25)
+            .flat_map(|from| (0..25).map(move |to| (from, to)))
+            .map(|(from, to)| table.count(from, to))
+            .sum();
+        assert_eq!(jumps, 2 * 24);
+
+        assert_eq!(
+            table.train(&STRANDED_5.board()),
+            Err(OrderingError::NotSolved)
+        );
+        assert_eq!(
+            MoveTable::new(6, 6, MoveRules::CLASSIC).train(&board),
+            Err(OrderingError::WrongSize {
+                expected: 36,
+                found: 25
+            })
+        );
+        let mut merged = MoveTable::for_board(&board);
+        merged.train(&board).unwrap();
+        merged.merge(&merged.clone()).unwrap();
+        assert_eq!(merged, table);
+
+        // a table is for boards of one shape and rules
+        assert_eq!(table.fits(&Board::new(5)), Ok(()));
+        let knight = Board::new(5).with_rules(MoveRules::knight());
+        let err = table.fits(&knight).unwrap_err();
+        assert!(err.to_string().starts_with("Table is for 5x5 '0,3 2,2"));
+        assert!(table.fits(&Board::with_dimensions(25, 5)).is_err());
+        assert!(table.merge(&MoveTable::for_board(&knight)).is_err());
+    }
+
+    #[test]
+    fn tables_round_trip() {
+        let board = SOLUTION_5.board();
+        let mut table = MoveTable::for_board(&board);
+        table.train(&board).unwrap();
+        let text = table.to_string();
+        let header = format!(
+            "square100 ordering v2\nsize 5x5\nrules {}\nsolutions 1\n",
+            MoveRules::CLASSIC
+        );
+        assert!(text.starts_with(&(header.clone() + "0 3:1\n")));
+        assert_eq!(text.parse::<MoveTable>(), Ok(table));
+        let knight = MoveTable::new(6, 5, MoveRules::knight().toroidal());
+        assert_eq!(knight.to_string().parse::<MoveTable>(), Ok(knight));
+
+        assert_eq!(
+            (header + "3 25:1\n").parse::<MoveTable>(),
+            Err(OrderingError::BadLine {
+                expected: "target:count".to_string(),
+                line: "3 25:1".to_string()
+            })
+        );
+        let rules = format!("rules {}\nsolutions 0\n", MoveRules::CLASSIC);
+        for size in ["18446744073709551615x5", "256x5", "5x4", "5", "x"].iter() {
+            let line = format!("size {}", size);
+            assert_eq!(
+                format!("square100 ordering v2\n{}\n{}", line, rules).parse::<MoveTable>(),
+                Err(OrderingError::BadLine {
+                    expected: "size".to_string(),
+                    line
+                })
+            );
+        }
+        assert_eq!(
+            "square100 ordering v2\nsize 5x5\nrules 0,3\nsolutions 0\n".parse::<MoveTable>(),
+            Err(OrderingError::BadLine {
+                expected: "rules".to_string(),
+                line: "rules 0,3".to_string()
+            })
+        );
+        // version 1 tables, which gave only a cell count, are not guessed at
+        let v1 = "square100 ordering v1\ncells 18446744073709551615\nsolutions 1\n";
+        assert!(matches!(
+            v1.parse::<MoveTable>(),
+            Err(OrderingError::Format(FormatError::Migration {
+                version: 1,
+                ..
+            }))
+        ));
+        assert!("square100 replay v1\n".parse::<MoveTable>().is_err());
+        assert!("cells x\n".parse::<MoveTable>().is_err());
+    }
+}
@@ -0,0 +1,596 @@
+/*!
+Puzzle packs: shareable collections of clue puzzles.
+
+A clue puzzle is an empty board with some values of a known solution
+revealed; the player has to complete it so that it agrees with every clue.
+A pack bundles a sequence of such puzzles with a manifest (title, author,
+rule variant) and, for each puzzle, a difficulty rating and a hash of its
+solution, so the player can be told whether a completion is the intended
+one without the pack giving the solution away. The ratings, in pack order,
+form the pack's difficulty curve.
+
+To keep packs varied, a generator can reject puzzles that are near
+duplicates of ones it already has with a `DuplicateFilter`. Puzzles are
+compared by `Fingerprint`, a MinHash signature of the clue pattern taken in
+a canonical orientation, so rotated, reflected and reversed (value `v`
+written as `n² + 1 - v`) copies of a puzzle count as the same puzzle.
+
+Packs are written by the generator and played in order. The text format is
+versioned with `PACK_FORMAT`:
+
+```text
+square100 pack v1
+title Warm-up
+author Mark
+variant classic
+puzzles 1
+
+difficulty 1
+solution 7be87b99e893cef7
+ 1  .  .  .  .
+ .  .  5  .  .
+ .  .  .  .  .
+ .  .  .  .  .
+ .  .  .  .  .
+```
+ */
+
+use crate::board::{symmetric_cells, Board, MAX_SIZE, MIN_SIZE};
+use crate::coord::Coord;
+use crate::format::{Format, FormatError};
+use crate::rng::Rng;
+use failure::Fail;
+use std::fmt;
+use std::str::FromStr;
+
+/// The text format of a puzzle pack.
+pub const PACK_FORMAT: Format = Format {
+    kind: "pack",
+    version: 1,
+    migrations: &[],
+};
+
+#[derive(Debug, Fail, PartialEq)]
+/// Errors raised while reading a puzzle pack.
+pub enum PackError {
+    #[fail(display = "{}", _0)]
+    Format(#[cause] FormatError),
+    #[fail(display = "Expected '{}' line, found '{}'", expected, line)]
+    BadLine { expected: String, line: String },
+    #[fail(display = "Unknown rule variant '{}'", name)]
+    UnknownVariant { name: String },
+    #[fail(display = "Invalid clue '{}' in puzzle {}", token, puzzle)]
+    InvalidClue { puzzle: usize, token: String },
+    #[fail(
+        display = "Puzzle {} is not a grid of 5 to 255 rows and columns",
+        puzzle
+    )]
+    BadGrid { puzzle: usize },
+    #[fail(display = "Pack declares {} puzzles but contains {}", declared, found)]
+    CountMismatch { declared: usize, found: usize },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Variant is the set of rules the puzzles of a pack are played under.
+pub enum Variant {
+    /// The single player puzzle.
+    Classic,
+    /// The two-player game of the `rules` module.
+    Duel,
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Variant::Classic => write!(f, "classic"),
+            Variant::Duel => write!(f, "duel"),
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = PackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Variant::Classic),
+            "duel" => Ok(Variant::Duel),
+            _ => Err(PackError::UnknownVariant {
+                name: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Return a hash of the values of `board` that is stable across platforms
+/// and releases (64-bit FNV-1a over the width and the values, row by row).
+/// Values are hashed as one byte on boards of up to 255 cells and as two
+/// little-endian bytes on larger ones.
+pub fn solution_hash(board: &Board) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let wide = board.cell_count() > 255;
+    let mut bytes = vec![board.width() as u8];
+    for (_, _, v) in board.iter_cells() {
+        bytes.push(v as u8);
+        if wide {
+            bytes.push((v >> 8) as u8);
+        }
+    }
+    for byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Read the line `<name> <value>` from `lines`, returning the value.
+fn field<'a>(lines: &mut impl Iterator<Item = &'a str>, name: &str) -> Result<String, PackError> {
+    let line = lines.next().unwrap_or("");
+    let prefix = format!("{} ", name);
+    if line.starts_with(&prefix) {
+        Ok(line[prefix.len()..].trim().to_string())
+    } else {
+        Err(PackError::BadLine {
+            expected: name.to_string(),
+            line: line.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Puzzle is a board with some values of a solution revealed.
+pub struct Puzzle {
+    width: usize,
+    height: usize,
+    /// The revealed values, row by row; 0 where nothing is revealed.
+    clues: Vec<u16>,
+    /// The difficulty rating; higher is harder.
+    pub difficulty: u32,
+    /// The `solution_hash` of the intended solution.
+    pub solution_hash: u64,
+}
+
+impl Puzzle {
+    /// Create a puzzle from `solution`, revealing the values in `reveal`.
+    pub fn from_solution(solution: &Board, reveal: &[u16], difficulty: u32) -> Puzzle {
+        Puzzle {
+            width: solution.width(),
+            height: solution.height(),
+            clues: solution
+                .iter_cells()
+                .map(|(_, _, v)| if reveal.contains(&v) { v } else { 0 })
+                .collect(),
+            difficulty,
+            solution_hash: solution_hash(solution),
+        }
+    }
+
+    /// Return the number of horizontal cells of the puzzle.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the number of vertical cells of the puzzle.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the revealed value at (x, y), or 0 if none is revealed.
+    pub fn clue_at(&self, x: usize, y: usize) -> u16 {
+        self.clues[y * self.width + x]
+    }
+
+    /// Return the revealed values with their (x, y) locations, row by row.
+    pub fn clues(&self) -> impl Iterator<Item = (usize, usize, u16)> + '_ {
+        let width = self.width;
+        self.clues
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| v > 0)
+            .map(move |(i, &v)| (i % width, i / width, v))
+    }
+
+    /// Return `true` if `board` is a complete board that agrees with every
+    /// clue.
+    pub fn is_completed_by(&self, board: &Board) -> bool {
+        (board.width(), board.height()) == (self.width, self.height)
+            && board.is_won()
+            && self
+                .clues()
+                .all(|(x, y, value)| board.value_at(Coord::new(x, y)) == value)
+    }
+
+    /// Return `true` if `board` is the intended solution.
+    pub fn is_solution(&self, board: &Board) -> bool {
+        self.is_completed_by(board) && solution_hash(board) == self.solution_hash
+    }
+}
+
+impl Puzzle {
+    /// Return the clues in canonical form: the smallest clue grid, row by
+    /// row, over the rotations and reflections of the puzzle, each with the
+    /// values as given and reversed.
+    pub fn canonical_clues(&self) -> Vec<u16> {
+        let (width, height) = (self.width, self.height);
+        let last = (width * height + 1) as u16;
+        let transforms = symmetric_cells(width, height, 0, 0).len();
+        let mut best: Option<Vec<u16>> = None;
+        for transform in 0..transforms {
+            for &reverse in [false, true].iter() {
+                let mut grid = vec![0; width * height];
+                for (x, y, v) in self.clues() {
+                    let (tx, ty) = symmetric_cells(width, height, x, y)[transform];
+                    grid[ty * width + tx] = if reverse { last - v } else { v };
+                }
+                if best.as_ref().is_none_or(|b| grid < *b) {
+                    best = Some(grid);
+                }
+            }
+        }
+        best.unwrap()
+    }
+
+    /// Return the similarity fingerprint of the puzzle.
+    pub fn fingerprint(&self) -> Fingerprint {
+        let n = ((self.width as u64) << 8) | self.height as u64;
+        let features: Vec<u64> = self
+            .canonical_clues()
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| v > 0)
+            .flat_map(|(i, &v)| {
+                let cell = (n << 32) | (i as u64) << 16;
+                // where a clue is, and which value is there
+                vec![cell, (1 << 48) | cell | u64::from(v)]
+            })
+            .collect();
+        let mut slots = [u64::MAX; FINGERPRINT_SLOTS];
+        for (k, slot) in slots.iter_mut().enumerate() {
+            for feature in &features {
+                let hash =
+                    Rng::new(feature.wrapping_mul(FINGERPRINT_SLOTS as u64) + k as u64).next_u64();
+                *slot = (*slot).min(hash);
+            }
+        }
+        Fingerprint { slots }
+    }
+}
+
+/// The number of hash slots in a `Fingerprint`.
+pub const FINGERPRINT_SLOTS: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Fingerprint is a MinHash signature of a puzzle's canonical clues. The
+/// fraction of slots two fingerprints share estimates the overlap of the
+/// clue sets (the Jaccard index).
+pub struct Fingerprint {
+    slots: [u64; FINGERPRINT_SLOTS],
+}
+
+impl Fingerprint {
+    /// Return the estimated similarity with `other`, from 0 (nothing in
+    /// common) to 1 (the same clues).
+    pub fn similarity(&self, other: &Fingerprint) -> f64 {
+        let same = self
+            .slots
+            .iter()
+            .zip(other.slots.iter())
+            .filter(|(a, b)| a == b)
+            .count();
+        same as f64 / FINGERPRINT_SLOTS as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+/// DuplicateFilter remembers the fingerprints of accepted puzzles and
+/// rejects puzzles too similar to any of them.
+pub struct DuplicateFilter {
+    /// Puzzles at least this similar to an accepted one are rejected.
+    pub threshold: f64,
+    seen: Vec<Fingerprint>,
+}
+
+impl DuplicateFilter {
+    /// The default threshold: three quarters of the clues in common.
+    pub const DEFAULT_THRESHOLD: f64 = 0.75;
+
+    /// Create an empty filter.
+    pub fn new(threshold: f64) -> Self {
+        DuplicateFilter {
+            threshold,
+            seen: Vec::new(),
+        }
+    }
+
+    /// Create a filter that already holds every puzzle of `puzzles`, e.g.
+    /// an existing pack or archive.
+    pub fn with_puzzles<'a>(threshold: f64, puzzles: impl IntoIterator<Item = &'a Puzzle>) -> Self {
+        DuplicateFilter {
+            threshold,
+            seen: puzzles.into_iter().map(Puzzle::fingerprint).collect(),
+        }
+    }
+
+    /// Return `true` if `puzzle` is a near duplicate of an accepted puzzle.
+    pub fn is_duplicate(&self, puzzle: &Puzzle) -> bool {
+        let fingerprint = puzzle.fingerprint();
+        self.seen
+            .iter()
+            .any(|seen| seen.similarity(&fingerprint) >= self.threshold)
+    }
+
+    /// Accept `puzzle` unless it is a near duplicate, returning `true` if
+    /// it was accepted.
+    pub fn insert(&mut self, puzzle: &Puzzle) -> bool {
+        if self.is_duplicate(puzzle) {
+            return false;
+        }
+        self.seen.push(puzzle.fingerprint());
+        true
+    }
+}
+
+impl Default for DuplicateFilter {
+    fn default() -> Self {
+        DuplicateFilter::new(DuplicateFilter::DEFAULT_THRESHOLD)
+    }
+}
+
+impl fmt::Display for Puzzle {
+    /// Write the clue grid, with '.' where nothing is revealed.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = (self.width * self.height).to_string().len();
+        for (y, row) in self.clues.chunks(self.width).enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            let cells: Vec<String> = row
+                .iter()
+                .map(|&v| match v {
+                    0 => format!("{:>w$}", ".", w = width),
+                    v => format!("{:>w$}", v, w = width),
+                })
+                .collect();
+            write!(f, "{}", cells.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Manifest describes a pack.
+pub struct Manifest {
+    pub title: String,
+    pub author: String,
+    pub variant: Variant,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Pack is a manifest and a sequence of puzzles, played in order.
+pub struct Pack {
+    pub manifest: Manifest,
+    pub puzzles: Vec<Puzzle>,
+}
+
+impl Pack {
+    /// Create an empty pack.
+    pub fn new(manifest: Manifest) -> Pack {
+        Pack {
+            manifest,
+            puzzles: Vec::new(),
+        }
+    }
+
+    /// Return the difficulty ratings of the puzzles, in pack order.
+    pub fn difficulty_curve(&self) -> Vec<u32> {
+        self.puzzles.iter().map(|p| p.difficulty).collect()
+    }
+}
+
+impl fmt::Display for Pack {
+    /// Write the pack in `PACK_FORMAT`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", PACK_FORMAT.header())?;
+        writeln!(f, "title {}", self.manifest.title)?;
+        writeln!(f, "author {}", self.manifest.author)?;
+        writeln!(f, "variant {}", self.manifest.variant)?;
+        writeln!(f, "puzzles {}", self.puzzles.len())?;
+        for puzzle in &self.puzzles {
+            writeln!(f)?;
+            writeln!(f, "difficulty {}", puzzle.difficulty)?;
+            writeln!(f, "solution {:016x}", puzzle.solution_hash)?;
+            writeln!(f, "{}", puzzle)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Pack {
+    type Err = PackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = PACK_FORMAT.upgrade(s).map_err(PackError::Format)?;
+        let mut lines = body.lines().map(str::trim).peekable();
+        let manifest = Manifest {
+            title: field(&mut lines, "title")?,
+            author: field(&mut lines, "author")?,
+            variant: field(&mut lines, "variant")?.parse()?,
+        };
+        let count = field(&mut lines, "puzzles")?;
+        let declared = count.parse::<usize>().map_err(|_| PackError::BadLine {
+            expected: "puzzles".to_string(),
+            line: count,
+        })?;
+
+        let mut puzzles = Vec::new();
+        loop {
+            while lines.peek() == Some(&"") {
+                lines.next();
+            }
+            if lines.peek().is_none() {
+                break;
+            }
+            let index = puzzles.len() + 1;
+            let text = field(&mut lines, "difficulty")?;
+            let difficulty = text.parse().map_err(|_| PackError::BadLine {
+                expected: "difficulty".to_string(),
+                line: text,
+            })?;
+            let text = field(&mut lines, "solution")?;
+            let solution_hash = u64::from_str_radix(&text, 16).map_err(|_| PackError::BadLine {
+                expected: "solution".to_string(),
+                line: text,
+            })?;
+
+            let mut clues = Vec::new();
+            let mut rows = 0;
+            let mut ragged = false;
+            while let Some(line) = lines.peek() {
+                if line.is_empty() {
+                    break;
+                }
+                let before = clues.len();
+                for token in line.split_whitespace() {
+                    clues.push(match token {
+                        "." => 0,
+                        _ => token.parse::<u16>().map_err(|_| PackError::InvalidClue {
+                            puzzle: index,
+                            token: token.to_string(),
+                        })?,
+                    });
+                }
+                let width = clues.len() - before;
+                ragged |= rows > 0 && width * (rows + 1) != clues.len();
+                rows += 1;
+                lines.next();
+            }
+            let width = clues.len() / rows.max(1);
+            let sizes = MIN_SIZE..=MAX_SIZE;
+            if ragged || !sizes.contains(&rows) || !sizes.contains(&width) {
+                return Err(PackError::BadGrid { puzzle: index });
+            }
+            if let Some(&v) = clues.iter().find(|&&v| v as usize > clues.len()) {
+                return Err(PackError::InvalidClue {
+                    puzzle: index,
+                    token: v.to_string(),
+                });
+            }
+            puzzles.push(Puzzle {
+                width,
+                height: rows,
+                clues,
+                difficulty,
+                solution_hash,
+            });
+        }
+        if puzzles.len() != declared {
+            return Err(PackError::CountMismatch {
+                declared,
+                found: puzzles.len(),
+            });
+        }
+        Ok(Pack { manifest, puzzles })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{solutions_10, SOLUTION_5};
+    use crate::{Coord, MoveRules};
+
+    fn pack() -> Pack {
+        let mut pack = Pack::new(Manifest {
+            title: "Warm-up".to_string(),
+            author: "Mark".to_string(),
+            variant: Variant::Classic,
+        });
+        pack.puzzles
+            .push(Puzzle::from_solution(&SOLUTION_5.board(), &[1, 5, 25], 1));
+        pack.puzzles
+            .push(Puzzle::from_solution(&solutions_10()[1], &[1, 50, 100], 4));
+        pack
+    }
+
+    #[test]
+    fn round_trip() {
+        let pack = pack();
+        let text = pack.to_string();
+        assert!(text.starts_with("square100 pack v1\ntitle Warm-up\n"));
+        assert_eq!(text.parse::<Pack>(), Ok(pack.clone()));
+        assert_eq!(pack.difficulty_curve(), vec![1, 4]);
+
+        let truncated = text.replace("puzzles 2", "puzzles 3");
+        assert_eq!(
+            truncated.parse::<Pack>(),
+            Err(PackError::CountMismatch {
+                declared: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            text.replace("classic", "toroidal").parse::<Pack>(),
+            Err(PackError::UnknownVariant {
+                name: "toroidal".to_string()
+            })
+        );
+        assert!(text.replace(" 5 ", " x ").parse::<Pack>().is_err());
+    }
+
+    #[test]
+    fn check_solutions() {
+        let pack = pack();
+        let puzzle = &pack.puzzles[0];
+        assert_eq!(puzzle.clues().count(), 3);
+        assert_eq!(puzzle.clue_at(0, 0), 1);
+        assert_eq!(puzzle.clue_at(1, 0), 0);
+        assert_eq!(puzzle.solution_hash, 0x7be8_7b99_e893_cef7);
+        assert!(puzzle.is_solution(&SOLUTION_5.board()));
+        assert!(!puzzle.is_solution(&SOLUTION_5.board_after(10)));
+
+        // the mirror image of the solution breaks the clues
+        let mirrored: Vec<_> = SOLUTION_5
+            .moves
+            .iter()
+            .map(|dir| {
+                let (dx, dy) = dir.delta(&MoveRules::CLASSIC).unwrap();
+                MoveRules::CLASSIC.direction_of(-dx, dy).unwrap()
+            })
+            .collect();
+        let other = Board::from_moves(5, Coord::new(4, 0), &mirrored).unwrap();
+        assert!(other.is_won());
+        assert!(!puzzle.is_completed_by(&other));
+        assert_ne!(solution_hash(&other), puzzle.solution_hash);
+    }
+
+    #[test]
+    fn reject_near_duplicates() {
+        let solution = &solutions_10()[0];
+        let puzzle = Puzzle::from_solution(solution, &[1, 20, 40, 60, 80, 100], 3);
+
+        // the mirror image of the same puzzle
+        let mirrored = Puzzle {
+            clues: (0..100)
+                .map(|i| puzzle.clue_at(9 - i % 10, i / 10))
+                .collect(),
+            ..puzzle.clone()
+        };
+        assert_eq!(mirrored.canonical_clues(), puzzle.canonical_clues());
+        assert_eq!(mirrored.fingerprint(), puzzle.fingerprint());
+
+        let mut filter = DuplicateFilter::default();
+        assert!(filter.insert(&puzzle));
+        assert!(!filter.insert(&mirrored));
+        // one clue more than an accepted puzzle
+        let extended = Puzzle::from_solution(solution, &[1, 20, 40, 60, 80, 90, 100], 3);
+        assert!(filter.is_duplicate(&extended));
+        // the same values revealed on a different solution
+        let other = Puzzle::from_solution(&solutions_10()[2], &[1, 20, 40, 60, 80, 100], 3);
+        assert!(puzzle.fingerprint().similarity(&other.fingerprint()) < 0.5);
+        assert!(filter.insert(&other));
+
+        let filter = DuplicateFilter::with_puzzles(0.75, &pack().puzzles);
+        assert!(filter.is_duplicate(&pack().puzzles[1]));
+        assert!(!filter.is_duplicate(&puzzle));
+    }
+}
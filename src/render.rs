@@ -0,0 +1,771 @@
+/*!
+Drawing boards for people to read.
+
+The `Display` impl of `Board` writes the plain grid that `FromStr` reads
+back. That is compact, but on a 12x12 or larger board the columns are hard
+to follow by eye, so `Style::Unicode` draws every cell in a box instead.
+The top-left corner of a 5x5 board with a hole looks like this:
+
+```text
+┌────┬────┬────┬─
+│  1 │  . │  . │
+├────┼────┼────┼─
+│  . │ ░░ │  . │
+├────┼────┼────┼─
+```
+
+Values are right-aligned, empty cells are shown as `.` and holes are
+shaded.
+
+With `RenderOptions::color` set, either style colors the values with ANSI
+escapes on a gradient from the 1 to the last cell, so the path can be
+followed by color, and shows the current position in reverse video.
+
+For figures outside the terminal, `to_svg` draws the board as an SVG
+document, optionally with the path through the values drawn on top.
+`to_image` draws the same picture as a raster image, with the values in a
+small built-in pixel font, and with the `png` feature `to_png` encodes it
+as a PNG file. With the `gif` feature `to_gif` animates the filling of the
+board, one frame per value.
+ */
+
+use crate::board::Board;
+use crate::coord::Coord;
+use failure::Fail;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "gif")]
+use std::time::Duration;
+
+#[derive(Debug, Fail, PartialEq)]
+#[fail(display = "Invalid style '{}': expected plain or unicode", name)]
+/// Error raised when parsing a `Style`.
+pub struct StyleError {
+    name: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+/// Style is the way a board is drawn.
+pub enum Style {
+    /// Whitespace separated values, as written by `Display`.
+    #[default]
+    Plain,
+    /// Values in a grid of box-drawing characters.
+    Unicode,
+}
+
+impl Style {
+    /// Draw `board` in this style, without color.
+    pub fn render(self, board: &Board) -> String {
+        RenderOptions {
+            style: self,
+            color: false,
+        }
+        .render(board)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+/// RenderOptions says how to draw a board in a terminal.
+pub struct RenderOptions {
+    pub style: Style,
+    /// Color the values with ANSI escapes, from blue for 1 to red for the
+    /// last cell, and show the current position in reverse video.
+    pub color: bool,
+}
+
+impl RenderOptions {
+    /// Draw `board` with these options.
+    pub fn render(&self, board: &Board) -> String {
+        match self.style {
+            Style::Plain if !self.color => board.to_string(),
+            Style::Plain => plain(board, self.color),
+            Style::Unicode => boxed(board, self.color),
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Style::Plain => "plain",
+            Style::Unicode => "unicode",
+        })
+    }
+}
+
+impl FromStr for Style {
+    type Err = StyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "plain" => Ok(Style::Plain),
+            "unicode" => Ok(Style::Unicode),
+            _ => Err(StyleError {
+                name: s.trim().to_string(),
+            }),
+        }
+    }
+}
+
+/// Draw `board` with box-drawing characters, one cell per box and one row
+/// per line. Like the plain style there is no newline after the last line,
+/// and a toroidal board starts with a `torus` line.
+pub fn to_unicode(board: &Board) -> String {
+    boxed(board, false)
+}
+
+/// The color of the first value, and of the last.
+const FIRST_COLOR: (u8, u8, u8) = (80, 140, 255);
+const LAST_COLOR: (u8, u8, u8) = (255, 80, 60);
+
+/// Return the ANSI escape that starts the color of cell (x, y). Values fade from `FIRST_COLOR` to `LAST_COLOR`;
+/// empty cells and holes are dimmed.
+fn color_of(board: &Board, x: usize, y: usize) -> String {
+    let value = board.value_at(Coord::new(x, y)) as usize;
+    if value == 0 {
+        return "\x1b[2m".to_string();
+    }
+    let t = (value - 1) as f64 / (board.cell_count() - 1).max(1) as f64;
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    let (r, g, b) = (
+        mix(FIRST_COLOR.0, LAST_COLOR.0),
+        mix(FIRST_COLOR.1, LAST_COLOR.1),
+        mix(FIRST_COLOR.2, LAST_COLOR.2),
+    );
+    let current = board.current_position() == Some(Coord::new(x, y));
+    format!(
+        "\x1b[{}38;2;{};{};{}m",
+        if current { "1;7;" } else { "" },
+        r,
+        g,
+        b
+    )
+}
+
+/// Return `text`, the drawing of cell (x, y), in its color if `color` is
+/// set.
+fn paint(board: &Board, x: usize, y: usize, text: String, color: bool) -> String {
+    if color {
+        format!("{}{}\x1b[0m", color_of(board, x, y), text)
+    } else {
+        text
+    }
+}
+
+/// Draw `board` as whitespace separated values, like its `Display` impl.
+fn plain(board: &Board, color: bool) -> String {
+    let width = board.cell_count().to_string().len();
+    let mut out = String::new();
+    if board.rules().is_toroidal() {
+        out.push_str("torus\n");
+    }
+    for y in 0..board.height() {
+        if y > 0 {
+            out.push('\n');
+        }
+        let cells: Vec<String> = (0..board.width())
+            .map(|x| {
+                let text = if board.is_hole(Coord::new(x, y)) {
+                    "#".to_string()
+                } else {
+                    match board.value_at(Coord::new(x, y)) {
+                        0 => ".".to_string(),
+                        value => value.to_string(),
+                    }
+                };
+                paint(
+                    board,
+                    x,
+                    y,
+                    format!("{:>width$}", text, width = width),
+                    color,
+                )
+            })
+            .collect();
+        out.push_str(&cells.join(" "));
+    }
+    out
+}
+
+/// Draw `board` with box-drawing characters.
+fn boxed(board: &Board, color: bool) -> String {
+    let width = board.cell_count().to_string().len();
+    let rule = |left: &str, middle: &str, right: &str| {
+        let line = vec!["─".repeat(width + 2); board.width()];
+        format!("{}{}{}", left, line.join(middle), right)
+    };
+    let mut out = String::new();
+    if board.rules().is_toroidal() {
+        out.push_str("torus\n");
+    }
+    out.push_str(&rule("┌", "┬", "┐"));
+    for y in 0..board.height() {
+        out.push('\n');
+        if y > 0 {
+            out.push_str(&rule("├", "┼", "┤"));
+            out.push('\n');
+        }
+        for x in 0..board.width() {
+            out.push('│');
+            let text = if board.is_hole(Coord::new(x, y)) {
+                format!(" {} ", "░".repeat(width))
+            } else {
+                match board.value_at(Coord::new(x, y)) {
+                    0 => format!(" {:>width$} ", ".", width = width),
+                    value => format!(" {:>width$} ", value, width = width),
+                }
+            };
+            out.push_str(&paint(board, x, y, text, color));
+        }
+        out.push('│');
+    }
+    out.push('\n');
+    out.push_str(&rule("└", "┴", "┘"));
+    out
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+/// SvgOptions says how to draw a board as SVG.
+pub struct SvgOptions {
+    /// The width and height of a cell, in pixels.
+    pub cell_size: f64,
+    /// Write the value in each filled cell.
+    pub numbers: bool,
+    /// Draw a line through the filled cells in order, from a dot on the 1
+    /// to a ring on the last value.
+    pub path: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            cell_size: 40.0,
+            numbers: true,
+            path: true,
+        }
+    }
+}
+
+/// Return the filled cells of `board`, in the order they were filled.
+fn filled_cells(board: &Board) -> Vec<Coord> {
+    (1..=board.score() as u16)
+        .filter_map(|value| board.position_of(value))
+        .collect()
+}
+
+/// Split `cells` into runs of moves that do not wrap around an edge, so the
+/// path can be drawn as one line per run. Runs of a single cell are left
+/// out.
+fn path_runs(board: &Board, cells: &[Coord]) -> Vec<Vec<Coord>> {
+    let mut runs: Vec<Vec<Coord>> = vec![cells.iter().take(1).copied().collect()];
+    for pair in cells.windows(2) {
+        let delta = pair[0].delta_to(pair[1]);
+        if board.rules().direction_of(delta.dx, delta.dy).is_none() {
+            runs.push(Vec::new());
+        }
+        runs.last_mut().unwrap().push(pair[1]);
+    }
+    runs.retain(|run| run.len() > 1);
+    runs
+}
+
+/// Draw `board` as a standalone SVG document. On a toroidal board the path
+/// is broken where a move wraps around an edge.
+pub fn to_svg(board: &Board, options: &SvgOptions) -> String {
+    let size = options.cell_size;
+    let centre = |cell: Coord| ((cell.x as f64 + 0.5) * size, (cell.y as f64 + 0.5) * size);
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n",
+        w = board.width() as f64 * size,
+        h = board.height() as f64 * size
+    );
+    for (x, y, _) in board.iter_cells() {
+        out.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"{}\" stroke=\"#999\"/>\n",
+            x as f64 * size,
+            y as f64 * size,
+            if board.is_hole(Coord::new(x, y)) {
+                "#444"
+            } else {
+                "none"
+            },
+            s = size
+        ));
+    }
+
+    let cells = filled_cells(board);
+    if options.path && !cells.is_empty() {
+        for run in path_runs(board, &cells) {
+            let points: Vec<String> = run
+                .iter()
+                .map(|cell| {
+                    let (x, y) = centre(*cell);
+                    format!("{},{}", x, y)
+                })
+                .collect();
+            out.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"#c33\" \
+                 stroke-width=\"{}\" stroke-linejoin=\"round\" stroke-opacity=\"0.6\"/>\n",
+                points.join(" "),
+                size / 16.0
+            ));
+        }
+        let (x, y) = centre(cells[0]);
+        out.push_str(&format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#c33\"/>\n",
+            x,
+            y,
+            size / 8.0
+        ));
+        let (x, y) = centre(cells[cells.len() - 1]);
+        out.push_str(&format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"#c33\" \
+             stroke-width=\"{}\"/>\n",
+            x,
+            y,
+            size * 0.4,
+            size / 16.0
+        ));
+    }
+
+    if options.numbers {
+        for (value, cell) in (1..).zip(&cells) {
+            let (x, y) = centre(*cell);
+            out.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" \
+                 text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                x,
+                y,
+                size * 0.4,
+                value
+            ));
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Rgb is a color, as red, green and blue levels.
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+/// PngOptions says how to draw a board as a raster image.
+pub struct PngOptions {
+    /// The width and height of a cell, in pixels.
+    pub cell_size: u32,
+    pub background: Rgb,
+    /// The color of the lines between cells.
+    pub grid: Rgb,
+    /// The color holes are filled with.
+    pub hole: Rgb,
+    /// The color of the values, or `None` to leave them out.
+    pub numbers: Option<Rgb>,
+    /// The color of the line through the filled cells in order, or `None`
+    /// to leave it out.
+    pub path: Option<Rgb>,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions {
+            cell_size: 40,
+            background: [255, 255, 255],
+            grid: [153, 153, 153],
+            hole: [68, 68, 68],
+            numbers: Some([0, 0, 0]),
+            path: Some([204, 51, 51]),
+        }
+    }
+}
+
+/// Digits 0 to 9 in a 3x5 pixel font, one row per entry, with the top bit
+/// of the three on the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b011, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+#[derive(Debug, Clone, PartialEq)]
+/// Image is an RGB raster image, stored row by row.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// Three bytes per pixel.
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Create an image filled with `color`.
+    fn new(width: u32, height: u32, color: Rgb) -> Image {
+        Image {
+            width,
+            height,
+            pixels: color.repeat((width * height) as usize),
+        }
+    }
+
+    /// Return the color of pixel (x, y).
+    pub fn pixel(&self, x: u32, y: u32) -> Rgb {
+        let i = ((y * self.width + x) * 3) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    /// Mix `color` into pixel (x, y) with weight `alpha` in 0..=1; pixels
+    /// off the image are ignored.
+    fn blend(&mut self, x: i64, y: i64, color: Rgb, alpha: f64) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 3) as usize;
+        for (pixel, level) in self.pixels[i..i + 3].iter_mut().zip(color.iter()) {
+            *pixel = (*pixel as f64 * (1.0 - alpha) + *level as f64 * alpha).round() as u8;
+        }
+    }
+
+    /// Fill the `width` x `height` rectangle at (x, y).
+    fn fill(&mut self, x: i64, y: i64, width: i64, height: i64, color: Rgb) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    /// Fill the disc of `radius` centred on (x, y).
+    fn disc(&mut self, (x, y): (f64, f64), radius: f64, color: Rgb, alpha: f64) {
+        let r = radius.ceil() as i64;
+        for py in y as i64 - r..=y as i64 + r {
+            for px in x as i64 - r..=x as i64 + r {
+                let (dx, dy) = (px as f64 + 0.5 - x, py as f64 + 0.5 - y);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.blend(px, py, color, alpha);
+                }
+            }
+        }
+    }
+
+    /// Draw a line `width` pixels wide from `from` to `to`, with round ends.
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: Rgb, alpha: f64) {
+        let r = width / 2.0;
+        let (x0, y0) = (from.0.min(to.0) - r, from.1.min(to.1) - r);
+        let (x1, y1) = (from.0.max(to.0) + r, from.1.max(to.1) + r);
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = dx * dx + dy * dy;
+        for py in y0.floor() as i64..=y1.ceil() as i64 {
+            for px in x0.floor() as i64..=x1.ceil() as i64 {
+                let (x, y) = (px as f64 + 0.5, py as f64 + 0.5);
+                // distance from the pixel to the nearest point of the segment
+                let t = if length == 0.0 {
+                    0.0
+                } else {
+                    (((x - from.0) * dx + (y - from.1) * dy) / length).clamp(0.0, 1.0)
+                };
+                let (nx, ny) = (from.0 + t * dx - x, from.1 + t * dy - y);
+                if nx * nx + ny * ny <= r * r {
+                    self.blend(px, py, color, alpha);
+                }
+            }
+        }
+    }
+
+    /// Write `value` centred on (x, y) with pixels `scale` wide.
+    fn number(&mut self, (x, y): (f64, f64), value: usize, scale: i64, color: Rgb) {
+        let digits: Vec<usize> = value
+            .to_string()
+            .bytes()
+            .map(|c| (c - b'0') as usize)
+            .collect();
+        let width = (digits.len() as i64 * 4 - 1) * scale;
+        let left = x as i64 - width / 2;
+        let top = y as i64 - 5 * scale / 2;
+        for (i, digit) in digits.iter().enumerate() {
+            for (row, bits) in DIGITS[*digit].iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) != 0 {
+                        self.fill(
+                            left + (i as i64 * 4 + column) * scale,
+                            top + row as i64 * scale,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draw `board` as a raster image: the grid with the holes filled in, then
+/// the path and the values on top.
+pub fn to_image(board: &Board, options: &PngOptions) -> Image {
+    let size = options.cell_size.max(1);
+    let mut image = Image::new(
+        board.width() as u32 * size + 1,
+        board.height() as u32 * size + 1,
+        options.background,
+    );
+    let size = size as i64;
+    for (x, y, _) in board.iter_cells() {
+        let (left, top) = (x as i64 * size, y as i64 * size);
+        if board.is_hole(Coord::new(x, y)) {
+            image.fill(left, top, size, size, options.hole);
+        }
+        image.fill(left, top, size + 1, 1, options.grid);
+        image.fill(left, top + size, size + 1, 1, options.grid);
+        image.fill(left, top, 1, size + 1, options.grid);
+        image.fill(left + size, top, 1, size + 1, options.grid);
+    }
+
+    let size = size as f64;
+    let centre = |cell: Coord| ((cell.x as f64 + 0.5) * size, (cell.y as f64 + 0.5) * size);
+    let cells = filled_cells(board);
+    if let (Some(color), false) = (options.path, cells.is_empty()) {
+        for run in path_runs(board, &cells) {
+            for pair in run.windows(2) {
+                image.line(centre(pair[0]), centre(pair[1]), size / 16.0, color, 0.6);
+            }
+        }
+        image.disc(centre(cells[0]), size / 8.0, color, 1.0);
+        let last = centre(cells[cells.len() - 1]);
+        image.disc(last, size * 0.4, color, 0.3);
+    }
+    if let Some(color) = options.numbers {
+        let scale = ((size * 0.4 / 5.0) as i64).max(1);
+        for (value, cell) in (1..).zip(&cells) {
+            image.number(centre(*cell), value, scale, color);
+        }
+    }
+    image
+}
+
+#[cfg(feature = "png")]
+/// Draw `board` as a PNG image, as `to_image` does, and return the encoded
+/// file.
+pub fn to_png(board: &Board, options: &PngOptions) -> Vec<u8> {
+    let image = to_image(board, options);
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&image.pixels))
+        .expect("writing a PNG to memory cannot fail");
+    data
+}
+
+#[cfg(feature = "gif")]
+/// Draw how `board` was filled as an animated GIF: one frame per value
+/// placed, each drawn as `to_image` does and shown for `delay`, looping
+/// forever.
+pub fn to_gif(board: &Board, options: &PngOptions, delay: Duration) -> Vec<u8> {
+    let mut frames = vec![board.clone()];
+    let mut earlier = board.clone();
+    while earlier.score() > 1 {
+        earlier
+            .unmake_move()
+            .expect("a filled board can be unwound");
+        frames.push(earlier.clone());
+    }
+    frames.reverse();
+
+    let images: Vec<Image> = frames
+        .iter()
+        .map(|frame| to_image(frame, options))
+        .collect();
+    let (width, height) = (images[0].width as u16, images[0].height as u16);
+    let mut data = Vec::new();
+    {
+        let mut encoder = gif::Encoder::new(&mut data, width, height, &[])
+            .expect("writing to memory cannot fail");
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .expect("writing to memory cannot fail");
+        for image in &images {
+            let mut frame = gif::Frame::from_rgb_speed(width, height, &image.pixels, 10);
+            // the delay is counted in hundredths of a second
+            frame.delay = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+            encoder
+                .write_frame(&frame)
+                .expect("writing to memory cannot fail");
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SOLUTION_5;
+    use crate::{BoardBuilder, MoveRules};
+
+    #[test]
+    fn unicode_grid() {
+        let board = BoardBuilder::new()
+            .size(5)
+            .hole(Coord::new(1, 1))
+            .value(Coord::new(0, 0), 1)
+            .value(Coord::new(3, 0), 2)
+            .build()
+            .unwrap();
+        let text = to_unicode(&board);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "┌────┬────┬────┬────┬────┐");
+        assert_eq!(lines[1], "│  1 │  . │  . │  2 │  . │");
+        assert_eq!(lines[2], "├────┼────┼────┼────┼────┤");
+        assert_eq!(lines[3], "│  . │ ░░ │  . │  . │  . │");
+        assert_eq!(lines[10], "└────┴────┴────┴────┴────┘");
+        let torus = board.with_rules(MoveRules::CLASSIC.toroidal());
+        assert!(to_unicode(&torus).starts_with("torus\n┌"));
+
+        let board = SOLUTION_5.board();
+        assert!(Style::Unicode
+            .render(&board)
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("│  1 │ 24 │"));
+        assert_eq!(Style::Plain.render(&board), board.to_string());
+    }
+
+    #[test]
+    fn colors() {
+        let board = SOLUTION_5.board_after(3);
+        for style in [Style::Plain, Style::Unicode] {
+            let options = RenderOptions { style, color: true };
+            let text = options.render(&board);
+            assert!(text.contains("\x1b[38;2;80;140;255m"));
+            assert_eq!(text.matches("\x1b[1;7;").count(), 1);
+            assert!(text.contains("\x1b[2m"));
+            // without the escapes the drawing is unchanged
+            let mut stripped = String::new();
+            let mut escape = false;
+            for c in text.chars() {
+                match c {
+                    '\x1b' => escape = true,
+                    'm' if escape => escape = false,
+                    c if !escape => stripped.push(c),
+                    _ => {}
+                }
+            }
+            assert_eq!(stripped, style.render(&board));
+        }
+        let last = SOLUTION_5.board();
+        let text = RenderOptions {
+            style: Style::Plain,
+            color: true,
+        }
+        .render(&last);
+        assert!(text.contains("\x1b[1;7;38;2;255;80;60m25\x1b[0m"));
+    }
+
+    #[test]
+    fn svg() {
+        let board = SOLUTION_5.board();
+        let svg = to_svg(&board, &SvgOptions::default());
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("width=\"200\" height=\"200\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect ").count(), 26);
+        assert_eq!(svg.matches("<text ").count(), 25);
+        assert_eq!(svg.matches("<polyline ").count(), 1);
+        assert!(svg.contains(">25</text>"));
+        assert!(svg.contains("<polyline points=\"20,20 "));
+
+        let options = SvgOptions {
+            cell_size: 10.0,
+            numbers: false,
+            path: false,
+        };
+        let svg = to_svg(&Board::new(5), &options);
+        assert!(svg.contains("width=\"50\""));
+        assert!(!svg.contains("<text") && !svg.contains("<polyline") && !svg.contains("<circle"));
+
+        // the path is broken where a move wraps around
+        let mut torus = Board::new(5).with_rules(MoveRules::CLASSIC.toroidal());
+        torus = torus.start_at(Coord::new(0, 0)).unwrap();
+        torus.make_move(crate::Direction::Up).unwrap();
+        torus.make_move(crate::Direction::Right).unwrap();
+        let svg = to_svg(&torus, &SvgOptions::default());
+        assert_eq!(svg.matches("<polyline ").count(), 1);
+        assert!(svg.contains("points=\"20,100 140,100\""));
+    }
+
+    #[test]
+    fn raster() {
+        let board = SOLUTION_5.board();
+        let options = PngOptions::default();
+        let image = to_image(&board, &options);
+        assert_eq!((image.width, image.height), (201, 201));
+        assert_eq!(image.pixels.len(), 201 * 201 * 3);
+        assert_eq!(image.pixel(0, 0), options.grid);
+        assert_eq!(image.pixel(40, 7), options.grid);
+        assert_eq!(image.pixel(3, 3), options.background);
+        // the 1 at A1 is drawn over a dot at the start of the path
+        assert_eq!(image.pixel(20, 20), options.numbers.unwrap());
+        assert!(image.pixels.chunks(3).any(|pixel| pixel == [204, 51, 51]));
+
+        let plain = PngOptions {
+            cell_size: 10,
+            numbers: None,
+            path: None,
+            ..options
+        };
+        let holes = BoardBuilder::new()
+            .size(5)
+            .hole(Coord::new(2, 2))
+            .build()
+            .unwrap();
+        let image = to_image(&holes, &plain);
+        assert_eq!(image.width, 51);
+        assert_eq!(image.pixel(25, 25), plain.hole);
+        assert_eq!(image.pixel(15, 15), plain.background);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png() {
+        let data = to_png(&SOLUTION_5.board(), &PngOptions::default());
+        assert_eq!(&data[..8], b"\x89PNG\r\n\x1a\n");
+        let decoder = png::Decoder::new(&data[..]);
+        let reader = decoder.read_info().unwrap();
+        assert_eq!(reader.info().width, 201);
+    }
+
+    #[cfg(feature = "gif")]
+    #[test]
+    fn gif() {
+        let board = SOLUTION_5.board_after(4);
+        let data = to_gif(&board, &PngOptions::default(), Duration::from_millis(250));
+        assert_eq!(&data[..6], b"GIF89a");
+        let mut decoder = gif::DecodeOptions::new().read_info(&data[..]).unwrap();
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 25);
+            frames += 1;
+        }
+        assert_eq!(frames, board.score());
+    }
+
+    #[test]
+    fn parse_styles() {
+        assert_eq!("plain".parse(), Ok(Style::Plain));
+        assert_eq!(" Unicode ".parse(), Ok(Style::Unicode));
+        assert!("fancy".parse::<Style>().is_err());
+        assert_eq!(Style::Unicode.to_string(), "unicode");
+        assert_eq!(Style::default(), Style::Plain);
+    }
+}
@@ -0,0 +1,357 @@
+/*!
+An interactive session for exploring positions by hand.
+
+Where `engine` speaks a protocol for programs, the REPL is for people: it
+keeps one board between commands, shows it after every change and answers
+in plain sentences. Moves are written as in the engine protocol (`U`, `DR`,
+...) or in full (`down right`), and cells as in the rest of the crate
+(`D4`, or `3,3` counting from 0).
+
+Commands:
+
+```text
+new <size> [torus]        start over on an empty board
+start <x> <y> | <cell>    place the 1
+move <move>...            play one or more moves
+undo [<n>]                take back the last move, or the last n
+hint [nodes <n>]          suggest the next move
+solve [nodes <n>]         complete the board from here
+show                      draw the board again
+help                      list the commands
+quit                      leave
+```
+ */
+
+use crate::board::{Board, Direction, MoveRules};
+use crate::coord::{Coord, CoordError};
+use crate::render::RenderOptions;
+use crate::solver::{Outcome, Solver, SolverConfig};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// The node budget of `solve` when none is given.
+pub const SOLVE_NODE_LIMIT: u64 = 10_000_000;
+
+/// The text of the `help` command.
+const HELP: &str = "\
+new <size> [torus]        start over on an empty board
+start <x> <y> | <cell>    place the 1, e.g. start 4 4 or start E5
+move <move>...            play one or more moves, e.g. move dr r
+undo [<n>]                take back the last move, or the last n
+hint [nodes <n>]          suggest the next move
+solve [nodes <n>]         complete the board from here
+show                      draw the board again
+help                      list the commands
+quit                      leave";
+
+#[derive(Debug, Clone, PartialEq)]
+/// ReplCommand is one line typed at the REPL.
+pub enum ReplCommand {
+    New { size: usize, toroidal: bool },
+    Start(Coord),
+    Move(Vec<Direction>),
+    Undo(usize),
+    Hint { nodes: Option<u64> },
+    Solve { nodes: Option<u64> },
+    Show,
+    Help,
+    Quit,
+}
+
+impl FromStr for ReplCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let number = |word: Option<&&str>, what: &str| -> Result<u64, String> {
+            word.and_then(|w| w.parse().ok())
+                .ok_or_else(|| format!("expected {} but found '{}'", what, word.unwrap_or(&"")))
+        };
+        let nodes = |rest: &[&str]| match rest {
+            [] => Ok(None),
+            ["nodes", count] => number(Some(count), "a node count").map(Some),
+            _ => Err(format!(
+                "expected 'nodes <n>' but found '{}'",
+                rest.join(" ")
+            )),
+        };
+        let (name, rest) = match words.split_first() {
+            Some((name, rest)) => (name.to_ascii_lowercase(), rest),
+            None => return Err("empty command".to_string()),
+        };
+        let command = match name.as_str() {
+            "new" => match rest {
+                [size] | [size, "torus"] => ReplCommand::New {
+                    size: number(Some(size), "a size")? as usize,
+                    toroidal: rest.len() == 2,
+                },
+                _ => return Err("expected 'new <size> [torus]'".to_string()),
+            },
+            "start" => match rest {
+                [cell] => {
+                    ReplCommand::Start(cell.parse().map_err(|err: CoordError| err.to_string())?)
+                }
+                [x, y] => ReplCommand::Start(Coord::new(
+                    number(Some(x), "x")? as usize,
+                    number(Some(y), "y")? as usize,
+                )),
+                _ => return Err("expected 'start <x> <y>' or 'start <cell>'".to_string()),
+            },
+            "move" if rest.is_empty() => return Err("expected a move".to_string()),
+            // a move written in full, e.g. `down right`, is one move
+            "move" => match rest.join(" ").parse::<Direction>() {
+                Ok(dir) => ReplCommand::Move(vec![dir]),
+                Err(_) => ReplCommand::Move(
+                    rest.iter()
+                        .map(|word| word.parse().map_err(|_| format!("invalid move '{}'", word)))
+                        .collect::<Result<_, _>>()?,
+                ),
+            },
+            "undo" => match rest {
+                [] => ReplCommand::Undo(1),
+                [n] => ReplCommand::Undo(number(Some(n), "a number of moves")? as usize),
+                _ => return Err("expected 'undo [<n>]'".to_string()),
+            },
+            "hint" => ReplCommand::Hint {
+                nodes: nodes(rest)?,
+            },
+            "solve" => ReplCommand::Solve {
+                nodes: nodes(rest)?,
+            },
+            "show" | "help" | "quit" if !rest.is_empty() => {
+                return Err(format!("unexpected '{}'", rest[0]))
+            }
+            "show" => ReplCommand::Show,
+            "help" => ReplCommand::Help,
+            "quit" | "exit" => ReplCommand::Quit,
+            _ => return Err(format!("unknown command '{}'; try help", name)),
+        };
+        Ok(command)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Session is the board the REPL works on and how it is drawn.
+pub struct Session {
+    board: Board,
+    options: RenderOptions,
+}
+
+impl Session {
+    /// Start a session on `board`, drawing it as `options` say.
+    pub fn new(board: Board, options: RenderOptions) -> Self {
+        Session { board, options }
+    }
+
+    /// Return the current board.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Run one command, returning what to show, or why it failed. The
+    /// board is left as it was if the command fails.
+    pub fn execute(&mut self, command: &ReplCommand) -> Result<String, String> {
+        match command {
+            ReplCommand::New { size, toroidal } => {
+                let rules = if *toroidal {
+                    MoveRules::CLASSIC.toroidal()
+                } else {
+                    MoveRules::CLASSIC
+                };
+                let board = Board::try_new(*size).map_err(|err| err.to_string())?;
+                self.board = board.with_rules(rules);
+                Ok(self.show())
+            }
+            ReplCommand::Start(cell) => {
+                if self.board.is_started() {
+                    return Err("the game has started; use new to start over".to_string());
+                }
+                self.board = self.board.start_at(*cell).map_err(|err| err.to_string())?;
+                Ok(self.show())
+            }
+            ReplCommand::Move(moves) => {
+                self.board = self
+                    .board
+                    .apply_moves(moves)
+                    .map_err(|err| err.to_string())?;
+                Ok(self.show())
+            }
+            ReplCommand::Undo(n) => {
+                let mut board = self.board.clone();
+                for _ in 0..*n {
+                    board.undo().map_err(|err| err.to_string())?;
+                }
+                self.board = board;
+                Ok(self.show())
+            }
+            ReplCommand::Hint { nodes } => {
+                let solver = Solver::new(SolverConfig {
+                    node_limit: *nodes,
+                    ..SolverConfig::default()
+                });
+                let hint = solver.best_move(&self.board).ok_or_else(|| {
+                    if self.board.is_started() {
+                        "there is no move left to play".to_string()
+                    } else {
+                        "the game has not started; choose a start cell first".to_string()
+                    }
+                })?;
+                let why = match hint.outcome {
+                    Outcome::Solved => "the board can still be completed",
+                    Outcome::LimitReached => "no completion found yet; this move gets furthest",
+                    Outcome::Unsolvable => "every move leads to a dead end",
+                };
+                Ok(format!("{} ({}): {}", hint.dir.to_compact(), hint.dir, why))
+            }
+            ReplCommand::Solve { nodes } => {
+                let solver = Solver::new(SolverConfig {
+                    node_limit: Some(nodes.unwrap_or(SOLVE_NODE_LIMIT)),
+                    ..SolverConfig::default()
+                });
+                let result = solver.solve(&self.board);
+                match result.outcome {
+                    Outcome::Solved => {
+                        self.board = result.best;
+                        Ok(self.show())
+                    }
+                    Outcome::Unsolvable => Err("the board cannot be completed from here".into()),
+                    Outcome::LimitReached => Err(format!(
+                        "no completion found within {} positions; the best reached {} of {}",
+                        result.stats.nodes,
+                        result.best.score(),
+                        result.best.cell_count()
+                    )),
+                }
+            }
+            ReplCommand::Show => Ok(self.show()),
+            ReplCommand::Help => Ok(HELP.to_string()),
+            ReplCommand::Quit => Ok(String::new()),
+        }
+    }
+
+    /// Draw the board, with the score and what can be played next.
+    fn show(&self) -> String {
+        let status = if self.board.is_won() {
+            "solved!".to_string()
+        } else if !self.board.is_started() {
+            "choose a start cell".to_string()
+        } else if self.board.is_blocked() {
+            "blocked; undo to take a move back".to_string()
+        } else {
+            let moves: Vec<&str> = self
+                .board
+                .possible_moves()
+                .into_iter()
+                .map(|dir| dir.to_compact())
+                .collect();
+            format!("moves: {}", moves.join(" "))
+        };
+        format!(
+            "{}\n{} / {}, {}",
+            self.options.render(&self.board),
+            self.board.score(),
+            self.board.cell_count(),
+            status
+        )
+    }
+}
+
+/// Run the REPL, reading commands from `input` and writing a prompt and the
+/// answers to `output`, until `quit` or the end of the input.
+pub fn run<R: BufRead, W: Write>(session: &mut Session, input: R, mut output: W) -> io::Result<()> {
+    write!(output, "> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            match line.parse::<ReplCommand>() {
+                Ok(ReplCommand::Quit) => return Ok(()),
+                Ok(command) => match session.execute(&command) {
+                    Ok(text) => writeln!(output, "{}", text)?,
+                    Err(message) => writeln!(output, "error: {}", message)?,
+                },
+                Err(message) => writeln!(output, "error: {}", message)?,
+            }
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SOLUTION_5;
+
+    fn transcript(input: &str) -> (Session, String) {
+        let mut session = Session::new(Board::new(5), RenderOptions::default());
+        let mut output = Vec::new();
+        run(&mut session, input.as_bytes(), &mut output).unwrap();
+        (session, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(
+            "new 8 torus".parse(),
+            Ok(ReplCommand::New {
+                size: 8,
+                toroidal: true
+            })
+        );
+        assert_eq!(
+            "start 5 4".parse(),
+            Ok(ReplCommand::Start(Coord::new(5, 4)))
+        );
+        assert_eq!("start B3".parse(), Ok(ReplCommand::Start(Coord::new(1, 2))));
+        assert_eq!(
+            "move dr R".parse(),
+            Ok(ReplCommand::Move(vec![
+                Direction::DownRight,
+                Direction::Right
+            ]))
+        );
+        assert_eq!(
+            "move down right".parse(),
+            Ok(ReplCommand::Move(vec![Direction::DownRight]))
+        );
+        assert_eq!("undo 3".parse(), Ok(ReplCommand::Undo(3)));
+        assert_eq!(
+            "solve nodes 100".parse(),
+            Ok(ReplCommand::Solve { nodes: Some(100) })
+        );
+        assert_eq!("HINT".parse(), Ok(ReplCommand::Hint { nodes: None }));
+        for bad in [
+            "",
+            "new",
+            "start 1",
+            "move",
+            "move sideways",
+            "hint fast",
+            "show all",
+        ] {
+            assert!(bad.parse::<ReplCommand>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn explore_a_position() {
+        let (session, text) = transcript("start 0 0\nmove r d\nundo\nshow\nquit\nmove l\n");
+        assert_eq!(session.board(), &SOLUTION_5.board_after(1));
+        assert!(text.starts_with("> "));
+        assert!(text.contains("\n2 / 25, moves: "));
+        assert!(text.ends_with("> "));
+
+        let (session, text) = transcript("hint\nstart A1\nhint\nsolve\nmove r\nnew 6\n");
+        assert!(text.contains("error: the game has not started"));
+        assert!(text.contains("): the board can still be completed"));
+        assert!(text.contains("25 / 25, solved!"));
+        assert!(text.contains("error: "));
+        assert_eq!(session.board(), &Board::new(6));
+
+        let (session, text) = transcript("start 2 2\nundo 2\nstart 9 9\nbogus\n");
+        assert_eq!(session.board().score(), 1);
+        assert_eq!(text.matches("error: ").count(), 3);
+    }
+}
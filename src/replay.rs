@@ -0,0 +1,237 @@
+/*!
+Replay files: a solo game as its board size, start cell and moves.
+
+A replay is the shortest way to write down a game, so it is the format for
+sharing tours. After the header come three lines, with an optional `torus`
+line before the start for boards whose moves wrap around:
+
+```text
+square100 replay v1
+size 5x5
+start A1
+moves D R U DL DR U DL R UL D UR UL DL R UL R DL DL U DR UR D L U
+```
+
+The moves are written in their compact form. Loading a replay plays the
+moves out, so a file with an illegal move is rejected.
+ */
+
+use crate::board::{Board, BoardBuilder, BoardError, Direction, MoveRules};
+use crate::coord::Coord;
+use crate::format::{Format, FormatError};
+use failure::Fail;
+use std::fmt;
+use std::str::FromStr;
+
+/// The text format of a replay.
+pub const REPLAY_FORMAT: Format = Format {
+    kind: "replay",
+    version: 1,
+    migrations: &[],
+};
+
+#[derive(Debug, Fail, PartialEq)]
+/// Errors raised while reading a replay.
+pub enum ReplayError {
+    #[fail(display = "{}", _0)]
+    Board(#[cause] BoardError),
+    #[fail(display = "{}", _0)]
+    Format(#[cause] FormatError),
+    #[fail(display = "Expected '{}' line, found '{}'", expected, line)]
+    BadLine { expected: String, line: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Replay is a solo game on a board without holes, from its start cell.
+pub struct Replay {
+    pub width: usize,
+    pub height: usize,
+    /// `true` if moves wrap around the edges of the board.
+    pub torus: bool,
+    pub start: Coord,
+    pub moves: Vec<Direction>,
+}
+
+impl Replay {
+    /// Return the replay of the game played on `board`, which must be
+    /// started, have no holes and use the classic moves.
+    pub fn from_board(board: &Board) -> Result<Replay, BoardError> {
+        if let Some((x, y, _)) = board
+            .iter_cells()
+            .find(|&(x, y, _)| board.is_hole(Coord::new(x, y)))
+        {
+            return Err(BoardError::Hole { x, y });
+        }
+        let start = board.position_of(1).ok_or(BoardError::NotStarted)?;
+        Ok(Replay {
+            width: board.width(),
+            height: board.height(),
+            torus: board.rules().is_toroidal(),
+            start,
+            moves: board.to_moves()?,
+        })
+    }
+
+    /// Return the empty board the game is played on.
+    pub fn empty_board(&self) -> Result<Board, BoardError> {
+        BoardBuilder::new()
+            .dimensions(self.width, self.height)
+            .rules(MoveRules::CLASSIC)
+            .toroidal(self.torus)
+            .build()
+    }
+
+    /// Play the game out, returning the final board, or the error of the
+    /// first move that cannot be played.
+    pub fn board(&self) -> Result<Board, BoardError> {
+        let mut board = self.empty_board()?.start_at(self.start)?;
+        for dir in &self.moves {
+            board.make_move(*dir)?;
+        }
+        Ok(board)
+    }
+}
+
+impl fmt::Display for Replay {
+    /// Write the replay in `REPLAY_FORMAT`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", REPLAY_FORMAT.header())?;
+        writeln!(f, "size {}x{}", self.width, self.height)?;
+        if self.torus {
+            writeln!(f, "torus")?;
+        }
+        writeln!(f, "start {}", self.start)?;
+        let moves: Vec<&str> = self.moves.iter().map(|dir| dir.to_compact()).collect();
+        writeln!(f, "moves {}", moves.join(" "))
+    }
+}
+
+impl FromStr for Replay {
+    type Err = ReplayError;
+
+    /// Parse a replay written by the `Display` impl, and check that its
+    /// moves can be played.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = REPLAY_FORMAT.upgrade(s).map_err(ReplayError::Format)?;
+        let mut lines = body.lines().map(str::trim).filter(|line| !line.is_empty());
+        let bad = |expected: &str, line: &str| ReplayError::BadLine {
+            expected: expected.to_string(),
+            line: line.to_string(),
+        };
+        let mut line = lines.next().unwrap_or("");
+        let field = |name: &str, line: &str| match line.split_once(' ') {
+            Some((key, value)) if key == name => Ok(value.trim().to_string()),
+            // a game that has only started has no moves after the key
+            None if line == name => Ok(String::new()),
+            _ => Err(bad(name, line)),
+        };
+
+        let size = field("size", line)?;
+        let (width, height) = match size.split_once('x') {
+            Some((width, height)) => (width.parse(), height.parse()),
+            None => (size.parse(), size.parse()),
+        };
+        let (width, height) = match (width, height) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Err(bad("size", line)),
+        };
+        line = lines.next().unwrap_or("");
+        let torus = line == "torus";
+        if torus {
+            line = lines.next().unwrap_or("");
+        }
+        let start = field("start", line)?
+            .parse()
+            .map_err(|_| bad("start", line))?;
+        line = lines.next().unwrap_or("");
+        let moves = field("moves", line)?
+            .split_whitespace()
+            .map(|token| token.parse().map_err(|_| bad("moves", line)))
+            .collect::<Result<_, _>>()?;
+        if let Some(extra) = lines.next() {
+            return Err(bad("end of replay", extra));
+        }
+
+        let replay = Replay {
+            width,
+            height,
+            torus,
+            start,
+            moves,
+        };
+        replay.board().map_err(ReplayError::Board)?;
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SOLUTION_5, STRANDED_5};
+
+    #[test]
+    fn replay_round_trip() {
+        let board = SOLUTION_5.board();
+        let replay = Replay::from_board(&board).unwrap();
+        assert_eq!(replay.start, SOLUTION_5.start);
+        assert_eq!(replay.moves, SOLUTION_5.moves);
+        let text = replay.to_string();
+        assert!(text.starts_with("square100 replay v1\nsize 5x5\nstart A1\nmoves "));
+        let parsed: Replay = text.parse().unwrap();
+        assert_eq!(parsed, replay);
+        assert_eq!(parsed.board().unwrap(), board);
+
+        // a square size may be written once, and the header left out
+        let short: Replay = "size 5\nstart A1\nmoves R\n".parse().unwrap();
+        assert_eq!(short.board().unwrap().score(), 2);
+
+        let torus = Board::new(5)
+            .with_rules(MoveRules::CLASSIC.toroidal())
+            .start_at(Coord::new(0, 0))
+            .unwrap()
+            .next_move(Direction::Up)
+            .unwrap();
+        let replay = Replay::from_board(&torus).unwrap();
+        assert!(replay.to_string().contains("\ntorus\nstart A1\n"));
+        assert_eq!(
+            replay.to_string().parse::<Replay>().unwrap().board(),
+            Ok(torus)
+        );
+
+        let started =
+            Replay::from_board(&Board::new(5).start_at(Coord::new(2, 2)).unwrap()).unwrap();
+        assert_eq!(started.to_string().parse(), Ok(started));
+
+        let partial = Replay::from_board(&STRANDED_5.board()).unwrap();
+        assert_eq!(partial.board().unwrap(), STRANDED_5.board());
+        assert_eq!(
+            Replay::from_board(&Board::new(5)),
+            Err(BoardError::NotStarted)
+        );
+    }
+
+    #[test]
+    fn bad_replays() {
+        assert_eq!(
+            "size 5\nbegin A1\nmoves R\n".parse::<Replay>(),
+            Err(ReplayError::BadLine {
+                expected: "start".to_string(),
+                line: "begin A1".to_string()
+            })
+        );
+        assert!("size five\nstart A1\nmoves R\n".parse::<Replay>().is_err());
+        assert!("size 5\nstart A1\nmoves R sideways\n"
+            .parse::<Replay>()
+            .is_err());
+        assert!("size 5\nstart A1\nmoves R\nR\n".parse::<Replay>().is_err());
+        // the first move is fine, the second leaves the board
+        assert!(matches!(
+            "size 5\nstart A1\nmoves R R\n".parse::<Replay>(),
+            Err(ReplayError::Board(_))
+        ));
+        assert!(matches!(
+            "square100 board v1\nsize 5\n".parse::<Replay>(),
+            Err(ReplayError::Format(_))
+        ));
+    }
+}
@@ -0,0 +1,370 @@
+/*!
+Scoring rules for the two-player (duel) variant.
+
+In a duel both players share one board and alternate placing the next number:
+the first player places 1, the second player 2, and so on, so the owner of a
+value follows from its parity. A game is *finished* when the player to move
+is blocked (or the board is full), and *adjourned* when it is stopped before
+that, e.g. on a clock or by agreement.
+
+* A finished game is won by the player who moved last.
+* An adjourned game is adjudicated on mobility, as the player who runs out
+  of moves first loses: the player to move scores the moves open to them,
+  and the other player the fewest replies any of those moves leaves them.
+  The higher score wins; equal scores are a draw.
+
+The result is written into the game record and checked again on load, so
+every frontend adjudicating the same record reports the same result. A
+record is an `Artifact`: `to_artifact` writes it with the header of
+`DUEL_FORMAT`.
+ */
+
+use crate::board::{Board, BoardError};
+use crate::format::{Artifact, Format, FormatError, Validate};
+use failure::Fail;
+use std::fmt;
+use std::str::FromStr;
+
+/// The text format of a duel game record.
+pub const DUEL_FORMAT: Format = Format {
+    kind: "duel",
+    version: 1,
+    migrations: &[],
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Player identifies one side of a duel.
+pub enum Player {
+    First,
+    Second,
+}
+
+impl Player {
+    /// Return the player who placed `value`.
+    pub fn of_value(value: u16) -> Player {
+        if value % 2 == 1 {
+            Player::First
+        } else {
+            Player::Second
+        }
+    }
+
+    /// Return the other player.
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::First => Player::Second,
+            Player::Second => Player::First,
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Player::First => write!(f, "first"),
+            Player::Second => write!(f, "second"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Outcome is the adjudicated result of a duel.
+pub enum Outcome {
+    /// The game has not started, so there is nothing to adjudicate.
+    NotStarted,
+    /// `winner` made the last move of a finished game.
+    LastMove { winner: Player },
+    /// `winner` had the higher mobility score when the game was adjourned.
+    Score { winner: Player },
+    /// The game was adjourned with equal scores.
+    Draw,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::NotStarted => write!(f, "not-started"),
+            Outcome::LastMove { winner } => write!(f, "{} last-move", winner),
+            Outcome::Score { winner } => write!(f, "{} score", winner),
+            Outcome::Draw => write!(f, "draw"),
+        }
+    }
+}
+
+#[derive(Debug, Fail, PartialEq)]
+/// Errors raised while reading a duel game record.
+pub enum RecordError {
+    #[fail(display = "{}", _0)]
+    Board(#[cause] BoardError),
+    #[fail(display = "{}", _0)]
+    Format(#[cause] FormatError),
+    #[fail(display = "Expected '{}' line, found '{}'", expected, line)]
+    BadLine { expected: String, line: String },
+    #[fail(
+        display = "Recorded result '{}' does not match adjudicated result '{}'",
+        recorded, computed
+    )]
+    ResultMismatch { recorded: String, computed: String },
+    #[fail(display = "Game is marked finished, but the player to move is not blocked")]
+    NotFinished,
+}
+
+impl From<FormatError> for RecordError {
+    fn from(err: FormatError) -> Self {
+        RecordError::Format(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// GameRecord is a duel, finished or adjourned.
+pub struct GameRecord {
+    /// The shared board.
+    pub board: Board,
+    /// `true` if the game was stopped before the player to move was blocked.
+    pub adjourned: bool,
+}
+
+impl GameRecord {
+    /// Return the mobility score of each player, the first player's first:
+    /// the moves open to the player to move, and the fewest replies any of
+    /// them leaves the other player. Both are 0 before the game has started.
+    pub fn scores(&self) -> (usize, usize) {
+        let to_move = match self.last_mover() {
+            None => return (0, 0),
+            Some(last) => last.opponent(),
+        };
+        let moves = self.board.possible_moves();
+        let replies = moves
+            .into_iter()
+            .filter_map(|dir| self.board.degree_after(dir))
+            .min()
+            .unwrap_or(0);
+        match to_move {
+            Player::First => (moves.len(), replies),
+            Player::Second => (replies, moves.len()),
+        }
+    }
+
+    /// Return the player who made the last move, if any.
+    pub fn last_mover(&self) -> Option<Player> {
+        match self.board.score() {
+            0 => None,
+            n => Some(Player::of_value(n as u16)),
+        }
+    }
+
+    /// Adjudicate the game. A game that is blocked is treated as finished
+    /// even if it was marked adjourned.
+    pub fn adjudicate(&self) -> Outcome {
+        let last = match self.last_mover() {
+            None => return Outcome::NotStarted,
+            Some(last) => last,
+        };
+        if !self.adjourned || self.board.is_blocked() {
+            return Outcome::LastMove { winner: last };
+        }
+        let (first, second) = self.scores();
+        if first > second {
+            Outcome::Score {
+                winner: Player::First,
+            }
+        } else if second > first {
+            Outcome::Score {
+                winner: Player::Second,
+            }
+        } else {
+            Outcome::Draw
+        }
+    }
+}
+
+impl fmt::Display for GameRecord {
+    /// Write the body of the record in `DUEL_FORMAT`: status, result and
+    /// grid.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = if self.adjourned {
+            "adjourned"
+        } else {
+            "finished"
+        };
+        writeln!(f, "status {}", status)?;
+        writeln!(f, "result {}", self.adjudicate())?;
+        write!(f, "{}", self.board)
+    }
+}
+
+impl Validate for GameRecord {
+    type Error = RecordError;
+
+    /// Check the board, and that a game marked finished is: the player to
+    /// move is blocked.
+    fn validate(&self) -> Result<(), RecordError> {
+        self.board.validate().map_err(RecordError::Board)?;
+        if !self.adjourned && !self.board.is_blocked() {
+            return Err(RecordError::NotFinished);
+        }
+        Ok(())
+    }
+}
+
+impl Artifact for GameRecord {
+    const FORMAT: Format = DUEL_FORMAT;
+
+    /// Parse a body written by the `Display` impl, rejecting it if the
+    /// recorded result disagrees with these rules.
+    fn parse_body(body: &str) -> Result<Self, RecordError> {
+        let mut lines = body.splitn(3, '\n');
+        let mut field = |name: &str| {
+            let line = lines.next().unwrap_or("").trim();
+            let prefix = format!("{} ", name);
+            if line.starts_with(&prefix) {
+                Ok(line[prefix.len()..].trim().to_string())
+            } else {
+                Err(RecordError::BadLine {
+                    expected: name.to_string(),
+                    line: line.to_string(),
+                })
+            }
+        };
+        let adjourned = match field("status")?.as_str() {
+            "adjourned" => true,
+            "finished" => false,
+            other => {
+                return Err(RecordError::BadLine {
+                    expected: "status".to_string(),
+                    line: other.to_string(),
+                })
+            }
+        };
+        let recorded = field("result")?;
+        let board = lines
+            .next()
+            .unwrap_or("")
+            .parse::<Board>()
+            .map_err(RecordError::Board)?;
+        let record = GameRecord { board, adjourned };
+        let computed = record.adjudicate().to_string();
+        if recorded != computed {
+            return Err(RecordError::ResultMismatch { recorded, computed });
+        }
+        Ok(record)
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = RecordError;
+
+    /// Parse a record written by `to_artifact`, rejecting it if the
+    /// recorded result disagrees with these rules, or if it is marked
+    /// finished while the player to move could still move.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GameRecord::parse_artifact(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Direction;
+    use crate::coord::Coord;
+
+    fn game(moves: &[Direction], adjourned: bool) -> GameRecord {
+        let mut board = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        for m in moves {
+            board = board.next_move(*m).unwrap();
+        }
+        GameRecord { board, adjourned }
+    }
+
+    #[test]
+    fn adjudicate_finished_and_adjourned() {
+        let empty = GameRecord {
+            board: Board::new(5),
+            adjourned: false,
+        };
+        assert_eq!(empty.adjudicate(), Outcome::NotStarted);
+
+        let opening = [Direction::Right, Direction::Down, Direction::Left];
+        // four numbers placed: last mover is second
+        assert_eq!(
+            game(&opening, false).adjudicate(),
+            Outcome::LastMove {
+                winner: Player::Second
+            }
+        );
+        // first, to move, has one move, which leaves second two replies
+        assert_eq!(game(&opening, true).scores(), (1, 2));
+        assert_eq!(
+            game(&opening, true).adjudicate(),
+            Outcome::Score {
+                winner: Player::Second
+            }
+        );
+        // first has three moves, none leaving second fewer than two replies
+        let diagonal = game(&[Direction::DownRight], true);
+        assert_eq!(diagonal.scores(), (3, 2));
+        assert_eq!(
+            diagonal.adjudicate(),
+            Outcome::Score {
+                winner: Player::First
+            }
+        );
+        assert_eq!(game(&opening[..1], true).scores(), (2, 2));
+        assert_eq!(game(&opening[..1], true).adjudicate(), Outcome::Draw);
+        assert_eq!(empty.scores(), (0, 0));
+    }
+
+    #[test]
+    fn record_round_trip() {
+        let record = game(&[Direction::Right, Direction::Down], true);
+        let text = record.to_artifact();
+        assert!(text.starts_with("square100 duel v1\nstatus adjourned\nresult second score\n"));
+        let parsed: GameRecord = text.parse().unwrap();
+        assert!(parsed.adjourned);
+        assert_eq!(parsed.adjudicate(), record.adjudicate());
+        // the body alone reads the same
+        assert!(record.to_string().starts_with("status adjourned\n"));
+        let body: GameRecord = record.to_string().parse().unwrap();
+        assert_eq!(body.adjudicate(), record.adjudicate());
+
+        let tampered = text.replace("second score", "first score");
+        assert_eq!(
+            tampered.parse::<GameRecord>().err(),
+            Some(RecordError::ResultMismatch {
+                recorded: "first score".to_string(),
+                computed: "second score".to_string()
+            })
+        );
+        assert!("square100 duel v1\nresult draw\n"
+            .parse::<GameRecord>()
+            .is_err());
+    }
+
+    #[test]
+    fn finished_records_are_blocked() {
+        let mut record = game(&[], false);
+        while let Some(dir) = record.board.possible_moves().first() {
+            record.board = record.board.next_move(dir).unwrap();
+        }
+        assert!(record.board.is_blocked());
+        let parsed: GameRecord = record.to_string().parse().unwrap();
+        assert_eq!(parsed.adjudicate(), record.adjudicate());
+
+        // the player to move could go on, so the game is not over
+        let open = game(&[Direction::Right], false);
+        assert_eq!(
+            open.to_string().parse::<GameRecord>().err(),
+            Some(RecordError::NotFinished)
+        );
+        let empty = GameRecord {
+            board: Board::new(5),
+            adjourned: false,
+        };
+        assert_eq!(
+            empty.to_string().parse::<GameRecord>().err(),
+            Some(RecordError::NotFinished)
+        );
+        let adjourned = game(&[Direction::Right], true);
+        assert!(adjourned.to_string().parse::<GameRecord>().is_ok());
+    }
+}
@@ -0,0 +1,83 @@
+/*!
+Whole-board scans, a word of cells at a time.
+
+Some questions can only be answered by looking at every cell: which cells
+are empty, and which empty cell comes first (where a connectivity search
+starts). The solver asks them at every position, and on large boards these
+scans dominate the time spent on one.
+
+With the `simd` feature `Board` answers them from its occupancy bits, 64
+cells to a machine word, rather than cell by cell, skipping whole words of
+filled cells and finding the empty cells of a word by counting trailing
+zeros. Stable Rust has no portable SIMD types, so the words are plain
+`u64`s. The number of empty cells, and the degree of each cell, need no
+scan at all: `Board::remaining` and `Board::degree` keep them up to date.
+ */
+
+#[inline]
+/// Return the number of words holding the bits of `len` cells.
+pub fn words(len: usize) -> usize {
+    len.div_ceil(64)
+}
+
+#[inline]
+/// Return the empty cells of word `w` of the bits `occupied` of a board of
+/// `len` cells.
+fn vacant_word(occupied: &[u64], len: usize, w: usize) -> u64 {
+    let cells = len - w * 64;
+    let on_board = if cells >= 64 { !0 } else { (1 << cells) - 1 };
+    !occupied[w] & on_board
+}
+
+/// Return the empty cells of a board of `len` cells whose occupied cells
+/// are the bits set in `occupied`, in order.
+pub fn vacant(occupied: &[u64], len: usize) -> impl Iterator<Item = usize> + '_ {
+    (0..words(len)).flat_map(move |w| Bits(vacant_word(occupied, len, w)).map(move |b| w * 64 + b))
+}
+
+/// Return the first empty cell of a board of `len` cells whose occupied
+/// cells are the bits set in `occupied`.
+pub fn first_vacant(occupied: &[u64], len: usize) -> Option<usize> {
+    (0..words(len)).find_map(|w| {
+        Bits(vacant_word(occupied, len, w))
+            .next()
+            .map(|b| w * 64 + b)
+    })
+}
+
+/// Bits iterates over the positions of the bits set in a word, lowest
+/// first.
+struct Bits(u64);
+
+impl Iterator for Bits {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let bit = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_match_the_cells() {
+        // 100 cells over two words, a third of them filled
+        let len = 100;
+        let mut occupied = vec![0u64; words(len)];
+        for cell in (0..len).filter(|cell| cell % 3 == 0) {
+            occupied[cell / 64] |= 1 << (cell % 64);
+        }
+        let empty: Vec<usize> = (0..len).filter(|cell| cell % 3 != 0).collect();
+        assert_eq!(vacant(&occupied, len).collect::<Vec<_>>(), empty);
+        assert_eq!(first_vacant(&occupied, len), Some(1));
+        assert_eq!(first_vacant(&[!0, !0], len), None);
+    }
+}
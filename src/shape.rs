@@ -0,0 +1,436 @@
+/*!
+Board shapes and the editor used to design them.
+
+A shape marks which cells of a rectangular grid take part in the puzzle;
+cells outside the shape are holes that are never visited. Shapes are stored
+in a catalog file holding any number of named shapes:
+
+```text
+square100 shape v1
+name plus
+. # # # .
+# # # # #
+# # # # #
+# # # # #
+. # # # .
+```
+
+`Shape::analyze` runs the cheap structural checks on the jump graph of a
+shape, so a design can be rejected as soon as it is drawn.
+`ShapeEditor` holds the state of an interactive editor: a cursor moving over
+the grid and toggling cells in or out of the shape. The `edit-shape`
+subcommand of the `tui` frontend drives it in the terminal.
+ */
+
+use crate::board::{Board, BoardError, Direction, MoveRules};
+use crate::coord::{Coord, Delta};
+use crate::format::{Format, FormatError};
+use failure::Fail;
+use std::fmt;
+use std::str::FromStr;
+
+/// The text format of a shape catalog.
+pub const SHAPE_FORMAT: Format = Format {
+    kind: "shape",
+    version: 1,
+    migrations: &[],
+};
+
+#[derive(Debug, Fail, PartialEq)]
+/// Errors raised while reading a shape catalog.
+pub enum ShapeError {
+    #[fail(display = "{}", _0)]
+    Format(#[cause] FormatError),
+    #[fail(display = "Invalid cell '{}' in shape '{}'", token, name)]
+    InvalidToken { name: String, token: String },
+    #[fail(display = "Rows of shape '{}' have different lengths", name)]
+    Ragged { name: String },
+    #[fail(display = "Expected 'name' line, found '{}'", line)]
+    MissingName { line: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Shape is a named mask over a `width` x `height` grid.
+pub struct Shape {
+    /// The name of the shape in its catalog.
+    pub name: String,
+    width: usize,
+    height: usize,
+    /// `true` for each cell inside the shape, row by row.
+    cells: Vec<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Verdict of the structural checks on a shape.
+pub enum Verdict {
+    /// The shape can not be filled by any tour; the reason says why.
+    Unsolvable(String),
+    /// No structural problem was found. The shape may still be unsolvable.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// ShapeReport summarizes the jump graph of a shape.
+pub struct ShapeReport {
+    /// The number of cells inside the shape.
+    pub cells: usize,
+    /// The number of connected regions of the jump graph.
+    pub regions: usize,
+    /// Cells with no jump to any other cell of the shape.
+    pub isolated: Vec<Coord>,
+    /// Cells with exactly one jump, which can only be a start or an end.
+    pub dead_ends: Vec<Coord>,
+    /// The overall verdict.
+    pub verdict: Verdict,
+}
+
+impl Shape {
+    /// Create a shape covering the whole `width` x `height` grid.
+    pub fn full(name: &str, width: usize, height: usize) -> Self {
+        Shape {
+            name: name.to_string(),
+            width,
+            height,
+            cells: vec![true; width * height],
+        }
+    }
+
+    /// The number of columns of the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows of the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return `true` if (x, y) is inside the shape. Cells off the grid are
+    /// outside.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.cells[y * self.width + x]
+    }
+
+    /// Put (x, y) inside the shape or take it out.
+    pub fn set(&mut self, x: usize, y: usize, inside: bool) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = inside;
+        }
+    }
+
+    /// Create an empty board of the shape, with the cells outside it as
+    /// holes.
+    pub fn to_board(&self) -> Result<Board, BoardError> {
+        Board::from_mask(self.width, self.height, |cell| {
+            self.contains(cell.x, cell.y)
+        })
+    }
+
+    /// Return the cells inside the shape one jump away from (x, y).
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<Coord> {
+        Direction::iterator()
+            .filter_map(|dir| Coord::new(x, y) + Delta::from(dir.delta(&MoveRules::CLASSIC)?))
+            .filter(|to| self.contains(to.x, to.y))
+            .collect()
+    }
+
+    /// Run the structural checks on the jump graph of the shape.
+    pub fn analyze(&self) -> ShapeReport {
+        let inside: Vec<Coord> = (0..self.cells.len())
+            .map(|i| Coord::from_index(i, self.width))
+            .filter(|cell| self.contains(cell.x, cell.y))
+            .collect();
+        let mut isolated = Vec::new();
+        let mut dead_ends = Vec::new();
+        for &cell in &inside {
+            match self.neighbors(cell.x, cell.y).len() {
+                0 => isolated.push(cell),
+                1 => dead_ends.push(cell),
+                _ => {}
+            }
+        }
+
+        let mut region = vec![usize::MAX; self.cells.len()];
+        let mut regions = 0;
+        for &cell in &inside {
+            if region[cell.index(self.width)] != usize::MAX {
+                continue;
+            }
+            let mut stack = vec![cell];
+            region[cell.index(self.width)] = regions;
+            while let Some(current) = stack.pop() {
+                for next in self.neighbors(current.x, current.y) {
+                    if region[next.index(self.width)] == usize::MAX {
+                        region[next.index(self.width)] = regions;
+                        stack.push(next);
+                    }
+                }
+            }
+            regions += 1;
+        }
+
+        let verdict = if inside.is_empty() {
+            Verdict::Unsolvable("the shape has no cells".to_string())
+        } else if inside.len() > 1 && !isolated.is_empty() {
+            Verdict::Unsolvable(format!("{} cell(s) can not be reached", isolated.len()))
+        } else if regions > 1 {
+            Verdict::Unsolvable(format!("the shape splits into {} regions", regions))
+        } else if dead_ends.len() > 2 {
+            Verdict::Unsolvable(format!(
+                "{} cells have a single jump but a tour has only two ends",
+                dead_ends.len()
+            ))
+        } else {
+            Verdict::Unknown
+        };
+        ShapeReport {
+            cells: inside.len(),
+            regions,
+            isolated,
+            dead_ends,
+            verdict,
+        }
+    }
+}
+
+impl fmt::Display for Shape {
+    /// Write the shape as a catalog entry: a name line and a grid of `#`
+    /// (inside) and `.` (outside).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "name {}", self.name)?;
+        for row in self.cells.chunks(self.width) {
+            writeln!(f)?;
+            let row: Vec<&str> = row.iter().map(|&c| if c { "#" } else { "." }).collect();
+            write!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Catalog is an ordered collection of shapes.
+pub struct Catalog {
+    pub shapes: Vec<Shape>,
+}
+
+impl Catalog {
+    /// Return the shape called `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&Shape> {
+        self.shapes.iter().find(|shape| shape.name == name)
+    }
+
+    /// Add `shape` at the end of the catalog, or in place of the shape of
+    /// the same name.
+    pub fn insert(&mut self, shape: Shape) {
+        match self.shapes.iter_mut().find(|old| old.name == shape.name) {
+            Some(old) => *old = shape,
+            None => self.shapes.push(shape),
+        }
+    }
+}
+
+impl fmt::Display for Catalog {
+    /// Write the catalog in `SHAPE_FORMAT`, shapes separated by blank lines.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", SHAPE_FORMAT.header())?;
+        for (i, shape) in self.shapes.iter().enumerate() {
+            if i > 0 {
+                write!(f, "\n\n")?;
+            }
+            write!(f, "{}", shape)?;
+        }
+        writeln!(f)
+    }
+}
+
+impl FromStr for Catalog {
+    type Err = ShapeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = SHAPE_FORMAT.upgrade(s).map_err(ShapeError::Format)?;
+        let mut shapes = Vec::new();
+        let mut lines = body.lines().map(str::trim).peekable();
+        loop {
+            while lines.peek() == Some(&"") {
+                lines.next();
+            }
+            let line = match lines.next() {
+                None => break,
+                Some(line) => line,
+            };
+            if !line.starts_with("name ") {
+                return Err(ShapeError::MissingName {
+                    line: line.to_string(),
+                });
+            }
+            let name = line["name ".len()..].trim().to_string();
+            let mut rows = Vec::new();
+            while let Some(line) = lines.peek() {
+                if line.is_empty() || line.starts_with("name ") {
+                    break;
+                }
+                let mut row = Vec::new();
+                for token in line.split_whitespace() {
+                    row.push(match token {
+                        "#" => true,
+                        "." => false,
+                        _ => {
+                            return Err(ShapeError::InvalidToken {
+                                name,
+                                token: token.to_string(),
+                            })
+                        }
+                    });
+                }
+                rows.push(row);
+                lines.next();
+            }
+            let width = rows.first().map_or(0, Vec::len);
+            if rows.iter().any(|r| r.len() != width) {
+                return Err(ShapeError::Ragged { name });
+            }
+            shapes.push(Shape {
+                name,
+                width,
+                height: rows.len(),
+                cells: rows.concat(),
+            });
+        }
+        Ok(Catalog { shapes })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+/// EditorKey is an input to the shape editor.
+pub enum EditorKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Toggle the cell under the cursor in or out of the shape.
+    Toggle,
+}
+
+#[derive(Debug, Clone)]
+/// ShapeEditor is the state of an interactive shape editor.
+pub struct ShapeEditor {
+    /// The shape being edited.
+    pub shape: Shape,
+    /// The location of the cursor.
+    pub cursor: Coord,
+}
+
+impl ShapeEditor {
+    /// Start editing `shape` with the cursor in the top-left corner.
+    pub fn new(shape: Shape) -> Self {
+        ShapeEditor {
+            shape,
+            cursor: Coord::new(0, 0),
+        }
+    }
+
+    /// Apply one key press. The cursor stops at the edges of the grid.
+    pub fn handle(&mut self, key: EditorKey) {
+        let Coord { x, y } = self.cursor;
+        match key {
+            EditorKey::Up => self.cursor.y = y.saturating_sub(1),
+            EditorKey::Down => self.cursor.y = (y + 1).min(self.shape.height - 1),
+            EditorKey::Left => self.cursor.x = x.saturating_sub(1),
+            EditorKey::Right => self.cursor.x = (x + 1).min(self.shape.width - 1),
+            EditorKey::Toggle => {
+                let inside = self.shape.contains(x, y);
+                self.shape.set(x, y, !inside);
+            }
+        }
+    }
+
+    /// Analyze the shape as drawn so far.
+    pub fn analyze(&self) -> ShapeReport {
+        self.shape.analyze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analyze_full_and_broken_shapes() {
+        let full = Shape::full("square", 5, 5);
+        let report = full.analyze();
+        assert_eq!(report.cells, 25);
+        assert_eq!(report.regions, 1);
+        assert_eq!(report.verdict, Verdict::Unknown);
+
+        // a 2x2 grid has no legal jumps at all
+        let tiny = Shape::full("tiny", 2, 2).analyze();
+        assert_eq!(tiny.isolated.len(), 4);
+        assert!(matches!(tiny.verdict, Verdict::Unsolvable(_)));
+
+        // cutting off both jumps into the corner strands it
+        let mut cut = Shape::full("cut", 5, 5);
+        for cell in full.neighbors(0, 0) {
+            cut.set(cell.x, cell.y, false);
+        }
+        let report = cut.analyze();
+        assert!(report.isolated.contains(&Coord::new(0, 0)));
+        assert!(matches!(report.verdict, Verdict::Unsolvable(_)));
+    }
+
+    #[test]
+    fn catalog_round_trip() {
+        let mut plus = Shape::full("plus", 5, 5);
+        for &(x, y) in &[(0, 0), (4, 0), (0, 4), (4, 4)] {
+            plus.set(x, y, false);
+        }
+        let catalog = Catalog {
+            shapes: vec![plus, Shape::full("wide", 6, 5)],
+        };
+        let text = catalog.to_string();
+        assert!(text.starts_with("square100 shape v1\nname plus\n. # # # .\n"));
+        assert_eq!(text.parse::<Catalog>().unwrap(), catalog);
+        let board = catalog.shapes[0].to_board().unwrap();
+        assert_eq!(board.cell_count(), 21);
+        assert!(board.is_hole(Coord::new(4, 4)));
+        assert_eq!(
+            "name x\n# ? #".parse::<Catalog>().err(),
+            Some(ShapeError::InvalidToken {
+                name: "x".to_string(),
+                token: "?".to_string()
+            })
+        );
+        assert_eq!(
+            "name x\n# #\n#".parse::<Catalog>().err(),
+            Some(ShapeError::Ragged {
+                name: "x".to_string()
+            })
+        );
+        assert!("# #".parse::<Catalog>().is_err());
+
+        let mut catalog = catalog;
+        catalog.insert(Shape::full("plus", 5, 5));
+        catalog.insert(Shape::full("tall", 5, 7));
+        let names: Vec<&str> = catalog.shapes.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["plus", "wide", "tall"]);
+        assert_eq!(catalog.get("plus"), Some(&Shape::full("plus", 5, 5)));
+        assert_eq!(catalog.get("none"), None);
+    }
+
+    #[test]
+    fn editor_moves_and_toggles() {
+        let mut editor = ShapeEditor::new(Shape::full("edit", 5, 5));
+        editor.handle(EditorKey::Left);
+        editor.handle(EditorKey::Up);
+        assert_eq!(editor.cursor, Coord::new(0, 0));
+        for _ in 0..10 {
+            editor.handle(EditorKey::Right);
+        }
+        editor.handle(EditorKey::Down);
+        assert_eq!(editor.cursor, Coord::new(4, 1));
+        editor.handle(EditorKey::Toggle);
+        assert!(!editor.shape.contains(4, 1));
+        assert_eq!(editor.analyze().cells, 24);
+        editor.handle(EditorKey::Toggle);
+        assert!(editor.shape.contains(4, 1));
+    }
+}
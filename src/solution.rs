@@ -0,0 +1,140 @@
+/*!
+Solutions packed at three bits a move.
+
+A solved `Board` keeps every value, its occupancy and degree counts and
+more, about 1.4 kB for a 10x10 board, which is a lot to hold for each of
+the millions of solutions an enumeration can find. A game is fully
+described by its start cell and its moves, and there are eight directions,
+so `Solution` keeps the start and the moves packed three bits apiece: a
+10x10 solution takes 62 bytes, 38 of them moves. The board is rebuilt by
+playing the moves out on an empty board of the shape the game was played
+on.
+ */
+
+use crate::board::{Board, BoardError, Direction};
+use crate::coord::Coord;
+
+/// The number of bits of a packed move.
+const MOVE_BITS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Solution is a game from its start cell, usually a solution, with its
+/// moves packed three bits apiece.
+pub struct Solution {
+    /// The start cell; boards have at most 255 columns and rows.
+    start: (u8, u8),
+    /// The number of moves.
+    len: u16,
+    /// The moves, in the order of `Direction::iterator`, packed from the
+    /// lowest bit of each byte up.
+    moves: Box<[u8]>,
+}
+
+impl Solution {
+    /// Return the game that starts at `start` and plays `moves`.
+    pub fn new(start: Coord, moves: &[Direction]) -> Solution {
+        let mut packed = vec![0u8; (moves.len() * MOVE_BITS).div_ceil(8)];
+        for (i, dir) in moves.iter().enumerate() {
+            let (byte, shift) = (i * MOVE_BITS / 8, i * MOVE_BITS % 8);
+            let bits = (*dir as u16) << shift;
+            packed[byte] |= bits as u8;
+            if shift + MOVE_BITS > 8 {
+                packed[byte + 1] |= (bits >> 8) as u8;
+            }
+        }
+        Solution {
+            start: (start.x as u8, start.y as u8),
+            len: moves.len() as u16,
+            moves: packed.into_boxed_slice(),
+        }
+    }
+
+    /// Return the game played on `board`, which must be started. Errors are
+    /// those of `Board::to_moves`.
+    pub fn from_board(board: &Board) -> Result<Solution, BoardError> {
+        let moves = board.to_moves()?;
+        let start = board.position_of(1).ok_or(BoardError::NotStarted)?;
+        Ok(Solution::new(start, &moves))
+    }
+
+    /// Return the start cell.
+    pub fn start(&self) -> Coord {
+        Coord::new(self.start.0 as usize, self.start.1 as usize)
+    }
+
+    /// Return the number of moves.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Return `true` if no move has been played after the start.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return move `index`, or `None` if there are not that many moves.
+    pub fn get(&self, index: usize) -> Option<Direction> {
+        if index >= self.len() {
+            return None;
+        }
+        let (byte, shift) = (index * MOVE_BITS / 8, index * MOVE_BITS % 8);
+        let mut bits = u16::from(self.moves[byte]);
+        if shift + MOVE_BITS > 8 {
+            bits |= u16::from(self.moves[byte + 1]) << 8;
+        }
+        let dir = (bits >> shift) as usize & ((1 << MOVE_BITS) - 1);
+        Direction::iterator().nth(dir).copied()
+    }
+
+    /// Return the moves in order.
+    pub fn moves(&self) -> impl Iterator<Item = Direction> + '_ {
+        (0..self.len()).map(move |index| self.get(index).unwrap())
+    }
+
+    /// Play the game out on `empty`, an empty board of the shape and rules
+    /// it was played on, and return the board. Errors are those of
+    /// `Board::start_at` and `Board::apply_moves`.
+    pub fn board(&self, empty: &Board) -> Result<Board, BoardError> {
+        let start = self.start();
+        let moves: Vec<Direction> = self.moves().collect();
+        empty.clone().start_at(start)?.apply_moves(&moves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{solutions_10, SOLUTION_5};
+    use std::mem;
+
+    #[test]
+    fn solutions_round_trip() {
+        let board = SOLUTION_5.board();
+        let solution = Solution::from_board(&board).unwrap();
+        assert_eq!(solution.start(), SOLUTION_5.start);
+        assert_eq!(solution.moves().collect::<Vec<_>>(), SOLUTION_5.moves);
+        assert_eq!(solution.get(24), None);
+        assert_eq!(solution.board(&Board::new(5)), Ok(board));
+
+        for board in solutions_10() {
+            let solution = Solution::from_board(&board).unwrap();
+            assert_eq!(solution.len(), 99);
+            assert_eq!(solution.moves.len(), 38);
+            assert!(mem::size_of::<Board>() > 20 * (mem::size_of::<Solution>() + 38));
+            assert_eq!(solution.board(&Board::new(10)).unwrap(), board);
+        }
+
+        // a game that has only started
+        let start = Board::new(6).start_at(Coord::new(2, 4)).unwrap();
+        let solution = Solution::from_board(&start).unwrap();
+        assert!(solution.is_empty());
+        assert_eq!(solution.board(&Board::new(6)), Ok(start));
+        assert_eq!(
+            Solution::from_board(&Board::new(6)),
+            Err(BoardError::NotStarted)
+        );
+        // a board too small for the game
+        let solution = Solution::from_board(&solutions_10()[0]).unwrap();
+        assert!(solution.board(&Board::new(5)).is_err());
+    }
+}
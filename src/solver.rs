@@ -0,0 +1,1438 @@
+/*!
+Depth-first solver for the puzzle.
+
+The solver extends the current position one move at a time, trying the
+moves in Warnsdorff order (the move to the cell with the fewest onward moves
+first) and backtracking out of dead ends. A node budget and a time limit
+bound the search; when either runs out the deepest position found so far is
+still reported.
+
+Positions that provably have no completion are cut off by pruning rules
+(see `Rule`). Each rule can be switched on or off in `SolverConfig`, and
+`SolveStats` counts the positions each rule pruned, so the contribution of
+a rule can be measured on any instance by solving it with and without it.
+
+To see why a search goes the way it does, `Solver::solve_traced` reports
+each step of it as a `SolveEvent`.
+ */
+
+use crate::board::{symmetric_cells, Board, Direction};
+use crate::coord::Coord;
+use crate::ordering::MoveTable;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The solver reads the clock for its time limit once every this many
+/// positions.
+const CLOCK_INTERVAL: u64 = 1024;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Rule is a pruning rule of the solver. Every rule only removes positions
+/// without a completion, so no rule changes whether a board is solvable.
+pub enum Rule {
+    /// An empty cell that cannot be entered, or more than one empty cell
+    /// that can only be the last one filled.
+    Degree,
+    /// The empty cells do not form a single region reachable from the
+    /// current cell.
+    Connectivity,
+    /// Removing one empty cell splits the other empty cells into three or
+    /// more regions, which no single path can visit.
+    Articulation,
+    /// On an empty board, start cells that are a rotation or reflection of
+    /// an earlier start cell are skipped.
+    Symmetry,
+}
+
+impl Rule {
+    /// Every rule, cheapest first.
+    pub const ALL: [Rule; 4] = [
+        Rule::Degree,
+        Rule::Connectivity,
+        Rule::Articulation,
+        Rule::Symmetry,
+    ];
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Rule::Degree => "degree",
+            Rule::Connectivity => "connectivity",
+            Rule::Articulation => "articulation",
+            Rule::Symmetry => "symmetry",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .iter()
+            .find(|rule| rule.to_string() == s.trim())
+            .copied()
+            .ok_or_else(|| format!("unknown pruning rule '{}'", s.trim()))
+    }
+}
+
+/// Parse a comma separated list of rules. `all` and `none` stand for every
+/// rule and no rule.
+pub fn parse_rules(list: &str) -> Result<BTreeSet<Rule>, String> {
+    match list.trim() {
+        "all" => Ok(Rule::ALL.iter().copied().collect()),
+        "none" | "" => Ok(BTreeSet::new()),
+        list => list.split(',').map(str::parse).collect(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// SolverConfig holds the options of a `Solver`. It can be changed between
+/// searches, e.g. to compare the work done with and without a rule.
+pub struct SolverConfig {
+    /// Stop after visiting this many positions; `None` searches until the
+    /// tree is exhausted.
+    pub node_limit: Option<u64>,
+    /// Stop after searching for this long; `None` sets no time limit.
+    pub time_limit: Option<Duration>,
+    /// The pruning rules in use; all of them by default.
+    pub rules: BTreeSet<Rule>,
+    /// A learned move table to break ties in Warnsdorff order with; `None`
+    /// by default. See `ordering`.
+    pub ordering: Option<Arc<MoveTable>>,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            node_limit: None,
+            time_limit: None,
+            rules: Rule::ALL.iter().copied().collect(),
+            ordering: None,
+        }
+    }
+}
+
+impl SolverConfig {
+    /// Return `true` if `rule` is in use.
+    pub fn is_enabled(&self, rule: Rule) -> bool {
+        self.rules.contains(&rule)
+    }
+
+    /// Switch `rule` on or off.
+    pub fn set_rule(&mut self, rule: Rule, enabled: bool) {
+        if enabled {
+            self.rules.insert(rule);
+        } else {
+            self.rules.remove(&rule);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// SolveStats counts the work done by a search.
+pub struct SolveStats {
+    /// The number of positions visited.
+    pub nodes: u64,
+    /// The number of moves taken back.
+    pub backtracks: u64,
+    /// The highest value placed in any position visited.
+    pub max_depth: usize,
+    /// The number of positions cut off by each rule. A position is only
+    /// attributed to the first rule, in `Rule::ALL` order, that prunes it.
+    pub pruned: BTreeMap<Rule, u64>,
+}
+
+impl SolveStats {
+    /// Return the number of positions cut off by `rule`.
+    pub fn pruned_by(&self, rule: Rule) -> u64 {
+        self.pruned.get(&rule).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// SolveEvent is one step of a search, as reported by
+/// `Solver::solve_traced`. Cells are numbered as in `Searchable`.
+pub enum SolveEvent {
+    /// The value `depth` was placed in `cell`.
+    Place { depth: usize, cell: usize },
+    /// The value `depth` was taken back from `cell`, which led to no
+    /// solution.
+    Backtrack { depth: usize, cell: usize },
+    /// The position with `depth` values placed was cut off by `rule`; on
+    /// an empty board a symmetric start cell is cut off at depth 0.
+    Prune { depth: usize, rule: Rule },
+    /// The node budget ran out with `depth` values placed.
+    LimitReached { depth: usize },
+    /// A complete board was found.
+    Solved { depth: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Outcome is how a search ended.
+pub enum Outcome {
+    /// A complete board was found.
+    Solved,
+    /// The whole tree was searched without finding a solution.
+    Unsolvable,
+    /// The node budget ran out first.
+    LimitReached,
+}
+
+/// Searchable is a puzzle the solver can search: a path through numbered
+/// cells, one jump at a time, that must fill every free cell. The solver
+/// places values on one position and takes them back as it backtracks, so
+/// nothing is copied per position visited. `Board` and `Board3D` implement
+/// it.
+pub trait Searchable: Clone {
+    /// Return the number of cells, numbered from 0, including any that can
+    /// never be filled.
+    fn cell_total(&self) -> usize;
+
+    /// Return `true` if `cell` is empty and may still be filled.
+    fn is_free(&self, cell: usize) -> bool;
+
+    /// Return the number of free cells.
+    fn free_count(&self) -> usize {
+        self.free_cells().count()
+    }
+
+    /// Return the free cells in order.
+    fn free_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.cell_total()).filter(move |&c| self.is_free(c))
+    }
+
+    /// Return the lowest free cell.
+    fn first_free(&self) -> Option<usize> {
+        self.free_cells().next()
+    }
+
+    /// Return the number of free cells one jump away from `cell`.
+    fn free_degree(&self, cell: usize) -> usize {
+        self.jumps(cell).filter(|&n| self.is_free(n)).count()
+    }
+
+    /// Return the cell filled last, or `None` if nothing has been filled.
+    fn current_cell(&self) -> Option<usize>;
+
+    /// Return the cells one jump away from `cell`, free or not.
+    fn jumps(&self, cell: usize) -> impl Iterator<Item = usize> + '_;
+
+    /// Return the highest value placed.
+    fn depth(&self) -> usize;
+
+    /// Return `true` if every cell to fill has been filled.
+    fn is_solved(&self) -> bool;
+
+    /// Place the next value in `cell`, which must be free and, unless
+    /// nothing has been filled, one jump away from the current cell.
+    fn fill(&mut self, cell: usize);
+
+    /// Take back the value placed last, making the cell of the one before
+    /// it current again.
+    fn unfill(&mut self);
+
+    /// Return `true` unless starting at `cell` is a rotation or reflection
+    /// of starting at a smaller cell, and so need not be searched.
+    fn is_canonical_start(&self, cell: usize) -> bool;
+
+    /// Return `true` if every jump can be made in reverse, so the number of
+    /// jumps out of a cell is also the number into it. The degree,
+    /// connectivity and articulation rules rely on it, and are skipped
+    /// otherwise.
+    fn is_reversible(&self) -> bool {
+        true
+    }
+}
+
+impl Searchable for Board {
+    fn cell_total(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    fn is_free(&self, cell: usize) -> bool {
+        !self.is_occupied(cell)
+    }
+
+    fn free_count(&self) -> usize {
+        self.remaining()
+    }
+
+    fn free_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        let width = self.width();
+        self.empty_cells().map(move |cell| cell.index(width))
+    }
+
+    fn first_free(&self) -> Option<usize> {
+        self.first_empty().map(|cell| cell.index(self.width()))
+    }
+
+    fn free_degree(&self, cell: usize) -> usize {
+        self.degree(Coord::from_index(cell, self.width()))
+    }
+
+    fn current_cell(&self) -> Option<usize> {
+        self.current_position().map(|cell| cell.index(self.width()))
+    }
+
+    fn jumps(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbors().targets(cell)
+    }
+
+    fn depth(&self) -> usize {
+        self.score()
+    }
+
+    fn is_solved(&self) -> bool {
+        self.is_won()
+    }
+
+    fn fill(&mut self, cell: usize) {
+        let value = self.score() as u16 + 1;
+        self.place_value(cell % self.width(), cell / self.width(), value);
+    }
+
+    fn unfill(&mut self) {
+        self.unmake_move().unwrap();
+    }
+
+    fn is_canonical_start(&self, cell: usize) -> bool {
+        let (width, height) = (self.width(), self.height());
+        let (x, y) = (cell % width, cell / width);
+        // holes and lopsided rules may break the symmetry of the board
+        self.cell_count() < width * height
+            || !self.rules().is_symmetric(width, height)
+            || canonical(width, height, x, y) == (x, y)
+    }
+
+    fn is_reversible(&self) -> bool {
+        self.rules().is_reversible()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// SolveResult is the result of `Solver::solve`.
+pub struct SolveResult<P = Board> {
+    /// How the search ended.
+    pub outcome: Outcome,
+    /// The solution if one was found, otherwise the deepest position seen.
+    pub best: P,
+    /// The work done by the search.
+    pub stats: SolveStats,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Solver searches for a complete board.
+pub struct Solver {
+    pub config: SolverConfig,
+}
+
+impl Solver {
+    /// Create a solver with the given configuration.
+    pub fn new(config: SolverConfig) -> Self {
+        Solver { config }
+    }
+
+    /// Search for a completion of `board`. An unstarted board is tried from
+    /// every start cell in turn, in cell order.
+    pub fn solve<P: Searchable>(&self, board: &P) -> SolveResult<P> {
+        self.solve_traced(board, &mut |_| {})
+    }
+
+    /// Search like `solve`, calling `trace` with each step of the search.
+    pub fn solve_traced<P: Searchable>(
+        &self,
+        board: &P,
+        trace: &mut dyn FnMut(&SolveEvent),
+    ) -> SolveResult<P> {
+        let mut search = Search::new(&self.config, board, trace);
+        self.solve_with(&mut search, board)
+    }
+
+    /// Solve each of `boards` as `solve` would, returning the results in
+    /// the same order. The boards are shared out between `threads` threads,
+    /// at least one.
+    /// Each thread keeps its search state, and the memory it has grown,
+    /// from one board to the next, and boards of the same size and rules
+    /// share one jump table (see `Neighbors`). Every board has the whole
+    /// node and time budget of the configuration to itself.
+    pub fn solve_many<P: Searchable + Send + Sync>(
+        &self,
+        boards: &[P],
+        threads: usize,
+    ) -> Vec<SolveResult<P>> {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; boards.len()]);
+        thread::scope(|scope| {
+            for _ in 0..threads.max(1).min(boards.len()) {
+                scope.spawn(|| {
+                    let mut ignore = |_: &SolveEvent| {};
+                    let mut search = Search::new(&self.config, &boards[0], &mut ignore);
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(board) = boards.get(i) else {
+                            break;
+                        };
+                        search.restart(board);
+                        let result = self.solve_with(&mut search, board);
+                        results.lock().unwrap()[i] = Some(result);
+                    }
+                });
+            }
+        });
+        let results = results.into_inner().unwrap();
+        results.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Search for a completion of `board` with `search`, which must be
+    /// fresh or restarted on `board`.
+    fn solve_with<P: Searchable>(&self, search: &mut Search<P>, board: &P) -> SolveResult<P> {
+        let mut position = board.clone();
+        let solved = if board.current_cell().is_some() {
+            search.run(&mut position)
+        } else {
+            let mut found = false;
+            for cell in (0..board.cell_total()).filter(|&c| board.is_free(c)) {
+                if self.config.is_enabled(Rule::Symmetry) && !board.is_canonical_start(cell) {
+                    *search.stats.pruned.entry(Rule::Symmetry).or_insert(0) += 1;
+                    (search.trace)(&SolveEvent::Prune {
+                        depth: 0,
+                        rule: Rule::Symmetry,
+                    });
+                    continue;
+                }
+                (search.trace)(&SolveEvent::Place { depth: 1, cell });
+                position.fill(cell);
+                found = search.run(&mut position);
+                if found || search.exhausted() {
+                    break;
+                }
+                position.unfill();
+                (search.trace)(&SolveEvent::Backtrack { depth: 1, cell });
+            }
+            found
+        };
+        let outcome = if solved {
+            Outcome::Solved
+        } else if search.exhausted() {
+            Outcome::LimitReached
+        } else {
+            Outcome::Unsolvable
+        };
+        SolveResult {
+            outcome,
+            best: mem::replace(&mut search.best, position),
+            stats: mem::take(&mut search.stats),
+        }
+    }
+
+    /// Search like `solve` on up to `threads` threads. The start cells of
+    /// an empty board, or the first moves from a started one, are shared
+    /// out between the threads, and the solution returned is the one
+    /// `solve` finds. Which other positions are visited, and so the stats,
+    /// vary from run to run; a node limit is shared by all threads, so the
+    /// outcome of a limited search may vary too.
+    pub fn solve_parallel<P: Searchable + Send + Sync>(
+        &self,
+        board: &P,
+        threads: usize,
+    ) -> SolveResult<P> {
+        if threads <= 1 || board.is_solved() {
+            return self.solve(board);
+        }
+        let mut stats = SolveStats::default();
+        let items: Vec<P> = match board.current_cell() {
+            Some(current) => {
+                stats.nodes = 1;
+                stats.max_depth = board.depth();
+                let graph = Graph { board, current };
+                let mut ignore = |_: &SolveEvent| {};
+                let root = Search::new(&self.config, board, &mut ignore);
+                if root.prune(&graph).is_some() {
+                    // there is nothing to share out
+                    return self.solve(board);
+                }
+                let mut order = Vec::new();
+                graph.warnsdorff_order(&mut order, self.config.ordering.as_deref());
+                order
+                    .into_iter()
+                    .map(|(_, cell)| filled(board, cell))
+                    .collect()
+            }
+            None => (0..board.cell_total())
+                .filter(|&cell| board.is_free(cell))
+                .filter(|&cell| {
+                    let skip =
+                        self.config.is_enabled(Rule::Symmetry) && !board.is_canonical_start(cell);
+                    if skip {
+                        *stats.pruned.entry(Rule::Symmetry).or_insert(0) += 1;
+                    }
+                    !skip
+                })
+                .map(|cell| filled(board, cell))
+                .collect(),
+        };
+        let started = board.current_cell().is_some();
+        let deadline = self.config.time_limit.map(|limit| Instant::now() + limit);
+        let work = Work {
+            items,
+            next: AtomicUsize::new(0),
+            nodes: AtomicU64::new(stats.nodes),
+            solved: AtomicUsize::new(usize::MAX),
+            out_of_time: AtomicBool::new(false),
+        };
+        let results: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.min(work.items.len()))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut ignore = |_: &SolveEvent| {};
+                        let mut search = Search::new(&self.config, board, &mut ignore);
+                        search.work = Some(&work);
+                        search.deadline = deadline;
+                        loop {
+                            let item = work.next.fetch_add(1, Ordering::Relaxed);
+                            if item >= work.items.len() || search.stopped_at(item) {
+                                return (search.stats, search.best, None);
+                            }
+                            search.item = item;
+                            let mut position = work.items[item].clone();
+                            if search.run(&mut position) {
+                                work.solved.fetch_min(item, Ordering::Relaxed);
+                                let solution = search.best.clone();
+                                return (search.stats, search.best, Some((item, solution)));
+                            }
+                            if started {
+                                search.stats.backtracks += 1;
+                            }
+                        }
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+
+        let mut best = board.clone();
+        let mut solution: Option<(usize, P)> = None;
+        for (part, part_best, found) in results {
+            stats.nodes += part.nodes;
+            stats.backtracks += part.backtracks;
+            stats.max_depth = stats.max_depth.max(part.max_depth);
+            for (rule, count) in part.pruned {
+                *stats.pruned.entry(rule).or_insert(0) += count;
+            }
+            if part_best.depth() > best.depth() {
+                best = part_best;
+            }
+            if let Some((item, found)) = found {
+                if solution.as_ref().is_none_or(|(first, _)| item < *first) {
+                    solution = Some((item, found));
+                }
+            }
+        }
+        let outcome = match solution {
+            Some((_, solution)) => {
+                best = solution;
+                Outcome::Solved
+            }
+            None if work.out_of_time.load(Ordering::Relaxed)
+                || self
+                    .config
+                    .node_limit
+                    .is_some_and(|limit| work.nodes.load(Ordering::Relaxed) >= limit) =>
+            {
+                Outcome::LimitReached
+            }
+            None => Outcome::Unsolvable,
+        };
+        SolveResult {
+            outcome,
+            best,
+            stats,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Hint is the move `Solver::best_move` suggests.
+pub struct Hint {
+    pub dir: Direction,
+    /// What the search after the move found: `Solved` if the board can
+    /// still be completed, `LimitReached` if that is not known, and
+    /// `Unsolvable` if every move leads to a dead end.
+    pub outcome: Outcome,
+}
+
+/// The node budget of each shallow search made by `Solver::best_move` when
+/// the configuration sets none.
+pub const HINT_NODE_LIMIT: u64 = 100_000;
+
+impl Solver {
+    /// Return the move from `board` most likely to keep it completable, or
+    /// `None` if the board is not started or there is no legal move.
+    ///
+    /// A shallow search is made after each legal move, with the configured
+    /// node limit, or `HINT_NODE_LIMIT` if there is none. A move after
+    /// which a solution is found is best; next is one whose search ran out
+    /// of budget, preferring the one that got furthest; a move proven to
+    /// lead to a dead end is only suggested if every move does. Ties go to
+    /// the move Warnsdorff's rule would play.
+    pub fn best_move(&self, board: &Board) -> Option<Hint> {
+        let shallow = Solver::new(SolverConfig {
+            node_limit: Some(self.config.node_limit.unwrap_or(HINT_NODE_LIMIT)),
+            ..self.config.clone()
+        });
+        let rank = |outcome: &Outcome| match outcome {
+            Outcome::Solved => 2,
+            Outcome::LimitReached => 1,
+            Outcome::Unsolvable => 0,
+        };
+        let mut best: Option<(Hint, usize)> = None;
+        for dir in warnsdorff_order(board) {
+            let next = board.clone().next_move(dir).unwrap();
+            let result = shallow.solve(&next);
+            let depth = result.stats.max_depth.max(next.score());
+            let better = match &best {
+                None => true,
+                Some((hint, best_depth)) => {
+                    (rank(&result.outcome), depth) > (rank(&hint.outcome), *best_depth)
+                }
+            };
+            if better {
+                let solved = result.outcome == Outcome::Solved;
+                best = Some((
+                    Hint {
+                        dir,
+                        outcome: result.outcome,
+                    },
+                    depth,
+                ));
+                if solved {
+                    break;
+                }
+            }
+        }
+        best.map(|(hint, _)| hint)
+    }
+}
+
+/// Work is the state shared by the threads of `Solver::solve_parallel`.
+struct Work<P> {
+    /// The positions to search, in the order `solve` searches them.
+    items: Vec<P>,
+    /// The next item to hand out.
+    next: AtomicUsize,
+    /// The positions visited by all threads.
+    nodes: AtomicU64,
+    /// The first item a solution was found in, or `usize::MAX`.
+    solved: AtomicUsize,
+    /// Set once any thread runs out of time.
+    out_of_time: AtomicBool,
+}
+
+/// Search is the state of one run of the solver.
+struct Search<'a, P> {
+    config: &'a SolverConfig,
+    stats: SolveStats,
+    /// The deepest position seen.
+    best: P,
+    trace: &'a mut dyn FnMut(&SolveEvent),
+    /// The shared work, if this is one thread of a parallel search.
+    work: Option<&'a Work<P>>,
+    /// The item of `work` being searched.
+    item: usize,
+    /// When the time limit runs out.
+    deadline: Option<Instant>,
+    /// Set once the time limit has run out.
+    out_of_time: bool,
+    /// The moves still to try and the levels of `run`, kept between runs.
+    moves: Vec<(usize, usize)>,
+    levels: Vec<(usize, usize)>,
+}
+
+impl<'a, P: Searchable> Search<'a, P> {
+    /// Start a search of `board`, timing it from now.
+    fn new(
+        config: &'a SolverConfig,
+        board: &P,
+        trace: &'a mut dyn FnMut(&SolveEvent),
+    ) -> Search<'a, P> {
+        Search {
+            config,
+            stats: SolveStats::default(),
+            best: board.clone(),
+            trace,
+            work: None,
+            item: 0,
+            deadline: config.time_limit.map(|limit| Instant::now() + limit),
+            out_of_time: false,
+            moves: Vec::new(),
+            levels: Vec::new(),
+        }
+    }
+
+    /// Make the search ready to start on `board`, as `new` would, keeping
+    /// the memory it has grown.
+    fn restart(&mut self, board: &P) {
+        self.stats = SolveStats::default();
+        self.best = board.clone();
+        self.deadline = self.config.time_limit.map(|limit| Instant::now() + limit);
+        self.out_of_time = false;
+    }
+
+    /// Return `true` if the node budget or the time limit has run out.
+    fn exhausted(&self) -> bool {
+        if self.out_of_time
+            || self
+                .work
+                .is_some_and(|work| work.out_of_time.load(Ordering::Relaxed))
+        {
+            return true;
+        }
+        let nodes = match self.work {
+            Some(work) => work.nodes.load(Ordering::Relaxed),
+            None => self.stats.nodes,
+        };
+        self.config.node_limit.is_some_and(|limit| nodes >= limit)
+    }
+
+    /// Return `true` if searching `item` of a parallel search is pointless:
+    /// the budget has run out, or a solution was found in an earlier item.
+    fn stopped_at(&self, item: usize) -> bool {
+        self.exhausted()
+            || self
+                .work
+                .is_some_and(|work| work.solved.load(Ordering::Relaxed) < item)
+    }
+
+    /// Search below `board`, returning `true` once a solution is found. A
+    /// solution is the deepest position there is, so it is left in `best`.
+    /// Values are placed and taken back on `board` itself, and the moves
+    /// still to try at each level wait on one stack, so a position costs no
+    /// copy or allocation. A search that ends within its budget without a
+    /// solution leaves `board` as it was.
+    fn run(&mut self, board: &mut P) -> bool {
+        let (mut moves, mut levels) = (mem::take(&mut self.moves), mem::take(&mut self.levels));
+        moves.clear();
+        levels.clear();
+        let found = self.walk(board, &mut moves, &mut levels);
+        (self.moves, self.levels) = (moves, levels);
+        found
+    }
+
+    /// Run the search of `run`, keeping the moves to try at each level in
+    /// `moves`, as (onward moves, cell) pairs, and for each level below
+    /// `board`, where its moves start in `moves` and the next one to try
+    /// in `levels`. Both start empty.
+    fn walk(
+        &mut self,
+        board: &mut P,
+        moves: &mut Vec<(usize, usize)>,
+        levels: &mut Vec<(usize, usize)>,
+    ) -> bool {
+        match self.visit(board, moves) {
+            Visit::Done(found) => return found,
+            Visit::Expanded(start) => levels.push((start, start)),
+        }
+        while let Some(level) = levels.last_mut() {
+            // the moves of the deepest level are the last ones on the stack
+            if level.1 < moves.len() {
+                let cell = moves[level.1].1;
+                level.1 += 1;
+                (self.trace)(&SolveEvent::Place {
+                    depth: board.depth() + 1,
+                    cell,
+                });
+                board.fill(cell);
+                match self.visit(board, moves) {
+                    Visit::Done(true) => return true,
+                    Visit::Done(false) => {}
+                    Visit::Expanded(start) => {
+                        levels.push((start, start));
+                        continue;
+                    }
+                }
+            } else {
+                let (start, _) = levels.pop().unwrap();
+                moves.truncate(start);
+                if levels.is_empty() {
+                    return false;
+                }
+            }
+            // the position just left has no completion
+            let cell = board.current_cell().unwrap();
+            board.unfill();
+            self.stats.backtracks += 1;
+            if self.stopped_at(self.item) {
+                // each level above counts the way back out as a backtrack
+                self.stats.backtracks += levels.len() as u64 - 1;
+                return false;
+            }
+            (self.trace)(&SolveEvent::Backtrack {
+                depth: board.depth() + 1,
+                cell,
+            });
+        }
+        false
+    }
+
+    /// Count a visit to `board` and check it. If the search below it ends
+    /// here, return whether it is solved; otherwise push its moves onto
+    /// `moves` in Warnsdorff order and return where they start.
+    fn visit(&mut self, board: &P, moves: &mut Vec<(usize, usize)>) -> Visit {
+        let depth = board.depth();
+        if self.exhausted() {
+            (self.trace)(&SolveEvent::LimitReached { depth });
+            return Visit::Done(false);
+        }
+        if self.stopped_at(self.item) {
+            return Visit::Done(false);
+        }
+        self.stats.nodes += 1;
+        if let Some(work) = self.work {
+            work.nodes.fetch_add(1, Ordering::Relaxed);
+        }
+        if self.stats.nodes.is_multiple_of(CLOCK_INTERVAL)
+            && self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.out_of_time = true;
+            if let Some(work) = self.work {
+                work.out_of_time.store(true, Ordering::Relaxed);
+            }
+        }
+        if depth > self.stats.max_depth {
+            self.stats.max_depth = depth;
+            self.best = board.clone();
+        }
+        if board.is_solved() {
+            (self.trace)(&SolveEvent::Solved { depth });
+            return Visit::Done(true);
+        }
+        let current = match board.current_cell() {
+            Some(current) => current,
+            None => return Visit::Done(false),
+        };
+        let graph = Graph { board, current };
+        if let Some(rule) = self.prune(&graph) {
+            *self.stats.pruned.entry(rule).or_insert(0) += 1;
+            (self.trace)(&SolveEvent::Prune { depth, rule });
+            return Visit::Done(false);
+        }
+        let start = moves.len();
+        graph.warnsdorff_order(moves, self.config.ordering.as_deref());
+        Visit::Expanded(start)
+    }
+
+    /// Return the first rule in use that proves the position of `graph`
+    /// has no completion.
+    fn prune(&self, graph: &Graph<P>) -> Option<Rule> {
+        if !graph.board.is_reversible() {
+            return None;
+        }
+        [Rule::Degree, Rule::Connectivity, Rule::Articulation]
+            .iter()
+            .copied()
+            .filter(|rule| self.config.is_enabled(*rule))
+            .find(|rule| match rule {
+                Rule::Degree => graph.degree_fails(),
+                Rule::Connectivity => graph.connectivity_fails(),
+                Rule::Articulation => graph.articulation_fails(),
+                Rule::Symmetry => false,
+            })
+    }
+}
+
+/// Visit is what `Search::visit` found at a position.
+enum Visit {
+    /// The search below the position is over; `true` if it is solved.
+    Done(bool),
+    /// The moves of the position were pushed, starting at this index.
+    Expanded(usize),
+}
+
+/// Return a copy of `board` with the next value placed in `cell`.
+fn filled<P: Searchable>(board: &P, cell: usize) -> P {
+    let mut next = board.clone();
+    next.fill(cell);
+    next
+}
+
+/// Graph is the jump graph of the empty cells of a position, together with
+/// the current cell.
+struct Graph<'a, P> {
+    board: &'a P,
+    current: usize,
+}
+
+impl<'a, P: Searchable> Graph<'a, P> {
+    /// Return the cells one jump away from `cell`.
+    fn jumps(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        self.board.jumps(cell)
+    }
+
+    /// Return the empty cells one jump away from `cell`.
+    fn free_jumps(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        self.jumps(cell).filter(move |n| self.board.is_free(*n))
+    }
+
+    /// Push the empty cells one jump away from the current cell onto
+    /// `order`, each with its number of onward jumps, the one with the
+    /// fewest first. Ties go to the jump `table` has seen in the most
+    /// solutions, if there is a table, and then keep jump order.
+    fn warnsdorff_order(&self, order: &mut Vec<(usize, usize)>, table: Option<&MoveTable>) {
+        let start = order.len();
+        order.extend(
+            self.free_jumps(self.current)
+                .map(|cell| (self.board.free_degree(cell), cell)),
+        );
+        match table {
+            Some(table) => order[start..]
+                .sort_by_key(|&(degree, cell)| (degree, Reverse(table.count(self.current, cell)))),
+            None => order[start..].sort_by_key(|&(degree, _)| degree),
+        }
+    }
+
+    /// The remaining path runs from the current cell through every empty
+    /// cell, so each empty cell needs two neighbors on it, except the one
+    /// filled last. An empty cell whose only neighbor is the current cell
+    /// must be filled next and last.
+    fn degree_fails(&self) -> bool {
+        let remaining = self.board.free_count();
+        let mut ends = 0;
+        for cell in self.board.free_cells() {
+            let entries = self.board.free_degree(cell);
+            if entries > 1 {
+                continue;
+            }
+            let from_current = self.jumps(cell).any(|n| n == self.current);
+            match (entries, from_current) {
+                (0, false) => return true,
+                (0, true) if remaining > 1 => return true,
+                (1, false) | (0, true) => ends += 1,
+                _ => {}
+            }
+        }
+        ends > 1
+    }
+
+    /// The empty cells must form one region, entered from the current cell.
+    fn connectivity_fails(&self) -> bool {
+        let first = match self.board.first_free() {
+            None => return false,
+            Some(first) => first,
+        };
+        if !self.free_jumps(self.current).any(|_| true) {
+            return true;
+        }
+        let mut seen = vec![false; self.board.cell_total()];
+        let mut stack = vec![first];
+        seen[first] = true;
+        let mut reached = 1;
+        while let Some(cell) = stack.pop() {
+            for n in self.free_jumps(cell) {
+                if !seen[n] {
+                    seen[n] = true;
+                    reached += 1;
+                    stack.push(n);
+                }
+            }
+        }
+        reached < self.board.free_count()
+    }
+
+    /// A path through every empty cell that passes a cell splits into at
+    /// most two parts when the cell is removed, so no empty cell may be a
+    /// cut vertex separating three or more regions.
+    fn articulation_fails(&self) -> bool {
+        let root = match self.board.first_free() {
+            None => return false,
+            Some(root) => root,
+        };
+        let mut cuts = Cuts {
+            order: vec![0; self.board.cell_total()],
+            low: vec![0; self.board.cell_total()],
+            next: 1,
+            failed: false,
+        };
+        let children = cuts.search(self, root);
+        cuts.failed || children >= 3
+    }
+}
+
+/// Cuts is the state of Tarjan's depth-first search for cut vertices.
+struct Cuts {
+    /// The visit order of each cell, starting at 1; 0 if not visited.
+    order: Vec<usize>,
+    /// The lowest visit order reachable from each cell's subtree.
+    low: Vec<usize>,
+    next: usize,
+    failed: bool,
+}
+
+/// Frame is a cell on the path of `Cuts::search`.
+struct Frame {
+    cell: usize,
+    parent: Option<usize>,
+    /// The number of the cell's empty neighbors looked at so far.
+    jump: usize,
+    children: usize,
+    /// The number of children whose subtrees removing the cell separates.
+    separated: usize,
+}
+
+impl Cuts {
+    /// Search from `root`, returning its number of children in the search
+    /// tree. The path down the tree can run through every empty cell, so
+    /// it is kept on a stack of its own rather than the thread's.
+    fn search<P: Searchable>(&mut self, graph: &Graph<P>, root: usize) -> usize {
+        self.enter(root);
+        let mut path = vec![Frame::new(root, None)];
+        let mut children = 0;
+        while let Some(frame) = path.last_mut() {
+            let cell = frame.cell;
+            match graph.free_jumps(cell).nth(frame.jump) {
+                Some(n) => {
+                    frame.jump += 1;
+                    if self.order[n] == 0 {
+                        frame.children += 1;
+                        self.enter(n);
+                        path.push(Frame::new(n, Some(cell)));
+                    } else if Some(n) != frame.parent {
+                        self.low[cell] = self.low[cell].min(self.order[n]);
+                    }
+                }
+                None => {
+                    let done = path.pop().unwrap();
+                    match path.last_mut() {
+                        Some(up) => {
+                            self.low[up.cell] = self.low[up.cell].min(self.low[cell]);
+                            if self.low[cell] >= self.order[up.cell] {
+                                up.separated += 1;
+                            }
+                            // removing a non-root cell leaves its separated
+                            // subtrees plus the part of the graph containing
+                            // its parent
+                            if done.separated + 1 >= 3 {
+                                self.failed = true;
+                            }
+                        }
+                        None => children = done.children,
+                    }
+                }
+            }
+        }
+        children
+    }
+
+    /// Give `cell` the next visit order.
+    fn enter(&mut self, cell: usize) {
+        self.order[cell] = self.next;
+        self.low[cell] = self.next;
+        self.next += 1;
+    }
+}
+
+impl Frame {
+    fn new(cell: usize, parent: Option<usize>) -> Frame {
+        Frame {
+            cell,
+            parent,
+            jump: 0,
+            children: 0,
+            separated: 0,
+        }
+    }
+}
+
+/// Return the smallest of the cells that (x, y) maps to under the rotations
+/// and reflections of a `width` x `height` board.
+fn canonical(width: usize, height: usize, x: usize, y: usize) -> (usize, usize) {
+    symmetric_cells(width, height, x, y)
+        .iter()
+        .map(|&(x, y)| (y, x))
+        .min()
+        .map(|(y, x)| (x, y))
+        .unwrap()
+}
+
+/// Return the possible moves from `board`, the move to the cell with the
+/// fewest onward moves first. Ties keep direction order.
+pub fn warnsdorff_order(board: &Board) -> Vec<Direction> {
+    let mut moves: Vec<(usize, Direction)> = board
+        .possible_moves()
+        .into_iter()
+        .map(|dir| (board.degree_after(dir).unwrap(), dir))
+        .collect();
+    moves.sort_by_key(|&(degree, _)| degree);
+    moves.into_iter().map(|(_, dir)| dir).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{MoveRules, MAX_SIZE};
+    use crate::fixtures::{DEAD_END_5, SINGLE_COMPLETION_5, SOLUTION_5, STRANDED_5};
+
+    fn without_rules() -> SolverConfig {
+        SolverConfig {
+            node_limit: None,
+            time_limit: None,
+            rules: BTreeSet::new(),
+            ordering: None,
+        }
+    }
+
+    #[test]
+    fn solves_small_boards() {
+        let solver = Solver::default();
+        let result = solver.solve(&SOLUTION_5.board_after(0));
+        assert_eq!(result.outcome, Outcome::Solved);
+        assert!(result.best.is_won());
+        assert_eq!(result.stats.max_depth, 25);
+        assert!(result.stats.nodes >= 25);
+
+        let result = solver.solve(&Board::new(6));
+        assert_eq!(result.outcome, Outcome::Solved);
+        assert!(result.best.is_won());
+
+        let result = solver.solve(&Board::new(10).start_at(Coord::new(0, 0)).unwrap());
+        assert_eq!(result.outcome, Outcome::Solved);
+
+        let result = solver.solve(&Board::new(20).start_at(Coord::new(0, 0)).unwrap());
+        assert_eq!(result.outcome, Outcome::Solved);
+        assert_eq!(result.best.score(), 400);
+    }
+
+    #[test]
+    fn solves_rectangles() {
+        for &(width, height) in [(5, 6), (6, 8), (9, 7)].iter() {
+            let result = Solver::default().solve(&Board::with_dimensions(width, height));
+            assert_eq!(result.outcome, Outcome::Solved);
+            assert_eq!(result.best.validate(), Ok(()));
+            assert!(result.best.is_won());
+            assert_eq!(result.best.width(), width);
+        }
+    }
+
+    #[test]
+    fn solves_knights_tours() {
+        for &size in [5, 6, 8].iter() {
+            let board = Board::new(size).with_rules(MoveRules::knight());
+            let result = Solver::default().solve(&board);
+            assert_eq!(result.outcome, Outcome::Solved);
+            assert_eq!(result.best.rules(), &MoveRules::knight());
+            assert_eq!(result.best.validate(), Ok(()));
+            assert!(result.best.is_won());
+        }
+
+        // on 5x5 a tour must start on one of the 13 cells of the majority
+        // color
+        let mut board = Board::new(5).with_rules(MoveRules::knight());
+        let result = Solver::default().solve(&board.start_at(Coord::new(1, 0)).unwrap());
+        assert_eq!(result.outcome, Outcome::Unsolvable);
+    }
+
+    #[test]
+    fn solves_rules_without_reverse_moves() {
+        // right, up and left one cell, but never down: the only games climb
+        // the board row by row from the bottom
+        let text = "rules - - 1,0 - 0,-1 - -1,0 -
+                    . . . . .
+                    . . . . .
+                    . . . . .
+                    . . . . .
+                    . . . . .";
+        let board: Board = text.parse().unwrap();
+        assert!(!board.rules().is_reversible());
+        assert!(!board.rules().is_symmetric(5, 5));
+        assert!(MoveRules::CLASSIC.is_symmetric(5, 5) && MoveRules::knight().is_reversible());
+        let result = Solver::default().solve(&board);
+        assert_eq!(result.outcome, Outcome::Solved);
+        assert_eq!(result.best.validate(), Ok(()));
+        assert!(result.best.is_won());
+
+        // from a corner of the bottom row too
+        let mut board = board;
+        let result = Solver::default().solve(&board.start_at(Coord::new(0, 4)).unwrap());
+        assert_eq!(result.outcome, Outcome::Solved);
+        assert!(result.best.is_won());
+    }
+
+    #[test]
+    fn solves_boards_with_holes() {
+        let mut board = Board::new(6);
+        board.block_cell(Coord::new(0, 0)).unwrap();
+        board.block_cell(Coord::new(5, 5)).unwrap();
+        let result = Solver::default().solve(&board);
+        assert_eq!(result.outcome, Outcome::Solved);
+        assert_eq!(result.best.validate(), Ok(()));
+        assert!(result.best.is_won());
+        assert_eq!(result.best.score(), 34);
+        assert!(result.best.is_hole(Coord::new(0, 0)));
+        assert_eq!(result.best.value_at(Coord::new(5, 5)), 0);
+    }
+
+    #[test]
+    fn proves_dead_positions() {
+        let result = Solver::default().solve(&DEAD_END_5.board());
+        assert_eq!(result.outcome, Outcome::Unsolvable);
+        assert!(result.stats.backtracks > 0);
+        assert!(result.best.score() > DEAD_END_5.board().score());
+    }
+
+    #[test]
+    fn trace_reports_each_step() {
+        let mut events = Vec::new();
+        let result = Solver::default()
+            .solve_traced(&DEAD_END_5.board(), &mut |event| events.push(event.clone()));
+        let count = |matches: fn(&SolveEvent) -> bool| events.iter().filter(|e| matches(e)).count();
+        let placed = count(|e| matches!(e, SolveEvent::Place { .. }));
+        assert!(placed > 0);
+        assert_eq!(placed, count(|e| matches!(e, SolveEvent::Backtrack { .. })));
+        assert_eq!(placed as u64, result.stats.backtracks);
+        let pruned: u64 = result.stats.pruned.values().sum();
+        assert_eq!(
+            count(|e| matches!(e, SolveEvent::Prune { .. })) as u64,
+            pruned
+        );
+        assert_eq!(count(|e| matches!(e, SolveEvent::Solved { .. })), 0);
+
+        let mut events = Vec::new();
+        let board = SOLUTION_5.board_after(22);
+        Solver::default().solve_traced(&board, &mut |event| events.push(event.clone()));
+        assert!(matches!(
+            events.first(),
+            Some(SolveEvent::Place { depth: 24, .. })
+        ));
+        assert_eq!(events.last(), Some(&SolveEvent::Solved { depth: 25 }));
+
+        let limited = Solver::new(SolverConfig {
+            node_limit: Some(3),
+            ..SolverConfig::default()
+        });
+        let mut last = None;
+        limited.solve_traced(&Board::new(8), &mut |event| last = Some(event.clone()));
+        assert!(matches!(last, Some(SolveEvent::LimitReached { .. })));
+    }
+
+    #[test]
+    fn parallel_search_agrees() {
+        let solver = Solver::default();
+        for board in [
+            Board::new(6),
+            Board::with_dimensions(6, 8)
+                .start_at(Coord::new(2, 3))
+                .unwrap(),
+            SOLUTION_5.board_after(22),
+            DEAD_END_5.board(),
+        ] {
+            let one = solver.solve(&board);
+            let many = solver.solve_parallel(&board, 4);
+            assert_eq!(many.outcome, one.outcome);
+            if one.outcome == Outcome::Solved {
+                assert_eq!(many.best, one.best);
+            }
+        }
+
+        let limited = Solver::new(SolverConfig {
+            node_limit: Some(100),
+            ..SolverConfig::default()
+        });
+        let result = limited.solve_parallel(&Board::new(12), 4);
+        assert_eq!(result.outcome, Outcome::LimitReached);
+        assert!(result.stats.nodes >= 100);
+        assert!(result.best.score() > 1);
+    }
+
+    #[test]
+    fn batches_agree() {
+        let solver = Solver::new(SolverConfig {
+            node_limit: Some(50_000),
+            ..SolverConfig::default()
+        });
+        let boards: Vec<Board> = vec![
+            Board::new(6),
+            SOLUTION_5.board_after(3),
+            DEAD_END_5.board(),
+            Board::new(7).start_at(Coord::new(1, 0)).unwrap(),
+            Board::new(12).start_at(Coord::new(5, 5)).unwrap(),
+            SOLUTION_5.board(),
+        ];
+        for threads in [0, 1, 3].iter() {
+            let results = solver.solve_many(&boards, *threads);
+            assert_eq!(results.len(), boards.len());
+            for (board, many) in boards.iter().zip(&results) {
+                let one = solver.solve(board);
+                assert_eq!(many.outcome, one.outcome);
+                assert_eq!(many.best, one.best);
+                assert_eq!(many.stats.nodes, one.stats.nodes);
+                assert_eq!(many.stats.pruned, one.stats.pruned);
+            }
+            assert!(solver.solve_many::<Board>(&[], *threads).is_empty());
+        }
+    }
+
+    #[test]
+    fn learned_ordering_breaks_ties() {
+        // from the corner, the jumps right and down have the same number of
+        // onward jumps; SOLUTION_5 goes right and its mirror image down
+        let start = Board::new(5).start_at(Coord::new(0, 0)).unwrap();
+        let solution = SOLUTION_5.board();
+        let mirror = solution
+            .symmetries()
+            .find(|b| b.value_at(Coord::new(0, 0)) == 1 && b.value_at(Coord::new(0, 3)) == 2)
+            .unwrap();
+        for (trained, second) in [(&solution, 3), (&mirror, 15)] {
+            let mut table = MoveTable::for_board(&start);
+            table.train(trained).unwrap();
+            let solver = Solver::new(SolverConfig {
+                ordering: Some(Arc::new(table)),
+                ..SolverConfig::default()
+            });
+            let mut first = None;
+            let result = solver.solve_traced(&start, &mut |event| {
+                if let SolveEvent::Place { depth: 2, cell } = event {
+                    first.get_or_insert(*cell);
+                }
+            });
+            assert_eq!(result.outcome, Outcome::Solved);
+            assert_eq!(first, Some(second));
+            assert_eq!(solver.solve_parallel(&start, 2).best, result.best);
+        }
+    }
+
+    #[test]
+    fn limits_stop_search() {
+        let solver = Solver::new(SolverConfig {
+            node_limit: Some(10),
+            ..without_rules()
+        });
+        let result = solver.solve(&DEAD_END_5.board());
+        assert_eq!(result.outcome, Outcome::LimitReached);
+        assert_eq!(result.stats.nodes, 10);
+
+        // the clock is read every CLOCK_INTERVAL positions; a 7x7 knight's
+        // tour from a cell of the minority color takes far longer to refute
+        let solver = Solver::new(SolverConfig {
+            time_limit: Some(Duration::ZERO),
+            ..SolverConfig::default()
+        });
+        let mut board = Board::new(7).with_rules(MoveRules::knight());
+        let board = board.start_at(Coord::new(1, 0)).unwrap();
+        let result = solver.solve(&board);
+        assert_eq!(result.outcome, Outcome::LimitReached);
+        assert_eq!(result.stats.nodes, CLOCK_INTERVAL);
+        let result = solver.solve_parallel(&board, 3);
+        assert_eq!(result.outcome, Outcome::LimitReached);
+        assert!(result.best.score() > 1);
+    }
+
+    #[test]
+    fn rules_agree_and_are_attributed() {
+        let games = [DEAD_END_5, STRANDED_5, SINGLE_COMPLETION_5];
+        let mut configs = vec![without_rules(), SolverConfig::default()];
+        for rule in Rule::ALL.iter() {
+            let mut config = without_rules();
+            config.set_rule(*rule, true);
+            configs.push(config);
+        }
+        for game in games.iter() {
+            let outcomes: Vec<Outcome> = configs
+                .iter()
+                .map(|config| Solver::new(config.clone()).solve(&game.board()).outcome)
+                .collect();
+            assert!(outcomes.iter().all(|o| *o == outcomes[0]), "{:?}", outcomes);
+        }
+
+        let plain = Solver::new(without_rules()).solve(&DEAD_END_5.board());
+        let pruned = Solver::default().solve(&DEAD_END_5.board());
+        assert!(pruned.stats.nodes < plain.stats.nodes);
+        assert!(plain.stats.pruned.is_empty());
+
+        // the stranded cell has no way in: caught by the degree rule, or
+        // by connectivity when degree is off
+        let mut solver = Solver::default();
+        let result = solver.solve(&STRANDED_5.board());
+        assert_eq!(result.stats.pruned_by(Rule::Degree), 1);
+        assert_eq!(result.stats.nodes, 1);
+        solver.config.set_rule(Rule::Degree, false);
+        let result = solver.solve(&STRANDED_5.board());
+        assert_eq!(result.stats.pruned_by(Rule::Connectivity), 1);
+    }
+
+    #[test]
+    fn articulation_search_fits_the_largest_board() {
+        // the depth-first search runs through all 65,024 empty cells
+        let mut config = without_rules();
+        config.node_limit = Some(1);
+        config.set_rule(Rule::Articulation, true);
+        let board = Board::new(MAX_SIZE).start_at(Coord::new(0, 0)).unwrap();
+        let result = Solver::new(config).solve(&board);
+        assert_eq!(result.outcome, Outcome::LimitReached);
+        assert_eq!(result.stats.pruned_by(Rule::Articulation), 0);
+    }
+
+    #[test]
+    fn symmetric_starts_are_skipped() {
+        let result = Solver::default().solve(&Board::new(5));
+        assert_eq!(result.outcome, Outcome::Solved);
+        assert_eq!(result.stats.pruned_by(Rule::Symmetry), 0);
+        assert_eq!(canonical(5, 5, 4, 4), (0, 0));
+        assert_eq!(canonical(5, 5, 0, 3), (1, 0));
+        assert_eq!(canonical(5, 5, 2, 2), (2, 2));
+        let distinct = (0..25).filter(|i| canonical(5, 5, i % 5, i / 5) == (i % 5, i / 5));
+        assert_eq!(distinct.count(), 6);
+        // a rectangle has no diagonal symmetry
+        assert_eq!(canonical(6, 5, 0, 1), (0, 1));
+        assert_eq!(canonical(6, 5, 5, 4), (0, 0));
+    }
+
+    #[test]
+    fn best_moves() {
+        let solver = Solver::default();
+        let board = SOLUTION_5.board_after(10);
+        let hint = solver.best_move(&board).unwrap();
+        assert_eq!(hint.outcome, Outcome::Solved);
+        let next = board.clone().next_move(hint.dir).unwrap();
+        assert_eq!(solver.solve(&next).outcome, Outcome::Solved);
+
+        let hint = solver.best_move(&DEAD_END_5.board()).unwrap();
+        assert_eq!(hint.outcome, Outcome::Unsolvable);
+        assert_eq!(solver.best_move(&SOLUTION_5.board()), None);
+        assert_eq!(solver.best_move(&Board::new(5)), None);
+
+        // with a tiny budget the move that gets furthest is suggested
+        let solver = Solver::new(SolverConfig {
+            node_limit: Some(1),
+            ..SolverConfig::default()
+        });
+        let hint = solver
+            .best_move(&Board::new(8).start_at(Coord::new(0, 0)).unwrap())
+            .unwrap();
+        assert_eq!(hint.outcome, Outcome::LimitReached);
+    }
+
+    #[test]
+    fn parse_rule_lists() {
+        assert_eq!(parse_rules("all").unwrap().len(), 4);
+        assert!(parse_rules("none").unwrap().is_empty());
+        assert_eq!(
+            parse_rules("degree, symmetry").unwrap(),
+            [Rule::Degree, Rule::Symmetry].iter().copied().collect()
+        );
+        assert_eq!(
+            parse_rules("degree,parity"),
+            Err("unknown pruning rule 'parity'".to_string())
+        );
+    }
+}
@@ -0,0 +1,163 @@
+/*!
+Where the tools keep their files.
+
+Everything the tools save (configuration, the leaderboard, the daily puzzle
+archive, solver checkpoints and tablebases) lives under a few base
+directories. By default these are the platform's conventional locations
+(the XDG base directories on Linux, `~/Library/Application Support` on
+macOS, `%APPDATA%` on Windows). A portable install instead keeps everything
+under one data directory, given with `--data-dir` or the `BOARD100_DATA_DIR`
+environment variable.
+ */
+
+use directories::ProjectDirs;
+use failure::Fail;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming a data directory, as an alternative to
+/// `--data-dir`.
+pub const DATA_DIR_ENV: &str = "BOARD100_DATA_DIR";
+
+#[derive(Debug, Fail)]
+/// Errors raised while locating or creating storage directories.
+pub enum StorageError {
+    #[fail(display = "Cannot determine the home directory; use --data-dir")]
+    NoHomeDirectory,
+    #[fail(display = "Cannot create {}: {}", path, error)]
+    Create {
+        path: String,
+        #[cause]
+        error: io::Error,
+    },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Area is one kind of saved file.
+pub enum Area {
+    /// Configuration files.
+    Config,
+    /// Best scores of finished games.
+    Leaderboard,
+    /// Past daily puzzles.
+    Archive,
+    /// Saved state of interrupted solver runs.
+    Checkpoints,
+    /// Precomputed endgame tables; these can be regenerated, so they are
+    /// kept with other cached data.
+    Tablebases,
+}
+
+impl Area {
+    /// Every area, in the order `board100 paths` lists them.
+    pub const ALL: [Area; 5] = [
+        Area::Config,
+        Area::Leaderboard,
+        Area::Archive,
+        Area::Checkpoints,
+        Area::Tablebases,
+    ];
+}
+
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Area::Config => "config",
+            Area::Leaderboard => "leaderboard",
+            Area::Archive => "archive",
+            Area::Checkpoints => "checkpoints",
+            Area::Tablebases => "tablebases",
+        };
+        f.pad(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Storage holds the base directories of the saved files.
+pub struct Storage {
+    config: PathBuf,
+    data: PathBuf,
+    cache: PathBuf,
+}
+
+impl Storage {
+    /// Return the platform's conventional directories.
+    pub fn discover() -> Result<Storage, StorageError> {
+        let dirs = ProjectDirs::from("", "", "board100").ok_or(StorageError::NoHomeDirectory)?;
+        Ok(Storage {
+            config: dirs.config_dir().to_path_buf(),
+            data: dirs.data_dir().to_path_buf(),
+            cache: dirs.cache_dir().to_path_buf(),
+        })
+    }
+
+    /// Return a portable layout keeping everything under `root`.
+    pub fn portable(root: &Path) -> Storage {
+        Storage {
+            config: root.join("config"),
+            data: root.to_path_buf(),
+            cache: root.join("cache"),
+        }
+    }
+
+    /// Return the portable layout under `data_dir` if one is given, else
+    /// under the directory named by `BOARD100_DATA_DIR` if set, else the
+    /// platform's conventional directories.
+    pub fn new(data_dir: Option<&Path>) -> Result<Storage, StorageError> {
+        match data_dir
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(DATA_DIR_ENV).map(PathBuf::from))
+        {
+            Some(root) => Ok(Storage::portable(&root)),
+            None => Storage::discover(),
+        }
+    }
+
+    /// Return the directory of `area`. The directory may not exist yet.
+    pub fn path(&self, area: Area) -> PathBuf {
+        match area {
+            Area::Config => self.config.clone(),
+            Area::Leaderboard => self.data.join("leaderboard"),
+            Area::Archive => self.data.join("archive"),
+            Area::Checkpoints => self.data.join("checkpoints"),
+            Area::Tablebases => self.cache.join("tablebases"),
+        }
+    }
+
+    /// Return the directory of `area`, creating it if needed.
+    pub fn ensure(&self, area: Area) -> Result<PathBuf, StorageError> {
+        let path = self.path(area);
+        fs::create_dir_all(&path).map_err(|error| StorageError::Create {
+            path: path.display().to_string(),
+            error,
+        })?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn portable_layout() {
+        let root = std::env::temp_dir().join(format!("square100-storage-{}", std::process::id()));
+        let storage = Storage::portable(&root);
+        assert_eq!(Storage::new(Some(&root)).unwrap(), storage);
+        assert_eq!(storage.path(Area::Config), root.join("config"));
+        assert_eq!(
+            storage.path(Area::Tablebases),
+            root.join("cache").join("tablebases")
+        );
+        for area in Area::ALL.iter() {
+            assert!(storage.path(*area).starts_with(&root));
+        }
+
+        let archive = storage.ensure(Area::Archive).unwrap();
+        assert!(archive.is_dir());
+        assert!(storage.ensure(Area::Archive).is_ok());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
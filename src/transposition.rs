@@ -0,0 +1,166 @@
+/*!
+A transposition table of fixed size.
+
+An exhaustive search reaches the same position along many paths: what is
+left of a game depends only on the cells taken and where the path stands
+(see `zobrist`). Remembering the positions already searched to the end
+saves searching them again, but a table of every such position grows with
+the search, without bound on a long count.
+
+`TranspositionTable` holds at most as many entries as fit in the memory it
+is given, and never allocates after it is made. Positions are stored by
+Zobrist key in buckets of two entries. The first entry of a bucket keeps
+the deepest position stored there, the one with the most empty cells, as
+it stands for the most work saved; the second takes the position stored
+last, or the deepest one it displaced. A new position never has to wait
+for room, and the positions that are costly to search again are the last
+to go.
+
+Only keys are compared, so two positions whose keys collide are taken for
+the same one. With 64-bit keys that is vanishingly rare, but it is why a
+table is kept per board shape and rules, where keys are otherwise unique
+per position.
+ */
+
+use std::mem;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+/// Entry is a stored position: its key and its number of empty cells, 0
+/// for an unused entry.
+struct Entry {
+    key: u64,
+    depth: u32,
+}
+
+#[derive(Debug, Clone)]
+/// TranspositionTable is a set of positions, by Zobrist key, of fixed
+/// capacity. When a bucket is full a new position replaces the most
+/// recent one, unless it is deeper than the deepest.
+pub struct TranspositionTable {
+    /// The buckets, two entries each: the deepest, then the most recent.
+    entries: Vec<Entry>,
+    /// The number of buckets less one; the count is a power of two.
+    mask: usize,
+    /// The number of entries in use.
+    len: usize,
+}
+
+/// The memory taken by one bucket.
+const BUCKET_BYTES: usize = 2 * mem::size_of::<Entry>();
+
+impl TranspositionTable {
+    /// Create a table taking at most `bytes` bytes. It has room for no
+    /// position if that is less than one bucket.
+    pub fn with_memory(bytes: usize) -> TranspositionTable {
+        let buckets = match bytes / BUCKET_BYTES {
+            0 => 0,
+            buckets => 1 << buckets.ilog2(),
+        };
+        TranspositionTable {
+            entries: vec![Entry::default(); 2 * buckets],
+            mask: buckets.saturating_sub(1),
+            len: 0,
+        }
+    }
+
+    /// Return the number of positions the table has room for.
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return the number of positions stored.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if no position is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    /// Return `true` if the position of `key` is stored.
+    pub fn contains(&self, key: u64) -> bool {
+        match self.bucket(key) {
+            Some(i) => self.entries[i..i + 2]
+                .iter()
+                .any(|entry| entry.depth > 0 && entry.key == key),
+            None => false,
+        }
+    }
+
+    /// Store the position of `key`, which has `depth` empty cells, at least
+    /// one. If it is at least as deep as the deepest position of its bucket
+    /// it takes its place, and the deepest replaces the most recent;
+    /// otherwise it replaces the most recent.
+    pub fn insert(&mut self, key: u64, depth: usize) {
+        let i = match self.bucket(key) {
+            Some(i) => i,
+            None => return,
+        };
+        if self.contains(key) {
+            return;
+        }
+        let entry = Entry {
+            key,
+            depth: depth.clamp(1, u32::MAX as usize) as u32,
+        };
+        let used = |bucket: &[Entry]| bucket.iter().filter(|entry| entry.depth > 0).count();
+        let before = used(&self.entries[i..i + 2]);
+        if entry.depth >= self.entries[i].depth {
+            self.entries[i + 1] = self.entries[i];
+            self.entries[i] = entry;
+        } else {
+            self.entries[i + 1] = entry;
+        }
+        self.len = self.len + used(&self.entries[i..i + 2]) - before;
+    }
+
+    /// Return the first entry of the bucket of `key`, or `None` if the
+    /// table has no room.
+    fn bucket(&self, key: u64) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        Some(2 * (key as usize & self.mask))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_keep_the_deepest_and_the_latest() {
+        assert_eq!(
+            TranspositionTable::with_memory(BUCKET_BYTES - 1).capacity(),
+            0
+        );
+        let mut none = TranspositionTable::with_memory(0);
+        none.insert(7, 3);
+        assert!(!none.contains(7) && none.is_empty());
+
+        // 2 buckets, as 3 are rounded down
+        let mut table = TranspositionTable::with_memory(3 * BUCKET_BYTES);
+        assert_eq!(table.capacity(), 4);
+        table.insert(0, 5);
+        table.insert(2, 3);
+        table.insert(1, 1);
+        assert!(table.contains(0) && table.contains(2) && table.contains(1));
+        assert_eq!(table.len(), 3);
+        // a shallower position replaces the latest one
+        table.insert(4, 2);
+        assert!(table.contains(0) && table.contains(4) && !table.contains(2));
+        // a deeper one takes the first entry, moving the deepest down
+        table.insert(6, 9);
+        assert!(table.contains(6) && table.contains(0) && !table.contains(4));
+        // storing a position again changes nothing
+        table.insert(6, 9);
+        assert!(table.contains(6) && table.contains(0));
+        assert_eq!(table.len(), 3);
+        table.insert(8, 1);
+        table.insert(3, 4);
+        assert!(table.contains(8) && table.contains(3) && table.contains(1));
+        assert_eq!(table.len(), table.capacity());
+    }
+}
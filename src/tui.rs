@@ -0,0 +1,537 @@
+/*!
+Full-screen terminal frontend, built with the `tui` feature.
+
+The board fills the left of the screen; the score, the available moves and
+the moves played so far are shown in panels on the right. Before the game
+starts the arrow keys (or `h` `j` `k` `l`) move a cursor and Enter places
+the 1. After that every key plays a move: the arrow keys and `h` `j` `k` `l`
+move horizontally and vertically, `y` `u` `b` `n` diagonally, as in vi-style
+games. Backspace takes a move back and `q` or Esc quits.
+
+The shape editor shows a grid of `#` (inside the shape) and `.` (outside)
+with the structural checks of `Shape::analyze` beside it, updated as the
+shape is drawn. The arrow keys (or `h` `j` `k` `l`) move the cursor and
+Space toggles the cell under it. Enter runs the solver on the shape, `s`
+saves it to its catalog file and `q` or Esc quits.
+ */
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::Frame;
+use square100::shape::{Catalog, EditorKey, Shape, ShapeEditor, Verdict};
+use square100::solver::{Outcome, Solver, SolverConfig};
+use square100::{Board, Coord, Direction};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The number of positions the shape editor searches before giving up on
+/// finding a tour.
+const SHAPE_NODE_LIMIT: u64 = 1_000_000;
+
+/// App is the state of the frontend: the game and what the keys act on.
+pub struct App {
+    board: Board,
+    /// The cell the 1 goes on when the game starts.
+    cursor: Coord,
+    /// The moves played, in order.
+    history: Vec<Direction>,
+    /// The last thing worth telling the player.
+    message: String,
+    quit: bool,
+}
+
+impl App {
+    /// Start a game on `board`, which must be empty.
+    pub fn new(board: Board) -> Self {
+        App {
+            board,
+            cursor: Coord::new(0, 0),
+            history: Vec::new(),
+            message: "Choose a start cell and press Enter".to_string(),
+            quit: false,
+        }
+    }
+
+    /// Return the direction a key plays, if any.
+    fn direction(code: KeyCode) -> Option<Direction> {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => Some(Direction::Up),
+            KeyCode::Down | KeyCode::Char('j') => Some(Direction::Down),
+            KeyCode::Left | KeyCode::Char('h') => Some(Direction::Left),
+            KeyCode::Right | KeyCode::Char('l') => Some(Direction::Right),
+            KeyCode::Char('y') => Some(Direction::UpLeft),
+            KeyCode::Char('u') => Some(Direction::UpRight),
+            KeyCode::Char('b') => Some(Direction::DownLeft),
+            KeyCode::Char('n') => Some(Direction::DownRight),
+            _ => None,
+        }
+    }
+
+    /// Act on one key press.
+    pub fn handle(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Backspace => match self.board.undo() {
+                Ok(()) => {
+                    self.history.pop();
+                    self.message = "Took the move back".to_string();
+                }
+                Err(err) => self.message = err.to_string(),
+            },
+            KeyCode::Enter if !self.board.is_started() => match self.board.start_at(self.cursor) {
+                Ok(board) => {
+                    self.board = board;
+                    self.message = format!("Started at {}", self.cursor);
+                }
+                Err(err) => self.message = err.to_string(),
+            },
+            code => match App::direction(code) {
+                Some(dir) if !self.board.is_started() => self.move_cursor(dir),
+                Some(dir) => self.play(dir),
+                None => {}
+            },
+        }
+    }
+
+    /// Move the cursor one cell in `dir`, staying on the board.
+    fn move_cursor(&mut self, dir: Direction) {
+        let (dx, dy) = match dir {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            _ => return,
+        };
+        if let Some(cell) = self.cursor + (dx, dy).into() {
+            if cell.within(self.board.width(), self.board.height()) {
+                self.cursor = cell;
+            }
+        }
+    }
+
+    /// Play a move in `dir`.
+    fn play(&mut self, dir: Direction) {
+        match self.board.make_move(dir) {
+            Ok(()) => {
+                self.history.push(dir);
+                self.message = if self.board.is_won() {
+                    "You won!".to_string()
+                } else if self.board.is_blocked() {
+                    "Blocked; press Backspace to take a move back".to_string()
+                } else {
+                    format!("Moved {}", dir)
+                };
+            }
+            Err(err) => self.message = err.to_string(),
+        }
+    }
+
+    /// Draw the whole screen.
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [grid, side] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(28)]).areas(main);
+        let [score, moves, history] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(10),
+            Constraint::Min(0),
+        ])
+        .areas(side);
+
+        frame.render_widget(
+            Paragraph::new(self.grid_lines()).block(Block::bordered().title(" Board ")),
+            grid,
+        );
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} / {}",
+                self.board.score(),
+                self.board.cell_count()
+            ))
+            .block(Block::bordered().title(" Score ")),
+            score,
+        );
+        let available: Vec<Line> = self
+            .board
+            .possible_moves()
+            .into_iter()
+            .map(|dir| Line::from(format!("{:<3} {}", dir.to_compact(), dir)))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(available).block(Block::bordered().title(" Moves ")),
+            moves,
+        );
+        let played: Vec<&str> = self.history.iter().map(|dir| dir.to_compact()).collect();
+        frame.render_widget(
+            Paragraph::new(played.join(" "))
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(" History ")),
+            history,
+        );
+        frame.render_widget(Paragraph::new(self.message.as_str()), status);
+    }
+
+    /// Return the rows of the board, with the current cell, the cells a
+    /// move can land on and, before the start, the cursor highlighted.
+    fn grid_lines(&self) -> Vec<Line<'_>> {
+        let width = self.board.cell_count().to_string().len();
+        let current = self.board.current_position();
+        let targets: Vec<Coord> = self
+            .board
+            .possible_moves()
+            .into_iter()
+            .filter_map(|dir| {
+                let from = current?;
+                self.board
+                    .rules()
+                    .target(self.board.width(), self.board.height(), from, dir)
+            })
+            .collect();
+        (0..self.board.height())
+            .map(|y| {
+                let spans: Vec<Span> = (0..self.board.width())
+                    .map(|x| {
+                        let cell = Coord::new(x, y);
+                        let text = if self.board.is_hole(Coord::new(x, y)) {
+                            "#".to_string()
+                        } else {
+                            match self.board.value_at(Coord::new(x, y)) {
+                                0 => ".".to_string(),
+                                value => value.to_string(),
+                            }
+                        };
+                        let style = if Some(cell) == current
+                            || (!self.board.is_started() && cell == self.cursor)
+                        {
+                            Style::new().add_modifier(Modifier::REVERSED)
+                        } else if targets.contains(&cell) {
+                            Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                        } else {
+                            Style::new()
+                        };
+                        Span::styled(format!(" {:>width$}", text, width = width), style)
+                    })
+                    .collect();
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+/// ShapeApp is the state of the shape editor: the shape being drawn and the
+/// catalog it is saved to.
+pub struct ShapeApp {
+    editor: ShapeEditor,
+    /// The catalog file the shape is saved to.
+    catalog: PathBuf,
+    /// `true` if the shape has not changed since it was last saved.
+    saved: bool,
+    /// The last thing worth telling the user.
+    message: String,
+    quit: bool,
+}
+
+impl ShapeApp {
+    /// Start editing `shape`, to be saved to the catalog file `catalog`.
+    pub fn new(shape: Shape, catalog: &Path) -> Self {
+        ShapeApp {
+            editor: ShapeEditor::new(shape),
+            catalog: catalog.to_path_buf(),
+            saved: false,
+            message: "Space toggles a cell, Enter solves, s saves".to_string(),
+            quit: false,
+        }
+    }
+
+    /// Act on one key press.
+    pub fn handle(&mut self, code: KeyCode) {
+        let key = match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.quit = true;
+                return;
+            }
+            KeyCode::Enter => return self.solve(),
+            KeyCode::Char('s') => return self.save(),
+            KeyCode::Up | KeyCode::Char('k') => EditorKey::Up,
+            KeyCode::Down | KeyCode::Char('j') => EditorKey::Down,
+            KeyCode::Left | KeyCode::Char('h') => EditorKey::Left,
+            KeyCode::Right | KeyCode::Char('l') => EditorKey::Right,
+            KeyCode::Char(' ') => EditorKey::Toggle,
+            _ => return,
+        };
+        if key == EditorKey::Toggle {
+            self.saved = false;
+        }
+        self.editor.handle(key);
+    }
+
+    /// Search the shape for a tour, from any start cell, within
+    /// `SHAPE_NODE_LIMIT` positions.
+    fn solve(&mut self) {
+        let board = match self.editor.shape.to_board() {
+            Ok(board) => board,
+            Err(err) => {
+                self.message = err.to_string();
+                return;
+            }
+        };
+        let solver = Solver::new(SolverConfig {
+            node_limit: Some(SHAPE_NODE_LIMIT),
+            ..SolverConfig::default()
+        });
+        let result = solver.solve(&board);
+        self.message = match result.outcome {
+            Outcome::Solved => format!("Solved: a tour fills all {} cells", board.cell_count()),
+            Outcome::Unsolvable => "Unsolvable: no tour fills the shape".to_string(),
+            Outcome::LimitReached => format!(
+                "No tour found in {} positions; best {} / {}",
+                SHAPE_NODE_LIMIT,
+                result.best.score(),
+                board.cell_count()
+            ),
+        };
+    }
+
+    /// Write the shape into its catalog file, in place of any shape of the
+    /// same name, keeping the other shapes there.
+    fn save(&mut self) {
+        let mut catalog = match fs::read_to_string(&self.catalog) {
+            Ok(text) => match text.parse::<Catalog>() {
+                Ok(catalog) => catalog,
+                Err(err) => {
+                    self.message = format!("cannot read {}: {}", self.catalog.display(), err);
+                    return;
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Catalog::default(),
+            Err(err) => {
+                self.message = format!("cannot read {}: {}", self.catalog.display(), err);
+                return;
+            }
+        };
+        catalog.insert(self.editor.shape.clone());
+        self.message = match fs::write(&self.catalog, catalog.to_string()) {
+            Ok(()) => {
+                self.saved = true;
+                format!(
+                    "Saved '{}' to {}",
+                    self.editor.shape.name,
+                    self.catalog.display()
+                )
+            }
+            Err(err) => format!("cannot write {}: {}", self.catalog.display(), err),
+        };
+    }
+
+    /// Draw the whole screen.
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [grid, side] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(36)]).areas(main);
+
+        let shape = &self.editor.shape;
+        let title = if self.saved {
+            format!(" {} ", shape.name)
+        } else {
+            format!(" {} (unsaved) ", shape.name)
+        };
+        frame.render_widget(
+            Paragraph::new(self.grid_lines()).block(Block::bordered().title(title)),
+            grid,
+        );
+        let report = self.editor.analyze();
+        let verdict = match &report.verdict {
+            Verdict::Unsolvable(reason) => format!("Unsolvable: {}", reason),
+            Verdict::Unknown => "No structural problem".to_string(),
+        };
+        let lines = vec![
+            Line::from(format!("Cells      {}", report.cells)),
+            Line::from(format!("Regions    {}", report.regions)),
+            Line::from(format!("Isolated   {}", report.isolated.len())),
+            Line::from(format!("Dead ends  {}", report.dead_ends.len())),
+            Line::from(""),
+            Line::from(verdict),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(" Analysis ")),
+            side,
+        );
+        frame.render_widget(Paragraph::new(self.message.as_str()), status);
+    }
+
+    /// Return the rows of the shape, with the cursor highlighted and the
+    /// cells the analysis found no way into or out of underlined.
+    fn grid_lines(&self) -> Vec<Line<'_>> {
+        let shape = &self.editor.shape;
+        let report = self.editor.analyze();
+        (0..shape.height())
+            .map(|y| {
+                let spans: Vec<Span> = (0..shape.width())
+                    .map(|x| {
+                        let cell = Coord::new(x, y);
+                        let text = if shape.contains(x, y) { " #" } else { " ." };
+                        let style = if cell == self.editor.cursor {
+                            Style::new().add_modifier(Modifier::REVERSED)
+                        } else if report.isolated.contains(&cell)
+                            || report.dead_ends.contains(&cell)
+                        {
+                            Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                        } else {
+                            Style::new()
+                        };
+                        Span::styled(text, style)
+                    })
+                    .collect();
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+/// Run the shape editor on `shape` until the user quits, returning the shape
+/// as last drawn and whether it was saved to `catalog` as it stands.
+pub fn edit_shape(shape: Shape, catalog: &Path) -> io::Result<(Shape, bool)> {
+    let mut terminal = ratatui::try_init()?;
+    let mut app = ShapeApp::new(shape, catalog);
+    let result = (|| {
+        while !app.quit {
+            terminal.draw(|frame| app.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle(key.code);
+                }
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result.map(|()| (app.editor.shape, app.saved))
+}
+
+/// Run the frontend on `board` until the player quits, returning the final
+/// board.
+pub fn run(board: Board) -> io::Result<Board> {
+    let mut terminal = ratatui::try_init()?;
+    let mut app = App::new(board);
+    let result = (|| {
+        while !app.quit {
+            terminal.draw(|frame| app.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle(key.code);
+                }
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result.map(|()| app.board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn keys_play_a_game() {
+        let mut app = App::new(Board::new(5));
+        app.handle(KeyCode::Char('l'));
+        app.handle(KeyCode::Down);
+        app.handle(KeyCode::Char('n'));
+        assert_eq!(app.cursor, Coord::new(1, 1));
+        app.handle(KeyCode::Enter);
+        assert_eq!(app.board.current_position(), Some(Coord::new(1, 1)));
+
+        app.handle(KeyCode::Char('l'));
+        app.handle(KeyCode::Up);
+        assert_eq!(app.history, vec![Direction::Right]);
+        assert!(app.message.starts_with("Moving in direction 'Up'"));
+        app.handle(KeyCode::Backspace);
+        assert!(app.history.is_empty());
+        assert_eq!(app.board.score(), 1);
+
+        app.handle(KeyCode::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn draw_the_panels() {
+        let mut app = App::new(Board::new(5));
+        app.handle(KeyCode::Enter);
+        app.handle(KeyCode::Right);
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for text in [
+            " Board ",
+            " Score ",
+            "2 / 25",
+            " Moves ",
+            "D   Down",
+            " History ",
+        ] {
+            assert!(screen.contains(text), "{}", text);
+        }
+        assert!(screen.contains("Moved Right"));
+    }
+
+    #[test]
+    fn draw_solve_and_save_a_shape() {
+        let catalog = std::env::temp_dir().join(format!("square100-tui-{}", std::process::id()));
+        fs::write(&catalog, "name other\n# #\n# #\n").unwrap();
+        let mut app = ShapeApp::new(Shape::full("corner", 5, 5), &catalog);
+        app.handle(KeyCode::Enter);
+        assert_eq!(app.message, "Solved: a tour fills all 25 cells");
+
+        // cut off every jump into the top-left corner
+        let keys = "lll jjh hhj ";
+        for key in keys.chars() {
+            app.handle(KeyCode::Char(key));
+        }
+        for &(x, y) in &[(3, 0), (2, 2), (0, 3)] {
+            assert!(!app.editor.shape.contains(x, y));
+        }
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for text in [
+            " corner (unsaved) ",
+            " Analysis ",
+            "Cells      22",
+            "Unsolvable",
+        ] {
+            assert!(screen.contains(text), "{}", text);
+        }
+
+        app.handle(KeyCode::Char('s'));
+        assert!(app.saved, "{}", app.message);
+        let saved: Catalog = fs::read_to_string(&catalog).unwrap().parse().unwrap();
+        fs::remove_file(&catalog).unwrap();
+        assert_eq!(saved.shapes.len(), 2);
+        assert_eq!(saved.get("corner"), Some(&app.editor.shape));
+        app.handle(KeyCode::Esc);
+        assert!(app.quit);
+    }
+}
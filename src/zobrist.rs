@@ -0,0 +1,52 @@
+/*!
+Zobrist keys of positions.
+
+What is left of a game depends only on which cells are taken and where the
+path stands, not on the order the cells were taken in. A position's key is
+the exclusive or of a random 64-bit key for each taken cell, values and
+holes alike, and another for the current cell. Filling a cell or taking it
+back flips one cell key and moves the current key, so `Board` keeps its key
+up to date in constant time per move and never hashes its values.
+
+Keys are a fixed function of the cell index (the SplitMix64 output for
+it), so they need no table, and are the same on every board, run and
+platform. Boards of different dimensions or rules can share a key; a
+lookup by key must still compare the positions, or keep tables per board
+shape.
+ */
+
+/// Return the key of `cell` holding a value or a hole.
+#[inline]
+pub fn occupied_key(cell: usize) -> u64 {
+    mix(2 * cell as u64)
+}
+
+/// Return the key of `cell` being the current cell.
+#[inline]
+pub fn current_key(cell: usize) -> u64 {
+    mix(2 * cell as u64 + 1)
+}
+
+#[inline]
+/// Return the SplitMix64 output for `n`.
+fn mix(n: u64) -> u64 {
+    let mut z = n.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn keys_are_distinct() {
+        let keys: HashSet<u64> = (0..65_536)
+            .flat_map(|cell| [occupied_key(cell), current_key(cell)])
+            .collect();
+        assert_eq!(keys.len(), 2 * 65_536);
+        assert!(!keys.contains(&0));
+    }
+}
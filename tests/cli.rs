@@ -0,0 +1,1031 @@
+//! End-to-end tests of the `board100` command line tool: each test runs the
+//! built binary and checks its output and exit code.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// Run `board100` with `args`, writing `input` to its stdin.
+fn board100(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_board100"))
+        .args(args)
+        .env_remove("BOARD100_METRICS")
+        .env_remove("BOARD100_DATA_DIR")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("cannot run board100");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// Return a path in the temporary directory unique to this test run.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("square100-cli-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn solve() {
+    let output = board100(&["solve", "--size", "5", "--start", "A1"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let board: square100::Board = stdout(&output).parse().unwrap();
+    assert!(board.is_won());
+    assert_eq!(board.value_at(square100::Coord::new(0, 0)), 1);
+
+    let output = board100(&["solve", "--size", "5", "--start", "A1", "--notation"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let (grid, notation) = text.trim_end().rsplit_once('\n').unwrap();
+    assert_eq!(grid.parse::<square100::Board>().unwrap(), board);
+    assert_eq!(notation, board.to_notation().unwrap());
+    assert!(notation.starts_with("A1: "));
+
+    let output = board100(
+        &[
+            "solve", "--size", "5", "--start", "A1", "--style", "unicode",
+        ],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert_eq!(text.lines().count(), 11);
+    assert!(text.starts_with("┌────┬"));
+    assert!(text.lines().nth(1).unwrap().starts_with("│  1 │"));
+
+    let output = board100(&["solve", "--size", "5", "--color", "always"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("\x1b[1;7;38;2;255;80;60m25\x1b[0m"));
+    // piped output is not colored unless asked for
+    let output = board100(&["solve", "--size", "5"], "");
+    assert!(!stdout(&output).contains('\x1b'));
+
+    let file = temp_path("solution.svg");
+    let output = board100(
+        &["solve", "--size", "5", "--output", file.to_str().unwrap()],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let svg = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains("<polyline "));
+    assert!(svg.contains(">25</text>"));
+
+    let output = board100(&["solve", "--style", "fancy"], "");
+    assert_eq!(output.status.code(), Some(64));
+
+    let output = board100(&["solve", "--size", "6", "--strategy", "warnsdorff"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let board: square100::Board = stdout(&output).parse().unwrap();
+    assert!(board.is_won());
+
+    let output = board100(
+        &[
+            "solve",
+            "--size",
+            "5",
+            "--start",
+            "A1",
+            "--animate",
+            "--delay",
+            "0ms",
+        ],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let frames: Vec<&str> = text.split("\x1b[2J\x1b[H").skip(1).collect();
+    assert_eq!(frames.len(), 25);
+    assert!(frames[0].ends_with("1 / 25\n"));
+    assert!(frames[24].ends_with("25 / 25\n"));
+
+    let output = board100(&["solve", "--animate", "--delay", "soon"], "");
+    assert_eq!(output.status.code(), Some(64));
+
+    let output = board100(&["solve", "--nodes", "1"], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: no solution found within "));
+
+    let output = board100(&["solve", "--strategy", "guess"], "");
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn solve_learn() {
+    let file = temp_path("ordering.txt");
+    let args = [
+        "solve",
+        "--size",
+        "5",
+        "--start",
+        "A1",
+        "--learn",
+        file.to_str().unwrap(),
+    ];
+    for solutions in 1..=2 {
+        let output = board100(&args, "");
+        assert!(output.status.success(), "{}", stderr(&output));
+        let table: square100::ordering::MoveTable =
+            std::fs::read_to_string(&file).unwrap().parse().unwrap();
+        assert_eq!(table.solutions(), solutions);
+    }
+
+    // a table is only for boards of its size and rules
+    for other in [&["--size", "6"][..], &["--size", "5", "--torus"][..]].iter() {
+        let mut args = vec!["solve", "--learn", file.to_str().unwrap()];
+        args.extend_from_slice(other);
+        let output = board100(&args, "");
+        assert!(!output.status.success());
+        assert!(stderr(&output).contains("Table is for 5x5 '0,3 2,2"));
+    }
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn play() {
+    let game = &square100::fixtures::SOLUTION_5;
+    let mut moves = vec![game.start.to_string()];
+    moves.extend(game.moves.iter().map(|dir| dir.to_compact().to_string()));
+    let output = board100(&["play", "--size", "5"], &moves.join("\n"));
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.starts_with(" . "));
+    assert!(text.contains("start cell (e.g. A1): "));
+    assert!(text.contains("moves: D DR R> "));
+    assert!(text.trim_end().ends_with("You won!"));
+
+    // bad input is reported and the game goes on until the input ends
+    let output = board100(
+        &["play", "--size", "5"],
+        "Z9\nA1\nsideways\nU\nR\nundo\nquit\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.contains("Cannot set cell [25,8]: out of range 5"));
+    assert!(text.contains("Invalid direction 'sideways'"));
+    assert!(text.contains("Moving in direction 'Up' from [0,0] is invalid"));
+    assert!(!text.contains("You won!"));
+}
+
+#[test]
+fn capabilities() {
+    let output = board100(&["capabilities", "--size", "5", "--start", "0,0"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.starts_with("estimated search tree: "));
+    assert!(text.contains("exhaustive search"));
+
+    let output = board100(&["capabilities", "--size", "5", "--start", "9,9"], "");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).starts_with("error: "));
+
+    let output = board100(&["capabilities", "--size", "3"], "");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("Invalid board dimensions 3x3"));
+
+    let output = board100(&["capabilities", "--torus", "--start", "E5"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = board100(&["capabilities", "--start", "zero"], "");
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn engine_session() {
+    let output = board100(
+        &["engine"],
+        "board100\nisready\nposition 5 start 0 0 moves R D\ngo\nfly\nquit\n",
+    );
+    assert!(output.status.success());
+    let lines: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+    assert!(lines[0].starts_with("id name board100 "));
+    assert_eq!(lines[1..3], ["board100ok", "readyok"]);
+    assert!(lines[3].starts_with("info depth 25 "));
+    assert!(lines[4].starts_with("bestmove "));
+    assert_eq!(lines[5], "error unknown command 'fly'");
+    assert_eq!(lines.len(), 6);
+}
+
+#[test]
+fn repl_session() {
+    let output = board100(
+        &["repl", "--size", "5"],
+        "start A1\nmove r\nundo 5\nsolve\nquit\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.contains("2 / 25, moves: "));
+    assert!(text.contains("error: "));
+    assert!(text.contains("25 / 25, solved!"));
+
+    let output = board100(&["--format", "json", "repl"], "");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("repl is interactive"));
+}
+
+#[test]
+fn paths_with_data_dir() {
+    let dir = temp_path("data");
+    let output = board100(&["paths", "--data-dir", dir.to_str().unwrap()], "");
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert_eq!(text.lines().count(), 5);
+    for line in text.lines() {
+        assert!(line.contains(dir.to_str().unwrap()), "{}", line);
+    }
+    assert!(!dir.exists());
+}
+
+#[test]
+fn metrics_and_report() {
+    let file = temp_path("metrics.jsonl");
+    let metrics = file.to_str().unwrap();
+    let _ = std::fs::remove_file(&file);
+    board100(&["--metrics", metrics, "paths", "--data-dir", "x"], "");
+    board100(
+        &["--metrics", metrics, "capabilities", "--start", "99,0"],
+        "",
+    );
+
+    let output = board100(&["report", metrics], "");
+    std::fs::remove_file(&file).unwrap();
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let rows: Vec<&str> = text.lines().skip(1).collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0].starts_with("capabilities"));
+    assert!(rows[0].contains("error=1"));
+    assert!(rows[1].starts_with("paths"));
+
+    let output = board100(&["report"], "");
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stderr(&output), "error: no metrics file given\n");
+}
+
+#[test]
+fn json_output() {
+    let output = board100(
+        &["solve", "--size", "5", "--start", "A1", "--format", "json"],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let solution: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(solution["width"], 5);
+    assert_eq!(solution["start"], "A1");
+    assert_eq!(solution["values"][0][0], 1);
+    assert_eq!(solution["moves"].as_array().unwrap().len(), 24);
+    assert_eq!(solution["strategy"], "search");
+    assert!(solution["stats"]["nodes"].as_u64().unwrap() >= 24);
+
+    let output = board100(
+        &[
+            "--format",
+            "json",
+            "capabilities",
+            "--size",
+            "5",
+            "--start",
+            "A1",
+        ],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert!(report["estimate"]["nodes"].as_f64().unwrap() > 0.0);
+    assert!(!report["capabilities"].as_array().unwrap().is_empty());
+
+    let output = board100(&["paths", "--data-dir", "x", "--format", "json"], "");
+    let paths: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(paths.as_object().unwrap().len(), 5);
+
+    let output = board100(&["play", "--format", "json"], "");
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        stderr(&output),
+        "error: play is interactive and has no json output\n"
+    );
+}
+
+#[test]
+fn csv_output() {
+    let output = board100(
+        &["solve", "--size", "5", "--start", "A1", "--format", "csv"],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let rows: Vec<Vec<u16>> = text
+        .lines()
+        .map(|line| line.split(',').map(|v| v.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(rows.len(), 5);
+    assert!(rows.iter().all(|row| row.len() == 5));
+    assert_eq!(rows[0][0], 1);
+
+    let output = board100(
+        &[
+            "capabilities",
+            "--size",
+            "5",
+            "--start",
+            "A1",
+            "--format",
+            "csv",
+        ],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert_eq!(text.lines().next(), Some("capability,availability,reason"));
+    assert!(text.lines().skip(1).all(|line| line.contains(",")));
+
+    let output = board100(&["paths", "--data-dir", "x", "--format", "csv"], "");
+    assert_eq!(stdout(&output).lines().count(), 6);
+
+    let output = board100(&["engine", "--format", "csv"], "");
+    assert_eq!(
+        stderr(&output),
+        "error: engine is interactive and has no csv output\n"
+    );
+}
+
+#[test]
+fn png_figure() {
+    let file = temp_path("solution.png");
+    let output = board100(
+        &["solve", "--size", "5", "--output", file.to_str().unwrap()],
+        "",
+    );
+    if cfg!(feature = "png") {
+        assert!(output.status.success(), "{}", stderr(&output));
+        let data = std::fs::read(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert!(data.starts_with(b"\x89PNG"));
+    } else {
+        assert_eq!(output.status.code(), Some(3));
+        assert!(stderr(&output).contains("without the png feature"));
+    }
+}
+
+#[test]
+fn gif_animation() {
+    let file = temp_path("solution.gif");
+    let output = board100(
+        &[
+            "solve",
+            "--size",
+            "5",
+            "--gif",
+            file.to_str().unwrap(),
+            "--delay",
+            "50ms",
+        ],
+        "",
+    );
+    if cfg!(feature = "gif") {
+        assert!(output.status.success(), "{}", stderr(&output));
+        let data = std::fs::read(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert!(data.starts_with(b"GIF89a"));
+    } else {
+        assert_eq!(output.status.code(), Some(3));
+        assert!(stderr(&output).contains("without the gif feature"));
+    }
+}
+
+#[test]
+fn hint() {
+    let file = temp_path("partial.txt");
+    let board = square100::fixtures::SOLUTION_5.board_after(10);
+    std::fs::write(&file, board.to_string()).unwrap();
+    let output = board100(&["hint", "--board", file.to_str().unwrap()], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let token = text.split_whitespace().next().unwrap();
+    let dir: square100::Direction = token.parse().unwrap();
+    assert!(board.possible_moves().contains(dir));
+    assert!(text.contains("can still be completed"));
+
+    let output = board100(
+        &[
+            "hint",
+            "--board",
+            file.to_str().unwrap(),
+            "--format",
+            "json",
+        ],
+        "",
+    );
+    let hint: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(hint["move"], token);
+    assert_eq!(hint["outcome"], "solved");
+
+    // a finished board written by solve --format json has no move left
+    let output = board100(
+        &["solve", "--size", "5", "--start", "A1", "--format", "json"],
+        "",
+    );
+    std::fs::write(&file, &output.stdout).unwrap();
+    let output = board100(&["hint", "--board", file.to_str().unwrap()], "");
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stderr(&output), "error: there is no move left to play\n");
+
+    let output = board100(&["hint", "--board", "/nonexistent/board"], "");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).starts_with("error: cannot read /nonexistent/board"));
+}
+
+#[test]
+fn json_boards_are_checked() {
+    // 1 and 2 are side by side, which is not a jump
+    let file = temp_path("bad.json");
+    let mut rows = vec![vec![0; 5]; 5];
+    rows[0][0] = 1;
+    rows[0][1] = 2;
+    std::fs::write(&file, serde_json::json!({ "values": rows }).to_string()).unwrap();
+    let output = board100(&["solve", file.to_str().unwrap()], "");
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stdout(&output).is_empty());
+
+    let text = board100(
+        &["solve", "-"],
+        "1 2 . . .\n. . . . .\n. . . . .\n. . . . .\n. . . . .\n",
+    );
+    assert_eq!(text.status.code(), Some(3));
+    assert_eq!(stderr(&output), stderr(&text));
+}
+
+#[test]
+fn json_boards_keep_their_rules() {
+    let rules = "- - 1,0 - 0,-1 - -1,0 -";
+    let text = format!("rules {}\n{}", rules, ". . . . .\n".repeat(5));
+    let output = board100(&["solve", "-", "--format", "json"], &text);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let solution: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(solution["rules"], rules);
+
+    let file = temp_path("rules.json");
+    std::fs::write(&file, &output.stdout).unwrap();
+    let output = board100(&["solve", file.to_str().unwrap()], "");
+    std::fs::remove_file(&file).unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let board: square100::Board = stdout(&output).parse().unwrap();
+    assert_eq!(board.rules().to_string(), rules);
+    assert!(board.is_won());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn save_and_resume() {
+    let file = temp_path("game.json");
+    let game = file.to_str().unwrap();
+    let output = board100(&["play", "--size", "5", "--save", game], "A1\nR\nD\nquit\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    assert_eq!(
+        (&saved["x"], &saved["y"]),
+        (&serde_json::json!(3), &serde_json::json!(3))
+    );
+
+    // the resumed game keeps its history, so moves can still be undone
+    let output = board100(
+        &["play", "--resume", game, "--save", game],
+        "undo\nundo\nquit\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with(" 1  .  .  2  .\n"));
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    assert_eq!(saved["values"][3], serde_json::json!(0));
+
+    // and its rules
+    let output = board100(
+        &["play", "--size", "5", "--torus", "--save", game],
+        "A1\nquit\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = board100(&["play", "--resume", game], "L\nquit\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(" 1  .  2  .  .\n"));
+
+    let mut saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    saved["values"][3] = serde_json::json!(2);
+    std::fs::write(&file, saved.to_string()).unwrap();
+    let output = board100(&["play", "--resume", game], "");
+    std::fs::remove_file(&file).unwrap();
+    assert!(stderr(&output).starts_with(&format!("error: cannot resume {}: ", game)));
+
+    let output = board100(&["play", "--resume", game, "--size", "6"], "");
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn random_play() {
+    let args = [
+        "play",
+        "--size",
+        "5",
+        "--random",
+        "--seed",
+        "42",
+        "--opening",
+        "3",
+    ];
+    let output = board100(&args, "quit\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.starts_with("Seed 42\n"));
+    let values: Vec<&str> = text
+        .lines()
+        .skip(1)
+        .flat_map(str::split_whitespace)
+        .collect();
+    assert!(values.contains(&"4"));
+    assert!(!values.contains(&"5"));
+    assert_eq!(stdout(&board100(&args, "quit\n")), text);
+
+    let output = board100(&["play", "--random"], "quit\n");
+    assert!(stdout(&output).starts_with("Seed "));
+    let output = board100(&["play", "--seed", "42"], "");
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn replay_files() {
+    let file = temp_path("tour.b100");
+    let tour = file.to_str().unwrap();
+    let output = board100(
+        &["solve", "--size", "5", "--start", "A1", "--output", tour],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = std::fs::read_to_string(&file).unwrap();
+    assert!(text.starts_with("square100 replay v1\nsize 5x5\nstart A1\nmoves "));
+
+    let solution = stdout(&output);
+    let output = board100(&["replay", tour], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), solution + "25 / 25\n");
+
+    let output = board100(&["replay", tour, "--animate", "--delay", "0"], "");
+    assert_eq!(stdout(&output).matches("\x1b[2J").count(), 25);
+
+    std::fs::write(&file, "size 5\nstart A1\nmoves R R\n").unwrap();
+    let output = board100(&["replay", tour], "");
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).starts_with("error: "));
+}
+
+#[test]
+fn bench() {
+    let output = board100(&["bench", "--size", "5", "--repeat", "1"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 27);
+    assert!(lines[0].starts_with("start  outcome"));
+    assert!(lines[1].starts_with("A1     solved"));
+    assert!(lines[26].starts_with("total  25/25"));
+
+    let output = board100(
+        &[
+            "bench",
+            "--size",
+            "6",
+            "--starts",
+            "A1",
+            "--starts",
+            "C3",
+            "--strategy",
+            "warnsdorff",
+            "--format",
+            "csv",
+        ],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines[0],
+        "start,outcome,nodes,backtracks,ms_per_solve,nodes_per_sec"
+    );
+    assert!(lines[1].starts_with("A1,solved,36,0,"));
+    assert!(lines[2].starts_with("C3,"));
+
+    let output = board100(
+        &["bench", "--size", "5", "--starts", "A1", "--format", "json"],
+        "",
+    );
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["runs"][0]["nodes"], report["total"]["nodes"]);
+    assert_eq!(report["total"]["solved"], 1);
+
+    let output = board100(&["bench", "--starts", "nowhere"], "");
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn threads() {
+    let one = board100(&["solve", "--size", "6", "--threads", "1"], "");
+    assert!(one.status.success(), "{}", stderr(&one));
+    let many = board100(&["solve", "--size", "6", "--threads", "3"], "");
+    assert!(many.status.success(), "{}", stderr(&many));
+    assert_eq!(stdout(&many), stdout(&one));
+
+    let output = board100(
+        &[
+            "count",
+            "--size",
+            "5",
+            "--start",
+            "C3",
+            "--threads",
+            "2",
+            "--tt-size",
+            "0",
+        ],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("352 solutions (70269 positions"));
+
+    let output = board100(&["solve-all", "--size", "5", "--threads", "2"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("25 of 25 starts solved"));
+
+    let output = board100(&["count", "--threads", "0"], "");
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr(&output).contains("at least 1"));
+}
+
+#[test]
+fn compare_solvers() {
+    let args = [
+        "compare-solvers",
+        "--size",
+        "6",
+        "--boards",
+        "4",
+        "--seed",
+        "2",
+    ];
+    let output = board100(&args, "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let names: Vec<&str> = text
+        .lines()
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "strategy",
+            "search",
+            "naive",
+            "warnsdorff",
+            "restart",
+            "mcts"
+        ]
+    );
+    assert!(text.lines().nth(1).unwrap().contains(" 4/4 "));
+
+    let output = board100(
+        &[
+            "compare-solvers",
+            "--size",
+            "6",
+            "--boards",
+            "3",
+            "--strategies",
+            "search,warnsdorff",
+            "--format",
+            "csv",
+        ],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("search,3,3,1.000,"));
+
+    for strategy in ["restart", "mcts"].iter() {
+        let output = board100(&["solve", "--size", "6", "--strategy", strategy], "");
+        assert!(output.status.success(), "{}", stderr(&output));
+        let board: square100::Board = stdout(&output).parse().unwrap();
+        assert!(board.is_won());
+    }
+}
+
+#[test]
+fn solve_all() {
+    let output = board100(&["solve-all", "--size", "5"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 13);
+    assert_eq!(lines[0], "✓ ✓ ✓ ✓ ✓");
+    assert_eq!(lines[5], "");
+    assert_eq!(lines[6].split_whitespace().count(), 5);
+    assert!(lines[12].starts_with("25 of 25 starts solved in "));
+
+    let output = board100(
+        &[
+            "solve-all",
+            "--size",
+            "5",
+            "--nodes",
+            "1",
+            "--format",
+            "csv",
+        ],
+        "",
+    );
+    let text = stdout(&output);
+    assert_eq!(text.lines().count(), 26);
+    assert!(text.lines().nth(1).unwrap().starts_with("A1,limit,"));
+}
+
+#[test]
+fn count() {
+    let output = board100(&["count", "--size", "5", "--start", "0,0"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("552 solutions ("));
+
+    let output = board100(
+        &[
+            "count",
+            "--size",
+            "5",
+            "--start",
+            "A1",
+            "--modulo-symmetry",
+            "--format",
+            "json",
+        ],
+        "",
+    );
+    let value: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(value["solutions"], 276);
+    assert_eq!(value["complete"], true);
+
+    // the table of dead positions saves work, not solutions
+    let output = board100(
+        &["count", "--size", "5", "--start", "C3", "--tt-size", "1M"],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.starts_with("352 solutions ("), "{}", text);
+    let nodes: u64 = text["352 solutions (".len()..]
+        .split(' ')
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(nodes < 70269, "{}", text);
+
+    let output = board100(&["count", "--size", "5", "--nodes", "100"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "At least 0 solutions; stopped after 100 positions\n"
+    );
+}
+
+#[test]
+fn generate() {
+    let args = [
+        "generate", "--size", "6", "--clues", "8", "--unique", "--seed", "3",
+    ];
+    let output = board100(&args, "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert_eq!(text.lines().count(), 6);
+    let clues = text
+        .split_whitespace()
+        .filter(|token| *token != ".")
+        .count();
+    assert_eq!(clues, 8);
+    assert_eq!(stdout(&board100(&args, "")), text);
+
+    let mut json = args.to_vec();
+    json.extend(["--format", "json"]);
+    let puzzle: serde_json::Value = serde_json::from_str(&stdout(&board100(&json, ""))).unwrap();
+    assert_eq!(puzzle["width"], 6);
+    assert_eq!(puzzle["seed"], 3);
+    assert_eq!(puzzle["unique"], true);
+
+    let output = board100(
+        &["generate", "--clues", "26", "--size", "5", "--seed", "1"],
+        "",
+    );
+    assert_eq!(
+        stderr(&output),
+        "error: Cannot reveal 26 clues on a board of 25 cells\n"
+    );
+}
+
+#[test]
+fn solve_trace() {
+    let output = board100(&["solve", "--size", "6", "--start", "A1", "-vv"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let trace = stderr(&output);
+    assert!(trace.starts_with("place depth=2 cell="));
+    assert!(trace.ends_with("solved depth=36\n"));
+    assert_eq!(stdout(&output).lines().count(), 6);
+
+    let output = board100(&["solve", "--size", "6", "--start", "A1", "-v"], "");
+    assert_eq!(stderr(&output), "solved depth=36\n");
+    let output = board100(&["solve", "--size", "6", "--start", "A1"], "");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn solve_watch() {
+    let output = board100(
+        &[
+            "solve", "--size", "7", "--start", "B2", "--nodes", "300", "--watch", "--delay", "0ms",
+        ],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(2));
+    let text = stdout(&output);
+    let frames: Vec<&str> = text.split("\x1b[2J\x1b[H").skip(1).collect();
+    assert!(frames.len() > 300);
+    assert!(frames.last().unwrap().contains(", 300 moves tried\n"));
+
+    let output = board100(&["solve", "--watch", "--format", "json"], "");
+    assert_eq!(output.status.code(), Some(3));
+    let output = board100(&["solve", "--watch", "-v"], "");
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn solve_stdin() {
+    let position = " 1 . . 2 .\n. . . . .\n. . . . .\n. . . . .\n. . . . .\n";
+    let output = board100(&["solve", "-"], position);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.starts_with(" 1 "));
+    let solution: square100::Board = text.parse().unwrap();
+    assert!(solution.is_won());
+    assert_eq!(solution.value_at(square100::Coord::new(3, 0)), 2);
+
+    let output = board100(&["solve", "-", "--format", "json"], &text);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["score"], 25);
+
+    let output = board100(
+        &["solve", "-"],
+        " 1 . . 3 .\n. . . . .\n. . . . .\n. . . . .\n. . . . .\n",
+    );
+    assert!(!output.status.success());
+    let output = board100(&["solve", "-", "--size", "6"], position);
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn budgets() {
+    // from B2 the 7x7 board has no tour, but the search takes long to see it
+    let output = board100(
+        &[
+            "solve",
+            "--size",
+            "7",
+            "--start",
+            "B2",
+            "--node-limit",
+            "1k",
+        ],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with(
+        "error: no solution found within 1000 nodes (1000 positions); the best game reached "
+    ));
+    assert_eq!(stdout(&output).lines().count(), 7);
+    assert!(stdout(&output).contains(" 1 "));
+
+    let output = board100(
+        &[
+            "solve",
+            "--size",
+            "7",
+            "--start",
+            "B2",
+            "--time-limit",
+            "50ms",
+            "--format",
+            "json",
+        ],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: no solution found within 50ms ("));
+    let value: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(value["solved"], false);
+    assert!(value["score"].as_u64().unwrap() > 1);
+
+    let output = board100(&["solve", "--size", "6", "--node-limit", "10M"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    for bad in [["--node-limit", "lots"], ["--time-limit", "soon"]] {
+        let output = board100(&["solve", bad[0], bad[1]], "");
+        assert_eq!(output.status.code(), Some(64));
+    }
+
+    // every command with a node budget spells and reads it the same way
+    let output = board100(&["count", "--size", "5", "--node-limit", "1k"], "");
+    assert_eq!(
+        stdout(&output),
+        "At least 0 solutions; stopped after 1000 positions\n"
+    );
+    for command in [
+        "hint",
+        "solve-all",
+        "bench",
+        "compare-solvers",
+        "count",
+        "generate",
+    ] {
+        let output = board100(&[command, "--node-limit", "lots"], "");
+        assert_eq!(output.status.code(), Some(64), "{}", command);
+        assert!(stderr(&output).contains("--node-limit"), "{}", command);
+    }
+}
+
+#[test]
+fn daily() {
+    let output = board100(&["daily", "--date", "2024-06-01", "--size", "6"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stderr(&output), "Puzzle of 2024-06-01\n");
+    let puzzle = stdout(&output);
+    assert_eq!(
+        stdout(&board100(
+            &["daily", "--date", "2024-06-01", "--size", "6"],
+            ""
+        )),
+        puzzle
+    );
+
+    let solved = board100(&["solve", "-"], &puzzle);
+    assert!(solved.status.success(), "{}", stderr(&solved));
+    let check = [
+        "daily",
+        "--date",
+        "2024-06-01",
+        "--size",
+        "6",
+        "--check",
+        "-",
+    ];
+    let output = board100(&check, &stdout(&solved));
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Solved the puzzle of 2024-06-01\n");
+
+    let output = board100(&check, &puzzle);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("not complete"));
+    let output = board100(&["daily", "--date", "2024-02-30"], "");
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn exit_codes() {
+    let dead_end = square100::fixtures::DEAD_END_5.board().to_string();
+    let output = board100(&["solve", "-"], &dead_end);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr(&output), "error: the board has no solution\n");
+
+    let output = board100(&["solve", "--size", "8", "--nodes", "5"], "");
+    assert_eq!(output.status.code(), Some(2));
+    let output = board100(&["solve", "-", "--strategy", "warnsdorff"], &dead_end);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = board100(&["solve", "/nonexistent/board"], "");
+    assert_eq!(output.status.code(), Some(3));
+    let output = board100(&["solve", "--size", "many"], "");
+    assert_eq!(output.status.code(), Some(64));
+    let output = board100(&["solve", "--help"], "");
+    assert_eq!(output.status.code(), Some(0));
+}
@@ -0,0 +1,238 @@
+//! End-to-end tests of `board100` on a terminal: each test runs the built
+//! binary on a pseudo-terminal, types keys into it and checks what it draws
+//! and its exit code.
+#![cfg(unix)]
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::FromRawFd;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for `board100` to draw something or to exit.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Terminal is `board100` running on a pseudo-terminal of 30 rows and 100
+/// columns, with everything it has written so far.
+struct Terminal {
+    child: Child,
+    master: File,
+    output: Arc<Mutex<Vec<u8>>>,
+    /// The end of the output matched by the last `expect`.
+    seen: usize,
+}
+
+impl Terminal {
+    /// Run `board100` with `args` on a new pseudo-terminal, as its
+    /// controlling terminal.
+    fn spawn(args: &[&str]) -> Terminal {
+        let (mut master, mut slave) = (0, 0);
+        let size = libc::winsize {
+            ws_row: 30,
+            ws_col: 100,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let opened =
+            unsafe { libc::openpty(&mut master, &mut slave, ptr::null_mut(), ptr::null(), &size) };
+        assert_eq!(opened, 0, "cannot open a pseudo-terminal");
+        let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+
+        let mut command = Command::new(env!("CARGO_BIN_EXE_board100"));
+        command
+            .args(args)
+            .env_remove("BOARD100_METRICS")
+            .env_remove("BOARD100_DATA_DIR")
+            .env_remove("NO_COLOR")
+            .env("TERM", "xterm-256color")
+            .stdin(slave.try_clone().unwrap())
+            .stdout(slave.try_clone().unwrap())
+            .stderr(slave);
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() < 0 || libc::ioctl(0, libc::TIOCSCTTY, 0) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn().expect("cannot run board100");
+        // the parent's copies of the slave must be closed for reads of the
+        // master to end when the child exits
+        drop(command);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let mut reader = master.try_clone().unwrap();
+        let sink = Arc::clone(&output);
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            while let Ok(n @ 1..) = reader.read(&mut buf) {
+                sink.lock().unwrap().extend_from_slice(&buf[..n]);
+            }
+        });
+        Terminal {
+            child,
+            master,
+            output,
+            seen: 0,
+        }
+    }
+
+    /// Return everything written to the terminal so far.
+    fn screen(&self) -> String {
+        String::from_utf8_lossy(&self.output.lock().unwrap()).into_owned()
+    }
+
+    /// Wait for `text` to be written after what was last expected.
+    fn expect(&mut self, text: &str) {
+        let start = Instant::now();
+        loop {
+            let screen = self.screen();
+            if let Some(at) = screen[self.seen.min(screen.len())..].find(text) {
+                self.seen += at + text.len();
+                return;
+            }
+            assert!(
+                start.elapsed() < TIMEOUT,
+                "{:?} not drawn; the terminal shows {:?}",
+                text,
+                screen
+            );
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Type `keys`.
+    fn send(&mut self, keys: &str) {
+        self.master.write_all(keys.as_bytes()).unwrap();
+        self.master.flush().unwrap();
+    }
+
+    /// Wait for `board100` to exit, returning its status.
+    fn wait(&mut self) -> ExitStatus {
+        let start = Instant::now();
+        loop {
+            if let Some(status) = self.child.try_wait().unwrap() {
+                return status;
+            }
+            if start.elapsed() > TIMEOUT {
+                self.child.kill().unwrap();
+                panic!(
+                    "board100 did not exit; the terminal shows {:?}",
+                    self.screen()
+                );
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Return a path in the temporary directory unique to this test run.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "square100-terminal-{}-{}",
+        std::process::id(),
+        name
+    ))
+}
+
+/// Return the outcome of the last run recorded in the metrics file `file`,
+/// and remove the file.
+fn outcome(file: &Path) -> String {
+    let text = std::fs::read_to_string(file).unwrap();
+    std::fs::remove_file(file).unwrap();
+    let run: serde_json::Value = serde_json::from_str(text.lines().last().unwrap()).unwrap();
+    run["outcome"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn play_colors_boards_on_a_terminal() {
+    let metrics = temp_path("play.jsonl");
+    let mut terminal = Terminal::spawn(&[
+        "--metrics",
+        metrics.to_str().unwrap(),
+        "play",
+        "--size",
+        "5",
+    ]);
+    terminal.expect("start cell (e.g. A1): ");
+    terminal.send("A1\r");
+    terminal.expect("moves: ");
+    terminal.send("R\r");
+    terminal.expect("moves: ");
+    terminal.send("quit\r");
+    assert!(terminal.wait().success(), "{}", terminal.screen());
+    assert_eq!(outcome(&metrics), "score 2");
+    assert!(terminal.screen().contains("\x1b["));
+
+    let mut terminal = Terminal::spawn(&["--color", "never", "play", "--size", "5"]);
+    terminal.expect("start cell (e.g. A1): ");
+    terminal.send("quit\r");
+    assert!(terminal.wait().success());
+    assert!(!terminal.screen().contains("\x1b["));
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn tui_plays_with_keys() {
+    let metrics = temp_path("tui.jsonl");
+    let mut terminal =
+        Terminal::spawn(&["--metrics", metrics.to_str().unwrap(), "tui", "--size", "5"]);
+    // the full-screen interface draws on the alternate screen
+    terminal.expect("\x1b[?1049h");
+    // words are drawn one by one, as spaces are skipped
+    terminal.expect("Choose");
+    // start on the cursor, move right and down, then quit
+    terminal.send("\r");
+    terminal.send("l");
+    terminal.send("j");
+    terminal.send("q");
+    assert!(terminal.wait().success(), "{}", terminal.screen());
+    // and leaves it for the screen it was started on
+    terminal.expect("\x1b[?1049l");
+    assert_eq!(outcome(&metrics), "score 3");
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn edit_shape_saves_with_keys() {
+    let catalog = temp_path("shapes.txt");
+    let path = catalog.to_str().unwrap();
+    let metrics = temp_path("edit-shape.jsonl");
+    let mut terminal = Terminal::spawn(&[
+        "--metrics",
+        metrics.to_str().unwrap(),
+        "edit-shape",
+        path,
+        "--name",
+        "notch",
+        "--width",
+        "6",
+        "--height",
+        "6",
+    ]);
+    terminal.expect("\x1b[?1049h");
+    // cut the corner cell, save and quit
+    terminal.send(" ");
+    terminal.send("s");
+    terminal.send("q");
+    assert!(terminal.wait().success(), "{}", terminal.screen());
+    assert_eq!(outcome(&metrics), format!("saved 'notch' to {}", path));
+    let saved = std::fs::read_to_string(&catalog).unwrap();
+    std::fs::remove_file(&catalog).unwrap();
+    let catalog: square100::shape::Catalog = saved.parse().unwrap();
+    let shape = catalog.get("notch").unwrap();
+    assert!(!shape.contains(0, 0) && shape.contains(1, 0));
+}